@@ -0,0 +1,25 @@
+use std::path::Path;
+use std::process::Command;
+
+// Record the git commit and target triple for `s3sh version`
+fn main() {
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=S3SH_GIT_HASH={}", git_hash);
+    println!("cargo:rustc-env=S3SH_TARGET={}", std::env::var("TARGET").unwrap_or_default());
+
+    // Rebuild when HEAD moves; outside a git checkout there's nothing to watch
+    for path in [".git/HEAD", ".git/refs"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
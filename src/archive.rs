@@ -0,0 +1,95 @@
+use anyhow::Result;
+use async_compression::tokio::write::GzipEncoder;
+use crate::errors::usage_error;
+use crate::filter::PathFilter;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// The archive `object upload --archive` packs a directory into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ArchiveFormat::Tar => "tar",
+            ArchiveFormat::TarGz => "tar.gz",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ArchiveFormat::Tar => "application/x-tar",
+            ArchiveFormat::TarGz => "application/gzip",
+        }
+    }
+}
+
+pub fn parse_archive_format(format: &str) -> Result<ArchiveFormat> {
+    match format {
+        "tar" => Ok(ArchiveFormat::Tar),
+        "tar.gz" | "tgz" => Ok(ArchiveFormat::TarGz),
+        _ => Err(usage_error(format!("Invalid archive format: {}. Use 'tar' or 'tar.gz'", format))),
+    }
+}
+
+/// A file to put in the archive under `name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveEntry {
+    pub path: PathBuf,
+    pub name: String,
+}
+
+/// The name of `dir` itself (e.g. `site` for `./site/`, or for `.` run
+/// inside it), which the archive's paths start with.
+pub fn archive_root(dir: &Path) -> Result<String> {
+    dir.canonicalize()?
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| usage_error(format!("Cannot archive '{}': it has no directory name", dir.display())))
+}
+
+/// Every file under `dir`, named `<dir name>/<relative path>` as `tar`
+/// would name them, sorted by name.
+pub fn archive_entries(dir: &Path) -> Result<Vec<ArchiveEntry>> {
+    let root = archive_root(dir)?;
+    let files = crate::objects::collect_sync_files(dir, Some(&format!("{}/", root)), &PathFilter::default())?;
+    Ok(files.into_iter().map(|(path, name)| ArchiveEntry { path, name }).collect())
+}
+
+/// Write `entries` as a tar (or tar.gz) archive to `writer`, streaming each
+/// file, and return the writer once the archive is complete.
+pub async fn write_archive<W>(entries: &[ArchiveEntry], format: ArchiveFormat, writer: W) -> Result<W>
+where
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    match format {
+        ArchiveFormat::Tar => {
+            let mut writer = write_tar(entries, writer).await?;
+            writer.shutdown().await?;
+            Ok(writer)
+        }
+        ArchiveFormat::TarGz => {
+            let mut encoder = write_tar(entries, GzipEncoder::new(writer)).await?;
+            // Ends the gzip stream and shuts down the writer under it
+            encoder.shutdown().await?;
+            Ok(encoder.into_inner())
+        }
+    }
+}
+
+async fn write_tar<W>(entries: &[ArchiveEntry], writer: W) -> Result<W>
+where
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let mut builder = tokio_tar::Builder::new(writer);
+    for entry in entries {
+        builder.append_path_with_name(&entry.path, &entry.name)
+            .await
+            .map_err(|e| anyhow::anyhow!("Could not add '{}' to the archive: {}", entry.path.display(), e))?;
+    }
+    Ok(builder.into_inner().await?)
+}
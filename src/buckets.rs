@@ -1,17 +1,112 @@
 use aws_sdk_s3::Client;
 use anyhow::Result;
+use crate::errors::usage_error;
+use crate::grants::Grants;
 use tabled::{Table, Tabled};
 use colored::*;
 use aws_sdk_s3::error::ProvideErrorMetadata;
 use aws_sdk_s3::types::LifecycleRuleFilter;
+use aws_sdk_s3::operation::get_bucket_versioning::GetBucketVersioningOutput;
+use aws_sdk_s3::operation::get_bucket_website::GetBucketWebsiteOutput;
+use aws_sdk_s3::operation::get_bucket_logging::GetBucketLoggingOutput;
+use aws_sdk_s3::operation::get_bucket_notification_configuration::GetBucketNotificationConfigurationOutput;
+use aws_sdk_s3::types::{AnalyticsConfiguration, AnalyticsFilter, BucketCannedAcl, Grant, InventoryConfiguration, MetricsConfiguration, MetricsFilter, ObjectOwnership, Owner, ReplicationConfiguration, ServerSideEncryptionConfiguration, Tag};
+use crate::ui::{confirm, footer, format_csv, format_size, note, pluralize, print_dry_run, status, success, OutputFormat, SortKey};
+use aws_sdk_s3::primitives::DateTime;
+use std::collections::BTreeMap;
 
 #[derive(Tabled)]
-struct BucketInfo {
-    name: String,
-    creation_date: String,
+pub struct BucketInfo {
+    pub name: String,
+    pub creation_date: String,
+    #[tabled(skip)]
+    pub created: Option<DateTime>,
 }
 
-pub async fn list_buckets(client: &Client) -> Result<()> {
+/// Sort buckets by name or creation date. Buckets have no size to sort by.
+pub fn sort_buckets(infos: &mut [BucketInfo], key: SortKey, reverse: bool) -> Result<()> {
+    match key {
+        SortKey::Name => infos.sort_by(|a, b| a.name.cmp(&b.name)),
+        SortKey::Modified => infos.sort_by_key(|i| i.created),
+        SortKey::Size => return Err(anyhow::anyhow!("Buckets can only be sorted by 'name' or 'modified'")),
+    }
+    if reverse {
+        infos.reverse();
+    }
+    Ok(())
+}
+
+/// Edit distance between two names, counting insertions, deletions and
+/// substitutions of single characters.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// The candidate closest to `target`, if any is close enough to be a likely
+/// typo: at most 2 edits, or a third of the name for longer names.
+pub fn suggest_bucket_name<'a>(target: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let max_distance = (target.chars().count() / 3).max(2);
+    candidates.iter()
+        .map(|name| (levenshtein(target, name), *name))
+        .filter(|(distance, _)| *distance > 0 && *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+/// Add a "did you mean" hint to a `NoSuchBucket` error when one of the
+/// caller's buckets has a similar name. Other errors are returned unchanged.
+pub async fn suggest_missing_bucket(client: &Client, bucket_name: &str, err: anyhow::Error) -> anyhow::Error {
+    if !crate::errors::is_no_such_bucket(&err) {
+        return err;
+    }
+    let Ok(resp) = client.list_buckets().send().await else {
+        return err;
+    };
+    let names: Vec<&str> = resp.buckets().iter().filter_map(|b| b.name()).collect();
+    match suggest_bucket_name(bucket_name, &names) {
+        Some(name) => err.context(format!("Bucket '{}' does not exist; did you mean '{}'?", bucket_name, name)),
+        None => err,
+    }
+}
+
+/// `bucket list --filter`: keep buckets whose name contains a substring
+/// (ignoring case) or, with `--regex`, matches a regular expression.
+#[derive(Debug, Clone)]
+pub enum NameFilter {
+    Substring(String),
+    Regex(regex_lite::Regex),
+}
+
+impl NameFilter {
+    pub fn new(pattern: &str, regex: bool) -> Result<NameFilter> {
+        if !regex {
+            return Ok(NameFilter::Substring(pattern.to_ascii_lowercase()));
+        }
+        regex_lite::Regex::new(pattern)
+            .map(NameFilter::Regex)
+            .map_err(|e| usage_error(format!("Invalid --filter regex '{}': {}", pattern, e)))
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NameFilter::Substring(substring) => name.to_ascii_lowercase().contains(substring.as_str()),
+            NameFilter::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+pub async fn list_buckets(client: &Client, sort: SortKey, reverse: bool, filter: Option<&NameFilter>, output: OutputFormat) -> Result<()> {
     let resp = client.list_buckets().send().await?;
     let buckets = resp.buckets();
 
@@ -19,36 +114,122 @@ pub async fn list_buckets(client: &Client) -> Result<()> {
 
     for bucket in buckets {
         let name = bucket.name().unwrap_or("<unknown>").to_string();
-        let creation_date = bucket.creation_date()
+        if filter.is_some_and(|f| !f.matches(&name)) {
+            continue;
+        }
+        let created = bucket.creation_date().cloned();
+        let creation_date = created
             .map(|d| d.to_string())
             .unwrap_or_else(|| "Unknown".to_string());
         
-        bucket_infos.push(BucketInfo { name, creation_date });
+        bucket_infos.push(BucketInfo { name, creation_date, created });
+    }
+
+    sort_buckets(&mut bucket_infos, sort, reverse)?;
+
+    if output == OutputFormat::Json {
+        let buckets: Vec<_> = bucket_infos.iter()
+            .map(|b| serde_json::json!({ "name": b.name, "creation_date": b.creation_date }))
+            .collect();
+        println!("{}", serde_json::json!({ "buckets": buckets, "count": bucket_infos.len() }));
+        return Ok(());
+    }
+    if output == OutputFormat::Csv {
+        print!("{}", format_csv(&bucket_infos, &[])?);
+        return Ok(());
     }
 
     if bucket_infos.is_empty() {
-        println!("{}", "No buckets found.".yellow());
+        match filter {
+            Some(_) => note("No buckets match the filter."),
+            None => note("No buckets found."),
+        }
     } else {
+        let summary = pluralize(bucket_infos.len(), "bucket");
         let table = Table::new(bucket_infos).to_string();
         println!("{}", table);
+        footer(summary);
     }
 
     Ok(())
 }
 
+/// Default encryption applied by `bucket create`/`update --encryption`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketEncryption {
+    pub algorithm: aws_sdk_s3::types::ServerSideEncryption,
+    pub kms_key_id: Option<String>,
+    /// Use an S3 Bucket Key to cut KMS request costs
+    pub bucket_key: bool,
+}
+
+/// Parse `--encryption`, `--kms-key-id` and `--bucket-key`. A KMS key id is
+/// only valid with `aws:kms`.
+pub fn parse_bucket_encryption(mode: &str, kms_key_id: Option<&str>, bucket_key: bool) -> Result<BucketEncryption> {
+    let algorithm = match mode {
+        "AES256" => aws_sdk_s3::types::ServerSideEncryption::Aes256,
+        "aws:kms" => aws_sdk_s3::types::ServerSideEncryption::AwsKms,
+        other => return Err(usage_error(format!("Invalid encryption mode: {}. Use 'AES256' or 'aws:kms'", other))),
+    };
+
+    if kms_key_id.is_some() && algorithm != aws_sdk_s3::types::ServerSideEncryption::AwsKms {
+        return Err(usage_error("--kms-key-id can only be used with --encryption aws:kms"));
+    }
+
+    Ok(BucketEncryption { algorithm, kms_key_id: kms_key_id.map(str::to_string), bucket_key })
+}
+
+/// Whether a canned ACL grants access beyond the bucket owner, which the
+/// public access block set by `--public false` would then reject.
+pub fn is_public_canned_acl(acl: &BucketCannedAcl) -> bool {
+    matches!(acl, BucketCannedAcl::PublicRead | BucketCannedAcl::PublicReadWrite | BucketCannedAcl::AuthenticatedRead)
+}
+
+/// Create a bucket, then apply any of the other settings. A canned `acl`
+/// other than `private` is sent with the create call, along with object
+/// ownership `BucketOwnerPreferred`, since new buckets have ACLs disabled.
+#[allow(clippy::too_many_arguments)]
 pub async fn create_bucket(
     client: &Client,
     bucket_name: &str,
     region: &str,
+    acl: Option<BucketCannedAcl>,
     public: Option<bool>,
     versioning: Option<bool>,
-    encryption: Option<String>,
+    encryption: Option<BucketEncryption>,
     tags: Vec<(String, String)>,
+    dry_run: bool,
 ) -> Result<()> {
     validate_bucket_name(bucket_name)?;
 
+    if let Some(acl) = &acl
+        && is_public_canned_acl(acl)
+        && public == Some(false)
+    {
+        eprintln!(
+            "{} {}",
+            "WARNING:".yellow().bold(),
+            format!("--acl {} with --public false: the public access block will override the ACL", acl.as_str()).yellow()
+        );
+    }
+
+    if dry_run {
+        match &acl {
+            Some(acl) => print_dry_run(&format!("CreateBucket (ACL '{}')", acl.as_str()), bucket_name, None),
+            None => print_dry_run("CreateBucket", bucket_name, None),
+        }
+        return update_bucket(client, bucket_name, public, versioning, None, encryption, tags, None, true).await;
+    }
+
     let mut builder = client.create_bucket().bucket(bucket_name);
 
+    if let Some(acl) = acl {
+        if acl != BucketCannedAcl::Private {
+            builder = builder.object_ownership(ObjectOwnership::BucketOwnerPreferred);
+        }
+        builder = builder.acl(acl);
+    }
+
     if region != "us-east-1" {
         let constraint = aws_sdk_s3::types::BucketLocationConstraint::from(region);
         let cfg = aws_sdk_s3::types::CreateBucketConfiguration::builder()
@@ -57,14 +238,28 @@ pub async fn create_bucket(
         builder = builder.create_bucket_configuration(cfg);
     }
 
-    builder.send().await?;
+    if let Err(err) = builder.send().await {
+        let owned = anyhow::anyhow!("Bucket '{}' already exists and is owned by you", bucket_name);
+        return Err(match err.code() {
+            Some("BucketAlreadyOwnedByYou") => owned,
+            // Some S3-compatible servers use this code for the caller's own buckets too
+            Some("BucketAlreadyExists") => match bucket_exists(client, bucket_name).await {
+                Ok(true) => owned,
+                _ => anyhow::anyhow!("Bucket name '{}' is already taken by another account", bucket_name),
+            },
+            Some("InvalidBucketAclWithBlockPublicAccessError") => anyhow::anyhow!(
+                "S3 blocks public ACLs on new buckets by default; create the bucket without --acl, turn off the public access block, then set the ACL with 'bucket acl set'"
+            ),
+            _ => err.into(),
+        });
+    }
 
-    println!("{} Bucket '{}' created successfully.", "✔".green(), bucket_name);
+    success(format!("Bucket '{}' created successfully.", bucket_name));
 
     // Apply configurations if any
     if public.is_some() || versioning.is_some() || encryption.is_some() || !tags.is_empty() {
-        println!("Applying configurations...");
-        update_bucket(client, bucket_name, public, versioning, encryption, tags).await?;
+        status("Applying configurations...");
+        update_bucket(client, bucket_name, public, versioning, None, encryption, tags, None, false).await?;
     }
 
     Ok(())
@@ -72,18 +267,50 @@ pub async fn create_bucket(
 
 pub fn validate_bucket_name(name: &str) -> Result<()> {
     if name.len() < 3 || name.len() > 63 {
-        return Err(anyhow::anyhow!("Bucket name must be between 3 and 63 characters"));
+        return Err(usage_error("Bucket name must be between 3 and 63 characters"));
     }
     if !name.chars().all(|c| c.is_ascii_lowercase() || c.is_numeric() || c == '.' || c == '-') {
-        return Err(anyhow::anyhow!("Bucket name must only contain lowercase letters, numbers, dots, and hyphens"));
+        return Err(usage_error("Bucket name must only contain lowercase letters, numbers, dots, and hyphens"));
     }
-    if name.starts_with(|c| c == '.' || c == '-') || name.ends_with(|c| c == '.' || c == '-') {
-         return Err(anyhow::anyhow!("Bucket name must begin and end with a letter or number"));
+    if name.starts_with(['.', '-']) || name.ends_with(['.', '-']) {
+         return Err(usage_error("Bucket name must begin and end with a letter or number"));
     }
     Ok(())
 }
 
-pub async fn get_bucket_config(client: &Client, bucket_name: &str) -> Result<()> {
+/// Whether a bucket exists, using `HeadBucket`. A bucket that exists but
+/// belongs to someone else is reported as an access-denied error.
+pub async fn bucket_exists(client: &Client, bucket_name: &str) -> Result<bool> {
+    match client.head_bucket().bucket(bucket_name).send().await {
+        Ok(_) => Ok(true),
+        // HEAD responses have no body, so go by the status code
+        Err(err) => match err.raw_response().map(|r| r.status().as_u16()) {
+            Some(404) => Ok(false),
+            Some(403) => Err(anyhow::anyhow!("Access denied to bucket '{}'", bucket_name)),
+            _ => Err(err.into()),
+        },
+    }
+}
+
+/// Status shown for a configuration sub-call that failed. The feature simply
+/// not being configured is reported as "Not set"; anything else as unavailable.
+fn config_error_status<E: ProvideErrorMetadata>(err: &E, not_configured_codes: &[&str]) -> ColoredString {
+    match err.code() {
+        Some(code) if not_configured_codes.contains(&code) => "Not set".yellow(),
+        Some(code) => format!("Unavailable ({})", code).red(),
+        None => "Unavailable".red(),
+    }
+}
+
+/// Show a bucket's region, public access, encryption, versioning and tags.
+/// With `--output json`, print the full [`BucketConfigExport`] instead.
+pub async fn get_bucket_config(client: &Client, bucket_name: &str, output: OutputFormat) -> Result<()> {
+    if output == OutputFormat::Json {
+        let export = export_bucket_config(client, bucket_name).await?;
+        println!("{}", serde_json::to_string_pretty(&export)?);
+        return Ok(());
+    }
+
     // 1. Location
     let location = client.get_bucket_location()
         .bucket(bucket_name)
@@ -102,15 +329,15 @@ pub async fn get_bucket_config(client: &Client, bucket_name: &str) -> Result<()>
         .await;
     
     let public_status = match public_access {
-        Ok(output) => {
-            let conf = output.public_access_block_configuration().unwrap();
-            if conf.block_public_acls().unwrap_or(false) && conf.ignore_public_acls().unwrap_or(false) && conf.block_public_policy().unwrap_or(false) && conf.restrict_public_buckets().unwrap_or(false) {
-                "Private (All Blocked)".green()
-            } else {
-                "Custom / Public".yellow()
-            }
+        Ok(output) => match output.public_access_block_configuration() {
+            Some(conf) if conf.block_public_acls().unwrap_or(false)
+                && conf.ignore_public_acls().unwrap_or(false)
+                && conf.block_public_policy().unwrap_or(false)
+                && conf.restrict_public_buckets().unwrap_or(false) => "Private (All Blocked)".green(),
+            Some(_) => "Custom / Public".yellow(),
+            None => "Not set".yellow(),
         },
-        Err(_) => "Not Configured".yellow(),
+        Err(err) => config_error_status(&err, &["NoSuchPublicAccessBlockConfiguration"]),
     };
 
     // 3. Encryption
@@ -121,21 +348,17 @@ pub async fn get_bucket_config(client: &Client, bucket_name: &str) -> Result<()>
     
     let encryption_status = match encryption {
         Ok(output) => {
-            if let Some(rules) = output.server_side_encryption_configuration().map(|c| c.rules()) {
-                if let Some(first) = rules.first() {
-                    if let Some(def) = first.apply_server_side_encryption_by_default() {
-                        format!("{:?}", def.sse_algorithm()).cyan()
-                    } else {
-                        "Enabled (Unknown Algo)".cyan()
-                    }
-                } else {
-                    "Disabled".yellow()
-                }
-            } else {
-                "Disabled".yellow()
+            let first_rule = output.server_side_encryption_configuration()
+                .and_then(|c| c.rules().first());
+            match first_rule {
+                Some(rule) => match rule.apply_server_side_encryption_by_default() {
+                    Some(def) => def.sse_algorithm().as_str().cyan(),
+                    None => "Enabled (Unknown Algo)".cyan(),
+                },
+                None => "Not set".yellow(),
             }
         },
-        Err(_) => "Disabled".yellow(),
+        Err(err) => config_error_status(&err, &["ServerSideEncryptionConfigurationNotFoundError"]),
     };
 
     // 4. Versioning
@@ -147,11 +370,12 @@ pub async fn get_bucket_config(client: &Client, bucket_name: &str) -> Result<()>
     let versioning_status = match versioning {
         Ok(output) => {
             match output.status() {
-                Some(s) => format!("{:?}", s).cyan(),
-                None => "Suspended".yellow(),
+                Some(s) => s.as_str().cyan(),
+                // Versioning has never been enabled on this bucket
+                None => "Not set".yellow(),
             }
         },
-        Err(_) => "Unknown".red(),
+        Err(err) => config_error_status(&err, &[]),
     };
 
     // 5. Tags
@@ -164,15 +388,16 @@ pub async fn get_bucket_config(client: &Client, bucket_name: &str) -> Result<()>
         Ok(output) => {
             let tags = output.tag_set();
             if tags.is_empty() {
-                "None".to_string()
+                "Not set".yellow()
             } else {
                 tags.iter()
                     .map(|t| format!("{}={}", t.key(), t.value()))
                     .collect::<Vec<_>>()
                     .join(", ")
+                    .normal()
             }
         },
-        Err(_) => "None".to_string(),
+        Err(err) => config_error_status(&err, &["NoSuchTagSet"]),
     };
 
     println!("Bucket: {}", bucket_name.bold());
@@ -185,24 +410,251 @@ pub async fn get_bucket_config(client: &Client, bucket_name: &str) -> Result<()>
     Ok(())
 }
 
+/// A bucket's configuration as `bucket config --output json` exports it,
+/// shaped to be read back and applied to a bucket. A setting that is not
+/// configured is `null` (or empty for `tags` and `lifecycle`).
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BucketConfigExport {
+    pub bucket: String,
+    pub region: String,
+    pub public_access_block: Option<PublicAccessBlockExport>,
+    pub encryption: Option<EncryptionExport>,
+    pub versioning: Option<VersioningExport>,
+    pub tags: BTreeMap<String, String>,
+    pub lifecycle: Vec<LifecycleRuleExport>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PublicAccessBlockExport {
+    pub block_public_acls: bool,
+    pub ignore_public_acls: bool,
+    pub block_public_policy: bool,
+    pub restrict_public_buckets: bool,
+}
+
+/// Default encryption, as `--encryption`, `--kms-key-id` and `--bucket-key`.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct EncryptionExport {
+    pub algorithm: String,
+    pub kms_key_id: Option<String>,
+    pub bucket_key: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct VersioningExport {
+    /// `Enabled` or `Suspended`
+    pub status: String,
+    pub mfa_delete: bool,
+}
+
+/// A lifecycle rule in the terms of `bucket lifecycle put`: `filter` has the
+/// `--filter` JSON shape and `transitions` the `--transitions` one.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LifecycleRuleExport {
+    pub id: String,
+    pub enabled: bool,
+    pub filter: LifecycleFilterExport,
+    pub transitions: Vec<TransitionExport>,
+    pub expiration_days: Option<i32>,
+    pub noncurrent_expiration_days: Option<i32>,
+    pub abort_incomplete_upload_days: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LifecycleFilterExport {
+    pub prefix: Option<String>,
+    pub tags: BTreeMap<String, String>,
+    pub object_size_greater_than: Option<i64>,
+    pub object_size_less_than: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TransitionExport {
+    pub days: Option<i32>,
+    pub storage_class: String,
+}
+
+impl From<&aws_sdk_s3::types::LifecycleRule> for LifecycleRuleExport {
+    fn from(rule: &aws_sdk_s3::types::LifecycleRule) -> LifecycleRuleExport {
+        let tags = |tags: &[Tag]| tags.iter().map(|t| (t.key().to_string(), t.value().to_string())).collect();
+        #[allow(deprecated)]
+        let legacy_prefix = rule.prefix();
+        let filter = match rule.filter() {
+            Some(filter) => match filter.and() {
+                Some(and) => LifecycleFilterExport {
+                    prefix: and.prefix().map(str::to_string),
+                    tags: tags(and.tags()),
+                    object_size_greater_than: and.object_size_greater_than(),
+                    object_size_less_than: and.object_size_less_than(),
+                },
+                None => LifecycleFilterExport {
+                    prefix: filter.prefix().map(str::to_string),
+                    tags: tags(filter.tag().map(std::slice::from_ref).unwrap_or_default()),
+                    object_size_greater_than: filter.object_size_greater_than(),
+                    object_size_less_than: filter.object_size_less_than(),
+                },
+            },
+            None => LifecycleFilterExport { prefix: legacy_prefix.map(str::to_string), ..Default::default() },
+        };
+
+        LifecycleRuleExport {
+            id: rule.id().unwrap_or_default().to_string(),
+            enabled: rule.status() == &aws_sdk_s3::types::ExpirationStatus::Enabled,
+            filter,
+            transitions: rule.transitions().iter()
+                .map(|t| TransitionExport {
+                    days: t.days(),
+                    storage_class: t.storage_class().map(|c| c.as_str().to_string()).unwrap_or_default(),
+                })
+                .collect(),
+            expiration_days: rule.expiration().and_then(|e| e.days()),
+            noncurrent_expiration_days: rule.noncurrent_version_expiration().and_then(|e| e.noncurrent_days()),
+            abort_incomplete_upload_days: rule.abort_incomplete_multipart_upload().and_then(|a| a.days_after_initiation()),
+        }
+    }
+}
+
+/// The result of a configuration read, with "not configured" errors (the
+/// codes in `not_configured_codes`) as `None`. Any other error fails the
+/// export rather than leaving a setting out of it.
+fn configured<T, E>(result: Result<T, aws_sdk_s3::error::SdkError<E>>, what: &str, not_configured_codes: &[&str]) -> Result<Option<T>>
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    match result {
+        Ok(output) => Ok(Some(output)),
+        Err(err) if err.code().is_some_and(|code| not_configured_codes.contains(&code)) => Ok(None),
+        Err(err) => Err(anyhow::Error::from(err).context(format!("Failed to read the {} configuration", what))),
+    }
+}
+
+/// Read everything [`BucketConfigExport`] holds from the bucket.
+pub async fn export_bucket_config(client: &Client, bucket_name: &str) -> Result<BucketConfigExport> {
+    let location = client.get_bucket_location().bucket(bucket_name).send().await?;
+    let region = location.location_constraint()
+        .map(|l| l.as_str())
+        .filter(|s| !s.is_empty())
+        .unwrap_or("us-east-1")
+        .to_string();
+
+    let public_access = client.get_public_access_block().bucket(bucket_name).send().await;
+    let public_access_block = configured(public_access, "public access block", &["NoSuchPublicAccessBlockConfiguration"])?
+        .and_then(|output| output.public_access_block_configuration().map(|conf| PublicAccessBlockExport {
+            block_public_acls: conf.block_public_acls().unwrap_or(false),
+            ignore_public_acls: conf.ignore_public_acls().unwrap_or(false),
+            block_public_policy: conf.block_public_policy().unwrap_or(false),
+            restrict_public_buckets: conf.restrict_public_buckets().unwrap_or(false),
+        }));
+
+    let encryption = client.get_bucket_encryption().bucket(bucket_name).send().await;
+    let encryption = configured(encryption, "encryption", &["ServerSideEncryptionConfigurationNotFoundError"])?
+        .and_then(|output| {
+            let rule = output.server_side_encryption_configuration()?.rules().first()?;
+            let default = rule.apply_server_side_encryption_by_default()?;
+            Some(EncryptionExport {
+                algorithm: default.sse_algorithm().as_str().to_string(),
+                kms_key_id: default.kms_master_key_id().map(str::to_string),
+                bucket_key: rule.bucket_key_enabled().unwrap_or(false),
+            })
+        });
+
+    let versioning = client.get_bucket_versioning().bucket(bucket_name).send().await;
+    let versioning = configured(versioning, "versioning", &[])?
+        .and_then(|output| {
+            // Absent on a bucket that has never had versioning enabled
+            let status = output.status()?.as_str().to_string();
+            let mfa_delete = output.mfa_delete() == Some(&aws_sdk_s3::types::MfaDeleteStatus::Enabled);
+            Some(VersioningExport { status, mfa_delete })
+        });
+
+    let tagging = client.get_bucket_tagging().bucket(bucket_name).send().await;
+    let tags = configured(tagging, "tagging", &["NoSuchTagSet"])?
+        .map(|output| output.tag_set().iter().map(|t| (t.key().to_string(), t.value().to_string())).collect())
+        .unwrap_or_default();
+
+    let lifecycle = get_lifecycle_rules(client, bucket_name).await?
+        .iter()
+        .map(LifecycleRuleExport::from)
+        .collect();
+
+    Ok(BucketConfigExport {
+        bucket: bucket_name.to_string(),
+        region,
+        public_access_block,
+        encryption,
+        versioning,
+        tags,
+        lifecycle,
+    })
+}
+
+pub async fn get_versioning_status(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_versioning()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    print!("{}", format_versioning_status(bucket_name, &resp));
+    Ok(())
+}
+
+/// Render a `get_bucket_versioning` response. S3 omits both fields for a
+/// bucket that has never had versioning configured.
+pub fn format_versioning_status(bucket_name: &str, versioning: &GetBucketVersioningOutput) -> String {
+    let status = versioning.status().map(|s| s.as_str()).unwrap_or("Not set");
+    let mfa_delete = versioning.mfa_delete().map(|m| m.as_str()).unwrap_or("Not set");
+    format!(
+        "Bucket: {}\nVersioning: {}\nMFA Delete: {}\n",
+        bucket_name.bold(),
+        status.cyan(),
+        mfa_delete.cyan()
+    )
+}
+
+/// Apply the given settings to a bucket. `mfa_delete` is changed together
+/// with `versioning` and, like it, needs `mfa` ("serial code") to be set.
+#[allow(clippy::too_many_arguments)]
 pub async fn update_bucket(
     client: &Client,
     bucket_name: &str,
     public: Option<bool>,
     versioning: Option<bool>,
-    encryption: Option<String>,
+    mfa_delete: Option<bool>,
+    encryption: Option<BucketEncryption>,
     tags: Vec<(String, String)>,
+    mfa: Option<&str>,
+    dry_run: bool,
 ) -> Result<()> {
+    if mfa_delete.is_some() && (versioning.is_none() || mfa.is_none()) {
+        return Err(anyhow::anyhow!("Changing MFA delete requires --versioning and --mfa"));
+    }
+
+    if dry_run {
+        if public.is_some() {
+            print_dry_run("PutPublicAccessBlock", bucket_name, None);
+        }
+        if versioning.is_some() {
+            print_dry_run("PutBucketVersioning", bucket_name, None);
+        }
+        if encryption.is_some() {
+            print_dry_run("PutBucketEncryption", bucket_name, None);
+        }
+        if !tags.is_empty() {
+            print_dry_run("PutBucketTagging", bucket_name, None);
+        }
+        return Ok(());
+    }
+
     if let Some(is_public) = public {
         set_public_access(client, bucket_name, is_public).await?;
     }
 
     if let Some(enabled) = versioning {
-        set_versioning(client, bucket_name, enabled).await?;
+        set_versioning(client, bucket_name, enabled, mfa_delete, mfa).await?;
     }
 
-    if let Some(mode) = encryption {
-        set_encryption(client, bucket_name, &mode).await?;
+    if let Some(encryption) = &encryption {
+        set_encryption(client, bucket_name, encryption).await?;
     }
 
     if !tags.is_empty() {
@@ -236,49 +688,52 @@ async fn set_public_access(client: &Client, bucket_name: &str, is_public: bool)
         .await?;
 
     let status = if is_public { "Public" } else { "Private" };
-    println!("{} Bucket '{}' public access set to: {}", "✔".green(), bucket_name, status.cyan());
+    success(format!("Bucket '{}' public access set to: {}", bucket_name, status.cyan()));
     Ok(())
 }
 
-async fn set_versioning(client: &Client, bucket_name: &str, enabled: bool) -> Result<()> {
+async fn set_versioning(client: &Client, bucket_name: &str, enabled: bool, mfa_delete: Option<bool>, mfa: Option<&str>) -> Result<()> {
     let status = if enabled {
         aws_sdk_s3::types::BucketVersioningStatus::Enabled
     } else {
         aws_sdk_s3::types::BucketVersioningStatus::Suspended
     };
 
+    let mfa_delete = mfa_delete.map(|enabled| if enabled {
+        aws_sdk_s3::types::MfaDelete::Enabled
+    } else {
+        aws_sdk_s3::types::MfaDelete::Disabled
+    });
+
     let config = aws_sdk_s3::types::VersioningConfiguration::builder()
         .status(status.clone())
+        .set_mfa_delete(mfa_delete.clone())
         .build();
 
     client.put_bucket_versioning()
         .bucket(bucket_name)
         .versioning_configuration(config)
+        .set_mfa(mfa.map(|m| m.to_string()))
         .send()
         .await?;
 
-    println!("{} Bucket '{}' versioning set to: {}", "✔".green(), bucket_name, format!("{:?}", status).cyan());
+    success(format!("Bucket '{}' versioning set to: {}", bucket_name, format!("{:?}", status).cyan()));
+    if let Some(mfa_delete) = mfa_delete {
+        success(format!("Bucket '{}' MFA delete set to: {}", bucket_name, mfa_delete.as_str().cyan()));
+    }
     Ok(())
 }
 
-async fn set_encryption(client: &Client, bucket_name: &str, mode: &str) -> Result<()> {
-    let rule = match mode {
-        "AES256" => aws_sdk_s3::types::ServerSideEncryptionRule::builder()
-            .apply_server_side_encryption_by_default(
-                aws_sdk_s3::types::ServerSideEncryptionByDefault::builder()
-                    .sse_algorithm(aws_sdk_s3::types::ServerSideEncryption::Aes256)
-                    .build()?
-            )
-            .build(),
-        "aws:kms" => aws_sdk_s3::types::ServerSideEncryptionRule::builder()
-            .apply_server_side_encryption_by_default(
-                aws_sdk_s3::types::ServerSideEncryptionByDefault::builder()
-                    .sse_algorithm(aws_sdk_s3::types::ServerSideEncryption::AwsKms)
-                    .build()?
-            )
-            .build(),
-        _ => return Err(anyhow::anyhow!("Invalid encryption mode. Use 'AES256' or 'aws:kms'")),
-    };
+async fn set_encryption(client: &Client, bucket_name: &str, encryption: &BucketEncryption) -> Result<()> {
+    let rule = aws_sdk_s3::types::ServerSideEncryptionRule::builder()
+        .apply_server_side_encryption_by_default(
+            aws_sdk_s3::types::ServerSideEncryptionByDefault::builder()
+                .sse_algorithm(encryption.algorithm.clone())
+                .set_kms_master_key_id(encryption.kms_key_id.clone())
+                .build()?
+        )
+        .set_bucket_key_enabled(encryption.bucket_key.then_some(true))
+        .build();
 
     let config = aws_sdk_s3::types::ServerSideEncryptionConfiguration::builder()
         .rules(rule)
@@ -290,7 +745,11 @@ async fn set_encryption(client: &Client, bucket_name: &str, mode: &str) -> Resul
         .send()
         .await?;
 
-    println!("{} Bucket '{}' encryption set to: {}", "✔".green(), bucket_name, mode.cyan());
+    let mut applied = encryption.algorithm.as_str().to_string();
+    if let Some(key) = &encryption.kms_key_id {
+        applied.push_str(&format!(" (key {})", key));
+    }
+    success(format!("Bucket '{}' encryption set to: {}", bucket_name, applied.cyan()));
     Ok(())
 }
 
@@ -311,7 +770,7 @@ async fn set_tags(client: &Client, bucket_name: &str, tags: Vec<(String, String)
         .send()
         .await?;
 
-    println!("{} Bucket '{}' tags updated.", "✔".green(), bucket_name);
+    success(format!("Bucket '{}' tags updated.", bucket_name));
     Ok(())
 }
 
@@ -321,74 +780,188 @@ struct TransitionInput {
     storage_class: String,
 }
 
-pub async fn put_lifecycle_rule(
-    client: &Client,
-    bucket_name: &str,
-    rule_id: &str,
-    prefix: &str,
-    transitions_json: &str,
-    expiration_days: Option<i32>,
-    status: bool,
-) -> Result<()> {
-    let transitions_input: Vec<TransitionInput> = serde_json::from_str(transitions_json)
-        .map_err(|e| anyhow::anyhow!("Invalid transitions JSON: {}", e))?;
-
-    let mut transitions = Vec::new();
-    for t in transitions_input {
-        let class = match t.storage_class.as_str() {
-            "STANDARD_IA" => aws_sdk_s3::types::TransitionStorageClass::StandardIa,
-            "ONEZONE_IA" => aws_sdk_s3::types::TransitionStorageClass::OnezoneIa,
-            "INTELLIGENT_TIERING" => aws_sdk_s3::types::TransitionStorageClass::IntelligentTiering,
-            "GLACIER" => aws_sdk_s3::types::TransitionStorageClass::Glacier,
-            "DEEP_ARCHIVE" => aws_sdk_s3::types::TransitionStorageClass::DeepArchive,
-             _ => return Err(anyhow::anyhow!("Invalid storage class: {}", t.storage_class)),
-        };
-        
-        transitions.push(
-            aws_sdk_s3::types::Transition::builder()
-                .days(t.days)
-                .storage_class(class)
-                .build(),
-        );
+/// A `--filter` JSON document, e.g.
+/// `{"prefix": "logs/", "tags": {"env": "prod"}, "object_size_greater_than": 1024}`.
+#[derive(serde::Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct LifecycleFilterInput {
+    prefix: Option<String>,
+    tags: std::collections::BTreeMap<String, String>,
+    object_size_greater_than: Option<i64>,
+    object_size_less_than: Option<i64>,
+}
+
+/// Build a rule's filter from a `--filter` JSON document. A single criterion
+/// is used on its own; several are combined with `And`, which S3 requires.
+/// An empty document matches every object.
+pub fn parse_lifecycle_filter(json: &str) -> Result<LifecycleRuleFilter> {
+    let input: LifecycleFilterInput = serde_json::from_str(json)
+        .map_err(|e| usage_error(format!("Invalid filter JSON: {}", e)))?;
+
+    if let (Some(min), Some(max)) = (input.object_size_greater_than, input.object_size_less_than)
+        && min >= max
+    {
+        return Err(usage_error(format!(
+            "object_size_greater_than ({}) must be less than object_size_less_than ({})", min, max
+        )));
+    }
+
+    let mut tags = Vec::new();
+    for (key, value) in input.tags {
+        tags.push(Tag::builder().key(key).value(value).build()?);
     }
 
-    let expiration = if let Some(days) = expiration_days {
-        Some(aws_sdk_s3::types::LifecycleExpiration::builder().days(days).build())
+    let criteria = usize::from(input.prefix.is_some())
+        + tags.len()
+        + usize::from(input.object_size_greater_than.is_some())
+        + usize::from(input.object_size_less_than.is_some());
+
+    let filter = if criteria > 1 {
+        LifecycleRuleFilter::builder()
+            .and(aws_sdk_s3::types::LifecycleRuleAndOperator::builder()
+                .set_prefix(input.prefix)
+                .set_tags(Some(tags))
+                .set_object_size_greater_than(input.object_size_greater_than)
+                .set_object_size_less_than(input.object_size_less_than)
+                .build())
+            .build()
     } else {
-        None
+        LifecycleRuleFilter::builder()
+            .set_prefix(input.prefix.or_else(|| (criteria == 0).then(String::new)))
+            .set_tag(tags.pop())
+            .set_object_size_greater_than(input.object_size_greater_than)
+            .set_object_size_less_than(input.object_size_less_than)
+            .build()
     };
+    Ok(filter)
+}
 
-    let rule_status = if status {
-        aws_sdk_s3::types::ExpirationStatus::Enabled
-    } else {
-        aws_sdk_s3::types::ExpirationStatus::Disabled
+/// Describe a rule's filter for `lifecycle list`, e.g.
+/// `prefix=logs/, env=prod, size>1024`.
+pub fn describe_lifecycle_filter(filter: Option<&LifecycleRuleFilter>) -> String {
+    let Some(filter) = filter else { return String::new() };
+    let describe = |prefix: Option<&str>, tags: &[Tag], min: Option<i64>, max: Option<i64>| {
+        let mut parts = Vec::new();
+        if let Some(prefix) = prefix.filter(|p| !p.is_empty()) {
+            parts.push(format!("prefix={}", prefix));
+        }
+        parts.extend(tags.iter().map(|t| format!("{}={}", t.key(), t.value())));
+        parts.extend(min.map(|n| format!("size>{}", n)));
+        parts.extend(max.map(|n| format!("size<{}", n)));
+        parts.join(", ")
     };
+    match filter.and() {
+        Some(and) => describe(and.prefix(), and.tags(), and.object_size_greater_than(), and.object_size_less_than()),
+        None => describe(
+            filter.prefix(),
+            filter.tag().map(std::slice::from_ref).unwrap_or_default(),
+            filter.object_size_greater_than(),
+            filter.object_size_less_than(),
+        ),
+    }
+}
 
-    let new_rule = aws_sdk_s3::types::LifecycleRule::builder()
-        .id(rule_id)
-        .filter(LifecycleRuleFilter::builder().prefix(prefix.to_string()).build())
-        .status(rule_status)
-        .set_transitions(Some(transitions))
-        .set_expiration(expiration)
-        .build()?;
+/// The options of `bucket lifecycle put`.
+#[derive(Debug, Clone, Default)]
+pub struct LifecycleRuleSpec {
+    pub id: String,
+    /// Prefix filter, used when `filter` is not given
+    pub prefix: String,
+    /// Richer filter as JSON (see `parse_lifecycle_filter`)
+    pub filter: Option<String>,
+    /// Transitions as a JSON array of `{"days", "storage_class"}`
+    pub transitions: String,
+    pub expiration_days: Option<i32>,
+    /// Days after becoming noncurrent that old versions are deleted
+    pub noncurrent_expiration_days: Option<i32>,
+    /// Days after starting that incomplete multipart uploads are aborted
+    pub abort_incomplete_upload_days: Option<i32>,
+    pub enabled: bool,
+}
 
-    // Fetch existing config
-    let current_config = client.get_bucket_lifecycle_configuration()
-        .bucket(bucket_name)
-        .send()
-        .await;
+impl LifecycleRuleSpec {
+    pub fn build(&self) -> Result<aws_sdk_s3::types::LifecycleRule> {
+        let transitions_input: Vec<TransitionInput> = serde_json::from_str(&self.transitions)
+            .map_err(|e| anyhow::anyhow!("Invalid transitions JSON: {}", e))?;
 
-    let mut rules = match current_config {
-        Ok(output) => output.rules.unwrap_or_default(),
-        Err(err) => {
-            // Check if error is NoSuchLifecycleConfiguration
-            if err.meta().code() == Some("NoSuchLifecycleConfiguration") {
-                Vec::new()
-            } else {
-                 return Err(anyhow::anyhow!("Failed to get lifecycle config: {}", err));
-            }
+        let mut transitions = Vec::new();
+        for t in transitions_input {
+            let class = match t.storage_class.as_str() {
+                "STANDARD_IA" => aws_sdk_s3::types::TransitionStorageClass::StandardIa,
+                "ONEZONE_IA" => aws_sdk_s3::types::TransitionStorageClass::OnezoneIa,
+                "INTELLIGENT_TIERING" => aws_sdk_s3::types::TransitionStorageClass::IntelligentTiering,
+                "GLACIER" => aws_sdk_s3::types::TransitionStorageClass::Glacier,
+                "DEEP_ARCHIVE" => aws_sdk_s3::types::TransitionStorageClass::DeepArchive,
+                 _ => return Err(anyhow::anyhow!("Invalid storage class: {}", t.storage_class)),
+            };
+
+            transitions.push(
+                aws_sdk_s3::types::Transition::builder()
+                    .days(t.days)
+                    .storage_class(class)
+                    .build(),
+            );
         }
-    };
+
+        if transitions.is_empty()
+            && self.expiration_days.is_none()
+            && self.noncurrent_expiration_days.is_none()
+            && self.abort_incomplete_upload_days.is_none()
+        {
+            return Err(usage_error(
+                "A lifecycle rule needs at least one action: --transitions, --expiration, --noncurrent-expiration or --abort-incomplete-upload",
+            ));
+        }
+
+        let filter = match &self.filter {
+            Some(json) => parse_lifecycle_filter(json)?,
+            None => LifecycleRuleFilter::builder().prefix(self.prefix.clone()).build(),
+        };
+
+        let expiration = self.expiration_days
+            .map(|days| aws_sdk_s3::types::LifecycleExpiration::builder().days(days).build());
+        let noncurrent_expiration = self.noncurrent_expiration_days
+            .map(|days| aws_sdk_s3::types::NoncurrentVersionExpiration::builder().noncurrent_days(days).build());
+        let abort_incomplete = self.abort_incomplete_upload_days
+            .map(|days| aws_sdk_s3::types::AbortIncompleteMultipartUpload::builder().days_after_initiation(days).build());
+
+        let rule_status = if self.enabled {
+            aws_sdk_s3::types::ExpirationStatus::Enabled
+        } else {
+            aws_sdk_s3::types::ExpirationStatus::Disabled
+        };
+
+        Ok(aws_sdk_s3::types::LifecycleRule::builder()
+            .id(&self.id)
+            .filter(filter)
+            .status(rule_status)
+            .set_transitions(Some(transitions))
+            .set_expiration(expiration)
+            .set_noncurrent_version_expiration(noncurrent_expiration)
+            .set_abort_incomplete_multipart_upload(abort_incomplete)
+            .build()?)
+    }
+}
+
+/// Add `rule` to the bucket's lifecycle configuration, replacing any rule
+/// with the same ID.
+pub async fn put_lifecycle_rule(
+    client: &Client,
+    bucket_name: &str,
+    rule: &LifecycleRuleSpec,
+    dry_run: bool,
+) -> Result<()> {
+    let rule_id = rule.id.as_str();
+    let new_rule = rule.build()?;
+
+    if dry_run {
+        print_dry_run("GetBucketLifecycleConfiguration", bucket_name, None);
+        print_dry_run("PutBucketLifecycleConfiguration", bucket_name, None);
+        return Ok(());
+    }
+
+    // Fetch existing config
+    let mut rules = get_lifecycle_rules(client, bucket_name).await?;
 
     // Remove existing rule with same ID
     rules.retain(|r| r.id.as_deref() != Some(rule_id));
@@ -406,78 +979,1610 @@ pub async fn put_lifecycle_rule(
         .send()
         .await?;
 
-    println!("{} Lifecycle rule '{}' set for bucket '{}'.", "✔".green(), rule_id, bucket_name);
+    success(format!("Lifecycle rule '{}' set for bucket '{}'.", rule_id, bucket_name));
     Ok(())
 }
 
+#[derive(Tabled)]
+struct LifecycleRuleInfo {
+    id: String,
+    filter: String,
+    status: String,
+    transitions: String,
+    expiration: String,
+}
 
-pub async fn delete_bucket(client: &Client, bucket_name: &str) -> Result<()> {
-    client.delete_bucket()
+async fn get_lifecycle_rules(client: &Client, bucket_name: &str) -> Result<Vec<aws_sdk_s3::types::LifecycleRule>> {
+    let current_config = client.get_bucket_lifecycle_configuration()
         .bucket(bucket_name)
         .send()
-        .await?;
-    
-    println!("{} Bucket '{}' deleted successfully.", "✔".green(), bucket_name);
-    Ok(())
-}
+        .await;
 
-pub async fn empty_bucket(client: &Client, bucket_name: &str) -> Result<()> {
-    println!("Emptying bucket '{}'...", bucket_name);
-    
-    loop {
-        let resp = client.list_object_versions()
-            .bucket(bucket_name)
-            .send()
-            .await?;
-        
-        let mut to_delete = Vec::new();
-
-        for v in resp.versions() {
-            if let (Some(key), Some(version_id)) = (v.key(), v.version_id()) {
-                to_delete.push(
-                    aws_sdk_s3::types::ObjectIdentifier::builder()
-                        .key(key)
-                        .version_id(version_id)
-                        .build()?
-                );
+    match current_config {
+        Ok(output) => Ok(output.rules.unwrap_or_default()),
+        Err(err) => {
+            // A bucket without lifecycle config simply has no rules
+            if err.meta().code() == Some("NoSuchLifecycleConfiguration") {
+                Ok(Vec::new())
+            } else {
+                Err(anyhow::anyhow!("Failed to get lifecycle config: {}", err))
             }
         }
+    }
+}
 
-        for m in resp.delete_markers() {
-            if let (Some(key), Some(version_id)) = (m.key(), m.version_id()) {
-                to_delete.push(
-                    aws_sdk_s3::types::ObjectIdentifier::builder()
-                        .key(key)
-                        .version_id(version_id)
-                        .build()?
-                );
-            }
-        }
+pub async fn list_lifecycle_rules(client: &Client, bucket_name: &str) -> Result<()> {
+    let rules = get_lifecycle_rules(client, bucket_name).await?;
 
+    let mut rule_infos = Vec::new();
 
-        if to_delete.is_empty() {
-            break;
+    for rule in &rules {
+        let id = rule.id().unwrap_or("<none>").to_string();
+        let filter = describe_lifecycle_filter(rule.filter());
+        let status = rule.status().as_str().to_string();
+        let transitions = rule.transitions().iter()
+            .map(|t| format!(
+                "{}d -> {}",
+                t.days().unwrap_or(0),
+                t.storage_class().map(|c| c.as_str()).unwrap_or("?")
+            ))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut expiration = rule.expiration()
+            .and_then(|e| e.days())
+            .map(|d| format!("{}d", d))
+            .unwrap_or_else(|| "-".to_string());
+        if let Some(days) = rule.noncurrent_version_expiration().and_then(|e| e.noncurrent_days()) {
+            expiration.push_str(&format!(", noncurrent {}d", days));
+        }
+        if let Some(days) = rule.abort_incomplete_multipart_upload().and_then(|a| a.days_after_initiation()) {
+            expiration.push_str(&format!(", incomplete uploads {}d", days));
         }
 
-        let count = to_delete.len();
-        
-        client.delete_objects()
+        rule_infos.push(LifecycleRuleInfo { id, filter, status, transitions, expiration });
+    }
+
+    if rule_infos.is_empty() {
+        note("No lifecycle rules found.");
+    } else {
+        let table = Table::new(rule_infos).to_string();
+        println!("{}", table);
+    }
+
+    Ok(())
+}
+
+pub async fn delete_lifecycle_rule(client: &Client, bucket_name: &str, rule_id: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        print_dry_run("GetBucketLifecycleConfiguration", bucket_name, None);
+        print_dry_run("PutBucketLifecycleConfiguration (or DeleteBucketLifecycle if no rules remain)", bucket_name, None);
+        return Ok(());
+    }
+
+    let mut rules = get_lifecycle_rules(client, bucket_name).await?;
+
+    let before = rules.len();
+    rules.retain(|r| r.id.as_deref() != Some(rule_id));
+
+    if rules.len() == before {
+        note(format!("Lifecycle rule '{}' not found in bucket '{}'.", rule_id, bucket_name));
+        return Ok(());
+    }
+
+    if rules.is_empty() {
+        client.delete_bucket_lifecycle()
             .bucket(bucket_name)
-            .delete(
-                aws_sdk_s3::types::Delete::builder()
-                    .set_objects(Some(to_delete))
-                    .build()?
-            )
             .send()
             .await?;
-            
-        println!("Deleted {} items...", count);
+    } else {
+        let lifecycle_config = aws_sdk_s3::types::BucketLifecycleConfiguration::builder()
+            .set_rules(Some(rules))
+            .build()?;
 
-        if !resp.is_truncated.unwrap_or(false) {
-            break;
+        client.put_bucket_lifecycle_configuration()
+            .bucket(bucket_name)
+            .lifecycle_configuration(lifecycle_config)
+            .send()
+            .await?;
+    }
+
+    success(format!("Lifecycle rule '{}' deleted from bucket '{}'.", rule_id, bucket_name));
+    Ok(())
+}
+
+
+pub async fn get_bucket_policy(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_policy()
+        .bucket(bucket_name)
+        .send()
+        .await;
+
+    let policy = match resp {
+        Ok(output) => output.policy.unwrap_or_default(),
+        Err(err) => {
+            if err.meta().code() == Some("NoSuchBucketPolicy") {
+                note(format!("No policy set for bucket '{}'.", bucket_name));
+                return Ok(());
+            }
+            return Err(anyhow::anyhow!("Failed to get bucket policy: {}", err));
         }
+    };
+
+    // Pretty-print when the document parses, otherwise show it as returned
+    match serde_json::from_str::<serde_json::Value>(&policy) {
+        Ok(value) => println!("{}", serde_json::to_string_pretty(&value)?),
+        Err(_) => println!("{}", policy),
+    }
+
+    Ok(())
+}
+
+pub async fn put_bucket_policy(client: &Client, bucket_name: &str, file_path: &str, dry_run: bool) -> Result<()> {
+    let policy = std::fs::read_to_string(file_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read policy file '{}': {}", file_path, e))?;
+
+    serde_json::from_str::<serde_json::Value>(&policy)
+        .map_err(|e| anyhow::anyhow!("Invalid policy JSON: {}", e))?;
+
+    if dry_run {
+        print_dry_run("PutBucketPolicy", bucket_name, None);
+        return Ok(());
     }
 
-    println!("{} Bucket '{}' is now empty.", "✔".green(), bucket_name);
+    client.put_bucket_policy()
+        .bucket(bucket_name)
+        .policy(policy)
+        .send()
+        .await?;
+
+    success(format!("Policy set for bucket '{}'.", bucket_name));
     Ok(())
 }
+
+pub async fn delete_bucket_policy(client: &Client, bucket_name: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        print_dry_run("DeleteBucketPolicy", bucket_name, None);
+        return Ok(());
+    }
+
+    client.delete_bucket_policy()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    success(format!("Policy deleted from bucket '{}'.", bucket_name));
+    Ok(())
+}
+
+#[derive(serde::Deserialize)]
+struct CorsRuleInput {
+    #[serde(default)]
+    id: Option<String>,
+    allowed_origins: Vec<String>,
+    allowed_methods: Vec<String>,
+    #[serde(default)]
+    allowed_headers: Vec<String>,
+    #[serde(default)]
+    expose_headers: Vec<String>,
+    #[serde(default)]
+    max_age_seconds: Option<i32>,
+}
+
+#[derive(Tabled)]
+struct CorsRuleInfo {
+    allowed_origins: String,
+    allowed_methods: String,
+    allowed_headers: String,
+    expose_headers: String,
+    max_age_seconds: String,
+}
+
+pub async fn get_bucket_cors(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_cors()
+        .bucket(bucket_name)
+        .send()
+        .await;
+
+    let output = match resp {
+        Ok(output) => output,
+        Err(err) => {
+            if err.meta().code() == Some("NoSuchCORSConfiguration") {
+                note(format!("No CORS configuration set for bucket '{}'.", bucket_name));
+                return Ok(());
+            }
+            return Err(anyhow::anyhow!("Failed to get CORS config: {}", err));
+        }
+    };
+
+    let mut rule_infos = Vec::new();
+
+    for rule in output.cors_rules() {
+        rule_infos.push(CorsRuleInfo {
+            allowed_origins: rule.allowed_origins().join(", "),
+            allowed_methods: rule.allowed_methods().join(", "),
+            allowed_headers: rule.allowed_headers().join(", "),
+            expose_headers: rule.expose_headers().join(", "),
+            max_age_seconds: rule.max_age_seconds()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+        });
+    }
+
+    if rule_infos.is_empty() {
+        note("No CORS rules found.");
+    } else {
+        let table = Table::new(rule_infos).to_string();
+        println!("{}", table);
+    }
+
+    Ok(())
+}
+
+pub async fn put_bucket_cors(client: &Client, bucket_name: &str, file_path: &str, dry_run: bool) -> Result<()> {
+    let contents = std::fs::read_to_string(file_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read CORS file '{}': {}", file_path, e))?;
+
+    let rules_input: Vec<CorsRuleInput> = serde_json::from_str(&contents)
+        .map_err(|e| anyhow::anyhow!("Invalid CORS JSON: {}", e))?;
+
+    let mut rules = Vec::new();
+    for r in rules_input {
+        rules.push(
+            aws_sdk_s3::types::CorsRule::builder()
+                .set_id(r.id)
+                .set_allowed_origins(Some(r.allowed_origins))
+                .set_allowed_methods(Some(r.allowed_methods))
+                .set_allowed_headers(Some(r.allowed_headers))
+                .set_expose_headers(Some(r.expose_headers))
+                .set_max_age_seconds(r.max_age_seconds)
+                .build()?,
+        );
+    }
+
+    let cors_config = aws_sdk_s3::types::CorsConfiguration::builder()
+        .set_cors_rules(Some(rules))
+        .build()?;
+
+    if dry_run {
+        print_dry_run("PutBucketCors", bucket_name, None);
+        return Ok(());
+    }
+
+    client.put_bucket_cors()
+        .bucket(bucket_name)
+        .cors_configuration(cors_config)
+        .send()
+        .await?;
+
+    success(format!("CORS configuration set for bucket '{}'.", bucket_name));
+    Ok(())
+}
+
+pub async fn delete_bucket_cors(client: &Client, bucket_name: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        print_dry_run("DeleteBucketCors", bucket_name, None);
+        return Ok(());
+    }
+
+    client.delete_bucket_cors()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    success(format!("CORS configuration deleted from bucket '{}'.", bucket_name));
+    Ok(())
+}
+
+pub async fn get_bucket_website(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_website()
+        .bucket(bucket_name)
+        .send()
+        .await;
+
+    let output = match resp {
+        Ok(output) => output,
+        Err(err) => {
+            if err.meta().code() == Some("NoSuchWebsiteConfiguration") {
+                note(format!("Static website hosting is not configured for bucket '{}'.", bucket_name));
+                return Ok(());
+            }
+            return Err(anyhow::anyhow!("Failed to get website config: {}", err));
+        }
+    };
+
+    print!("{}", format_bucket_website(&output));
+    Ok(())
+}
+
+/// Render a `get_bucket_website` response, one setting per line.
+pub fn format_bucket_website(website: &GetBucketWebsiteOutput) -> String {
+    let mut out = String::new();
+
+    if let Some(redirect) = website.redirect_all_requests_to() {
+        let protocol = redirect.protocol().map(|p| format!("{}://", p.as_str())).unwrap_or_default();
+        out.push_str(&format!("Redirect All Requests To: {}{}\n", protocol, redirect.host_name()));
+    }
+    if let Some(index) = website.index_document() {
+        out.push_str(&format!("Index Document: {}\n", index.suffix()));
+    }
+    if let Some(error) = website.error_document() {
+        out.push_str(&format!("Error Document: {}\n", error.key()));
+    }
+
+    for rule in website.routing_rules() {
+        let condition = rule.condition()
+            .map(|c| {
+                let mut parts = Vec::new();
+                if let Some(prefix) = c.key_prefix_equals() {
+                    parts.push(format!("prefix '{}'", prefix));
+                }
+                if let Some(code) = c.http_error_code_returned_equals() {
+                    parts.push(format!("error {}", code));
+                }
+                parts.join(" and ")
+            })
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| "all requests".to_string());
+        let redirect = rule.redirect()
+            .map(|r| {
+                let target = r.replace_key_prefix_with()
+                    .map(|p| format!("prefix '{}'", p))
+                    .or_else(|| r.replace_key_with().map(|k| format!("key '{}'", k)))
+                    .unwrap_or_else(|| "same key".to_string());
+                match r.host_name() {
+                    Some(host) => format!("{} on {}", target, host),
+                    None => target,
+                }
+            })
+            .unwrap_or_default();
+        out.push_str(&format!("Redirect Rule: {} -> {}\n", condition, redirect));
+    }
+
+    out
+}
+
+pub async fn put_bucket_website(client: &Client, bucket_name: &str, index_document: &str, error_document: Option<&str>, dry_run: bool) -> Result<()> {
+    let error_document = error_document
+        .map(|key| aws_sdk_s3::types::ErrorDocument::builder().key(key).build())
+        .transpose()?;
+
+    let website_config = aws_sdk_s3::types::WebsiteConfiguration::builder()
+        .index_document(aws_sdk_s3::types::IndexDocument::builder().suffix(index_document).build()?)
+        .set_error_document(error_document)
+        .build();
+
+    if dry_run {
+        print_dry_run("PutBucketWebsite", bucket_name, None);
+        return Ok(());
+    }
+
+    client.put_bucket_website()
+        .bucket(bucket_name)
+        .website_configuration(website_config)
+        .send()
+        .await?;
+
+    success(format!("Static website hosting enabled for bucket '{}'.", bucket_name));
+    Ok(())
+}
+
+pub async fn delete_bucket_website(client: &Client, bucket_name: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        print_dry_run("DeleteBucketWebsite", bucket_name, None);
+        return Ok(());
+    }
+
+    client.delete_bucket_website()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    success(format!("Website configuration deleted from bucket '{}'.", bucket_name));
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct NotificationInfo {
+    #[tabled(rename = "type")]
+    kind: String,
+    id: String,
+    target: String,
+    events: String,
+    filter: String,
+}
+
+pub async fn get_bucket_notifications(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_notification_configuration()
+        .bucket(bucket_name)
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to get notification config: {}", e))?;
+
+    print!("{}", format_bucket_notifications(&resp));
+    Ok(())
+}
+
+/// Render a `get_bucket_notification_configuration` response as a table with
+/// one row per topic, queue, or Lambda configuration.
+pub fn format_bucket_notifications(notifications: &GetBucketNotificationConfigurationOutput) -> String {
+    fn describe_filter(filter: Option<&aws_sdk_s3::types::NotificationConfigurationFilter>) -> String {
+        let rules: Vec<String> = filter
+            .and_then(|f| f.key())
+            .map(|k| k.filter_rules())
+            .unwrap_or_default()
+            .iter()
+            .map(|r| format!(
+                "{}={}",
+                r.name().map(|n| n.as_str()).unwrap_or("-"),
+                r.value().unwrap_or("")
+            ))
+            .collect();
+        if rules.is_empty() { "-".to_string() } else { rules.join(", ") }
+    }
+    fn describe_events(events: &[aws_sdk_s3::types::Event]) -> String {
+        events.iter().map(|e| e.as_str()).collect::<Vec<_>>().join(", ")
+    }
+
+    let mut infos = Vec::new();
+
+    for topic in notifications.topic_configurations() {
+        infos.push(NotificationInfo {
+            kind: "Topic".to_string(),
+            id: topic.id().unwrap_or("-").to_string(),
+            target: topic.topic_arn().to_string(),
+            events: describe_events(topic.events()),
+            filter: describe_filter(topic.filter()),
+        });
+    }
+    for queue in notifications.queue_configurations() {
+        infos.push(NotificationInfo {
+            kind: "Queue".to_string(),
+            id: queue.id().unwrap_or("-").to_string(),
+            target: queue.queue_arn().to_string(),
+            events: describe_events(queue.events()),
+            filter: describe_filter(queue.filter()),
+        });
+    }
+    for lambda in notifications.lambda_function_configurations() {
+        infos.push(NotificationInfo {
+            kind: "Lambda".to_string(),
+            id: lambda.id().unwrap_or("-").to_string(),
+            target: lambda.lambda_function_arn().to_string(),
+            events: describe_events(lambda.events()),
+            filter: describe_filter(lambda.filter()),
+        });
+    }
+
+    let mut out = String::new();
+    if infos.is_empty() {
+        if notifications.event_bridge_configuration().is_none() {
+            out.push_str(&format!("{}\n", "No notifications configured.".yellow()));
+        }
+    } else {
+        out.push_str(&format!("{}\n", Table::new(infos)));
+    }
+    // EventBridge delivery has no target or filter of its own
+    if notifications.event_bridge_configuration().is_some() {
+        out.push_str("EventBridge: enabled\n");
+    }
+    out
+}
+
+#[derive(Tabled)]
+struct ReplicationRuleInfo {
+    id: String,
+    status: String,
+    priority: String,
+    destination: String,
+}
+
+pub async fn get_bucket_replication(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_replication()
+        .bucket(bucket_name)
+        .send()
+        .await;
+
+    let output = match resp {
+        Ok(output) => output,
+        Err(err) => {
+            if err.meta().code() == Some("ReplicationConfigurationNotFoundError") {
+                note(format!("No replication configuration set for bucket '{}'.", bucket_name));
+                return Ok(());
+            }
+            return Err(anyhow::anyhow!("Failed to get replication config: {}", err));
+        }
+    };
+
+    match output.replication_configuration() {
+        Some(replication) => print!("{}", format_bucket_replication(replication)),
+        None => note(format!("No replication configuration set for bucket '{}'.", bucket_name)),
+    }
+    Ok(())
+}
+
+/// Render a replication configuration: the IAM role followed by a table of rules.
+pub fn format_bucket_replication(replication: &ReplicationConfiguration) -> String {
+    let mut rule_infos = Vec::new();
+
+    for rule in replication.rules() {
+        let destination = rule.destination()
+            .map(|d| match d.storage_class() {
+                Some(class) => format!("{} ({})", d.bucket(), class.as_str()),
+                None => d.bucket().to_string(),
+            })
+            .unwrap_or_else(|| "-".to_string());
+        rule_infos.push(ReplicationRuleInfo {
+            id: rule.id().unwrap_or("-").to_string(),
+            status: rule.status().as_str().to_string(),
+            priority: rule.priority()
+                .map(|p| p.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            destination,
+        });
+    }
+
+    let mut out = format!("Role: {}\n", replication.role());
+    if rule_infos.is_empty() {
+        out.push_str(&format!("{}\n", "No replication rules found.".yellow()));
+    } else {
+        out.push_str(&format!("{}\n", Table::new(rule_infos)));
+    }
+    out
+}
+
+pub async fn delete_bucket_replication(client: &Client, bucket_name: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        print_dry_run("DeleteBucketReplication", bucket_name, None);
+        return Ok(());
+    }
+
+    client.delete_bucket_replication()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    success(format!("Replication configuration deleted from bucket '{}'.", bucket_name));
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct EncryptionRuleInfo {
+    algorithm: String,
+    kms_key_id: String,
+    bucket_key: String,
+}
+
+pub async fn get_bucket_encryption(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_encryption()
+        .bucket(bucket_name)
+        .send()
+        .await;
+
+    let output = match resp {
+        Ok(output) => output,
+        Err(err) => {
+            if err.meta().code() == Some("ServerSideEncryptionConfigurationNotFoundError") {
+                note(format!("No default encryption set for bucket '{}'.", bucket_name));
+                return Ok(());
+            }
+            return Err(anyhow::anyhow!("Failed to get encryption config: {}", err));
+        }
+    };
+
+    match output.server_side_encryption_configuration() {
+        Some(encryption) => print!("{}", format_bucket_encryption(encryption)),
+        None => note(format!("No default encryption set for bucket '{}'.", bucket_name)),
+    }
+    Ok(())
+}
+
+/// Render the default encryption rules: algorithm, KMS key id and whether
+/// an S3 Bucket Key is used.
+pub fn format_bucket_encryption(encryption: &ServerSideEncryptionConfiguration) -> String {
+    let rule_infos: Vec<EncryptionRuleInfo> = encryption.rules().iter()
+        .map(|rule| {
+            let default = rule.apply_server_side_encryption_by_default();
+            EncryptionRuleInfo {
+                algorithm: default
+                    .map(|d| d.sse_algorithm().as_str().to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                kms_key_id: default
+                    .and_then(|d| d.kms_master_key_id())
+                    .unwrap_or("-")
+                    .to_string(),
+                bucket_key: match rule.bucket_key_enabled() {
+                    Some(true) => "enabled".to_string(),
+                    _ => "disabled".to_string(),
+                },
+            }
+        })
+        .collect();
+
+    if rule_infos.is_empty() {
+        return format!("{}\n", "No encryption rules found.".yellow());
+    }
+    format!("{}\n", Table::new(rule_infos))
+}
+
+pub async fn delete_bucket_encryption(client: &Client, bucket_name: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        print_dry_run("DeleteBucketEncryption", bucket_name, None);
+        return Ok(());
+    }
+
+    client.delete_bucket_encryption()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    success(format!("Default encryption removed from bucket '{}'.", bucket_name));
+    Ok(())
+}
+
+pub fn parse_object_ownership(rule: &str) -> Result<aws_sdk_s3::types::ObjectOwnership> {
+    match rule.to_ascii_lowercase().as_str() {
+        "bucketownerenforced" => Ok(aws_sdk_s3::types::ObjectOwnership::BucketOwnerEnforced),
+        "bucketownerpreferred" => Ok(aws_sdk_s3::types::ObjectOwnership::BucketOwnerPreferred),
+        "objectwriter" => Ok(aws_sdk_s3::types::ObjectOwnership::ObjectWriter),
+        _ => Err(usage_error(format!(
+            "Invalid ownership rule: {}. Use 'BucketOwnerEnforced', 'BucketOwnerPreferred' or 'ObjectWriter'",
+            rule
+        ))),
+    }
+}
+
+/// What an Object Ownership setting means for uploaded objects and ACLs.
+pub fn describe_object_ownership(ownership: &aws_sdk_s3::types::ObjectOwnership) -> &'static str {
+    match ownership {
+        aws_sdk_s3::types::ObjectOwnership::BucketOwnerEnforced => "ACLs are disabled; the bucket owner owns every object",
+        aws_sdk_s3::types::ObjectOwnership::BucketOwnerPreferred => "the bucket owner owns objects uploaded with the bucket-owner-full-control ACL",
+        aws_sdk_s3::types::ObjectOwnership::ObjectWriter => "the uploading account owns each object; ACLs are enabled",
+        _ => "unknown setting",
+    }
+}
+
+pub async fn get_bucket_ownership_controls(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_ownership_controls()
+        .bucket(bucket_name)
+        .send()
+        .await;
+
+    let output = match resp {
+        Ok(output) => output,
+        Err(err) => {
+            if err.meta().code() == Some("OwnershipControlsNotFoundError") {
+                note(format!("No ownership controls set for bucket '{}' (ACLs are enabled, as with ObjectWriter).", bucket_name));
+                return Ok(());
+            }
+            return Err(anyhow::anyhow!("Failed to get ownership controls: {}", err));
+        }
+    };
+
+    let rules = output.ownership_controls().map(|c| c.rules()).unwrap_or_default();
+    if rules.is_empty() {
+        note(format!("No ownership controls set for bucket '{}' (ACLs are enabled, as with ObjectWriter).", bucket_name));
+    }
+    for rule in rules {
+        let ownership = rule.object_ownership();
+        println!("Object Ownership: {} ({})", ownership.as_str().cyan(), describe_object_ownership(ownership));
+    }
+    Ok(())
+}
+
+pub async fn put_bucket_ownership_controls(client: &Client, bucket_name: &str, rule: &str, dry_run: bool) -> Result<()> {
+    let ownership = parse_object_ownership(rule)?;
+
+    if dry_run {
+        print_dry_run(&format!("PutBucketOwnershipControls (ownership '{}')", ownership.as_str()), bucket_name, None);
+        return Ok(());
+    }
+
+    client.put_bucket_ownership_controls()
+        .bucket(bucket_name)
+        .ownership_controls(
+            aws_sdk_s3::types::OwnershipControls::builder()
+                .rules(aws_sdk_s3::types::OwnershipControlsRule::builder().object_ownership(ownership.clone()).build()?)
+                .build()?
+        )
+        .send()
+        .await?;
+
+    success(format!("Bucket '{}' Object Ownership set to: {}", bucket_name, ownership.as_str().cyan()));
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct TagInfo {
+    key: String,
+    value: String,
+}
+
+pub async fn get_bucket_tags(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_tagging()
+        .bucket(bucket_name)
+        .send()
+        .await;
+
+    match resp {
+        Ok(output) => print!("{}", format_tags(output.tag_set())),
+        Err(err) => {
+            if err.meta().code() == Some("NoSuchTagSet") {
+                note(format!("No tags set on bucket '{}'.", bucket_name));
+                return Ok(());
+            }
+            return Err(anyhow::anyhow!("Failed to get bucket tags: {}", err));
+        }
+    }
+    Ok(())
+}
+
+/// Render a tag set as a key/value table, sorted by key.
+pub fn format_tags(tags: &[Tag]) -> String {
+    if tags.is_empty() {
+        return format!("{}\n", "No tags set.".yellow());
+    }
+    let mut rows: Vec<TagInfo> = tags.iter()
+        .map(|t| TagInfo { key: t.key().to_string(), value: t.value().to_string() })
+        .collect();
+    rows.sort_by(|a, b| a.key.cmp(&b.key));
+    format!("{}\n", Table::new(rows))
+}
+
+pub async fn delete_bucket_tags(client: &Client, bucket_name: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        print_dry_run("DeleteBucketTagging", bucket_name, None);
+        return Ok(());
+    }
+
+    client.delete_bucket_tagging()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    success(format!("Tags removed from bucket '{}'.", bucket_name));
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct GrantInfo {
+    grantee: String,
+    #[tabled(rename = "type")]
+    kind: String,
+    permission: String,
+}
+
+pub fn parse_bucket_canned_acl(acl: &str) -> Result<aws_sdk_s3::types::BucketCannedAcl> {
+    if !aws_sdk_s3::types::BucketCannedAcl::values().contains(&acl) {
+        return Err(usage_error(format!(
+            "Invalid canned ACL: {}. Use one of: {}",
+            acl,
+            aws_sdk_s3::types::BucketCannedAcl::values().join(", ")
+        )));
+    }
+    Ok(aws_sdk_s3::types::BucketCannedAcl::from(acl))
+}
+
+pub async fn get_bucket_acl(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_acl()
+        .bucket(bucket_name)
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to get bucket ACL: {}", e))?;
+
+    print!("{}", format_acl(resp.owner(), resp.grants()));
+    Ok(())
+}
+
+/// Render an ACL: the owner followed by a table of grants. Shared by bucket
+/// and object ACLs.
+pub fn format_acl(owner: Option<&Owner>, grants: &[Grant]) -> String {
+    let owner = owner
+        .and_then(|o| o.display_name().or(o.id()))
+        .unwrap_or("Unknown");
+
+    let grant_infos = grants.iter()
+        .map(|grant| {
+            let grantee = grant.grantee();
+            GrantInfo {
+                grantee: grantee
+                    .and_then(|g| g.display_name().or(g.id()).or(g.uri()).or(g.email_address()))
+                    .unwrap_or("-")
+                    .to_string(),
+                kind: grantee.map(|g| g.r#type().as_str().to_string()).unwrap_or_else(|| "-".to_string()),
+                permission: grant.permission().map(|p| p.as_str().to_string()).unwrap_or_else(|| "-".to_string()),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut out = format!("Owner: {}\n", owner);
+    if grant_infos.is_empty() {
+        out.push_str(&format!("{}\n", "No grants found.".yellow()));
+    } else {
+        out.push_str(&format!("{}\n", Table::new(grant_infos)));
+    }
+    out
+}
+
+/// Replace the bucket ACL with a canned ACL or with explicit grants
+/// (exactly one of the two).
+pub async fn put_bucket_acl(client: &Client, bucket_name: &str, canned: Option<&str>, grants: &Grants, dry_run: bool) -> Result<()> {
+    let acl = canned.map(parse_bucket_canned_acl).transpose()?;
+    if acl.is_none() == grants.is_empty() {
+        return Err(usage_error("Give either a canned ACL or --grant-* options"));
+    }
+
+    if dry_run {
+        match &acl {
+            Some(acl) => print_dry_run(&format!("PutBucketAcl (ACL '{}')", acl.as_str()), bucket_name, None),
+            None => print_dry_run("PutBucketAcl (the given grants)", bucket_name, None),
+        }
+        return Ok(());
+    }
+
+    client.put_bucket_acl()
+        .bucket(bucket_name)
+        .set_acl(acl)
+        .set_grant_read(grants.read.clone())
+        .set_grant_write(grants.write.clone())
+        .set_grant_read_acp(grants.read_acp.clone())
+        .set_grant_write_acp(grants.write_acp.clone())
+        .set_grant_full_control(grants.full_control.clone())
+        .send()
+        .await?;
+
+    match canned {
+        Some(canned) => success(format!("Bucket '{}' ACL set to: {}", bucket_name, canned.cyan())),
+        None => success(format!("Bucket '{}' ACL replaced with the given grants.", bucket_name)),
+    }
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct InventoryInfo {
+    id: String,
+    enabled: String,
+    destination: String,
+    format: String,
+    schedule: String,
+    versions: String,
+}
+
+/// List every inventory configuration on the bucket, following continuation tokens.
+pub async fn list_bucket_inventory(client: &Client, bucket_name: &str) -> Result<()> {
+    let mut configurations = Vec::new();
+    let mut continuation_token = None;
+
+    loop {
+        let resp = client.list_bucket_inventory_configurations()
+            .bucket(bucket_name)
+            .set_continuation_token(continuation_token)
+            .send()
+            .await?;
+
+        configurations.extend(resp.inventory_configuration_list().iter().cloned());
+
+        match resp.next_continuation_token() {
+            Some(token) if resp.is_truncated() == Some(true) => continuation_token = Some(token.to_string()),
+            _ => break,
+        }
+    }
+
+    if configurations.is_empty() {
+        note(format!("No inventory configurations set for bucket '{}'.", bucket_name));
+        return Ok(());
+    }
+
+    print!("{}", format_inventory_configurations(&configurations));
+    Ok(())
+}
+
+pub async fn get_bucket_inventory(client: &Client, bucket_name: &str, id: &str) -> Result<()> {
+    let resp = client.get_bucket_inventory_configuration()
+        .bucket(bucket_name)
+        .id(id)
+        .send()
+        .await?;
+
+    match resp.inventory_configuration() {
+        Some(configuration) => print!("{}", format_inventory_configurations(std::slice::from_ref(configuration))),
+        None => note(format!("No inventory configuration '{}' on bucket '{}'.", id, bucket_name)),
+    }
+    Ok(())
+}
+
+/// Render inventory configurations as a table of destination, format and schedule.
+pub fn format_inventory_configurations(configurations: &[InventoryConfiguration]) -> String {
+    let infos = configurations.iter()
+        .map(|configuration| {
+            let destination = configuration.destination().and_then(|d| d.s3_bucket_destination());
+            InventoryInfo {
+                id: configuration.id().to_string(),
+                enabled: if configuration.is_enabled() { "yes" } else { "no" }.to_string(),
+                destination: destination
+                    .map(|d| {
+                        // Destinations are bucket ARNs; the name is easier to read
+                        let bucket = d.bucket().trim_start_matches("arn:aws:s3:::");
+                        match d.prefix() {
+                            Some(prefix) => format!("{}/{}", bucket, prefix),
+                            None => bucket.to_string(),
+                        }
+                    })
+                    .unwrap_or_else(|| "-".to_string()),
+                format: destination
+                    .map(|d| d.format().as_str().to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                schedule: configuration.schedule()
+                    .map(|s| s.frequency().as_str().to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                versions: configuration.included_object_versions().as_str().to_string(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    format!("{}\n", Table::new(infos))
+}
+
+pub async fn delete_bucket_inventory(client: &Client, bucket_name: &str, id: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        print_dry_run(&format!("DeleteBucketInventoryConfiguration (id '{}')", id), bucket_name, None);
+        return Ok(());
+    }
+
+    client.delete_bucket_inventory_configuration()
+        .bucket(bucket_name)
+        .id(id)
+        .send()
+        .await?;
+
+    success(format!("Inventory configuration '{}' deleted from bucket '{}'.", id, bucket_name));
+    Ok(())
+}
+
+/// Describe a configuration filter as `prefix=logs/, env=prod`; empty when
+/// the configuration covers the whole bucket.
+fn describe_config_filter(prefix: Option<&str>, tags: &[Tag], access_point: Option<&str>) -> String {
+    let mut parts = Vec::new();
+    if let Some(prefix) = prefix.filter(|p| !p.is_empty()) {
+        parts.push(format!("prefix={}", prefix));
+    }
+    parts.extend(tags.iter().map(|t| format!("{}={}", t.key(), t.value())));
+    if let Some(arn) = access_point {
+        parts.push(format!("access-point={}", arn));
+    }
+    if parts.is_empty() { "(whole bucket)".to_string() } else { parts.join(", ") }
+}
+
+#[derive(Tabled)]
+struct AnalyticsInfo {
+    id: String,
+    filter: String,
+    export: String,
+}
+
+/// List every storage class analysis configuration on the bucket, following continuation tokens.
+pub async fn list_bucket_analytics(client: &Client, bucket_name: &str) -> Result<()> {
+    let mut configurations = Vec::new();
+    let mut continuation_token = None;
+
+    loop {
+        let resp = client.list_bucket_analytics_configurations()
+            .bucket(bucket_name)
+            .set_continuation_token(continuation_token)
+            .send()
+            .await?;
+
+        configurations.extend(resp.analytics_configuration_list().iter().cloned());
+
+        match resp.next_continuation_token() {
+            Some(token) if resp.is_truncated() == Some(true) => continuation_token = Some(token.to_string()),
+            _ => break,
+        }
+    }
+
+    if configurations.is_empty() {
+        note(format!("No analytics configurations set for bucket '{}'.", bucket_name));
+        return Ok(());
+    }
+
+    print!("{}", format_analytics_configurations(&configurations));
+    Ok(())
+}
+
+pub async fn get_bucket_analytics(client: &Client, bucket_name: &str, id: &str) -> Result<()> {
+    let resp = client.get_bucket_analytics_configuration()
+        .bucket(bucket_name)
+        .id(id)
+        .send()
+        .await?;
+
+    match resp.analytics_configuration() {
+        Some(configuration) => print!("{}", format_analytics_configurations(std::slice::from_ref(configuration))),
+        None => note(format!("No analytics configuration '{}' on bucket '{}'.", id, bucket_name)),
+    }
+    Ok(())
+}
+
+/// Render analytics configurations as a table of filter and export destination.
+pub fn format_analytics_configurations(configurations: &[AnalyticsConfiguration]) -> String {
+    let infos = configurations.iter()
+        .map(|configuration| {
+            let filter = match configuration.filter() {
+                Some(AnalyticsFilter::Prefix(prefix)) => describe_config_filter(Some(prefix), &[], None),
+                Some(AnalyticsFilter::Tag(tag)) => describe_config_filter(None, std::slice::from_ref(tag), None),
+                Some(AnalyticsFilter::And(and)) => describe_config_filter(and.prefix(), and.tags(), None),
+                _ => describe_config_filter(None, &[], None),
+            };
+            let export = configuration.storage_class_analysis()
+                .and_then(|a| a.data_export())
+                .and_then(|e| e.destination())
+                .and_then(|d| d.s3_bucket_destination())
+                .map(|d| {
+                    // Destinations are bucket ARNs; the name is easier to read
+                    let bucket = d.bucket().trim_start_matches("arn:aws:s3:::");
+                    match d.prefix() {
+                        Some(prefix) => format!("{}/{} ({})", bucket, prefix, d.format().as_str()),
+                        None => format!("{} ({})", bucket, d.format().as_str()),
+                    }
+                })
+                .unwrap_or_else(|| "-".to_string());
+            AnalyticsInfo { id: configuration.id().to_string(), filter, export }
+        })
+        .collect::<Vec<_>>();
+
+    format!("{}\n", Table::new(infos))
+}
+
+pub async fn delete_bucket_analytics(client: &Client, bucket_name: &str, id: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        print_dry_run(&format!("DeleteBucketAnalyticsConfiguration (id '{}')", id), bucket_name, None);
+        return Ok(());
+    }
+
+    client.delete_bucket_analytics_configuration()
+        .bucket(bucket_name)
+        .id(id)
+        .send()
+        .await?;
+
+    success(format!("Analytics configuration '{}' deleted from bucket '{}'.", id, bucket_name));
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct MetricsInfo {
+    id: String,
+    filter: String,
+}
+
+/// List every request metrics configuration on the bucket, following continuation tokens.
+pub async fn list_bucket_metrics(client: &Client, bucket_name: &str) -> Result<()> {
+    let mut configurations = Vec::new();
+    let mut continuation_token = None;
+
+    loop {
+        let resp = client.list_bucket_metrics_configurations()
+            .bucket(bucket_name)
+            .set_continuation_token(continuation_token)
+            .send()
+            .await?;
+
+        configurations.extend(resp.metrics_configuration_list().iter().cloned());
+
+        match resp.next_continuation_token() {
+            Some(token) if resp.is_truncated() == Some(true) => continuation_token = Some(token.to_string()),
+            _ => break,
+        }
+    }
+
+    if configurations.is_empty() {
+        note(format!("No metrics configurations set for bucket '{}'.", bucket_name));
+        return Ok(());
+    }
+
+    print!("{}", format_metrics_configurations(&configurations));
+    Ok(())
+}
+
+pub async fn get_bucket_metrics(client: &Client, bucket_name: &str, id: &str) -> Result<()> {
+    let resp = client.get_bucket_metrics_configuration()
+        .bucket(bucket_name)
+        .id(id)
+        .send()
+        .await?;
+
+    match resp.metrics_configuration() {
+        Some(configuration) => print!("{}", format_metrics_configurations(std::slice::from_ref(configuration))),
+        None => note(format!("No metrics configuration '{}' on bucket '{}'.", id, bucket_name)),
+    }
+    Ok(())
+}
+
+/// Render request metrics configurations as a table of ID and filter.
+pub fn format_metrics_configurations(configurations: &[MetricsConfiguration]) -> String {
+    let infos = configurations.iter()
+        .map(|configuration| {
+            let filter = match configuration.filter() {
+                Some(MetricsFilter::Prefix(prefix)) => describe_config_filter(Some(prefix), &[], None),
+                Some(MetricsFilter::Tag(tag)) => describe_config_filter(None, std::slice::from_ref(tag), None),
+                Some(MetricsFilter::AccessPointArn(arn)) => describe_config_filter(None, &[], Some(arn)),
+                Some(MetricsFilter::And(and)) => describe_config_filter(and.prefix(), and.tags(), and.access_point_arn()),
+                _ => describe_config_filter(None, &[], None),
+            };
+            MetricsInfo { id: configuration.id().to_string(), filter }
+        })
+        .collect::<Vec<_>>();
+
+    format!("{}\n", Table::new(infos))
+}
+
+pub async fn delete_bucket_metrics(client: &Client, bucket_name: &str, id: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        print_dry_run(&format!("DeleteBucketMetricsConfiguration (id '{}')", id), bucket_name, None);
+        return Ok(());
+    }
+
+    client.delete_bucket_metrics_configuration()
+        .bucket(bucket_name)
+        .id(id)
+        .send()
+        .await?;
+
+    success(format!("Metrics configuration '{}' deleted from bucket '{}'.", id, bucket_name));
+    Ok(())
+}
+
+pub async fn get_bucket_logging(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_logging()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    print!("{}", format_bucket_logging(&resp));
+    Ok(())
+}
+
+/// Render the access logging target, or "disabled" when logging is off.
+pub fn format_bucket_logging(logging: &GetBucketLoggingOutput) -> String {
+    match logging.logging_enabled() {
+        Some(enabled) => format!(
+            "Logging: {}\nTarget Bucket: {}\nTarget Prefix: {}\n",
+            "enabled".green(),
+            enabled.target_bucket(),
+            if enabled.target_prefix().is_empty() { "-" } else { enabled.target_prefix() }
+        ),
+        None => format!("Logging: {}\n", "disabled".yellow()),
+    }
+}
+
+/// Send server access logs for `bucket_name` to `target_bucket`, or turn
+/// logging off when `target_bucket` is `None` (S3 disables it on an empty status).
+pub async fn put_bucket_logging(client: &Client, bucket_name: &str, target_bucket: Option<&str>, target_prefix: &str, dry_run: bool) -> Result<()> {
+    let logging_enabled = target_bucket
+        .map(|target| {
+            aws_sdk_s3::types::LoggingEnabled::builder()
+                .target_bucket(target)
+                .target_prefix(target_prefix)
+                .build()
+        })
+        .transpose()?;
+
+    if dry_run {
+        match target_bucket {
+            Some(target) => print_dry_run(&format!("PutBucketLogging (to '{}')", target), bucket_name, None),
+            None => print_dry_run("PutBucketLogging (logging disabled)", bucket_name, None),
+        }
+        return Ok(());
+    }
+
+    client.put_bucket_logging()
+        .bucket(bucket_name)
+        .bucket_logging_status(
+            aws_sdk_s3::types::BucketLoggingStatus::builder()
+                .set_logging_enabled(logging_enabled)
+                .build()
+        )
+        .send()
+        .await?;
+
+    match target_bucket {
+        Some(target) => success(format!("Access logs for bucket '{}' now go to '{}'.", bucket_name, target)),
+        None => success(format!("Access logging disabled for bucket '{}'.", bucket_name)),
+    }
+    Ok(())
+}
+
+pub fn parse_payer(payer: &str) -> Result<aws_sdk_s3::types::Payer> {
+    match payer.to_ascii_lowercase().as_str() {
+        "bucketowner" => Ok(aws_sdk_s3::types::Payer::BucketOwner),
+        "requester" => Ok(aws_sdk_s3::types::Payer::Requester),
+        _ => Err(usage_error(format!("Invalid payer: {}. Use 'BucketOwner' or 'Requester'", payer))),
+    }
+}
+
+pub async fn get_bucket_request_payment(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_request_payment()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    let payer = resp.payer().map(|p| p.as_str()).unwrap_or("BucketOwner");
+    println!("Payer: {}", payer.cyan());
+    Ok(())
+}
+
+pub async fn put_bucket_request_payment(client: &Client, bucket_name: &str, payer: &str, dry_run: bool) -> Result<()> {
+    let payer = parse_payer(payer)?;
+
+    if dry_run {
+        print_dry_run(&format!("PutBucketRequestPayment (payer '{}')", payer.as_str()), bucket_name, None);
+        return Ok(());
+    }
+
+    client.put_bucket_request_payment()
+        .bucket(bucket_name)
+        .request_payment_configuration(
+            aws_sdk_s3::types::RequestPaymentConfiguration::builder()
+                .payer(payer.clone())
+                .build()?
+        )
+        .send()
+        .await?;
+
+    success(format!("Bucket '{}' request payer set to: {}", bucket_name, payer.as_str().cyan()));
+    Ok(())
+}
+
+pub async fn get_bucket_accelerate(client: &Client, bucket_name: &str) -> Result<()> {
+    let resp = client.get_bucket_accelerate_configuration()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    print!("{}", format_accelerate_status(bucket_name, resp.status()));
+    Ok(())
+}
+
+/// Render the Transfer Acceleration status (Enabled, Suspended or NotSet),
+/// with the accelerate endpoint when it is enabled.
+pub fn format_accelerate_status(bucket_name: &str, status: Option<&aws_sdk_s3::types::BucketAccelerateStatus>) -> String {
+    match status {
+        Some(aws_sdk_s3::types::BucketAccelerateStatus::Enabled) => format!(
+            "Transfer Acceleration: {}\nEndpoint: {}.s3-accelerate.amazonaws.com\n",
+            "Enabled".green(),
+            bucket_name
+        ),
+        Some(status) => format!("Transfer Acceleration: {}\n", status.as_str().yellow()),
+        None => format!("Transfer Acceleration: {}\n", "NotSet".yellow()),
+    }
+}
+
+pub async fn put_bucket_accelerate(client: &Client, bucket_name: &str, enabled: bool, dry_run: bool) -> Result<()> {
+    let status = if enabled {
+        aws_sdk_s3::types::BucketAccelerateStatus::Enabled
+    } else {
+        aws_sdk_s3::types::BucketAccelerateStatus::Suspended
+    };
+
+    if dry_run {
+        print_dry_run(&format!("PutBucketAccelerateConfiguration (status '{}')", status.as_str()), bucket_name, None);
+        return Ok(());
+    }
+
+    client.put_bucket_accelerate_configuration()
+        .bucket(bucket_name)
+        .accelerate_configuration(
+            aws_sdk_s3::types::AccelerateConfiguration::builder()
+                .status(status.clone())
+                .build()
+        )
+        .send()
+        .await?;
+
+    success(format!("Bucket '{}' transfer acceleration set to: {}", bucket_name, status.as_str().cyan()));
+    if enabled {
+        note(format!("Use the endpoint {}.s3-accelerate.amazonaws.com to upload through it.", bucket_name));
+    }
+    Ok(())
+}
+
+/// Object count and total size for `bucket du`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Usage {
+    pub count: usize,
+    pub bytes: i64,
+}
+
+impl Usage {
+    fn add(&mut self, size: i64) {
+        self.count += 1;
+        self.bytes += size;
+    }
+}
+
+/// Label for objects directly under the prefix when grouping by sub-prefix.
+pub const NO_SUB_PREFIX: &str = "(no sub-prefix)";
+
+/// Total the objects under `prefix`. With `group_by_prefix`, also total each
+/// top-level sub-prefix (the key up to the first `/` after `prefix`), in name
+/// order; objects directly under `prefix` are grouped as `NO_SUB_PREFIX`.
+pub fn summarize_usage(
+    objects: &[aws_sdk_s3::types::Object],
+    prefix: Option<&str>,
+    group_by_prefix: bool,
+) -> (Usage, Vec<(String, Usage)>) {
+    let mut tally = UsageTally::new(prefix, group_by_prefix);
+    tally.add_objects(objects);
+    tally.finish()
+}
+
+/// Running totals for `summarize_usage`, so a listing can be added a page at
+/// a time without keeping every object.
+struct UsageTally<'a> {
+    prefix: &'a str,
+    group_by_prefix: bool,
+    total: Usage,
+    groups: std::collections::BTreeMap<String, Usage>,
+}
+
+impl<'a> UsageTally<'a> {
+    fn new(prefix: Option<&'a str>, group_by_prefix: bool) -> Self {
+        UsageTally {
+            prefix: prefix.unwrap_or(""),
+            group_by_prefix,
+            total: Usage::default(),
+            groups: std::collections::BTreeMap::new(),
+        }
+    }
+
+    fn add_objects(&mut self, objects: &[aws_sdk_s3::types::Object]) {
+        for object in objects {
+            let size = object.size().unwrap_or(0);
+            self.total.add(size);
+            if self.group_by_prefix {
+                let key = object.key().unwrap_or_default();
+                let rest = key.strip_prefix(self.prefix).unwrap_or(key);
+                let group = match rest.find('/') {
+                    Some(end) => format!("{}{}", self.prefix, &rest[..=end]),
+                    None => NO_SUB_PREFIX.to_string(),
+                };
+                self.groups.entry(group).or_default().add(size);
+            }
+        }
+    }
+
+    fn finish(self) -> (Usage, Vec<(String, Usage)>) {
+        (self.total, self.groups.into_iter().collect())
+    }
+}
+
+#[derive(Tabled)]
+struct UsageInfo {
+    prefix: String,
+    objects: usize,
+    size: String,
+}
+
+/// Report the number and total size of the objects under `prefix`,
+/// optionally broken down by top-level sub-prefix. Lists every object, so it
+/// takes one request per 1,000 objects.
+pub async fn bucket_usage(
+    client: &Client,
+    bucket_name: &str,
+    prefix: Option<&str>,
+    group_by_prefix: bool,
+    bytes: bool,
+    output: OutputFormat,
+) -> Result<()> {
+    // Totalled a page at a time, so a large bucket isn't held in memory
+    let mut tally = UsageTally::new(prefix, group_by_prefix);
+    let mut continuation_token = None;
+    loop {
+        let resp = client.list_objects_v2()
+            .bucket(bucket_name)
+            .set_prefix(prefix.map(|p| p.to_string()))
+            .set_continuation_token(continuation_token)
+            .send()
+            .await?;
+        tally.add_objects(resp.contents());
+        match resp.next_continuation_token() {
+            Some(token) if resp.is_truncated() == Some(true) => continuation_token = Some(token.to_string()),
+            _ => break,
+        }
+    }
+    let (total, groups) = tally.finish();
+    let size = |b: i64| if bytes { b.to_string() } else { format_size(b) };
+
+    if output == OutputFormat::Json {
+        let mut report = serde_json::json!({
+            "prefix": prefix.unwrap_or(""),
+            "count": total.count,
+            "total_size": total.bytes,
+        });
+        if group_by_prefix {
+            report["groups"] = groups.iter()
+                .map(|(name, usage)| serde_json::json!({ "prefix": name, "count": usage.count, "total_size": usage.bytes }))
+                .collect();
+        }
+        println!("{}", report);
+        return Ok(());
+    }
+
+    if group_by_prefix && !groups.is_empty() {
+        let rows: Vec<UsageInfo> = groups.iter()
+            .map(|(name, usage)| UsageInfo { prefix: name.clone(), objects: usage.count, size: size(usage.bytes) })
+            .collect();
+        println!("{}", Table::new(rows));
+    }
+    let location = match prefix {
+        Some(prefix) => format!("s3://{}/{}", bucket_name, prefix),
+        None => format!("s3://{}", bucket_name),
+    };
+    println!("{}: {}, {}", location, pluralize(total.count, "object"), size(total.bytes));
+    Ok(())
+}
+
+pub async fn delete_bucket(client: &Client, bucket_name: &str, assume_yes: bool, dry_run: bool) -> Result<()> {
+    if dry_run {
+        print_dry_run("DeleteBucket", bucket_name, None);
+        return Ok(());
+    }
+
+    if !confirm(&format!("delete bucket '{}'", bucket_name), assume_yes)? {
+        note("Aborted.");
+        return Ok(());
+    }
+
+    client.delete_bucket()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+    
+    success(format!("Bucket '{}' deleted successfully.", bucket_name));
+    Ok(())
+}
+
+pub async fn empty_bucket(client: &Client, bucket_name: &str, versions: bool, mfa: Option<&str>, assume_yes: bool, dry_run: bool) -> Result<()> {
+    if dry_run {
+        let listing = if versions { "ListObjectVersions" } else { "ListObjectsV2" };
+        print_dry_run(listing, bucket_name, None);
+        print_dry_run("DeleteObjects (batches of up to 1000 listed items)", bucket_name, None);
+        return Ok(());
+    }
+
+    let target = if versions {
+        format!("delete every object and version in bucket '{}'", bucket_name)
+    } else {
+        format!("delete every object in bucket '{}'", bucket_name)
+    };
+    if !confirm(&target, assume_yes)? {
+        note("Aborted.");
+        return Ok(());
+    }
+
+    status(format!("Emptying bucket '{}'...", bucket_name));
+
+    let mut total = 0;
+
+    // Deleted items disappear from the listing, so each pass lists from the start again
+    loop {
+        let (to_delete, is_truncated) = if versions {
+            list_version_identifiers(client, bucket_name).await?
+        } else {
+            list_object_identifiers(client, bucket_name).await?
+        };
+
+        if to_delete.is_empty() {
+            break;
+        }
+
+        total += delete_batch(client, bucket_name, to_delete, mfa).await?;
+        status(format!("Deleted {} items...", total));
+
+        if !is_truncated {
+            break;
+        }
+    }
+
+    success(format!("Bucket '{}' is now empty ({} items deleted).", bucket_name, total));
+    Ok(())
+}
+
+async fn list_object_identifiers(client: &Client, bucket_name: &str) -> Result<(Vec<aws_sdk_s3::types::ObjectIdentifier>, bool)> {
+    let resp = client.list_objects_v2()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    let mut identifiers = Vec::new();
+    for object in resp.contents() {
+        if let Some(key) = object.key() {
+            identifiers.push(aws_sdk_s3::types::ObjectIdentifier::builder().key(key).build()?);
+        }
+    }
+
+    Ok((identifiers, resp.is_truncated().unwrap_or(false)))
+}
+
+async fn list_version_identifiers(client: &Client, bucket_name: &str) -> Result<(Vec<aws_sdk_s3::types::ObjectIdentifier>, bool)> {
+    let resp = client.list_object_versions()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    let mut identifiers = Vec::new();
+
+    for v in resp.versions() {
+        if let (Some(key), Some(version_id)) = (v.key(), v.version_id()) {
+            identifiers.push(
+                aws_sdk_s3::types::ObjectIdentifier::builder()
+                    .key(key)
+                    .version_id(version_id)
+                    .build()?
+            );
+        }
+    }
+
+    for m in resp.delete_markers() {
+        if let (Some(key), Some(version_id)) = (m.key(), m.version_id()) {
+            identifiers.push(
+                aws_sdk_s3::types::ObjectIdentifier::builder()
+                    .key(key)
+                    .version_id(version_id)
+                    .build()?
+            );
+        }
+    }
+
+    Ok((identifiers, resp.is_truncated().unwrap_or(false)))
+}
+
+/// Delete up to 1000 objects in one request, returning how many were deleted.
+pub(crate) async fn delete_batch(
+    client: &Client,
+    bucket_name: &str,
+    objects: Vec<aws_sdk_s3::types::ObjectIdentifier>,
+    mfa: Option<&str>,
+) -> Result<usize> {
+    let (deleted, errors) = delete_batch_report(client, bucket_name, objects, mfa).await?;
+
+    if let Some((key, message)) = errors.first() {
+        return Err(anyhow::anyhow!(
+            "Failed to delete {} items (first: '{}': {})",
+            errors.len(),
+            key,
+            message
+        ));
+    }
+
+    Ok(deleted)
+}
+
+/// Delete up to 1000 objects in one request, returning how many were deleted
+/// and the key and message of each object S3 could not delete.
+pub(crate) async fn delete_batch_report(
+    client: &Client,
+    bucket_name: &str,
+    objects: Vec<aws_sdk_s3::types::ObjectIdentifier>,
+    mfa: Option<&str>,
+) -> Result<(usize, Vec<(String, String)>)> {
+    let delete = aws_sdk_s3::types::Delete::builder()
+        .set_objects(Some(objects))
+        .build()?;
+
+    let resp = crate::client::retry_transient("DeleteObjects", || {
+        client.delete_objects()
+            .bucket(bucket_name)
+            .set_mfa(mfa.map(|m| m.to_string()))
+            .delete(delete.clone())
+            .send()
+    }).await?;
+
+    let errors = resp.errors().iter()
+        .map(|e| (
+            e.key().unwrap_or("<unknown>").to_string(),
+            e.message().unwrap_or("unknown error").to_string(),
+        ))
+        .collect();
+
+    Ok((resp.deleted().len(), errors))
+}
@@ -1,8 +1,101 @@
 use aws_sdk_s3::Client;
 use aws_config::meta::region::RegionProviderChain;
 use aws_config::BehaviorVersion;
+use aws_config::SdkConfig;
+use aws_config::sts::AssumeRoleProvider;
+use aws_config::retry::RetryConfig;
+use aws_config::timeout::TimeoutConfig;
+use aws_sdk_s3::config::{ConfigBag, Credentials, Intercept, RuntimeComponents, SharedCredentialsProvider};
+use aws_sdk_s3::config::interceptors::BeforeTransmitInterceptorContextMut;
+use aws_sdk_s3::error::ErrorMetadata;
+use aws_smithy_runtime_api::box_error::BoxError;
+use aws_smithy_runtime_api::client::http::{http_client_fn, HttpConnector, HttpConnectorFuture, SharedHttpClient, SharedHttpConnector};
+use aws_smithy_runtime_api::client::orchestrator::{HttpRequest, HttpResponse, Metadata};
+use aws_smithy_runtime_api::client::result::ConnectorError;
+use aws_smithy_types::body::SdkBody;
+use aws_types::service_config::ServiceConfigKey;
+use colored::*;
+use hyper_rustls::HttpsConnector;
+use hyper_util::client::legacy::connect::HttpConnector as HyperHttpConnector;
+use hyper_util::rt::TokioExecutor;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use crate::errors::usage_error;
+use crate::ui::success;
+use std::collections::HashMap;
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
 
-pub async fn create_client(region: Option<String>, profile: Option<String>) -> Client {
+/// Bucket regions resolved so far, cached for the life of the process.
+static BUCKET_REGIONS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+
+/// Error codes S3 returns when the caller's credentials are missing, wrong or expired.
+pub(crate) const AUTH_ERROR_CODES: &[&str] = &[
+    "AccessDenied",
+    "InvalidAccessKeyId",
+    "SignatureDoesNotMatch",
+    "ExpiredToken",
+    "InvalidToken",
+    "TokenRefreshRequired",
+];
+
+#[allow(clippy::too_many_arguments)]
+pub async fn create_client(
+    region: Option<String>,
+    profile: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    max_retries: Option<u32>,
+    timeout_secs: Option<u64>,
+    endpoint_url: Option<String>,
+    force_path_style: Option<bool>,
+    no_verify_ssl: bool,
+    credentials: Option<Credentials>,
+) -> Client {
+    let config = load_config(region, profile, role_arn, role_session_name, max_retries, timeout_secs, endpoint_url, credentials).await;
+    s3_client(&config, force_path_style, no_verify_ssl)
+}
+
+/// Credentials from `--access-key-id`, `--secret-access-key` and
+/// `--session-token`, if given. The key and secret must come together.
+pub fn static_credentials(
+    access_key_id: Option<String>,
+    secret_access_key: Option<String>,
+    session_token: Option<String>,
+) -> anyhow::Result<Option<Credentials>> {
+    match (access_key_id, secret_access_key) {
+        (Some(access_key_id), Some(secret_access_key)) => {
+            eprintln!(
+                "{} {}",
+                "WARNING:".yellow().bold(),
+                "Secrets passed on the command line are visible in process listings and shell history; prefer AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY or a profile.".yellow()
+            );
+            Ok(Some(Credentials::new(access_key_id, secret_access_key, session_token, None, "s3sh-static")))
+        }
+        (None, None) if session_token.is_none() => Ok(None),
+        _ => Err(usage_error("--access-key-id and --secret-access-key must be given together")),
+    }
+}
+
+/// Resolve the shared AWS config: region, profile, retries, timeouts, endpoint
+/// and credentials: static `credentials` if given (bypassing the profile
+/// chain), and with `role_arn`, those from assuming that role.
+#[allow(clippy::too_many_arguments)]
+pub async fn load_config(
+    region: Option<String>,
+    profile: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    max_retries: Option<u32>,
+    timeout_secs: Option<u64>,
+    endpoint_url: Option<String>,
+    credentials: Option<Credentials>,
+) -> SdkConfig {
     let region_provider = RegionProviderChain::first_try(region.map(aws_sdk_s3::config::Region::new))
         .or_default_provider()
         .or_else(aws_sdk_s3::config::Region::new("us-east-1"));
@@ -14,6 +107,526 @@ pub async fn create_client(region: Option<String>, profile: Option<String>) -> C
         config_loader = config_loader.profile_name(profile_name);
     }
 
+    // Leave the SDK defaults alone unless the user asked for something else
+    if let Some(retry_config) = retry_config(max_retries) {
+        config_loader = config_loader.retry_config(retry_config);
+    }
+
+    if let Some(timeout_config) = timeout_config(timeout_secs) {
+        config_loader = config_loader.timeout_config(timeout_config);
+    }
+
+    if let Some(endpoint_url) = endpoint_url {
+        config_loader = config_loader.endpoint_url(endpoint_url);
+    }
+
+    if let Some(credentials) = credentials {
+        config_loader = config_loader.credentials_provider(credentials);
+    }
+
     let config = config_loader.load().await;
-    Client::new(&config)
+
+    match role_arn {
+        Some(role_arn) => {
+            // The profile's credentials are used as the source for AssumeRole
+            let provider = assume_role_provider(&config, role_arn, role_session_name).await;
+            config.to_builder()
+                .credentials_provider(SharedCredentialsProvider::new(provider))
+                .build()
+        }
+        None => config,
+    }
+}
+
+/// The endpoint the S3 client will use, with the SDK's precedence: one set
+/// explicitly (`--endpoint-url` or the config file), then the `s3` entry of
+/// the profile's `services` section (or `AWS_ENDPOINT_URL_S3`), then the
+/// profile's or environment's global `endpoint_url`.
+pub fn s3_endpoint_url(config: &SdkConfig) -> Option<String> {
+    if config.get_origin("endpoint_url").is_client_config() {
+        return config.endpoint_url().map(|e| e.to_string());
+    }
+    let key = ServiceConfigKey::builder()
+        .service_id("S3")
+        .env("AWS_ENDPOINT_URL")
+        .profile("endpoint_url")
+        .build()
+        .expect("service config key has every field set");
+    config.service_config()
+        .and_then(|service_config| service_config.load_config(key))
+        .or_else(|| config.endpoint_url().map(|e| e.to_string()))
+}
+
+/// Build the S3 client for a loaded config. Without an explicit
+/// `force_path_style`, path-style addressing is used for non-AWS endpoints.
+/// `no_verify_ssl` skips certificate checks, but only for a custom endpoint.
+pub fn s3_client(config: &SdkConfig, force_path_style: Option<bool>, no_verify_ssl: bool) -> Client {
+    let endpoint_url = s3_endpoint_url(config);
+    let path_style = resolve_path_style(endpoint_url.as_deref(), force_path_style);
+    if force_path_style.is_none() && path_style {
+        tracing::info!(endpoint = endpoint_url.as_deref(), "Using path-style addressing for a non-AWS endpoint");
+    }
+    let mut s3_config = aws_sdk_s3::config::Builder::from(config)
+        .force_path_style(path_style);
+
+    if no_verify_ssl {
+        match endpoint_url.as_deref() {
+            Some(endpoint) => {
+                eprintln!(
+                    "{} {}",
+                    "WARNING:".red().bold(),
+                    format!("--no-verify-ssl: TLS certificates from {} are NOT verified. This is insecure; use it only for local testing.", endpoint).red()
+                );
+                s3_config = s3_config.http_client(insecure_http_client());
+            }
+            None => eprintln!("{} {}", "WARNING:".yellow().bold(), "--no-verify-ssl is ignored without a custom --endpoint-url".yellow()),
+        }
+    }
+    Client::from_conf(s3_config.build())
+}
+
+/// Connect and read timeouts an insecure connector was built with.
+type ConnectorTimeouts = (Option<Duration>, Option<Duration>);
+
+/// An HTTP client that accepts any TLS certificate, for local endpoints with
+/// self-signed certificates (`--no-verify-ssl`). Handshake signatures are
+/// still checked; the certificate chain and host name are not. The connect
+/// and read timeouts from the SDK's timeout config are applied as usual.
+pub fn insecure_http_client() -> SharedHttpClient {
+    let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+    let tls_config = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .expect("the default TLS versions are supported by aws-lc-rs")
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(NoCertificateVerification(provider)))
+        .with_no_client_auth();
+    // One connector per distinct pair of timeouts, so connections are reused
+    let connectors: Mutex<HashMap<ConnectorTimeouts, SharedHttpConnector>> = Mutex::default();
+    http_client_fn(move |settings, _| {
+        let timeouts = (settings.connect_timeout(), settings.read_timeout());
+        let mut connectors = connectors.lock().unwrap_or_else(|e| e.into_inner());
+        connectors.entry(timeouts)
+            .or_insert_with(|| {
+                let mut http = HyperHttpConnector::new();
+                http.enforce_http(false);
+                http.set_connect_timeout(timeouts.0);
+                let https = hyper_rustls::HttpsConnectorBuilder::new()
+                    .with_tls_config(tls_config.clone())
+                    .https_or_http()
+                    .enable_all_versions()
+                    .wrap_connector(http);
+                SharedHttpConnector::new(InsecureConnector {
+                    client: hyper_util::client::legacy::Client::builder(TokioExecutor::new()).build(https),
+                    read_timeout: timeouts.1,
+                })
+            })
+            .clone()
+    })
+}
+
+#[derive(Debug)]
+struct NoCertificateVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Sends SDK requests through a hyper client with the insecure TLS config.
+#[derive(Debug, Clone)]
+struct InsecureConnector {
+    client: hyper_util::client::legacy::Client<HttpsConnector<HyperHttpConnector>, SdkBody>,
+    /// How long to wait for the response to start once the request is sent.
+    read_timeout: Option<Duration>,
+}
+
+impl HttpConnector for InsecureConnector {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+        let client = self.client.clone();
+        let read_timeout = self.read_timeout;
+        HttpConnectorFuture::new(async move {
+            let request = request.try_into_http1x().map_err(|e| ConnectorError::user(e.into()))?;
+            let response = client.request(request);
+            let response = match read_timeout {
+                Some(timeout) => tokio::time::timeout(timeout, response).await.map_err(|_| {
+                    ConnectorError::timeout(format!("no response within the {:?} read timeout", timeout).into())
+                })?,
+                None => response.await,
+            };
+            let response = response.map_err(|e| {
+                if e.is_connect() {
+                    ConnectorError::io(e.into())
+                } else {
+                    ConnectorError::other(e.into(), None)
+                }
+            })?;
+            HttpResponse::try_from(response.map(SdkBody::from_body_1_x))
+                .map_err(|e| ConnectorError::other(e.into(), None))
+        })
+    }
+}
+
+/// Whether to address buckets as `<endpoint>/<bucket>`: as set explicitly,
+/// otherwise only for a custom endpoint outside `amazonaws.com`, where
+/// `<bucket>.<endpoint>` rarely resolves (e.g. MinIO on localhost:9000).
+pub fn resolve_path_style(endpoint_url: Option<&str>, explicit: Option<bool>) -> bool {
+    if let Some(explicit) = explicit {
+        return explicit;
+    }
+    let Some(endpoint_url) = endpoint_url else {
+        return false;
+    };
+
+    let authority = endpoint_url.split_once("://").map_or(endpoint_url, |(_, rest)| rest);
+    let authority = authority.split(['/', '?']).next().unwrap_or_default();
+    let host = match authority.strip_prefix('[') {
+        // IPv6 literal, e.g. [::1]:9000
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => authority.rsplit_once(':').map_or(authority, |(host, _)| host),
+    };
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let aws = ["amazonaws.com", "amazonaws.com.cn"].iter()
+        .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)));
+    !aws
+}
+
+/// Standard retry config allowing `max_retries` retries after the first attempt.
+pub fn retry_config(max_retries: Option<u32>) -> Option<RetryConfig> {
+    max_retries.map(|retries| RetryConfig::standard().with_max_attempts(retries + 1))
+}
+
+/// Timeout config limiting each operation (including retries) to `timeout_secs`.
+pub fn timeout_config(timeout_secs: Option<u64>) -> Option<TimeoutConfig> {
+    timeout_secs.map(|secs| {
+        TimeoutConfig::builder()
+            .operation_timeout(std::time::Duration::from_secs(secs))
+            .build()
+    })
+}
+
+/// Error codes worth retrying at the app layer, on top of the SDK's own retries.
+const TRANSIENT_ERROR_CODES: &[&str] = &["SlowDown", "RequestTimeout", "ServiceUnavailable"];
+
+/// Delay before the first app-level retry; doubled for each one after that.
+const TRANSIENT_RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+/// Total attempts made by `retry_transient` (`--transient-attempts`).
+static TRANSIENT_ATTEMPTS: AtomicU32 = AtomicU32::new(3);
+
+pub fn set_transient_attempts(attempts: u32) {
+    TRANSIENT_ATTEMPTS.store(attempts.max(1), Ordering::Relaxed);
+}
+
+/// Whether `err` is throttling or a timeout that may succeed if sent again:
+/// `SlowDown`, `RequestTimeout` or a 503 response.
+pub fn is_transient<E: ProvideErrorMetadata>(err: &SdkError<E>) -> bool {
+    err.code().is_some_and(|code| TRANSIENT_ERROR_CODES.contains(&code))
+        || err.raw_response().is_some_and(|r| r.status().as_u16() == 503)
+}
+
+/// Run `send` until it succeeds, fails with a non-transient error, or runs
+/// out of attempts, backing off exponentially between tries. Used for
+/// operations S3 commonly throttles (restores and bulk deletes).
+pub async fn retry_transient<T, E, F, Fut>(operation: &str, mut send: F) -> Result<T, SdkError<E>>
+where
+    E: ProvideErrorMetadata,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, SdkError<E>>>,
+{
+    let attempts = TRANSIENT_ATTEMPTS.load(Ordering::Relaxed);
+    let mut attempt = 1;
+    loop {
+        match send().await {
+            Err(err) if attempt < attempts && is_transient(&err) => {
+                let delay = TRANSIENT_RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+                tracing::info!(
+                    operation,
+                    attempt,
+                    code = err.code().unwrap_or("503"),
+                    delay_ms = delay.as_millis() as u64,
+                    "Transient error, retrying"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Look up the region a bucket lives in with `GetBucketLocation`.
+pub async fn resolve_bucket_region(client: &Client, bucket_name: &str) -> anyhow::Result<String> {
+    let cache = BUCKET_REGIONS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(region) = cache.lock().unwrap().get(bucket_name) {
+        return Ok(region.clone());
+    }
+
+    let location = client.get_bucket_location()
+        .bucket(bucket_name)
+        .send()
+        .await?;
+
+    // Buckets in us-east-1 report no constraint; "EU" is the legacy name for eu-west-1
+    let region = match location.location_constraint().map(|l| l.as_str()) {
+        None | Some("") => "us-east-1",
+        Some("EU") => "eu-west-1",
+        Some(other) => other,
+    }.to_string();
+
+    cache.lock().unwrap().insert(bucket_name.to_string(), region.clone());
+    Ok(region)
+}
+
+/// Return a client that talks to the region `bucket_name` lives in, so
+/// requests are not redirected. Falls back to `client` when the lookup fails,
+/// leaving the real request to report the problem.
+pub async fn client_for_bucket(client: &Client, bucket_name: &str) -> Client {
+    let region = match resolve_bucket_region(client, bucket_name).await {
+        Ok(region) => region,
+        Err(_) => return client.clone(),
+    };
+
+    if client.config().region().map(|r| r.as_ref()) == Some(region.as_str()) {
+        return client.clone();
+    }
+
+    tracing::debug!(bucket = bucket_name, region = region.as_str(), "Using the bucket's region");
+    let config = client.config()
+        .to_builder()
+        .region(aws_sdk_s3::config::Region::new(region))
+        .build();
+    Client::from_conf(config)
+}
+
+/// Adds `x-amz-request-payer: requester` to every request, acknowledging
+/// that the caller pays for requests to requester-pays buckets.
+#[derive(Debug)]
+struct RequesterPays;
+
+impl Intercept for RequesterPays {
+    fn name(&self) -> &'static str {
+        "RequesterPays"
+    }
+
+    fn modify_before_signing(
+        &self,
+        context: &mut BeforeTransmitInterceptorContextMut<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let headers = context.request_mut().headers_mut();
+        if !headers.contains_key("x-amz-request-payer") {
+            headers.insert("x-amz-request-payer", "requester");
+        }
+        Ok(())
+    }
+}
+
+/// Return a copy of `client` that sends `--request-payer requester` with
+/// every request, so requester-pays buckets can be read.
+pub fn with_requester_pays(client: &Client) -> Client {
+    let config = client.config()
+        .to_builder()
+        .interceptor(RequesterPays)
+        .build();
+    Client::from_conf(config)
+}
+
+/// Caps `max-keys` on every listing request at a page size, so listings take
+/// more, smaller requests. A smaller `max-keys` already on the request (e.g.
+/// from `--limit`) is kept.
+#[derive(Debug)]
+struct PageSize(u32);
+
+impl Intercept for PageSize {
+    fn name(&self) -> &'static str {
+        "PageSize"
+    }
+
+    fn modify_before_signing(
+        &self,
+        context: &mut BeforeTransmitInterceptorContextMut<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let operation = cfg.load::<Metadata>().map(|m| m.name());
+        if !matches!(operation, Some("ListObjectsV2" | "ListObjects" | "ListObjectVersions")) {
+            return Ok(());
+        }
+        let uri = with_max_keys(context.request().uri(), self.0);
+        context.request_mut().set_uri(uri)?;
+        Ok(())
+    }
+}
+
+/// `uri` with its `max-keys` query parameter set to `page_size`, unless it
+/// already asks for fewer.
+fn with_max_keys(uri: &str, page_size: u32) -> String {
+    let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+    let mut max_keys = page_size;
+    let mut params = Vec::new();
+    for param in query.split('&').filter(|p| !p.is_empty()) {
+        match param.strip_prefix("max-keys=").map(str::parse::<u32>) {
+            Some(Ok(existing)) => max_keys = max_keys.min(existing),
+            _ => params.push(param.to_string()),
+        }
+    }
+    params.push(format!("max-keys={}", max_keys));
+    format!("{}?{}", path, params.join("&"))
+}
+
+/// The largest page S3 returns for a listing.
+pub const MAX_PAGE_SIZE: u32 = 1000;
+
+/// Return a copy of `client` whose object and version listings ask for at
+/// most `page_size` keys per request (capped at 1000). Only the number of
+/// requests changes; every page is still fetched.
+pub fn with_page_size(client: &Client, page_size: u32) -> Client {
+    let config = client.config()
+        .to_builder()
+        .interceptor(PageSize(page_size.clamp(1, MAX_PAGE_SIZE)))
+        .build();
+    Client::from_conf(config)
+}
+
+/// Check connectivity with a cheap request: `HeadBucket` when a bucket is
+/// given (which also checks access to it), otherwise `ListBuckets`.
+pub async fn ping(client: &Client, bucket_name: Option<&str>) -> anyhow::Result<()> {
+    let target = format!("S3 in {}", client.config().region().map(|r| r.as_ref()).unwrap_or("unknown region"));
+    let started = std::time::Instant::now();
+
+    match bucket_name {
+        Some(bucket_name) => {
+            client.head_bucket()
+                .bucket(bucket_name)
+                .send()
+                .await
+                .map_err(|err| {
+                    let status = err.raw_response().map(|r| r.status().as_u16());
+                    let reason = match status {
+                        Some(301) => "it is in a different region (301)".to_string(),
+                        Some(403) => "access denied (403)".to_string(),
+                        Some(404) => "it does not exist (404)".to_string(),
+                        _ => return anyhow::Error::from(err).context(format!("Could not reach {}", target)),
+                    };
+                    anyhow::anyhow!("Reached {} but bucket '{}' is not usable: {}", target, bucket_name, reason)
+                })?;
+        }
+        None => {
+            client.list_buckets()
+                .send()
+                .await
+                .map_err(|err| anyhow::Error::from(err).context(format!("Could not reach {}", target)))?;
+        }
+    }
+
+    success(format!("Reached {} in {} ms.", target, started.elapsed().as_millis()));
+    Ok(())
+}
+
+/// Build a provider that assumes `role_arn` using the credentials of `base_config`.
+pub async fn assume_role_provider(
+    base_config: &SdkConfig,
+    role_arn: String,
+    session_name: Option<String>,
+) -> AssumeRoleProvider {
+    AssumeRoleProvider::builder(role_arn)
+        .session_name(session_name.unwrap_or_else(default_session_name))
+        .configure(base_config)
+        .build()
+        .await
+}
+
+pub fn default_session_name() -> String {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format!("s3sh-{}", timestamp)
+}
+
+/// Check a region name's characters. The AWS `us-east-1` shape isn't
+/// required, since S3-compatible services use names like `auto` or `minio`.
+pub fn validate_region(region: &str) -> anyhow::Result<()> {
+    let valid = !region.is_empty()
+        && region.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !region.starts_with('-')
+        && !region.ends_with('-');
+    if !valid {
+        return Err(usage_error(format!("Invalid region '{}'. Expected something like 'us-east-1'", region)));
+    }
+    Ok(())
+}
+
+/// Check an `--mfa` value: the MFA device serial number (or ARN) and the
+/// current 6-digit code, separated by a space.
+pub fn validate_mfa(mfa: &str) -> anyhow::Result<()> {
+    let parts = mfa.split(' ').collect::<Vec<_>>();
+    let valid = parts.len() == 2
+        && !parts[0].is_empty()
+        && parts[1].len() == 6
+        && parts[1].chars().all(|c| c.is_ascii_digit());
+    if !valid {
+        return Err(usage_error(format!(
+            "Invalid --mfa value '{}'. Expected \"<serial-number> <6-digit-code>\"",
+            mfa
+        )));
+    }
+    Ok(())
+}
+
+/// Add an actionable hint to errors caused by missing or rejected credentials.
+/// Other errors are returned unchanged.
+pub fn explain_error(err: anyhow::Error, profile: Option<&str>) -> anyhow::Error {
+    let auth_code = err.chain()
+        .filter_map(|e| e.downcast_ref::<ErrorMetadata>())
+        .filter_map(|meta| meta.code())
+        .find(|code| AUTH_ERROR_CODES.contains(code))
+        .map(|code| code.to_string());
+
+    let missing_credentials = err.chain()
+        .any(|e| e.to_string().to_lowercase().contains("credentials"));
+
+    let profile_hint = match profile {
+        Some(name) => format!("check that profile '{}' exists and has valid credentials", name),
+        None => "run `aws configure` or pass --profile".to_string(),
+    };
+
+    if let Some(code) = auth_code {
+        err.context(format!("AWS rejected the request ({}): {}", code, profile_hint))
+    } else if missing_credentials {
+        err.context(format!("No usable AWS credentials were found: {}", profile_hint))
+    } else {
+        err
+    }
 }
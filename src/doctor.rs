@@ -0,0 +1,119 @@
+use anyhow::Result;
+use aws_sdk_s3::Client;
+use aws_config::SdkConfig;
+use aws_sdk_s3::config::ProvideCredentials;
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use colored::*;
+use crate::client::AUTH_ERROR_CODES;
+
+/// The outcome of one `doctor` diagnostic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+    /// What to try next when the check failed
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Check {
+        Check { name, passed: true, detail: detail.into(), hint: None }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Check {
+        Check { name, passed: false, detail: detail.into(), hint: Some(hint.into()) }
+    }
+}
+
+/// Show only the last four characters of an access key id.
+pub fn mask_access_key(key: &str) -> String {
+    let visible = key.len().saturating_sub(4);
+    format!("{}{}", "*".repeat(visible), &key[visible..])
+}
+
+/// Run every diagnostic, in order. Nothing is created or changed.
+///
+/// `config` is the loaded AWS config `client` was built from; its credentials
+/// are resolved directly and also used for STS.
+pub async fn run_checks(client: &Client, config: &SdkConfig, profile: Option<&str>) -> Vec<Check> {
+    let profile_hint = match profile {
+        Some(name) => format!("check that profile '{}' exists in ~/.aws/config and has valid credentials", name),
+        None => "run `aws configure`, set AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY, or pass --profile".to_string(),
+    };
+    let mut checks = Vec::new();
+
+    checks.push(match config.region() {
+        Some(region) => Check::pass("Region", region.to_string()),
+        None => Check::fail("Region", "not set", "pass --region, set AWS_REGION, or add region to s3sh.toml"),
+    });
+
+    let credentials = match config.credentials_provider() {
+        Some(provider) => provider.provide_credentials().await.map_err(|e| e.to_string()),
+        None => Err("no credentials provider is configured".to_string()),
+    };
+    checks.push(match &credentials {
+        Ok(creds) => {
+            let kind = if creds.session_token().is_some() { "temporary" } else { "long-term" };
+            Check::pass("Credentials", format!("{} access key {}", kind, mask_access_key(creds.access_key_id())))
+        }
+        Err(reason) => Check::fail("Credentials", reason.clone(), profile_hint.clone()),
+    });
+
+    let sts = aws_sdk_sts::Client::new(config);
+    checks.push(match sts.get_caller_identity().send().await {
+        Ok(identity) => Check::pass(
+            "Identity",
+            format!("{} (account {})", identity.arn().unwrap_or("<unknown>"), identity.account().unwrap_or("<unknown>")),
+        ),
+        Err(err) => {
+            let hint = match err.code() {
+                Some(code) if AUTH_ERROR_CODES.contains(&code) => format!("AWS rejected the credentials ({}): {}", code, profile_hint),
+                _ if credentials.is_err() => profile_hint.clone(),
+                _ => "check network access to STS; custom endpoints (e.g. MinIO) may not provide it".to_string(),
+            };
+            Check::fail("Identity", anyhow::Error::from(err).to_string(), hint)
+        }
+    });
+
+    checks.push(match client.list_buckets().send().await {
+        Ok(resp) => Check::pass("S3 access", format!("ListBuckets returned {} bucket(s)", resp.buckets().len())),
+        Err(err) => {
+            let hint = match err.code() {
+                Some("AccessDenied") => "the identity needs s3:ListAllMyBuckets; bucket commands may still work".to_string(),
+                Some(code) if AUTH_ERROR_CODES.contains(&code) => format!("AWS rejected the credentials ({}): {}", code, profile_hint),
+                _ => "check network access to S3 and the --endpoint-url setting".to_string(),
+            };
+            Check::fail("S3 access", anyhow::Error::from(err).to_string(), hint)
+        }
+    });
+
+    checks
+}
+
+/// Render a check as `✔ Region: us-east-1`, with an indented hint on failure.
+pub fn format_check(check: &Check) -> String {
+    let mut line = if check.passed {
+        format!("{} {}: {}", "✔".green(), check.name.bold(), check.detail)
+    } else {
+        format!("{} {}: {}", "✘".red(), check.name.bold(), check.detail)
+    };
+    if let Some(hint) = &check.hint {
+        line.push_str(&format!("\n  {} {}", "→".yellow(), hint));
+    }
+    line
+}
+
+/// Print each diagnostic as pass/fail, returning an error if any failed.
+pub async fn doctor(client: &Client, config: &SdkConfig, profile: Option<&str>) -> Result<()> {
+    let checks = run_checks(client, config, profile).await;
+    for check in &checks {
+        println!("{}", format_check(check));
+    }
+
+    let failed = checks.iter().filter(|c| !c.passed).count();
+    if failed > 0 {
+        return Err(anyhow::anyhow!("{} of {} checks failed", failed, checks.len()));
+    }
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use aws_sdk_s3::error::ErrorMetadata;
+use aws_sdk_s3::types::error::{NoSuchBucket, NoSuchKey, NoSuchUpload, NotFound};
+use std::fmt;
+
+/// Broad reason a command failed, reported through the process exit code so
+/// scripts can branch on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    Generic,
+    Usage,
+    AccessDenied,
+    NotFound,
+}
+
+impl FailureKind {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            FailureKind::Generic => 1,
+            FailureKind::Usage => 2,
+            FailureKind::AccessDenied => 3,
+            FailureKind::NotFound => 4,
+        }
+    }
+}
+
+/// An error whose kind is known where it is raised, e.g. invalid input that
+/// never reaches S3.
+#[derive(Debug)]
+pub struct KnownFailure {
+    pub kind: FailureKind,
+    pub message: String,
+}
+
+impl fmt::Display for KnownFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for KnownFailure {}
+
+/// Invalid command-line input or configuration.
+pub fn usage_error(message: impl Into<String>) -> anyhow::Error {
+    KnownFailure { kind: FailureKind::Usage, message: message.into() }.into()
+}
+
+/// A request S3 refused, for responses that carry no error code (HEAD).
+pub fn access_denied_error(message: impl Into<String>) -> anyhow::Error {
+    KnownFailure { kind: FailureKind::AccessDenied, message: message.into() }.into()
+}
+
+/// Something that does not exist, when the check itself succeeded (e.g. `object exists`).
+pub fn not_found_error(message: impl Into<String>) -> anyhow::Error {
+    KnownFailure { kind: FailureKind::NotFound, message: message.into() }.into()
+}
+
+/// Work out why `err` happened from the errors in its chain: a
+/// [`KnownFailure`], a modeled S3 "not found" error, or the S3 error code.
+pub fn classify_error(err: &anyhow::Error) -> FailureKind {
+    for cause in err.chain() {
+        if let Some(known) = cause.downcast_ref::<KnownFailure>() {
+            return known.kind;
+        }
+        if cause.is::<NoSuchKey>() || cause.is::<NoSuchBucket>() || cause.is::<NoSuchUpload>() || cause.is::<NotFound>() {
+            return FailureKind::NotFound;
+        }
+        if let Some(code) = cause.downcast_ref::<ErrorMetadata>().and_then(|meta| meta.code()) {
+            return classify_error_code(code);
+        }
+    }
+    FailureKind::Generic
+}
+
+/// Whether `err` says the bucket does not exist.
+pub fn is_no_such_bucket(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause.is::<NoSuchBucket>()
+            || cause.downcast_ref::<ErrorMetadata>().and_then(|meta| meta.code()) == Some("NoSuchBucket")
+    })
+}
+
+/// Map an S3 error code such as `AccessDenied` or `NoSuchLifecycleConfiguration`.
+pub fn classify_error_code(code: &str) -> FailureKind {
+    match code {
+        _ if crate::client::AUTH_ERROR_CODES.contains(&code) => FailureKind::AccessDenied,
+        "AllAccessDisabled" | "AccountProblem" => FailureKind::AccessDenied,
+        "NotFound" => FailureKind::NotFound,
+        _ if code.starts_with("NoSuch") || code.ends_with("NotFoundError") => FailureKind::NotFound,
+        "InvalidBucketName" | "InvalidArgument" => FailureKind::Usage,
+        _ => FailureKind::Generic,
+    }
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use crate::errors::usage_error;
+use globset::{Glob, GlobMatcher};
+
+/// One `--include` or `--exclude` pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FilterRule {
+    Include(String),
+    Exclude(String),
+}
+
+/// Include/exclude globs applied to relative paths (or keys), aws-cli style:
+/// everything is included by default, rules are checked in the order given,
+/// and the last rule that matches decides.
+///
+/// Patterns match the whole relative path, not just the file name, and `*`
+/// also matches `/`: `*.log` matches `app.log` and `logs/2024/app.log`, while
+/// `logs/*` matches everything under `logs/`.
+#[derive(Default)]
+pub struct PathFilter {
+    rules: Vec<(bool, GlobMatcher)>,
+}
+
+impl PathFilter {
+    pub fn new(rules: &[FilterRule]) -> Result<PathFilter> {
+        let mut compiled = Vec::new();
+        for rule in rules {
+            let (include, pattern) = match rule {
+                FilterRule::Include(p) => (true, p),
+                FilterRule::Exclude(p) => (false, p),
+            };
+            let glob = Glob::new(pattern)
+                .map_err(|e| usage_error(format!("Invalid pattern '{}': {}", pattern, e)))?;
+            compiled.push((include, glob.compile_matcher()));
+        }
+        Ok(PathFilter { rules: compiled })
+    }
+
+    pub fn is_included(&self, path: &str) -> bool {
+        let mut included = true;
+        for (include, matcher) in &self.rules {
+            if matcher.is_match(path) {
+                included = *include;
+            }
+        }
+        included
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use crate::errors::usage_error;
+
+/// Grantee types S3 accepts in `x-amz-grant-*` headers.
+const GRANTEE_TYPES: [&str; 3] = ["id", "uri", "emailAddress"];
+
+/// Explicit grants from the `--grant-*` options. Each field is the value of
+/// the matching `x-amz-grant-*` header, e.g. `id="abc", uri="http://..."`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Grants {
+    pub read: Option<String>,
+    pub write: Option<String>,
+    pub read_acp: Option<String>,
+    pub write_acp: Option<String>,
+    pub full_control: Option<String>,
+}
+
+impl Grants {
+    pub fn is_empty(&self) -> bool {
+        self.read.is_none()
+            && self.write.is_none()
+            && self.read_acp.is_none()
+            && self.write_acp.is_none()
+            && self.full_control.is_none()
+    }
+
+    /// Objects have no WRITE permission; reject `--grant-write` for them.
+    pub fn check_object(&self) -> Result<()> {
+        if self.write.is_some() {
+            return Err(usage_error("--grant-write only applies to buckets"));
+        }
+        Ok(())
+    }
+}
+
+/// Parse grantees given as `type=value`, where type is `id`, `uri` or
+/// `emailAddress`, into a header value. Each value may list several
+/// grantees separated by commas. Returns `None` when there are none.
+pub fn parse_grantees(values: &[String]) -> Result<Option<String>> {
+    let mut grantees = Vec::new();
+    for grantee in values.iter().flat_map(|v| v.split(',')) {
+        let grantee = grantee.trim();
+        let (kind, value) = grantee.split_once('=')
+            .ok_or_else(|| usage_error(format!("Invalid grantee '{}'. Use id=..., uri=... or emailAddress=...", grantee)))?;
+        let kind = GRANTEE_TYPES.iter()
+            .find(|t| t.eq_ignore_ascii_case(kind.trim()))
+            .ok_or_else(|| usage_error(format!("Invalid grantee type '{}'. Use id, uri or emailAddress", kind.trim())))?;
+        let value = value.trim().trim_matches('"');
+        if value.is_empty() {
+            return Err(usage_error(format!("Grantee '{}' has no value", grantee)));
+        }
+        if *kind == "uri" && !value.starts_with("http://") && !value.starts_with("https://") {
+            return Err(usage_error(format!("Grantee uri '{}' must be a group URI, e.g. http://acs.amazonaws.com/groups/global/AllUsers", value)));
+        }
+        grantees.push(format!("{}=\"{}\"", kind, value));
+    }
+
+    if grantees.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(grantees.join(", ")))
+}
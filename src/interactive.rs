@@ -0,0 +1,130 @@
+use anyhow::Result;
+use aws_sdk_s3::Client;
+use aws_sdk_s3::types::Object;
+use colored::*;
+use console::{Key, Term};
+use crate::errors::usage_error;
+use crate::ui::{format_size, note};
+use std::io::IsTerminal;
+
+/// Objects listed per bucket; narrow a bigger bucket with `object list --prefix`.
+const MAX_OBJECTS: usize = 1000;
+
+/// Menu shown for a selected object, in order.
+pub const OBJECT_ACTIONS: [&str; 3] = ["Download", "Delete", "Back"];
+
+/// One menu line per object: the key and its size.
+pub fn object_menu_items(objects: &[Object]) -> Vec<String> {
+    objects.iter()
+        .map(|o| format!("{} ({})", o.key().unwrap_or("<unknown>"), format_size(o.size().unwrap_or(0))))
+        .collect()
+}
+
+/// The lines to draw for `items` with `cursor` highlighted, showing at most
+/// `page` items: the page the cursor is on.
+pub fn render_menu(items: &[String], cursor: usize, page: usize) -> Vec<String> {
+    let page = page.max(1);
+    let start = cursor / page * page;
+    let end = (start + page).min(items.len());
+
+    let mut lines: Vec<String> = items[start..end].iter().enumerate()
+        .map(|(i, item)| {
+            if start + i == cursor {
+                format!("{} {}", ">".cyan(), item.cyan())
+            } else {
+                format!("  {}", item)
+            }
+        })
+        .collect();
+    if items.len() > page {
+        lines.push(format!("  ({}-{} of {})", start + 1, end, items.len()).dimmed().to_string());
+    }
+    lines
+}
+
+/// Let the user pick one of `items` with the arrow keys (or j/k) and Enter.
+/// Returns `None` on Escape or q.
+fn select(term: &Term, prompt: &str, items: &[String]) -> Result<Option<usize>> {
+    let page = (term.size().0 as usize).saturating_sub(4).clamp(5, 20);
+    let mut cursor = 0;
+
+    term.write_line(&format!("{} {}", prompt.bold(), "(↑/↓ to move, Enter to select, q to go back)".dimmed()))?;
+    term.hide_cursor()?;
+    let choice = loop {
+        let lines = render_menu(items, cursor, page);
+        for line in &lines {
+            term.write_line(line)?;
+        }
+
+        let key = term.read_key()?;
+        term.clear_last_lines(lines.len())?;
+        match key {
+            Key::ArrowUp | Key::Char('k') => cursor = cursor.checked_sub(1).unwrap_or(items.len() - 1),
+            Key::ArrowDown | Key::Char('j') => cursor = (cursor + 1) % items.len(),
+            Key::Enter => break Some(cursor),
+            Key::Escape | Key::Char('q') => break None,
+            _ => {}
+        }
+    };
+    term.show_cursor()?;
+    term.clear_last_lines(1)?;
+
+    if let Some(index) = choice {
+        term.write_line(&format!("{} {}", prompt.bold(), items[index]))?;
+    }
+    Ok(choice)
+}
+
+/// Browse buckets and objects with a menu, downloading or deleting the
+/// selected object. Needs a terminal; use the other commands in scripts.
+/// With `dry_run`, the actions only print what they would do.
+pub async fn interactive(client: &Client, mfa: Option<&str>, assume_yes: bool, dry_run: bool) -> Result<()> {
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        return Err(usage_error("Interactive mode needs a terminal; use the bucket and object commands in scripts"));
+    }
+    let term = Term::stderr();
+
+    let resp = client.list_buckets().send().await?;
+    let buckets: Vec<String> = resp.buckets().iter()
+        .filter_map(|b| b.name())
+        .map(|name| name.to_string())
+        .collect();
+    if buckets.is_empty() {
+        note("No buckets found.");
+        return Ok(());
+    }
+
+    while let Some(b) = select(&term, "Bucket:", &buckets)? {
+        let bucket = &buckets[b];
+        let regional = crate::client::client_for_bucket(client, bucket).await;
+
+        loop {
+            let (objects, truncated) = crate::objects::fetch_objects(&regional, bucket, None, None, Some(MAX_OBJECTS)).await?;
+            if objects.is_empty() {
+                note(format!("Bucket '{}' is empty.", bucket));
+                break;
+            }
+            if truncated {
+                note(format!("Showing the first {} objects.", MAX_OBJECTS));
+            }
+
+            let Some(o) = select(&term, "Object:", &object_menu_items(&objects))? else {
+                break;
+            };
+            let key = objects[o].key().unwrap_or_default();
+
+            let actions: Vec<String> = OBJECT_ACTIONS.iter().map(|a| a.to_string()).collect();
+            let result = match select(&term, "Action:", &actions)?.map(|a| OBJECT_ACTIONS[a]) {
+                Some("Download") => crate::objects::download_object(&regional, bucket, key, None, None, 1, dry_run).await,
+                Some("Delete") => crate::objects::delete_object(&regional, bucket, key, None, mfa, assume_yes, dry_run).await,
+                _ => Ok(()),
+            };
+            // Stay in the browser after a failed action
+            if let Err(err) = result {
+                eprintln!("{} {:#}", "✘".red(), err);
+            }
+        }
+    }
+
+    Ok(())
+}
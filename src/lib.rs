@@ -2,3 +2,15 @@ pub mod client;
 pub mod buckets;
 pub mod objects;
 pub mod ui;
+pub mod logging;
+pub mod settings;
+pub mod filter;
+pub mod errors;
+pub mod doctor;
+pub mod interactive;
+pub mod grants;
+pub mod presign;
+pub mod progress;
+pub mod version;
+pub mod archive;
+pub mod policy;
@@ -0,0 +1,183 @@
+use anyhow::Result;
+use aws_sdk_s3::Client;
+use aws_sdk_s3::config::{ConfigBag, Intercept, RuntimeComponents};
+use aws_sdk_s3::config::interceptors::{BeforeSerializationInterceptorContextRef, BeforeTransmitInterceptorContextRef, FinalizerInterceptorContextRef};
+use aws_smithy_runtime_api::box_error::BoxError;
+use aws_smithy_runtime_api::client::orchestrator::Metadata;
+use aws_smithy_types::config_bag::{Storable, StoreReplace};
+use colored::*;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing_subscriber::EnvFilter;
+use crate::ui::OutputFormat;
+
+/// Log filter for a `-v` count, or `None` to stay quiet.
+pub fn log_filter(verbosity: u8) -> Option<&'static str> {
+    match verbosity {
+        0 => None,
+        1 => Some("warn,s3sh=info"),
+        // The SDK logs each request and response at debug
+        2 => Some("info,s3sh=debug,aws_smithy_runtime=debug,aws_sdk_s3=debug"),
+        _ => Some("debug,s3sh=trace,aws_smithy_runtime=trace,aws_sdk_s3=trace"),
+    }
+}
+
+/// Send tracing output to stderr for the given `-v` count, keeping stdout
+/// clean for piped output. Does nothing without `-v`.
+pub fn init_logging(verbosity: u8) -> Result<()> {
+    let Some(filter) = log_filter(verbosity) else {
+        return Ok(());
+    };
+
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(filter))
+        .with_writer(std::io::stderr)
+        .try_init()
+        .map_err(|e| anyhow::anyhow!("Failed to initialize logging: {}", e))
+}
+
+/// Logs each S3 operation name and the endpoint it is sent to.
+#[derive(Debug)]
+struct RequestLogger;
+
+impl Intercept for RequestLogger {
+    fn name(&self) -> &'static str {
+        "RequestLogger"
+    }
+
+    fn read_before_transmit(
+        &self,
+        context: &BeforeTransmitInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let operation = cfg.load::<Metadata>().map(|m| m.name()).unwrap_or("<unknown>");
+        tracing::debug!(operation, endpoint = context.request().uri(), "Sending S3 request");
+        Ok(())
+    }
+}
+
+/// Return a copy of `client` that logs every operation before it is sent.
+pub fn with_request_logging(client: &Client) -> Client {
+    let config = client.config()
+        .to_builder()
+        .interceptor(RequestLogger)
+        .build();
+    Client::from_conf(config)
+}
+
+/// The duration of one S3 API call, retries included.
+#[derive(Debug, Clone, Serialize)]
+pub struct CallTiming {
+    pub operation: String,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
+    pub duration: Duration,
+}
+
+fn serialize_millis<S: serde::Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+/// Every S3 call made through clients from [`with_timing`], in the order
+/// they finished, plus when recording started.
+#[derive(Debug, Clone)]
+pub struct CallTimings {
+    started: Instant,
+    calls: Arc<Mutex<Vec<CallTiming>>>,
+}
+
+impl Default for CallTimings {
+    fn default() -> Self {
+        CallTimings { started: Instant::now(), calls: Arc::default() }
+    }
+}
+
+impl CallTimings {
+    pub fn calls(&self) -> Vec<CallTiming> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Print each call and the total wall-clock time to stderr, as a
+    /// `timings` JSON object with `--output json`.
+    pub fn report(&self, output: OutputFormat) {
+        let calls = self.calls();
+        let total = self.started.elapsed();
+        match output {
+            OutputFormat::Json => {
+                let report = serde_json::json!({
+                    "timings": calls,
+                    "total_ms": total.as_secs_f64() * 1000.0,
+                });
+                eprintln!("{}", report);
+            }
+            OutputFormat::Table | OutputFormat::Csv => eprint!("{}", format_timings(&calls, total)),
+        }
+    }
+}
+
+/// One line per call, then the number of calls and the total time.
+pub fn format_timings(calls: &[CallTiming], total: Duration) -> String {
+    let mut out = String::new();
+    for call in calls {
+        out.push_str(&format!("{:>10.1} ms  {}\n", call.duration.as_secs_f64() * 1000.0, call.operation));
+    }
+    out.push_str(&format!(
+        "{} {} in {:.1} ms\n",
+        "Total:".bold(),
+        crate::ui::pluralize(calls.len(), "S3 call"),
+        total.as_secs_f64() * 1000.0
+    ));
+    out
+}
+
+/// When the current operation started, kept in its config bag.
+#[derive(Debug, Clone)]
+struct CallStarted(Instant);
+
+impl Storable for CallStarted {
+    type Storer = StoreReplace<Self>;
+}
+
+/// Records how long each operation takes, from before it is serialized to
+/// after its response (or error) is handled.
+#[derive(Debug)]
+struct CallTimer(CallTimings);
+
+impl Intercept for CallTimer {
+    fn name(&self) -> &'static str {
+        "CallTimer"
+    }
+
+    fn read_before_execution(
+        &self,
+        _context: &BeforeSerializationInterceptorContextRef<'_>,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        cfg.interceptor_state().store_put(CallStarted(Instant::now()));
+        Ok(())
+    }
+
+    fn read_after_execution(
+        &self,
+        _context: &FinalizerInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        if let Some(CallStarted(started)) = cfg.load::<CallStarted>() {
+            let operation = cfg.load::<Metadata>().map(|m| m.name()).unwrap_or("<unknown>").to_string();
+            self.0.calls.lock().unwrap().push(CallTiming { operation, duration: started.elapsed() });
+        }
+        Ok(())
+    }
+}
+
+/// Return a copy of `client` that records the duration of every call in
+/// `timings`.
+pub fn with_timing(client: &Client, timings: &CallTimings) -> Client {
+    let config = client.config()
+        .to_builder()
+        .interceptor(CallTimer(timings.clone()))
+        .build();
+    Client::from_conf(config)
+}
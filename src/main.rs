@@ -1,10 +1,15 @@
-use clap::{Parser, Subcommand};
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::Shell;
 use anyhow::Result;
-use s3sh::{client, buckets, objects};
+use aws_config::SdkConfig;
+use aws_sdk_s3::Client;
+use s3sh::{archive, client, buckets, doctor, errors, interactive, logging, objects, policy, presign, settings::Settings, ui, version};
+use s3sh::filter::{FilterRule, PathFilter};
+use s3sh::grants::{self, Grants};
 
 #[derive(Parser)]
 #[command(name = "s3sh")]
-#[command(about = "A simple S3 CLI in Rust", long_about = None)]
+#[command(about = "A simple S3 CLI in Rust", long_about = None, version)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
@@ -16,6 +21,98 @@ struct Cli {
     /// AWS Profile
     #[arg(short, long, global = true)]
     profile: Option<String>,
+
+    /// IAM role to assume (uses --profile credentials as the source)
+    #[arg(long, global = true)]
+    role_arn: Option<String>,
+
+    /// Session name for the assumed role (default: s3sh-<timestamp>)
+    #[arg(long, global = true, requires = "role_arn")]
+    role_session_name: Option<String>,
+
+    /// Access key to use instead of the profile chain (requires --secret-access-key; visible in process listings)
+    #[arg(long, global = true, requires = "secret_access_key")]
+    access_key_id: Option<String>,
+
+    /// Secret key for --access-key-id
+    #[arg(long, global = true, requires = "access_key_id")]
+    secret_access_key: Option<String>,
+
+    /// Session token for temporary --access-key-id credentials
+    #[arg(long, global = true, requires = "access_key_id")]
+    session_token: Option<String>,
+
+    /// Maximum number of retries for failed requests (default: SDK standard, 2)
+    #[arg(long, global = true)]
+    max_retries: Option<u32>,
+
+    /// Give up on an operation (including retries) after this many seconds
+    #[arg(long, global = true)]
+    timeout_secs: Option<u64>,
+
+    /// Attempts for restores and bulk deletes throttled by S3 (SlowDown, RequestTimeout, 503), with backoff
+    #[arg(long, global = true, default_value = "3")]
+    transient_attempts: u32,
+
+    /// Skip confirmation prompts for destructive operations
+    #[arg(short, long, global = true)]
+    yes: bool,
+
+    /// MFA device serial and current code ("SERIAL CODE") for MFA-protected deletes and versioning changes
+    #[arg(long, global = true)]
+    mfa: Option<String>,
+
+    /// Print the API calls mutating commands would make without sending them
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Disable colored output (also honors the NO_COLOR environment variable)
+    #[arg(long, global = true, conflicts_with = "color")]
+    no_color: bool,
+
+    /// Force colored output even when stdout is not a terminal
+    #[arg(long, global = true)]
+    color: bool,
+
+    /// S3-compatible endpoint to send requests to, e.g. http://localhost:9000 (overrides endpoint_url in the config file)
+    #[arg(long, global = true)]
+    endpoint_url: Option<String>,
+
+    /// Skip TLS certificate verification for the custom endpoint (insecure; for local testing only)
+    #[arg(long, global = true)]
+    no_verify_ssl: bool,
+
+    /// Address buckets as <endpoint>/<bucket> (default: only for non-AWS endpoints)
+    #[arg(long, global = true, conflicts_with = "no_path_style")]
+    path_style: bool,
+
+    /// Address buckets as <bucket>.<endpoint>, even for a non-AWS endpoint
+    #[arg(long, global = true)]
+    no_path_style: bool,
+
+    /// Acknowledge paying for requests to requester-pays buckets (only "requester")
+    #[arg(long, global = true, value_parser = ["requester"])]
+    request_payer: Option<String>,
+
+    /// Keys to ask for per list request (capped at 1000) in object listings and bulk commands; does not change how many results are returned
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(1..))]
+    page_size: Option<u32>,
+
+    /// Output format: table, json or csv (only some commands support json; csv is for bucket and object listings)
+    #[arg(long, global = true)]
+    output: Option<String>,
+
+    /// Suppress confirmations, notes and progress bars; only command output and errors are printed
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Print how long each S3 call took, and the total, to stderr
+    #[arg(long, global = true)]
+    timing: bool,
+
+    /// Log to stderr (-v info, -vv SDK requests, -vvv region, endpoint and operations)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
 }
 
 #[derive(Subcommand)]
@@ -30,17 +127,52 @@ enum Commands {
         #[command(subcommand)]
         action: ObjectAction,
     },
+    /// Check connectivity (and access to a bucket, if given)
+    Ping {
+        /// Also check that this bucket exists and is accessible
+        #[arg(long)]
+        bucket: Option<String>,
+    },
+    /// Diagnose credential, region and access problems (read-only)
+    Doctor,
+    /// Browse buckets and objects with a menu, then download or delete (needs a terminal)
+    Interactive,
+    /// Show the s3sh and AWS SDK versions, target and git commit (for bug reports)
+    Version,
+    /// Print a shell completion script to stdout
+    #[command(hide = true)]
+    Completions {
+        /// Shell to generate completions for
+        shell: Shell,
+    },
 }
 
 #[derive(Subcommand)]
 enum BucketAction {
     /// List all buckets
-    List,
+    List {
+        /// Sort by name or modified (creation date)
+        #[arg(long, default_value = "name")]
+        sort: String,
+        /// Reverse the sort order
+        #[arg(long)]
+        reverse: bool,
+        /// Only list buckets whose name contains this (or matches it, with --regex)
+        #[arg(long)]
+        filter: Option<String>,
+        /// Treat --filter as a regular expression
+        #[arg(long, requires = "filter")]
+        regex: bool,
+    },
     /// Create a new bucket
     Create {
         /// Name of the bucket
         name: String,
 
+        /// Canned ACL to create the bucket with (e.g. private, public-read)
+        #[arg(long)]
+        acl: Option<String>,
+
         /// Set public access (true = public, false = private/block all)
         #[arg(long)]
         public: Option<bool>,
@@ -53,6 +185,14 @@ enum BucketAction {
         #[arg(long)]
         encryption: Option<String>,
 
+        /// KMS key for default encryption (requires --encryption aws:kms)
+        #[arg(long, requires = "encryption")]
+        kms_key_id: Option<String>,
+
+        /// Use an S3 Bucket Key for KMS encryption to reduce KMS requests
+        #[arg(long, requires = "encryption")]
+        bucket_key: bool,
+
         /// Add tags (Key=Value)
         #[arg(long, value_parser = parse_key_val::<String, String>)]
         tags: Vec<(String, String)>,
@@ -62,6 +202,11 @@ enum BucketAction {
         /// Name of the bucket
         name: String,
     },
+    /// Show versioning and MFA delete status
+    Versioning {
+        /// Name of the bucket
+        name: String,
+    },
     /// Update bucket configuration
     Update {
         /// Name of the bucket
@@ -75,16 +220,145 @@ enum BucketAction {
         #[arg(long)]
         versioning: Option<bool>,
 
+        /// Enable or disable MFA delete (requires --versioning and --mfa)
+        #[arg(long, requires = "versioning")]
+        mfa_delete: Option<bool>,
+
         /// Set encryption mode (AES256 or aws:kms)
         #[arg(long)]
         encryption: Option<String>,
 
+        /// KMS key for default encryption (requires --encryption aws:kms)
+        #[arg(long, requires = "encryption")]
+        kms_key_id: Option<String>,
+
+        /// Use an S3 Bucket Key for KMS encryption to reduce KMS requests
+        #[arg(long, requires = "encryption")]
+        bucket_key: bool,
+
         /// Add tags (Key=Value)
         #[arg(long, value_parser = parse_key_val::<String, String>)]
         tags: Vec<(String, String)>,
     },
     /// Manage lifecycle rules
     Lifecycle {
+        #[command(subcommand)]
+        action: LifecycleAction,
+    },
+    /// Manage the bucket policy
+    Policy {
+        #[command(subcommand)]
+        action: PolicyAction,
+    },
+    /// Manage CORS rules
+    Cors {
+        #[command(subcommand)]
+        action: CorsAction,
+    },
+    /// Manage static website hosting
+    Website {
+        #[command(subcommand)]
+        action: WebsiteAction,
+    },
+    /// Manage the bucket ACL
+    Acl {
+        #[command(subcommand)]
+        action: AclAction,
+    },
+    /// Manage Object Ownership (set BucketOwnerEnforced to disable ACLs)
+    #[command(name = "ownership-controls", alias = "ownership")]
+    Ownership {
+        #[command(subcommand)]
+        action: OwnershipAction,
+    },
+    /// Show or remove bucket tags (set them with `update --tags`)
+    #[command(alias = "tagging")]
+    Tags {
+        #[command(subcommand)]
+        action: TagsAction,
+    },
+    /// Manage default encryption
+    Encryption {
+        #[command(subcommand)]
+        action: EncryptionAction,
+    },
+    /// Manage cross-region replication
+    Replication {
+        #[command(subcommand)]
+        action: ReplicationAction,
+    },
+    /// Manage S3 Inventory report configurations
+    Inventory {
+        #[command(subcommand)]
+        action: InventoryAction,
+    },
+    /// Manage storage class analysis configurations
+    Analytics {
+        #[command(subcommand)]
+        action: AnalyticsAction,
+    },
+    /// Manage CloudWatch request metrics configurations
+    Metrics {
+        #[command(subcommand)]
+        action: MetricsAction,
+    },
+    /// Manage server access logging
+    Logging {
+        #[command(subcommand)]
+        action: LoggingAction,
+    },
+    /// Manage Transfer Acceleration
+    Accelerate {
+        #[command(subcommand)]
+        action: AccelerateAction,
+    },
+    /// Manage who pays for requests (requester pays)
+    RequestPayment {
+        #[command(subcommand)]
+        action: RequestPaymentAction,
+    },
+    /// Show event notification configuration
+    Notification {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Delete a bucket
+    Delete {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Show the number and total size of objects (like du)
+    Du {
+        /// Name of the bucket
+        name: String,
+
+        /// Only count keys starting with this prefix
+        #[arg(long)]
+        prefix: Option<String>,
+
+        /// Also show totals for each top-level sub-prefix
+        #[arg(long)]
+        group_by_prefix: bool,
+
+        /// Show sizes in raw bytes instead of KiB/MiB/GiB
+        #[arg(long)]
+        bytes: bool,
+    },
+    /// Empty a bucket (delete all objects)
+    Empty {
+        /// Name of the bucket
+        name: String,
+
+        /// Also delete all object versions and delete markers
+        #[arg(long)]
+        versions: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum LifecycleAction {
+    /// Add or replace a lifecycle rule
+    Put {
         /// Name of the bucket
         name: String,
 
@@ -93,33 +367,349 @@ enum BucketAction {
         id: String,
 
         /// Prefix filter (default empty)
-        #[arg(long, default_value = "")]
+        #[arg(long, default_value = "", conflicts_with = "filter")]
         prefix: String,
 
-        /// Transitions JSON string (e.g. '[{"days": 30, "storage_class": "STANDARD_IA"}]')
+        /// Filter JSON with any of prefix, tags, object_size_greater_than and object_size_less_than
+        /// (e.g. '{"prefix": "logs/", "tags": {"env": "prod"}, "object_size_greater_than": 1024}')
         #[arg(long)]
+        filter: Option<String>,
+
+        /// Transitions JSON string (e.g. '[{"days": 30, "storage_class": "STANDARD_IA"}]')
+        #[arg(long, default_value = "[]")]
         transitions: String,
 
         /// Expiration days
         #[arg(long)]
         expiration: Option<i32>,
 
+        /// Days after an object version becomes noncurrent before it is deleted
+        #[arg(long)]
+        noncurrent_expiration: Option<i32>,
+
+        /// Days after an incomplete multipart upload starts before it is aborted
+        #[arg(long)]
+        abort_incomplete_upload: Option<i32>,
+
         /// Enable rule (default true)
         #[arg(long, default_value = "true")]
         status: bool,
     },
-    /// Delete a bucket
+    /// List lifecycle rules
+    List {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Delete a lifecycle rule
     Delete {
         /// Name of the bucket
         name: String,
+
+        /// Rule ID
+        #[arg(long)]
+        id: String,
     },
-    /// Empty a bucket (delete all objects and versions)
-    Empty {
+}
+
+#[derive(Subcommand)]
+enum PolicyAction {
+    /// Show the bucket policy
+    Get {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Set the bucket policy from a JSON file
+    Set {
+        /// Name of the bucket
+        name: String,
+
+        /// Path to the policy JSON file
+        file: String,
+    },
+    /// Delete the bucket policy
+    Delete {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Check a policy JSON file for mistakes without calling AWS
+    Validate {
+        /// Path to the policy JSON file
+        file: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum CorsAction {
+    /// Show the CORS rules
+    Get {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Set the CORS rules from a JSON file
+    Set {
+        /// Name of the bucket
+        name: String,
+
+        /// Path to the CORS rules JSON file
+        file: String,
+    },
+    /// Delete the CORS configuration
+    Delete {
+        /// Name of the bucket
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum WebsiteAction {
+    /// Show the website configuration
+    Get {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Enable static website hosting
+    Set {
+        /// Name of the bucket
+        name: String,
+
+        /// Object served for directory requests (e.g. index.html)
+        #[arg(long, default_value = "index.html")]
+        index_document: String,
+
+        /// Object served on errors (e.g. error.html)
+        #[arg(long)]
+        error_document: Option<String>,
+    },
+    /// Delete the website configuration
+    Delete {
+        /// Name of the bucket
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AclAction {
+    /// Show the owner and grants
+    Get {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Apply a canned ACL
+    Set {
+        /// Name of the bucket
+        name: String,
+
+        /// Canned ACL (private, public-read, public-read-write or authenticated-read)
+        #[arg(required_unless_present_any = GRANT_ARGS)]
+        canned: Option<String>,
+
+        #[command(flatten)]
+        grants: GrantArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum OwnershipAction {
+    /// Show the bucket's Object Ownership setting
+    Get {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Set who owns uploaded objects and whether ACLs apply
+    Set {
+        /// Name of the bucket
+        name: String,
+
+        /// BucketOwnerEnforced, BucketOwnerPreferred or ObjectWriter
+        rule: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagsAction {
+    /// Show the bucket's tags
+    Get {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Remove all of the bucket's tags
+    Delete {
         /// Name of the bucket
         name: String,
     },
 }
 
+#[derive(Subcommand)]
+enum EncryptionAction {
+    /// Show the default encryption algorithm and KMS key
+    Get {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Remove the default encryption configuration
+    Delete {
+        /// Name of the bucket
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReplicationAction {
+    /// Show the replication role and rules
+    Get {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Delete the replication configuration
+    Delete {
+        /// Name of the bucket
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum InventoryAction {
+    /// List the inventory configurations
+    List {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Show one inventory configuration
+    Get {
+        /// Name of the bucket
+        name: String,
+
+        /// Inventory configuration ID
+        #[arg(long)]
+        id: String,
+    },
+    /// Delete an inventory configuration
+    Delete {
+        /// Name of the bucket
+        name: String,
+
+        /// Inventory configuration ID
+        #[arg(long)]
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AnalyticsAction {
+    /// List the analytics configurations
+    List {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Show one analytics configuration
+    Get {
+        /// Name of the bucket
+        name: String,
+
+        /// Analytics configuration ID
+        #[arg(long)]
+        id: String,
+    },
+    /// Delete an analytics configuration
+    Delete {
+        /// Name of the bucket
+        name: String,
+
+        /// Analytics configuration ID
+        #[arg(long)]
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum MetricsAction {
+    /// List the metrics configurations
+    List {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Show one metrics configuration
+    Get {
+        /// Name of the bucket
+        name: String,
+
+        /// Metrics configuration ID
+        #[arg(long)]
+        id: String,
+    },
+    /// Delete a metrics configuration
+    Delete {
+        /// Name of the bucket
+        name: String,
+
+        /// Metrics configuration ID
+        #[arg(long)]
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum LoggingAction {
+    /// Show where access logs are delivered
+    Get {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Deliver access logs to a target bucket
+    Set {
+        /// Name of the bucket
+        name: String,
+
+        /// Bucket that receives the log files
+        #[arg(long)]
+        target_bucket: String,
+
+        /// Prefix for the log file keys (e.g. "logs/")
+        #[arg(long, default_value = "")]
+        target_prefix: String,
+    },
+    /// Turn access logging off
+    Disable {
+        /// Name of the bucket
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AccelerateAction {
+    /// Show whether Transfer Acceleration is enabled
+    Get {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Enable or suspend Transfer Acceleration
+    Set {
+        /// Name of the bucket
+        name: String,
+
+        /// true to enable, false to suspend
+        #[arg(long, action = clap::ArgAction::Set)]
+        enabled: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum RequestPaymentAction {
+    /// Show who pays for requests
+    Get {
+        /// Name of the bucket
+        name: String,
+    },
+    /// Set who pays for requests
+    Set {
+        /// Name of the bucket
+        name: String,
+
+        /// BucketOwner or Requester
+        payer: String,
+    },
+}
+
 fn parse_key_val<T, U>(s: &str) -> Result<(T, U), Box<dyn std::error::Error + Send + Sync + 'static>>
 where
     T: std::str::FromStr,
@@ -133,32 +723,407 @@ where
     Ok((s[..pos].parse()?, s[pos + 1..].parse()?))
 }
 
+/// HTTP headers shared by `object upload` and `object set-meta`
+#[derive(Args)]
+struct HeaderArgs {
+    /// Cache-Control header (e.g. "public, max-age=31536000")
+    #[arg(long)]
+    cache_control: Option<String>,
+    /// Content-Disposition header (e.g. "attachment; filename=report.pdf")
+    #[arg(long)]
+    content_disposition: Option<String>,
+    /// Content-Encoding header (e.g. gzip)
+    #[arg(long)]
+    content_encoding: Option<String>,
+    /// Expires header: an HTTP date, RFC3339, or a duration from now (e.g. 12h, 7d)
+    #[arg(long)]
+    expires: Option<String>,
+}
+
+impl HeaderArgs {
+    fn into_headers(self, content_type: Option<String>) -> Result<objects::ObjectHeaders> {
+        let expires = self.expires
+            .map(|e| objects::parse_expires(&e, std::time::SystemTime::now()))
+            .transpose()?;
+        Ok(objects::ObjectHeaders {
+            content_type,
+            cache_control: self.cache_control,
+            content_disposition: self.content_disposition,
+            content_encoding: self.content_encoding,
+            expires,
+        })
+    }
+}
+
+/// Argument ids of the `--grant-*` options, for `acl set` without a canned ACL.
+const GRANT_ARGS: [&str; 5] = ["grant_read", "grant_write", "grant_read_acp", "grant_write_acp", "grant_full_control"];
+
+/// Explicit ACL grants shared by `object upload` and the `acl set` commands.
+/// Each takes grantees as id=..., uri=... or emailAddress=..., comma-separated.
+#[derive(Args)]
+struct GrantArgs {
+    /// Grant READ (e.g. uri=http://acs.amazonaws.com/groups/global/AllUsers)
+    #[arg(long)]
+    grant_read: Vec<String>,
+    /// Grant WRITE (buckets only)
+    #[arg(long)]
+    grant_write: Vec<String>,
+    /// Grant READ_ACP: permission to read the ACL
+    #[arg(long)]
+    grant_read_acp: Vec<String>,
+    /// Grant WRITE_ACP: permission to change the ACL
+    #[arg(long)]
+    grant_write_acp: Vec<String>,
+    /// Grant FULL_CONTROL (e.g. id=<canonical-user-id>)
+    #[arg(long)]
+    grant_full_control: Vec<String>,
+}
+
+impl GrantArgs {
+    fn into_grants(self) -> Result<Grants> {
+        Ok(Grants {
+            read: grants::parse_grantees(&self.grant_read)?,
+            write: grants::parse_grantees(&self.grant_write)?,
+            read_acp: grants::parse_grantees(&self.grant_read_acp)?,
+            write_acp: grants::parse_grantees(&self.grant_write_acp)?,
+            full_control: grants::parse_grantees(&self.grant_full_control)?,
+        })
+    }
+}
+
 #[derive(Subcommand)]
 enum ObjectAction {
     /// List objects in a bucket
     List {
         /// Name of the bucket
         bucket: String,
+        /// Only list keys starting with this prefix
+        #[arg(long)]
+        prefix: Option<String>,
+        /// Start listing after this key (e.g. the last key of a previous --limit listing)
+        #[arg(long, alias = "after")]
+        start_after: Option<String>,
+        /// Stop after this many objects (counting only matches of --since/--until)
+        #[arg(long, alias = "max-keys", value_parser = clap::value_parser!(u64).range(1..))]
+        limit: Option<u64>,
+        /// Only show objects modified at or after this time (RFC3339, a date, or an age like 7d)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only show objects modified at or before this time (RFC3339, a date, or an age like 7d)
+        #[arg(long)]
+        until: Option<String>,
+        /// Show sizes in raw bytes instead of KiB/MiB/GiB
+        #[arg(long)]
+        bytes: bool,
+        /// Also show each object's ETag and storage class
+        #[arg(long, conflicts_with = "tree")]
+        wide: bool,
+        /// List every key under the prefix, however deeply nested (the default)
+        #[arg(long)]
+        recursive: bool,
+        /// With --recursive, show the keys as an indented tree of their /-separated segments
+        #[arg(long, requires = "recursive")]
+        tree: bool,
+        /// Head each object to add its content type and user metadata columns
+        #[arg(long, conflicts_with = "tree")]
+        with_metadata: bool,
+        /// Number of objects to head at once with --with-metadata
+        #[arg(long, default_value = "8", requires = "with_metadata")]
+        concurrency: usize,
+        /// Sort by name, size or modified
+        #[arg(long, default_value = "name")]
+        sort: String,
+        /// Reverse the sort order
+        #[arg(long)]
+        reverse: bool,
     },
     /// Upload an object to a bucket
     Upload {
         /// Name of the bucket
         bucket: String,
-        /// Path to the file to upload
+        /// Path to the file to upload, or - to read from stdin (requires --key)
         file: String,
         /// Optional key for the object (defaults to filename)
         #[arg(short, long)]
         key: Option<String>,
+        /// Have S3 verify the upload with this checksum (crc32, crc32c, sha1 or sha256)
+        #[arg(long)]
+        checksum: Option<String>,
+        /// Encrypt the object (AES256 or aws:kms), overriding the bucket default
+        #[arg(long)]
+        sse: Option<String>,
+        /// KMS key to encrypt with (requires --sse aws:kms)
+        #[arg(long)]
+        sse_kms_key_id: Option<String>,
+        /// Canned ACL for the object (e.g. private, public-read, bucket-owner-full-control)
+        #[arg(long)]
+        acl: Option<String>,
+        /// Content type (default: guessed from the file name; application/octet-stream for stdin)
+        #[arg(long)]
+        content_type: Option<String>,
+        /// Detect the content type from the file's first bytes when its name gives none (e.g. no extension)
+        #[arg(long, conflicts_with = "content_type")]
+        guess_content: bool,
+        #[command(flatten)]
+        headers: HeaderArgs,
+        #[command(flatten)]
+        grants: Box<GrantArgs>,
+        /// Tag the object (Key=Value, up to 10), in the same request as the upload
+        #[arg(long, value_parser = parse_key_val::<String, String>)]
+        tags: Vec<(String, String)>,
+        /// Send a Content-MD5 header so the store can verify the body (some S3-compatible stores require it)
+        #[arg(long)]
+        content_md5: bool,
+        /// Don't save or resume the progress of a multipart upload; abort it on failure
+        #[arg(long)]
+        no_resume: bool,
+        /// Skip the upload if the key already holds an object of the same size
+        #[arg(long, visible_alias = "if-size-changed")]
+        skip_existing: bool,
+        /// With --skip-existing, also require the ETag to match the file's MD5
+        #[arg(long, requires = "skip_existing")]
+        compare_etag: bool,
+        /// Upload a directory as one archive object (tar or tar.gz), named <dir>.<format> unless --key is given
+        #[arg(long, conflicts_with_all = ["skip_existing", "content_md5", "guess_content"])]
+        archive: Option<String>,
+    },
+    /// Upload every file in a local directory
+    Sync {
+        /// Local directory to upload
+        dir: String,
+        /// Name of the bucket
+        bucket: String,
+        /// Prefix to put in front of every key (e.g. "backups/")
+        #[arg(long)]
+        prefix: Option<String>,
+        /// Number of files to upload at once
+        #[arg(long, default_value = "4")]
+        concurrency: usize,
+        /// Only upload files whose relative path matches this glob (repeatable)
+        #[arg(long)]
+        include: Vec<String>,
+        /// Skip files whose relative path matches this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+        /// Afterwards delete remote objects under the prefix that have no local file (asks first unless --yes)
+        #[arg(long)]
+        delete: bool,
+    },
+    /// Download an object, or everything under a prefix with --recursive
+    Download {
+        /// Name of the bucket
+        bucket: String,
+        /// Key of the object (the prefix, with --recursive)
+        key: String,
+        /// Local file or directory (default: the current directory)
+        dest: Option<String>,
+        /// Download every object whose key starts with KEY, mirroring the key layout
+        #[arg(long)]
+        recursive: bool,
+        /// Only download these bytes of the object (START-END, inclusive)
+        #[arg(long, conflicts_with = "recursive")]
+        range: Option<String>,
+        /// Download a large object as this many byte ranges at once
+        #[arg(long, default_value = "1", value_parser = clap::value_parser!(u64).range(1..), conflicts_with_all = ["recursive", "range"])]
+        parallel: u64,
+        /// With --recursive, number of objects to download at once
+        #[arg(long, default_value = "4")]
+        concurrency: usize,
+        /// With --recursive, only download keys matching this glob (repeatable)
+        #[arg(long, requires = "recursive")]
+        include: Vec<String>,
+        /// With --recursive, skip keys matching this glob (repeatable)
+        #[arg(long, requires = "recursive")]
+        exclude: Vec<String>,
     },
     /// Delete an object from a bucket
     Delete {
+        /// Name of the bucket
+        bucket: String,
+        /// Key of the object (the prefix, with --recursive)
+        key: String,
+        /// Delete a specific version instead of the current object
+        #[arg(long, conflicts_with = "recursive")]
+        version_id: Option<String>,
+        /// Delete every object whose key starts with KEY
+        #[arg(long)]
+        recursive: bool,
+        /// With --recursive, only delete keys matching this glob (repeatable)
+        #[arg(long, requires = "recursive")]
+        include: Vec<String>,
+        /// With --recursive, keep keys matching this glob (repeatable)
+        #[arg(long, requires = "recursive")]
+        exclude: Vec<String>,
+        /// With --recursive, number of 1000-key batches to delete at once
+        #[arg(long, default_value = "4", requires = "recursive")]
+        concurrency: usize,
+    },
+    /// Copy an object, or everything under a prefix with --recursive
+    Copy {
+        /// Bucket of the source object
+        src_bucket: String,
+        /// Key of the source object (the source prefix, with --recursive)
+        src_key: String,
+        /// Destination bucket (may be the same as the source)
+        dst_bucket: String,
+        /// Destination key (the prefix that replaces the source prefix, with --recursive)
+        dst_key: String,
+        /// Storage class for the copy (e.g. STANDARD_IA, GLACIER)
+        #[arg(long)]
+        storage_class: Option<String>,
+        /// COPY keeps the source metadata; REPLACE uses --metadata instead
+        #[arg(long, default_value = "COPY", conflicts_with = "recursive")]
+        metadata_directive: String,
+        /// Metadata for the copy (Key=Value, requires --metadata-directive REPLACE)
+        #[arg(long, value_parser = parse_key_val::<String, String>, conflicts_with = "recursive")]
+        metadata: Vec<(String, String)>,
+        /// Copy every object whose key starts with SRC_KEY, keeping the key layout under DST_KEY
+        #[arg(long)]
+        recursive: bool,
+        /// With --recursive, number of objects to copy at once
+        #[arg(long, default_value = "4", requires = "recursive")]
+        concurrency: usize,
+        /// With --recursive, only copy keys matching this glob (repeatable)
+        #[arg(long, requires = "recursive")]
+        include: Vec<String>,
+        /// With --recursive, skip keys matching this glob (repeatable)
+        #[arg(long, requires = "recursive")]
+        exclude: Vec<String>,
+    },
+    /// Move or rename an object (copy, then delete the source)
+    Move {
+        /// Bucket of the source object
+        src_bucket: String,
+        /// Key of the source object
+        src_key: String,
+        /// Destination bucket (may be the same as the source)
+        dst_bucket: String,
+        /// Destination key
+        dst_key: String,
+    },
+    /// Move every object under one prefix to another within the bucket
+    Rename {
+        /// Name of the bucket
+        bucket: String,
+        /// Prefix of the keys to move (e.g. "old/")
+        old_prefix: String,
+        /// Prefix to move them to (e.g. "new/"); must not overlap the old one
+        new_prefix: String,
+        /// Number of objects to move at once
+        #[arg(long, default_value = "4")]
+        concurrency: usize,
+    },
+    /// Change an object's content type or user metadata in place
+    SetMeta {
+        /// Name of the bucket
+        bucket: String,
+        /// Key of the object
+        key: String,
+        /// New content type (default: keep the current one)
+        #[arg(long)]
+        content_type: Option<String>,
+        #[command(flatten)]
+        headers: HeaderArgs,
+        /// New user metadata, replacing the current set (Key=Value, repeatable; default: keep)
+        #[arg(long, value_parser = parse_key_val::<String, String>)]
+        metadata: Vec<(String, String)>,
+    },
+    /// List object versions and delete markers
+    Versions {
+        /// Name of the bucket
+        bucket: String,
+        /// Only list keys starting with this prefix
+        #[arg(long)]
+        prefix: Option<String>,
+    },
+    /// Print an object's contents to stdout
+    Cat {
+        /// Name of the bucket
+        bucket: String,
+        /// Key of the object
+        key: String,
+        /// Only print these bytes of the object (START-END, inclusive)
+        #[arg(long)]
+        range: Option<String>,
+    },
+    /// Query a CSV or JSON object with SQL (S3 Select)
+    Select {
         /// Name of the bucket
         bucket: String,
         /// Key of the object
         key: String,
+        /// SQL expression, e.g. "SELECT s.name FROM s3object s WHERE s.city = 'Paris'"
+        query: String,
+        /// Format of the object (csv or json; CSV needs a header row, JSON one object per line)
+        #[arg(long, default_value = "csv")]
+        input_format: String,
+        /// Format of the printed records (csv or json)
+        #[arg(long, default_value = "csv")]
+        output_format: String,
+        /// Compression of the object (none or gzip)
+        #[arg(long, default_value = "none")]
+        compression: String,
     },
-    /// Restore an archived object
+    /// Print a signed HTML form (URL and fields, as JSON) for browser uploads
+    PresignPost {
+        /// Name of the bucket
+        bucket: String,
+        /// Key prefix uploads must use (e.g. "uploads/"); the file name is appended
+        #[arg(default_value = "")]
+        prefix: String,
+        /// Seconds until the form expires (at most 7 days)
+        #[arg(long, default_value = "3600", value_parser = clap::value_parser!(u64).range(1..=604800))]
+        expires_in: u64,
+        /// Allowed upload size in bytes as MIN,MAX (e.g. 0,10485760)
+        #[arg(long)]
+        content_length_range: Option<String>,
+        /// Required Content-Type; end it with / to allow a family (e.g. image/)
+        #[arg(long)]
+        content_type: Option<String>,
+    },
+    /// Restore an archived object, or every one under a prefix with --recursive
     Restore {
+        /// Name of the bucket
+        bucket: String,
+        /// Key of the object (the prefix, with --recursive)
+        key: String,
+        /// Number of days to keep the restored copy
+        #[arg(long, default_value = "1")]
+        days: i32,
+        /// Retrieval tier (Standard, Bulk or Expedited)
+        #[arg(long, default_value = "Standard")]
+        tier: String,
+        /// Restore every archived object whose key starts with KEY
+        #[arg(long)]
+        recursive: bool,
+        /// With --recursive, only restore objects in this storage class (repeatable; default GLACIER and DEEP_ARCHIVE)
+        #[arg(long, requires = "recursive")]
+        storage_class: Vec<String>,
+        /// With --recursive, number of restore requests to send at once
+        #[arg(long, default_value = "8", requires = "recursive")]
+        concurrency: usize,
+        /// Block until the restored copy is ready to download
+        #[arg(long, conflicts_with = "recursive")]
+        wait: bool,
+        /// Seconds between status checks while waiting
+        #[arg(long, default_value = "60", requires = "wait", value_parser = clap::value_parser!(u64).range(1..))]
+        poll_interval: u64,
+        /// Give up waiting after this many seconds
+        #[arg(long, default_value = "86400", requires = "wait")]
+        timeout: u64,
+    },
+    /// Manage Object Lock retention and legal hold
+    Lock {
+        #[command(subcommand)]
+        action: LockAction,
+    },
+    /// Manage an object's ACL
+    Acl {
+        #[command(subcommand)]
+        action: ObjectAclAction,
+    },
+    /// Check whether an object exists (exit code 0 if so, 4 if not; a failed check uses the usual exit codes)
+    Exists {
         /// Name of the bucket
         bucket: String,
         /// Key of the object
@@ -170,61 +1135,755 @@ enum ObjectAction {
         bucket: String,
         /// Key of the object
         key: String,
+        /// Use a lightweight HEAD request (no part count, but shows content type, restore and lock status)
+        #[arg(long)]
+        head: bool,
+        /// Print every header of the HEAD response, including user metadata and encryption (JSON with --output json)
+        #[arg(long, visible_alias = "raw", conflicts_with = "head")]
+        all: bool,
+    },
+    /// Check an object against a known checksum; exits non-zero on a mismatch
+    Verify {
+        /// Name of the bucket
+        bucket: String,
+        /// Key of the object
+        key: String,
+        /// Expected checksum, as hex or base64
+        expected: String,
+        /// Checksum algorithm (crc32, crc32c, sha1, sha256)
+        #[arg(long, default_value = "sha256")]
+        algorithm: String,
+        /// Download the object and compute the checksum even if S3 has one stored
+        #[arg(long)]
+        download: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ObjectAclAction {
+    /// Show the owner and grants
+    Get {
+        /// Name of the bucket
+        bucket: String,
+        /// Key of the object
+        key: String,
+    },
+    /// Apply a canned ACL
+    Set {
+        /// Name of the bucket
+        bucket: String,
+        /// Key of the object
+        key: String,
+        /// Canned ACL (e.g. private, public-read, bucket-owner-full-control)
+        #[arg(required_unless_present_any = GRANT_ARGS)]
+        canned: Option<String>,
+
+        #[command(flatten)]
+        grants: GrantArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum LockAction {
+    /// Set a retention period on an object
+    Retention {
+        /// Name of the bucket
+        bucket: String,
+        /// Key of the object
+        key: String,
+        /// Retention mode (GOVERNANCE or COMPLIANCE)
+        #[arg(long)]
+        mode: String,
+        /// Retain until this RFC3339 date (e.g. 2030-01-01T00:00:00Z)
+        #[arg(long)]
+        retain_until: String,
+    },
+    /// Turn legal hold on or off for an object
+    LegalHold {
+        /// Name of the bucket
+        bucket: String,
+        /// Key of the object
+        key: String,
+        /// Legal hold state (on or off)
+        state: String,
     },
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+async fn main() {
+    if let Err(err) = try_main().await {
+        eprintln!("Error: {:?}", err);
+        std::process::exit(errors::classify_error(&err).exit_code());
+    }
+}
 
-    let client = client::create_client(cli.region, cli.profile).await;
+async fn try_main() -> Result<()> {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    let filter = PathFilter::new(&filter_rules(&matches))?;
+
+    // Flags win over the config file
+    let settings = Settings::load()?.merge(Settings {
+        region: cli.region.clone(),
+        profile: cli.profile.clone(),
+        endpoint_url: cli.endpoint_url.clone(),
+        output: cli.output.clone(),
+        color: if cli.color { Some(true) } else if cli.no_color { Some(false) } else { None },
+        force_path_style: if cli.path_style { Some(true) } else if cli.no_path_style { Some(false) } else { None },
+    });
+
+    ui::init_color(settings.color == Some(true), settings.color == Some(false));
+    ui::set_quiet(cli.quiet);
+    client::set_transient_attempts(cli.transient_attempts);
+    logging::init_logging(cli.verbose)?;
+
+    // Completions need no AWS client
+    if let Commands::Completions { shell } = cli.command {
+        clap_complete::generate(shell, &mut Cli::command(), "s3sh", &mut std::io::stdout());
+        return Ok(());
+    }
+
+    if let Some(region) = &settings.region {
+        client::validate_region(region)?;
+    }
+
+    if let Some(mfa) = &cli.mfa {
+        client::validate_mfa(mfa)?;
+    }
+
+    let output = ui::parse_output_format(settings.output.as_deref().unwrap_or("table"))?;
+    if output == ui::OutputFormat::Csv {
+        // Scripts read CSV; no escape codes in it, even with --color
+        ui::init_color(false, true);
+    }
+
+    if let Commands::Version = cli.command {
+        let info = version::version_info();
+        match output {
+            ui::OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&info)?),
+            ui::OutputFormat::Table | ui::OutputFormat::Csv => print!("{}", version::format_version(&info)),
+        }
+        return Ok(());
+    }
+
+    // A local lint, so it needs no credentials either
+    if let Commands::Bucket { action: BucketAction::Policy { action: PolicyAction::Validate { file } } } = &cli.command {
+        return policy::validate_policy_file(file, output);
+    }
+
+    let credentials = client::static_credentials(cli.access_key_id.clone(), cli.secret_access_key.clone(), cli.session_token.clone())?;
+    let profile = settings.profile.clone();
+    let config = client::load_config(
+        settings.region,
+        settings.profile,
+        cli.role_arn.clone(),
+        cli.role_session_name.clone(),
+        cli.max_retries,
+        cli.timeout_secs,
+        settings.endpoint_url,
+        credentials,
+    ).await;
+    let client = client::s3_client(&config, settings.force_path_style, cli.no_verify_ssl);
+
+    let client = if cli.request_payer.is_some() {
+        client::with_requester_pays(&client)
+    } else {
+        client
+    };
+
+    let client = match cli.page_size {
+        Some(page_size) => client::with_page_size(&client, page_size),
+        None => client,
+    };
+
+    let client = if cli.verbose >= 3 {
+        tracing::debug!(region = ?client.config().region(), "Resolved region");
+        logging::with_request_logging(&client)
+    } else {
+        client
+    };
+
+    let timings = cli.timing.then(logging::CallTimings::default);
+    let client = match &timings {
+        Some(timings) => logging::with_timing(&client, timings),
+        None => client,
+    };
+
+    // Doctor gives its own hints, so its failures skip explain_error
+    if let Commands::Doctor = cli.command {
+        let result = doctor::doctor(&client, &config, profile.as_deref()).await;
+        if let Some(timings) = timings {
+            timings.report(output);
+        }
+        return result;
+    }
+
+    // Object commands on a mistyped bucket get a "did you mean" hint. Copy and
+    // move name two buckets, and the error does not say which one is missing.
+    let object_bucket = match &cli.command {
+        Commands::Object { action: ObjectAction::Copy { .. } | ObjectAction::Move { .. } } => None,
+        Commands::Object { .. } => target_bucket(&cli.command).map(|b| b.to_string()),
+        _ => None,
+    };
+
+    let result = run(cli, &client, &config, output, &filter).await;
+    let result = match (result, object_bucket) {
+        (Err(err), Some(bucket)) => Err(buckets::suggest_missing_bucket(&client, &bucket, err).await),
+        (result, _) => result,
+    };
+    if let Some(timings) = timings {
+        timings.report(output);
+    }
+    result.map_err(|err| client::explain_error(err, profile.as_deref()))
+}
+
+/// The `--include`/`--exclude` patterns given to the subcommand, in the order
+/// they appeared on the command line (later rules take precedence).
+fn filter_rules(matches: &ArgMatches) -> Vec<FilterRule> {
+    let mut leaf = matches;
+    while let Some((_, sub)) = leaf.subcommand() {
+        leaf = sub;
+    }
+
+    let mut rules = Vec::new();
+    for (id, rule) in [("include", FilterRule::Include as fn(String) -> FilterRule), ("exclude", FilterRule::Exclude)] {
+        // Not every subcommand takes filters, and indices_of panics on unknown ids
+        if let Ok(Some(values)) = leaf.try_get_many::<String>(id) {
+            let indices = leaf.indices_of(id).into_iter().flatten();
+            rules.extend(indices.zip(values).map(|(index, value)| (index, rule(value.clone()))));
+        }
+    }
+
+    rules.sort_by_key(|(index, _)| *index);
+    rules.into_iter().map(|(_, rule)| rule).collect()
+}
+
+/// The bucket a command operates on, if it targets an existing bucket.
+fn target_bucket(command: &Commands) -> Option<&str> {
+    match command {
+        Commands::Bucket { action } => match action {
+            BucketAction::List { .. } | BucketAction::Create { .. } => None,
+            BucketAction::Config { name }
+            | BucketAction::Versioning { name }
+            | BucketAction::Notification { name }
+            | BucketAction::Update { name, .. }
+            | BucketAction::Delete { name }
+            | BucketAction::Du { name, .. }
+            | BucketAction::Empty { name, .. } => Some(name),
+            BucketAction::Lifecycle { action } => match action {
+                LifecycleAction::Put { name, .. }
+                | LifecycleAction::List { name }
+                | LifecycleAction::Delete { name, .. } => Some(name),
+            },
+            BucketAction::Policy { action } => match action {
+                PolicyAction::Get { name } | PolicyAction::Set { name, .. } | PolicyAction::Delete { name } => Some(name),
+                PolicyAction::Validate { .. } => None,
+            },
+            BucketAction::Cors { action } => match action {
+                CorsAction::Get { name } | CorsAction::Set { name, .. } | CorsAction::Delete { name } => Some(name),
+            },
+            BucketAction::Website { action } => match action {
+                WebsiteAction::Get { name } | WebsiteAction::Set { name, .. } | WebsiteAction::Delete { name } => Some(name),
+            },
+            BucketAction::Acl { action } => match action {
+                AclAction::Get { name } | AclAction::Set { name, .. } => Some(name),
+            },
+            BucketAction::Ownership { action } => match action {
+                OwnershipAction::Get { name } | OwnershipAction::Set { name, .. } => Some(name),
+            },
+            BucketAction::Tags { action } => match action {
+                TagsAction::Get { name } | TagsAction::Delete { name } => Some(name),
+            },
+            BucketAction::Encryption { action } => match action {
+                EncryptionAction::Get { name } | EncryptionAction::Delete { name } => Some(name),
+            },
+            BucketAction::Replication { action } => match action {
+                ReplicationAction::Get { name } | ReplicationAction::Delete { name } => Some(name),
+            },
+            BucketAction::Inventory { action } => match action {
+                InventoryAction::List { name } | InventoryAction::Get { name, .. } | InventoryAction::Delete { name, .. } => Some(name),
+            },
+            BucketAction::Analytics { action } => match action {
+                AnalyticsAction::List { name } | AnalyticsAction::Get { name, .. } | AnalyticsAction::Delete { name, .. } => Some(name),
+            },
+            BucketAction::Metrics { action } => match action {
+                MetricsAction::List { name } | MetricsAction::Get { name, .. } | MetricsAction::Delete { name, .. } => Some(name),
+            },
+            BucketAction::Logging { action } => match action {
+                LoggingAction::Get { name } | LoggingAction::Set { name, .. } | LoggingAction::Disable { name } => Some(name),
+            },
+            BucketAction::Accelerate { action } => match action {
+                AccelerateAction::Get { name } | AccelerateAction::Set { name, .. } => Some(name),
+            },
+            BucketAction::RequestPayment { action } => match action {
+                RequestPaymentAction::Get { name } | RequestPaymentAction::Set { name, .. } => Some(name),
+            },
+        },
+        Commands::Object { action } => match action {
+            ObjectAction::List { bucket, .. }
+            | ObjectAction::Upload { bucket, .. }
+            | ObjectAction::Sync { bucket, .. }
+            | ObjectAction::Download { bucket, .. }
+            | ObjectAction::Delete { bucket, .. }
+            | ObjectAction::Versions { bucket, .. }
+            // CopyObject is sent to the destination bucket
+            | ObjectAction::Copy { dst_bucket: bucket, .. }
+            | ObjectAction::Move { dst_bucket: bucket, .. }
+            | ObjectAction::Rename { bucket, .. }
+            | ObjectAction::SetMeta { bucket, .. }
+            | ObjectAction::Cat { bucket, .. }
+            | ObjectAction::Select { bucket, .. }
+            | ObjectAction::PresignPost { bucket, .. }
+            | ObjectAction::Restore { bucket, .. }
+            | ObjectAction::Exists { bucket, .. }
+            | ObjectAction::Attributes { bucket, .. }
+            | ObjectAction::Verify { bucket, .. } => Some(bucket),
+            ObjectAction::Acl { action } => match action {
+                ObjectAclAction::Get { bucket, .. } | ObjectAclAction::Set { bucket, .. } => Some(bucket),
+            },
+            ObjectAction::Lock { action } => match action {
+                LockAction::Retention { bucket, .. } | LockAction::LegalHold { bucket, .. } => Some(bucket),
+            },
+        },
+        // Ping reports a bucket in another region instead of following it
+        Commands::Ping { .. } | Commands::Doctor | Commands::Interactive | Commands::Version | Commands::Completions { .. } => None,
+    }
+}
+
+/// Whether the command reads the bucket even under `--dry-run`: sync
+/// `--delete` and rename list the keys they would delete or move.
+fn lists_under_dry_run(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Object { action: ObjectAction::Sync { delete: true, .. } | ObjectAction::Rename { .. } }
+    )
+}
+
+async fn run(cli: Cli, default_client: &Client, config: &SdkConfig, output: ui::OutputFormat, filter: &PathFilter) -> Result<()> {
+    // Talk to the bucket's own region. Skipped under --dry-run, where mutating
+    // commands only print their calls, unless the command still lists the bucket
+    let regional_client;
+    let client = match target_bucket(&cli.command) {
+        Some(bucket) if !cli.dry_run || lists_under_dry_run(&cli.command) => {
+            regional_client = client::client_for_bucket(default_client, bucket).await;
+            &regional_client
+        }
+        _ => default_client,
+    };
+    // Copy and move also read from (or delete in) the source bucket
+    let source_client = |bucket: &str| {
+        let bucket = bucket.to_string();
+        async move {
+            match cli.dry_run {
+                true => default_client.clone(),
+                false => client::client_for_bucket(default_client, &bucket).await,
+            }
+        }
+    };
 
     match cli.command {
         Commands::Bucket { action } => match action {
-            BucketAction::List => {
-                buckets::list_buckets(&client).await?;
+            BucketAction::List { sort, reverse, filter, regex } => {
+                let filter = filter.map(|pattern| buckets::NameFilter::new(&pattern, regex)).transpose()?;
+                buckets::list_buckets(client, ui::parse_sort_key(&sort)?, reverse, filter.as_ref(), output).await?;
             }
-            BucketAction::Create { name, public, versioning, encryption, tags } => {
+            BucketAction::Create { name, acl, public, versioning, encryption, kms_key_id, bucket_key, tags } => {
+                let acl = acl.as_deref().map(buckets::parse_bucket_canned_acl).transpose()?;
+                let encryption = encryption
+                    .map(|mode| buckets::parse_bucket_encryption(&mode, kms_key_id.as_deref(), bucket_key))
+                    .transpose()?;
                 // For create bucket, we might need a region if not globally provided, 
                 // but for now we'll rely on the client's region or default.
                 // However, create_bucket in buckets.rs expects a region string for constraint.
                 // We'll fetch the region from the client config if possible, or default to us-east-1.
                 let region = client.config().region().map(|r| r.as_ref()).unwrap_or("us-east-1");
-                buckets::create_bucket(&client, &name, region, public, versioning, encryption, tags).await?;
+                buckets::create_bucket(client, &name, region, acl, public, versioning, encryption, tags, cli.dry_run).await?;
             }
             BucketAction::Config { name } => {
-                buckets::get_bucket_config(&client, &name).await?;
+                buckets::get_bucket_config(client, &name, output).await?;
+            }
+            BucketAction::Versioning { name } => {
+                buckets::get_versioning_status(client, &name).await?;
             }
-            BucketAction::Update { name, public, versioning, encryption, tags } => {
-                buckets::update_bucket(&client, &name, public, versioning, encryption, tags).await?;
+            BucketAction::Update { name, public, versioning, mfa_delete, encryption, kms_key_id, bucket_key, tags } => {
+                let encryption = encryption
+                    .map(|mode| buckets::parse_bucket_encryption(&mode, kms_key_id.as_deref(), bucket_key))
+                    .transpose()?;
+                buckets::update_bucket(
+                    client,
+                    &name,
+                    public,
+                    versioning,
+                    mfa_delete,
+                    encryption,
+                    tags,
+                    cli.mfa.as_deref(),
+                    cli.dry_run,
+                ).await?;
             }
-            BucketAction::Lifecycle { name, id, prefix, transitions, expiration, status } => {
-                buckets::put_lifecycle_rule(&client, &name, &id, &prefix, &transitions, expiration, status).await?;
+            BucketAction::Lifecycle { action } => match action {
+                LifecycleAction::Put { name, id, prefix, filter, transitions, expiration, noncurrent_expiration, abort_incomplete_upload, status } => {
+                    let rule = buckets::LifecycleRuleSpec {
+                        id,
+                        prefix,
+                        filter,
+                        transitions,
+                        expiration_days: expiration,
+                        noncurrent_expiration_days: noncurrent_expiration,
+                        abort_incomplete_upload_days: abort_incomplete_upload,
+                        enabled: status,
+                    };
+                    buckets::put_lifecycle_rule(client, &name, &rule, cli.dry_run).await?;
+                }
+                LifecycleAction::List { name } => {
+                    buckets::list_lifecycle_rules(client, &name).await?;
+                }
+                LifecycleAction::Delete { name, id } => {
+                    buckets::delete_lifecycle_rule(client, &name, &id, cli.dry_run).await?;
+                }
+            },
+            BucketAction::Policy { action } => match action {
+                PolicyAction::Get { name } => {
+                    buckets::get_bucket_policy(client, &name).await?;
+                }
+                PolicyAction::Set { name, file } => {
+                    buckets::put_bucket_policy(client, &name, &file, cli.dry_run).await?;
+                }
+                PolicyAction::Delete { name } => {
+                    buckets::delete_bucket_policy(client, &name, cli.dry_run).await?;
+                }
+                PolicyAction::Validate { .. } => unreachable!("policy validate is handled before the client is created"),
+            },
+            BucketAction::Cors { action } => match action {
+                CorsAction::Get { name } => {
+                    buckets::get_bucket_cors(client, &name).await?;
+                }
+                CorsAction::Set { name, file } => {
+                    buckets::put_bucket_cors(client, &name, &file, cli.dry_run).await?;
+                }
+                CorsAction::Delete { name } => {
+                    buckets::delete_bucket_cors(client, &name, cli.dry_run).await?;
+                }
+            },
+            BucketAction::Website { action } => match action {
+                WebsiteAction::Get { name } => {
+                    buckets::get_bucket_website(client, &name).await?;
+                }
+                WebsiteAction::Set { name, index_document, error_document } => {
+                    buckets::put_bucket_website(client, &name, &index_document, error_document.as_deref(), cli.dry_run).await?;
+                }
+                WebsiteAction::Delete { name } => {
+                    buckets::delete_bucket_website(client, &name, cli.dry_run).await?;
+                }
+            },
+            BucketAction::Acl { action } => match action {
+                AclAction::Get { name } => {
+                    buckets::get_bucket_acl(client, &name).await?;
+                }
+                AclAction::Set { name, canned, grants } => {
+                    buckets::put_bucket_acl(client, &name, canned.as_deref(), &grants.into_grants()?, cli.dry_run).await?;
+                }
+            },
+            BucketAction::Ownership { action } => match action {
+                OwnershipAction::Get { name } => {
+                    buckets::get_bucket_ownership_controls(client, &name).await?;
+                }
+                OwnershipAction::Set { name, rule } => {
+                    buckets::put_bucket_ownership_controls(client, &name, &rule, cli.dry_run).await?;
+                }
+            },
+            BucketAction::Tags { action } => match action {
+                TagsAction::Get { name } => {
+                    buckets::get_bucket_tags(client, &name).await?;
+                }
+                TagsAction::Delete { name } => {
+                    buckets::delete_bucket_tags(client, &name, cli.dry_run).await?;
+                }
+            },
+            BucketAction::Encryption { action } => match action {
+                EncryptionAction::Get { name } => {
+                    buckets::get_bucket_encryption(client, &name).await?;
+                }
+                EncryptionAction::Delete { name } => {
+                    buckets::delete_bucket_encryption(client, &name, cli.dry_run).await?;
+                }
+            },
+            BucketAction::Replication { action } => match action {
+                ReplicationAction::Get { name } => {
+                    buckets::get_bucket_replication(client, &name).await?;
+                }
+                ReplicationAction::Delete { name } => {
+                    buckets::delete_bucket_replication(client, &name, cli.dry_run).await?;
+                }
+            },
+            BucketAction::Inventory { action } => match action {
+                InventoryAction::List { name } => {
+                    buckets::list_bucket_inventory(client, &name).await?;
+                }
+                InventoryAction::Get { name, id } => {
+                    buckets::get_bucket_inventory(client, &name, &id).await?;
+                }
+                InventoryAction::Delete { name, id } => {
+                    buckets::delete_bucket_inventory(client, &name, &id, cli.dry_run).await?;
+                }
+            },
+            BucketAction::Analytics { action } => match action {
+                AnalyticsAction::List { name } => {
+                    buckets::list_bucket_analytics(client, &name).await?;
+                }
+                AnalyticsAction::Get { name, id } => {
+                    buckets::get_bucket_analytics(client, &name, &id).await?;
+                }
+                AnalyticsAction::Delete { name, id } => {
+                    buckets::delete_bucket_analytics(client, &name, &id, cli.dry_run).await?;
+                }
+            },
+            BucketAction::Metrics { action } => match action {
+                MetricsAction::List { name } => {
+                    buckets::list_bucket_metrics(client, &name).await?;
+                }
+                MetricsAction::Get { name, id } => {
+                    buckets::get_bucket_metrics(client, &name, &id).await?;
+                }
+                MetricsAction::Delete { name, id } => {
+                    buckets::delete_bucket_metrics(client, &name, &id, cli.dry_run).await?;
+                }
+            },
+            BucketAction::Logging { action } => match action {
+                LoggingAction::Get { name } => {
+                    buckets::get_bucket_logging(client, &name).await?;
+                }
+                LoggingAction::Set { name, target_bucket, target_prefix } => {
+                    buckets::put_bucket_logging(client, &name, Some(&target_bucket), &target_prefix, cli.dry_run).await?;
+                }
+                LoggingAction::Disable { name } => {
+                    buckets::put_bucket_logging(client, &name, None, "", cli.dry_run).await?;
+                }
+            },
+            BucketAction::Accelerate { action } => match action {
+                AccelerateAction::Get { name } => {
+                    buckets::get_bucket_accelerate(client, &name).await?;
+                }
+                AccelerateAction::Set { name, enabled } => {
+                    buckets::put_bucket_accelerate(client, &name, enabled, cli.dry_run).await?;
+                }
+            },
+            BucketAction::RequestPayment { action } => match action {
+                RequestPaymentAction::Get { name } => {
+                    buckets::get_bucket_request_payment(client, &name).await?;
+                }
+                RequestPaymentAction::Set { name, payer } => {
+                    buckets::put_bucket_request_payment(client, &name, &payer, cli.dry_run).await?;
+                }
+            },
+            BucketAction::Notification { name } => {
+                buckets::get_bucket_notifications(client, &name).await?;
             }
             BucketAction::Delete { name } => {
-                buckets::delete_bucket(&client, &name).await?;
+                buckets::delete_bucket(client, &name, cli.yes, cli.dry_run).await?;
             }
-            BucketAction::Empty { name } => {
-                buckets::empty_bucket(&client, &name).await?;
+            BucketAction::Du { name, prefix, group_by_prefix, bytes } => {
+                buckets::bucket_usage(client, &name, prefix.as_deref(), group_by_prefix, bytes, output).await?;
+            }
+            BucketAction::Empty { name, versions } => {
+                buckets::empty_bucket(client, &name, versions, cli.mfa.as_deref(), cli.yes, cli.dry_run).await?;
             }
         },
         Commands::Object { action } => match action {
-            ObjectAction::List { bucket } => {
-                objects::list_objects(&client, &bucket).await?;
+            ObjectAction::List { bucket, prefix, start_after, limit, since, until, bytes, wide, tree, with_metadata, concurrency, sort, reverse, .. } => {
+                let limit = limit.map(|n| n as usize);
+                let now = std::time::SystemTime::now();
+                let since = since.as_deref().map(|s| objects::parse_time_bound(s, now)).transpose()?;
+                let until = until.as_deref().map(|s| objects::parse_time_bound(s, now)).transpose()?;
+                let modified = objects::ModifiedWindow::new(since, until)?;
+                let metadata_concurrency = with_metadata.then_some(concurrency);
+                objects::list_objects(client, &bucket, prefix.as_deref(), start_after.as_deref(), limit, modified, bytes, wide, tree, metadata_concurrency, ui::parse_sort_key(&sort)?, reverse, output).await?;
+            }
+            ObjectAction::Upload { bucket, file, key, checksum, sse, sse_kms_key_id, acl, content_type, guess_content, headers, grants, tags, content_md5, no_resume, skip_existing, compare_etag, archive } => {
+                let archive = archive.as_deref().map(archive::parse_archive_format).transpose()?;
+                let content_type = match content_type {
+                    None if guess_content && file != "-" => Some(objects::guess_content_type(std::path::Path::new(&file))),
+                    content_type => content_type,
+                };
+                let headers = headers.into_headers(content_type)?;
+                let grants = grants.into_grants()?;
+                let skip_existing = match (skip_existing, compare_etag) {
+                    (false, _) => None,
+                    (true, false) => Some(objects::SkipExisting::Size),
+                    (true, true) => Some(objects::SkipExisting::SizeAndEtag),
+                };
+                objects::upload_object(
+                    client,
+                    &bucket,
+                    &file,
+                    key,
+                    checksum.as_deref(),
+                    sse.as_deref(),
+                    sse_kms_key_id.as_deref(),
+                    acl.as_deref(),
+                    &grants,
+                    &tags,
+                    &headers,
+                    content_md5,
+                    !no_resume,
+                    skip_existing,
+                    archive,
+                    cli.dry_run,
+                ).await?;
+            }
+            ObjectAction::Sync { dir, bucket, prefix, concurrency, delete, .. } => {
+                objects::sync_directory(
+                    client,
+                    &dir,
+                    &bucket,
+                    prefix.as_deref(),
+                    concurrency,
+                    filter,
+                    delete,
+                    cli.mfa.as_deref(),
+                    cli.yes,
+                    output,
+                    cli.dry_run,
+                ).await?;
+            }
+            ObjectAction::Download { bucket, key, dest, recursive: true, concurrency, .. } => {
+                let dir = dest.unwrap_or_else(|| ".".to_string());
+                objects::download_prefix(client, &bucket, &key, &dir, concurrency, filter, output, cli.dry_run).await?;
+            }
+            ObjectAction::Download { bucket, key, dest, range, parallel, .. } => {
+                let range = range.as_deref().map(objects::parse_byte_range).transpose()?;
+                objects::download_object(client, &bucket, &key, dest.as_deref(), range, parallel as usize, cli.dry_run).await?;
+            }
+            ObjectAction::Delete { bucket, key, recursive: true, concurrency, .. } => {
+                objects::delete_prefix(client, &bucket, &key, filter, concurrency, cli.mfa.as_deref(), cli.yes, output, cli.dry_run).await?;
+            }
+            ObjectAction::Delete { bucket, key, version_id, .. } => {
+                objects::delete_object(client, &bucket, &key, version_id.as_deref(), cli.mfa.as_deref(), cli.yes, cli.dry_run).await?;
+            }
+            ObjectAction::Copy { src_bucket, src_key, dst_bucket, dst_key, storage_class, recursive: true, concurrency, .. } => {
+                let src_client = source_client(&src_bucket).await;
+                objects::copy_prefix(
+                    client,
+                    &src_client,
+                    &src_bucket,
+                    &src_key,
+                    &dst_bucket,
+                    &dst_key,
+                    storage_class.as_deref(),
+                    concurrency,
+                    filter,
+                    output,
+                    cli.dry_run,
+                ).await?;
+            }
+            ObjectAction::Copy { src_bucket, src_key, dst_bucket, dst_key, storage_class, metadata_directive, metadata, .. } => {
+                objects::copy_object(
+                    client,
+                    &src_bucket,
+                    &src_key,
+                    &dst_bucket,
+                    &dst_key,
+                    storage_class.as_deref(),
+                    &metadata_directive,
+                    metadata,
+                    cli.dry_run,
+                ).await?;
+            }
+            ObjectAction::Move { src_bucket, src_key, dst_bucket, dst_key } => {
+                let src_client = source_client(&src_bucket).await;
+                objects::move_object(client, &src_client, &src_bucket, &src_key, &dst_bucket, &dst_key, cli.dry_run).await?;
+            }
+            ObjectAction::Rename { bucket, old_prefix, new_prefix, concurrency } => {
+                objects::rename_prefix(client, &bucket, &old_prefix, &new_prefix, concurrency, output, cli.dry_run).await?;
+            }
+            ObjectAction::SetMeta { bucket, key, content_type, headers, metadata } => {
+                let headers = headers.into_headers(content_type)?;
+                objects::set_object_metadata(client, &bucket, &key, &headers, metadata, cli.dry_run).await?;
+            }
+            ObjectAction::Versions { bucket, prefix } => {
+                objects::list_object_versions(client, &bucket, prefix.as_deref()).await?;
+            }
+            ObjectAction::Cat { bucket, key, range } => {
+                let range = range.as_deref().map(objects::parse_byte_range).transpose()?;
+                objects::cat_object(client, &bucket, &key, range).await?;
+            }
+            ObjectAction::Select { bucket, key, query, input_format, output_format, compression } => {
+                let input = objects::select_input_serialization(&input_format, &compression)?;
+                let output = objects::select_output_serialization(&output_format)?;
+                objects::select_object(client, &bucket, &key, &query, input, output).await?;
+            }
+            ObjectAction::PresignPost { bucket, prefix, expires_in, content_length_range, content_type } => {
+                let policy = presign::PostPolicy {
+                    bucket,
+                    key_prefix: prefix,
+                    expires_in: std::time::Duration::from_secs(expires_in),
+                    content_length_range: content_length_range.as_deref().map(presign::parse_length_range).transpose()?,
+                    content_type,
+                };
+                let region = client.config().region().map(|r| r.as_ref()).unwrap_or("us-east-1");
+                presign::presign_post(config, region, &policy).await?;
+            }
+            ObjectAction::Restore { bucket, key, days, tier, recursive: true, storage_class, concurrency, .. } => {
+                let storage_classes = match storage_class {
+                    classes if classes.is_empty() => objects::ARCHIVED_STORAGE_CLASSES.map(String::from).to_vec(),
+                    classes => classes,
+                };
+                objects::restore_prefix(client, &bucket, &key, days, &tier, &storage_classes, concurrency, output, cli.dry_run).await?;
             }
-            ObjectAction::Upload { bucket, file, key } => {
-                objects::upload_object(&client, &bucket, &file, key).await?;
+            ObjectAction::Restore { bucket, key, days, tier, wait, poll_interval, timeout, .. } => {
+                objects::restore_object(client, &bucket, &key, days, &tier, cli.dry_run).await?;
+                if wait && !cli.dry_run {
+                    objects::wait_for_restore(
+                        client,
+                        &bucket,
+                        &key,
+                        std::time::Duration::from_secs(poll_interval),
+                        std::time::Duration::from_secs(timeout),
+                    ).await?;
+                }
             }
-            ObjectAction::Delete { bucket, key } => {
-                objects::delete_object(&client, &bucket, &key).await?;
+            ObjectAction::Lock { action } => match action {
+                LockAction::Retention { bucket, key, mode, retain_until } => {
+                    objects::set_object_retention(client, &bucket, &key, &mode, &retain_until, cli.dry_run).await?;
+                }
+                LockAction::LegalHold { bucket, key, state } => {
+                    objects::set_object_legal_hold(client, &bucket, &key, &state, cli.dry_run).await?;
+                }
+            },
+            ObjectAction::Acl { action } => match action {
+                ObjectAclAction::Get { bucket, key } => {
+                    objects::get_object_acl(client, &bucket, &key).await?;
+                }
+                ObjectAclAction::Set { bucket, key, canned, grants } => {
+                    objects::put_object_acl(client, &bucket, &key, canned.as_deref(), &grants.into_grants()?, cli.dry_run).await?;
+                }
+            },
+            ObjectAction::Exists { bucket, key } => {
+                // A failed check exits through the usual error codes
+                let exists = objects::object_exists(client, &bucket, &key).await?;
+                if output == ui::OutputFormat::Json {
+                    println!("{}", serde_json::json!({ "exists": exists }));
+                }
+                if !exists {
+                    return Err(errors::not_found_error(format!("Object '{}' not found in bucket '{}'", key, bucket)));
+                }
             }
-            ObjectAction::Restore { bucket, key } => {
-                objects::restore_object(&client, &bucket, &key).await?;
+            ObjectAction::Attributes { bucket, key, head, all } => {
+                objects::get_object_attributes(client, &bucket, &key, head, all, output).await?;
             }
-            ObjectAction::Attributes { bucket, key } => {
-                objects::get_object_attributes(&client, &bucket, &key).await?;
+            ObjectAction::Verify { bucket, key, expected, algorithm, download } => {
+                objects::verify_object(client, &bucket, &key, &expected, &algorithm, download).await?;
             }
         },
+        Commands::Ping { bucket } => {
+            client::ping(client, bucket.as_deref()).await?;
+        }
+        Commands::Interactive => {
+            interactive::interactive(client, cli.mfa.as_deref(), cli.yes, cli.dry_run).await?;
+        }
+        Commands::Doctor => unreachable!("doctor is handled before run"),
+        Commands::Version => unreachable!("version is handled before the client is created"),
+        Commands::Completions { .. } => unreachable!("completions are handled before the client is created"),
     }
 
     Ok(())
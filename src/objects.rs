@@ -1,115 +1,3354 @@
 use aws_sdk_s3::Client;
+use aws_sdk_s3::error::ProvideErrorMetadata;
 use anyhow::Result;
 use tabled::{Table, Tabled};
+use tabled::settings::{location::ByColumnName, Remove};
 use colored::*;
 use std::path::Path;
-use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::primitives::{ByteStream, DateTime, DateTimeFormat, Length};
+use aws_sdk_s3::operation::get_object::GetObjectOutput;
+use aws_sdk_s3::operation::head_object::HeadObjectOutput;
+use aws_sdk_s3::operation::get_object_attributes::GetObjectAttributesOutput;
 use indicatif::{ProgressBar, ProgressStyle};
+use crate::progress::{item_bar, BulkProgress, BulkSummary};
+use serde::{Deserialize, Serialize};
+use futures::stream::{self, StreamExt};
+use std::path::PathBuf;
+use crate::errors::{access_denied_error, usage_error};
+use crate::archive::{archive_entries, archive_root, write_archive, ArchiveFormat};
+use crate::filter::PathFilter;
+use crate::grants::Grants;
+use crate::ui::{confirm, footer, format_csv, format_size, is_quiet, note, pluralize, print_dry_run, progress_target, success, OutputFormat, SortKey};
+use tokio::io::{AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+use aws_smithy_types::body::SdkBody;
+use bytes::Bytes;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+use aws_sdk_s3::config::{ConfigBag, Intercept, RuntimeComponents};
+use aws_sdk_s3::config::interceptors::BeforeDeserializationInterceptorContextRef;
+use aws_smithy_runtime_api::box_error::BoxError;
 
 #[derive(Tabled)]
-struct ObjectInfo {
+pub struct ObjectInfo {
+    pub key: String,
+    pub size: String,
+    pub last_modified: String,
+    #[tabled(rename = "etag")]
+    pub e_tag: String,
+    pub storage_class: String,
+    /// Filled in by `fetch_object_metadata`; blank if the object wasn't headed
+    #[tabled(display = "display_or_blank")]
+    pub content_type: Option<String>,
+    #[tabled(display = "display_metadata")]
+    pub metadata: Option<BTreeMap<String, String>>,
+    #[tabled(skip)]
+    pub size_bytes: i64,
+    #[tabled(skip)]
+    pub modified: Option<DateTime>,
+}
+
+fn display_or_blank(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+fn display_metadata(metadata: &Option<BTreeMap<String, String>>) -> String {
+    metadata.iter()
+        .flatten()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Totals line for an object listing, e.g. `42 objects, 1.3 GiB`.
+pub fn summarize_objects(infos: &[ObjectInfo], bytes: bool) -> String {
+    let total: i64 = infos.iter().map(|i| i.size_bytes).sum();
+    let size = if bytes { format!("{} bytes", total) } else { format_size(total) };
+    format!("{}, {}", pluralize(infos.len(), "object"), size)
+}
+
+/// Sort objects by `key`, comparing raw sizes and timestamps rather than the
+/// rendered strings. Ties keep their listing order.
+pub fn sort_objects(infos: &mut [ObjectInfo], key: SortKey, reverse: bool) {
+    match key {
+        SortKey::Name => infos.sort_by(|a, b| a.key.cmp(&b.key)),
+        SortKey::Size => infos.sort_by_key(|i| i.size_bytes),
+        SortKey::Modified => infos.sort_by_key(|i| i.modified),
+    }
+    if reverse {
+        infos.reverse();
+    }
+}
+
+/// Rows for an object listing. Sizes are human-readable unless `bytes` is
+/// set; the ETag is shown without its surrounding quotes.
+pub fn object_infos(objects: &[aws_sdk_s3::types::Object], bytes: bool) -> Vec<ObjectInfo> {
+    objects.iter()
+        .map(|object| {
+            let size_bytes = object.size().unwrap_or(0);
+            let modified = object.last_modified().cloned();
+            ObjectInfo {
+                key: object.key().unwrap_or("<unknown>").to_string(),
+                size: if bytes { size_bytes.to_string() } else { format_size(size_bytes) },
+                last_modified: modified
+                    .map(|d| d.to_string())
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                e_tag: object.e_tag().unwrap_or("-").trim_matches('"').to_string(),
+                storage_class: object.storage_class()
+                    .map(|c| c.as_str().to_string())
+                    .unwrap_or_else(|| "STANDARD".to_string()),
+                content_type: None,
+                metadata: None,
+                size_bytes,
+                modified,
+            }
+        })
+        .collect()
+}
+
+/// Head every listed object, `concurrency` at a time, to fill in its content
+/// type and user metadata. An object that can't be headed (deleted since the
+/// listing, access denied, ...) keeps those fields blank.
+pub async fn fetch_object_metadata(client: &Client, bucket_name: &str, infos: &mut [ObjectInfo], concurrency: usize) {
+    let heads: Vec<(usize, HeadObjectOutput)> = stream::iter(infos.iter().enumerate())
+        .map(|(index, info)| async move {
+            client.head_object()
+                .bucket(bucket_name)
+                .key(&info.key)
+                .send()
+                .await
+                .ok()
+                .map(|head| (index, head))
+        })
+        .buffer_unordered(concurrency.max(1))
+        .filter_map(|head| async move { head })
+        .collect()
+        .await;
+
+    for (index, head) in heads {
+        let info = &mut infos[index];
+        info.content_type = head.content_type().map(|t| t.to_string());
+        info.metadata = Some(head.metadata().cloned().unwrap_or_default().into_iter().collect());
+    }
+}
+
+/// Render an object listing as a table: key, size and last modified, plus
+/// the ETag and storage class when `wide` is set and the content type and
+/// user metadata when `with_metadata` is.
+pub fn format_object_table(infos: &[ObjectInfo], wide: bool, with_metadata: bool) -> String {
+    let mut table = Table::new(infos);
+    for column in hidden_object_columns(wide, with_metadata) {
+        table.with(Remove::column(ByColumnName::new(column)));
+    }
+    table.to_string()
+}
+
+/// The same columns as `format_object_table`, as CSV.
+pub fn format_object_csv(infos: &[ObjectInfo], wide: bool, with_metadata: bool) -> Result<String> {
+    format_csv(infos, &hidden_object_columns(wide, with_metadata))
+}
+
+/// Columns left out without `--wide` and `--with-metadata`.
+fn hidden_object_columns(wide: bool, with_metadata: bool) -> Vec<&'static str> {
+    let mut hidden = Vec::new();
+    if !wide {
+        hidden.extend(["etag", "storage_class"]);
+    }
+    if !with_metadata {
+        hidden.extend(["content_type", "metadata"]);
+    }
+    hidden
+}
+
+/// Keys arranged by their `/`-separated path segments, for `object list --tree`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KeyTree {
+    pub children: BTreeMap<String, KeyTree>,
+    /// Size of the object whose key ends at this node, if there is one
+    pub size: Option<i64>,
+}
+
+impl KeyTree {
+    /// Build the tree from `(key, size)` pairs. A key ending in `/` (a folder
+    /// placeholder) only creates its folder.
+    pub fn from_keys<'a>(keys: impl IntoIterator<Item = (&'a str, i64)>) -> KeyTree {
+        let mut root = KeyTree::default();
+        for (key, size) in keys {
+            let mut node = &mut root;
+            let segments = key.split('/').collect::<Vec<_>>();
+            for (i, segment) in segments.iter().enumerate() {
+                if segment.is_empty() && i == segments.len() - 1 {
+                    break;
+                }
+                node = node.children.entry(segment.to_string()).or_default();
+                if i == segments.len() - 1 {
+                    node.size = Some(size);
+                }
+            }
+        }
+        root
+    }
+
+    /// One line per node, indented two spaces per level; folders end in `/`.
+    pub fn render(&self, bytes: bool) -> String {
+        let mut out = String::new();
+        self.render_into(&mut out, 0, bytes);
+        out
+    }
+
+    fn render_into(&self, out: &mut String, depth: usize, bytes: bool) {
+        for (name, node) in &self.children {
+            let indent = "  ".repeat(depth);
+            if node.children.is_empty() {
+                let size = node.size.unwrap_or(0);
+                let size = if bytes { size.to_string() } else { format_size(size) };
+                out.push_str(&format!("{}{} {}\n", indent, name, format!("({})", size).dimmed()));
+            } else {
+                out.push_str(&format!("{}{}\n", indent, format!("{}/", name).blue().bold()));
+                node.render_into(out, depth + 1, bytes);
+            }
+        }
+    }
+}
+
+/// List a bucket's objects (under `prefix`, at most `limit` of them). Sizes
+/// are human-readable unless `bytes` is set; `wide` adds the ETag and storage
+/// class columns, and `tree` shows the keys as an indented tree instead.
+/// Only objects last modified within `modified` are shown, and only they
+/// count toward `limit`. With `metadata_concurrency`, each object is also
+/// headed (that many at a time) for its content type and user metadata.
+#[allow(clippy::too_many_arguments)]
+pub async fn list_objects(
+    client: &Client,
+    bucket_name: &str,
+    prefix: Option<&str>,
+    start_after: Option<&str>,
+    limit: Option<usize>,
+    modified: ModifiedWindow,
+    bytes: bool,
+    wide: bool,
+    tree: bool,
+    metadata_concurrency: Option<usize>,
+    sort: SortKey,
+    reverse: bool,
+    output: OutputFormat,
+) -> Result<()> {
+    let (objects, truncated) = fetch_objects_within(client, bucket_name, prefix, start_after, limit, modified).await?;
+    let mut object_infos = object_infos(&objects, bytes);
+    if let Some(concurrency) = metadata_concurrency {
+        fetch_object_metadata(client, bucket_name, &mut object_infos, concurrency).await;
+    }
+
+    // In listing order, so it can be passed to --start-after whatever the sort
+    let last_key = objects.last().and_then(|o| o.key()).map(|k| k.to_string());
+    sort_objects(&mut object_infos, sort, reverse);
+
+    if output == OutputFormat::Json {
+        let total: i64 = object_infos.iter().map(|i| i.size_bytes).sum();
+        let objects: Vec<_> = object_infos.iter()
+            .map(|i| {
+                let mut entry = serde_json::json!({
+                    "key": i.key,
+                    "size": i.size_bytes,
+                    "last_modified": i.last_modified,
+                    "etag": i.e_tag,
+                    "storage_class": i.storage_class,
+                });
+                if metadata_concurrency.is_some() {
+                    entry["content_type"] = serde_json::json!(i.content_type);
+                    entry["metadata"] = serde_json::json!(i.metadata);
+                }
+                entry
+            })
+            .collect();
+        println!("{}", serde_json::json!({
+            "objects": objects,
+            "count": object_infos.len(),
+            "total_size": total,
+            "truncated": truncated,
+            "last_key": last_key,
+        }));
+        return Ok(());
+    }
+    if output == OutputFormat::Csv {
+        // Flat even with --tree, and only the records on stdout
+        print!("{}", format_object_csv(&object_infos, wide, metadata_concurrency.is_some())?);
+        if truncated && !is_quiet() {
+            eprintln!(
+                "Showing the first {} objects; more exist. Continue with --start-after '{}' or raise --limit.",
+                objects.len(),
+                last_key.as_deref().unwrap_or_default()
+            );
+        }
+        return Ok(());
+    }
+
+    if object_infos.is_empty() {
+        note("No objects found.");
+    } else if tree {
+        let tree = KeyTree::from_keys(object_infos.iter().map(|i| (i.key.as_str(), i.size_bytes)));
+        print!("{}", tree.render(bytes));
+        footer(summarize_objects(&object_infos, bytes));
+    } else {
+        println!("{}", format_object_table(&object_infos, wide, metadata_concurrency.is_some()));
+        footer(summarize_objects(&object_infos, bytes));
+    }
+
+    if truncated {
+        note(format!(
+            "Showing the first {} objects; more exist. Continue with --start-after '{}' or raise --limit.",
+            objects.len(),
+            last_key.as_deref().unwrap_or_default()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Fetch the objects under `prefix`, following continuation tokens, stopping
+/// after `limit` objects. Also returns whether objects were left out because
+/// of the limit.
+pub async fn fetch_objects(
+    client: &Client,
+    bucket_name: &str,
+    prefix: Option<&str>,
+    start_after: Option<&str>,
+    limit: Option<usize>,
+) -> Result<(Vec<aws_sdk_s3::types::Object>, bool)> {
+    fetch_objects_within(client, bucket_name, prefix, start_after, limit, ModifiedWindow::default()).await
+}
+
+/// Like [`fetch_objects`], but only keeps objects last modified within
+/// `modified`, so `limit` counts matching objects rather than listed ones.
+pub async fn fetch_objects_within(
+    client: &Client,
+    bucket_name: &str,
+    prefix: Option<&str>,
+    start_after: Option<&str>,
+    limit: Option<usize>,
+    modified: ModifiedWindow,
+) -> Result<(Vec<aws_sdk_s3::types::Object>, bool)> {
+    let mut objects = Vec::new();
+    let mut continuation_token = None;
+
+    loop {
+        // Don't ask for more than the limit still needs (S3 caps pages at 1000).
+        // With a window, any page may hold fewer matches, so ask for full pages.
+        let max_keys = limit
+            .filter(|_| modified.is_unbounded())
+            .map(|limit| (limit - objects.len()).min(1000) as i32);
+        // Later pages resume from the continuation token instead
+        let start_after = start_after.filter(|_| continuation_token.is_none());
+        let resp = client.list_objects_v2()
+            .bucket(bucket_name)
+            .set_prefix(prefix.map(|p| p.to_string()))
+            .set_start_after(start_after.map(|k| k.to_string()))
+            .set_max_keys(max_keys)
+            .set_continuation_token(continuation_token)
+            .send()
+            .await?;
+
+        objects.extend(resp.contents().iter()
+            .filter(|o| modified.contains(o.last_modified()))
+            .cloned());
+        let more = resp.is_truncated() == Some(true);
+
+        if let Some(limit) = limit
+            && objects.len() >= limit
+        {
+            let truncated = more || objects.len() > limit;
+            objects.truncate(limit);
+            return Ok((objects, truncated));
+        }
+
+        match resp.next_continuation_token() {
+            Some(token) if more => continuation_token = Some(token.to_string()),
+            _ => return Ok((objects, false)),
+        }
+    }
+}
+
+pub fn parse_checksum_algorithm(algorithm: &str) -> Result<aws_sdk_s3::types::ChecksumAlgorithm> {
+    match algorithm.to_ascii_lowercase().as_str() {
+        "crc32" => Ok(aws_sdk_s3::types::ChecksumAlgorithm::Crc32),
+        "crc32c" => Ok(aws_sdk_s3::types::ChecksumAlgorithm::Crc32C),
+        "sha1" => Ok(aws_sdk_s3::types::ChecksumAlgorithm::Sha1),
+        "sha256" => Ok(aws_sdk_s3::types::ChecksumAlgorithm::Sha256),
+        _ => Err(usage_error(format!("Invalid checksum algorithm: {}. Use 'crc32', 'crc32c', 'sha1' or 'sha256'", algorithm))),
+    }
+}
+
+/// Parse `--sse` and `--sse-kms-key-id`. A KMS key id is only valid with `aws:kms`.
+pub fn parse_sse(sse: Option<&str>, kms_key_id: Option<&str>) -> Result<Option<aws_sdk_s3::types::ServerSideEncryption>> {
+    let encryption = match sse {
+        None => None,
+        Some("AES256") => Some(aws_sdk_s3::types::ServerSideEncryption::Aes256),
+        Some("aws:kms") => Some(aws_sdk_s3::types::ServerSideEncryption::AwsKms),
+        Some(other) => return Err(usage_error(format!("Invalid encryption mode: {}. Use 'AES256' or 'aws:kms'", other))),
+    };
+
+    if kms_key_id.is_some() && encryption != Some(aws_sdk_s3::types::ServerSideEncryption::AwsKms) {
+        return Err(usage_error("--sse-kms-key-id can only be used with --sse aws:kms"));
+    }
+
+    Ok(encryption)
+}
+
+pub fn parse_storage_class(class: &str) -> Result<aws_sdk_s3::types::StorageClass> {
+    match class.to_ascii_uppercase().as_str() {
+        "STANDARD" => Ok(aws_sdk_s3::types::StorageClass::Standard),
+        "REDUCED_REDUNDANCY" => Ok(aws_sdk_s3::types::StorageClass::ReducedRedundancy),
+        "STANDARD_IA" => Ok(aws_sdk_s3::types::StorageClass::StandardIa),
+        "ONEZONE_IA" => Ok(aws_sdk_s3::types::StorageClass::OnezoneIa),
+        "INTELLIGENT_TIERING" => Ok(aws_sdk_s3::types::StorageClass::IntelligentTiering),
+        "GLACIER" => Ok(aws_sdk_s3::types::StorageClass::Glacier),
+        "GLACIER_IR" => Ok(aws_sdk_s3::types::StorageClass::GlacierIr),
+        "DEEP_ARCHIVE" => Ok(aws_sdk_s3::types::StorageClass::DeepArchive),
+        _ => Err(usage_error(format!(
+            "Invalid storage class: {}. Use STANDARD, REDUCED_REDUNDANCY, STANDARD_IA, ONEZONE_IA, INTELLIGENT_TIERING, GLACIER, GLACIER_IR or DEEP_ARCHIVE",
+            class
+        ))),
+    }
+}
+
+pub fn parse_metadata_directive(directive: &str) -> Result<aws_sdk_s3::types::MetadataDirective> {
+    match directive.to_ascii_uppercase().as_str() {
+        "COPY" => Ok(aws_sdk_s3::types::MetadataDirective::Copy),
+        "REPLACE" => Ok(aws_sdk_s3::types::MetadataDirective::Replace),
+        _ => Err(usage_error(format!("Invalid metadata directive: {}. Use 'COPY' or 'REPLACE'", directive))),
+    }
+}
+
+pub fn parse_object_canned_acl(acl: &str) -> Result<aws_sdk_s3::types::ObjectCannedAcl> {
+    if !aws_sdk_s3::types::ObjectCannedAcl::values().contains(&acl) {
+        return Err(usage_error(format!(
+            "Invalid canned ACL: {}. Use one of: {}",
+            acl,
+            aws_sdk_s3::types::ObjectCannedAcl::values().join(", ")
+        )));
+    }
+    Ok(aws_sdk_s3::types::ObjectCannedAcl::from(acl))
+}
+
+/// Standard HTTP headers stored with an object and returned when it is downloaded.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ObjectHeaders {
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub content_disposition: Option<String>,
+    pub content_encoding: Option<String>,
+    pub expires: Option<DateTime>,
+}
+
+/// Parse `--expires`: an HTTP date (`Wed, 21 Oct 2026 07:28:00 GMT`), an RFC3339
+/// timestamp, or a duration from `now` such as `30m`, `12h`, `7d` or `2w`.
+pub fn parse_expires(value: &str, now: std::time::SystemTime) -> Result<DateTime> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::from_str(value, DateTimeFormat::HttpDate) {
+        return Ok(date);
+    }
+    if let Ok(date) = DateTime::from_str(value, DateTimeFormat::DateTimeWithOffset) {
+        return Ok(date);
+    }
+
+    let invalid = || usage_error(format!(
+        "Invalid expiry: {}. Use an HTTP date (e.g. 'Wed, 21 Oct 2026 07:28:00 GMT'), RFC3339, or a duration like 12h or 7d",
+        value
+    ));
+    let duration = parse_duration(value).ok_or_else(invalid)?;
+    Ok(DateTime::from(now + duration))
+}
+
+/// A duration such as `30m`, `12h`, `7d` or `2w` (also `s`).
+fn parse_duration(value: &str) -> Option<std::time::Duration> {
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let count: u64 = value[..split].parse().ok()?;
+    let unit = match &value[split..] {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    Some(std::time::Duration::from_secs(count.checked_mul(unit)?))
+}
+
+/// Parse `--since`/`--until`: an RFC3339 timestamp, a date (`2024-06-01`,
+/// midnight UTC), or a duration before `now` such as `12h` or `7d`.
+pub fn parse_time_bound(value: &str, now: std::time::SystemTime) -> Result<DateTime> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::from_str(value, DateTimeFormat::DateTimeWithOffset) {
+        return Ok(date);
+    }
+    if let Ok(date) = DateTime::from_str(&format!("{}T00:00:00Z", value), DateTimeFormat::DateTime) {
+        return Ok(date);
+    }
+
+    let invalid = || usage_error(format!(
+        "Invalid time: {}. Use RFC3339 (e.g. 2024-06-01T12:00:00Z), a date (2024-06-01) or an age like 12h or 7d",
+        value
+    ));
+    let duration = parse_duration(value).ok_or_else(invalid)?;
+    let then = now.checked_sub(duration).ok_or_else(invalid)?;
+    Ok(DateTime::from(then))
+}
+
+/// Bounds on when listed objects were last modified, both inclusive.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ModifiedWindow {
+    pub since: Option<DateTime>,
+    pub until: Option<DateTime>,
+}
+
+impl ModifiedWindow {
+    pub fn new(since: Option<DateTime>, until: Option<DateTime>) -> Result<ModifiedWindow> {
+        if let (Some(since), Some(until)) = (since, until)
+            && since > until
+        {
+            return Err(usage_error("--since must not be after --until"));
+        }
+        Ok(ModifiedWindow { since, until })
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    /// Whether an object modified at `modified` falls in the window. With
+    /// any bound set, an object without a modification time does not.
+    pub fn contains(&self, modified: Option<&DateTime>) -> bool {
+        if self.is_unbounded() {
+            return true;
+        }
+        modified.is_some_and(|modified| {
+            self.since.is_none_or(|since| *modified >= since) && self.until.is_none_or(|until| *modified <= until)
+        })
+    }
+}
+
+/// How much of a file is read to detect its type from its content.
+const SNIFF_LEN: u64 = 8192;
+
+/// Detect a content type from a file's first bytes (magic numbers), for
+/// common binary formats such as images, PDFs and archives.
+pub fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    infer::get(bytes).map(|kind| kind.mime_type())
+}
+
+/// The content type for `path`, guessed from its extension or, when that
+/// says nothing (e.g. no extension), from its first few KiB. Falls back to
+/// `application/octet-stream`.
+pub fn guess_content_type(path: &Path) -> String {
+    if let Some(mime) = mime_guess::from_path(path).first() {
+        return mime.to_string();
+    }
+
+    let mut head = Vec::new();
+    if let Ok(file) = std::fs::File::open(path) {
+        use std::io::Read;
+        let _ = file.take(SNIFF_LEN).read_to_end(&mut head);
+    }
+    sniff_content_type(&head).unwrap_or("application/octet-stream").to_string()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_object(
+    client: &Client,
+    bucket_name: &str,
+    file_path: &str,
+    key: Option<String>,
+    checksum: Option<&str>,
+    sse: Option<&str>,
+    sse_kms_key_id: Option<&str>,
+    acl: Option<&str>,
+    grants: &Grants,
+    tags: &[(String, String)],
+    headers: &ObjectHeaders,
+    content_md5: bool,
+    resume: bool,
+    skip_existing: Option<SkipExisting>,
+    archive: Option<ArchiveFormat>,
+    dry_run: bool,
+) -> Result<()> {
+    let checksum_algorithm = checksum.map(parse_checksum_algorithm).transpose()?;
+    let encryption = parse_sse(sse, sse_kms_key_id)?;
+    let acl = acl.map(parse_object_canned_acl).transpose()?;
+    grants.check_object()?;
+    if acl.is_some() && !grants.is_empty() {
+        return Err(usage_error("--acl cannot be combined with --grant-* options"));
+    }
+    let tagging = encode_tagging(tags)?;
+
+    if let Some(format) = archive {
+        return upload_archive(
+            client,
+            bucket_name,
+            Path::new(file_path),
+            key,
+            format,
+            checksum_algorithm,
+            encryption,
+            sse_kms_key_id,
+            acl,
+            grants,
+            tagging.as_deref(),
+            headers,
+            dry_run,
+        ).await;
+    }
+
+    if file_path == "-" {
+        if skip_existing.is_some() {
+            return Err(usage_error("--skip-existing needs a file to compare; it can't be used with stdin"));
+        }
+        let object_key = key.ok_or_else(|| usage_error("--key is required when uploading from stdin"))?;
+        if dry_run {
+            print_dry_run("PutObject", bucket_name, Some(&object_key));
+            return Ok(());
+        }
+        let mut stdin = tokio::io::stdin();
+        return upload_from_reader(
+            client,
+            bucket_name,
+            &object_key,
+            &mut stdin,
+            checksum_algorithm,
+            encryption,
+            sse_kms_key_id,
+            acl,
+            grants,
+            tagging.as_deref(),
+            headers,
+            content_md5,
+        ).await;
+    }
+
+    let path = Path::new(file_path);
+    let file_name = path.file_name().ok_or_else(|| anyhow::anyhow!("Invalid file path"))?.to_str().unwrap();
+    let object_key = key.unwrap_or_else(|| file_name.to_string());
+
+    let metadata = std::fs::metadata(path).ok().filter(|m| m.is_file());
+    let multipart = metadata.as_ref().is_some_and(|m| m.len() >= MULTIPART_THRESHOLD);
+
+    if dry_run {
+        if skip_existing.is_some() {
+            print_dry_run("HeadObject", bucket_name, Some(&object_key));
+        }
+        let operation = if multipart { "CreateMultipartUpload" } else { "PutObject" };
+        print_dry_run(operation, bucket_name, Some(&object_key));
+        return Ok(());
+    }
+
+    if let Some(check) = skip_existing
+        && remote_is_unchanged(client, bucket_name, &object_key, path, check).await?
+    {
+        note(format!("Skipped '{}': '{}' already has it with the same {}.", object_key, bucket_name, check.describe()));
+        return Ok(());
+    }
+
+    // A determinate bar needs the length up front; anything that is not a
+    // regular file (e.g. a pipe) gets a spinner instead
+    let pb = item_bar("Uploading", &object_key, metadata.as_ref().map(|m| m.len()), None)?;
+
+    match metadata {
+        Some(metadata) if multipart => {
+            if content_md5 {
+                note("--content-md5 is ignored for multipart uploads; each part would need its own digest.");
+            }
+            let state_dir = if resume { upload_state_dir() } else { None };
+            upload_multipart(
+                client,
+                bucket_name,
+                path,
+                &object_key,
+                checksum_algorithm,
+                encryption,
+                sse_kms_key_id,
+                acl,
+                grants,
+                tagging.as_deref(),
+                headers,
+                part_size_for(metadata.len()),
+                state_dir.as_deref(),
+                Some(&pb),
+            ).await?;
+        }
+        _ => {
+            put_file(client, bucket_name, path, &object_key, checksum_algorithm, encryption, sse_kms_key_id, acl, grants, tagging.as_deref(), headers, content_md5, Some(&pb)).await?;
+        }
+    }
+
+    pb.finish_with_message("Upload complete");
+    success(format!("Object '{}' uploaded to '{}'.", object_key, bucket_name));
+
+    Ok(())
+}
+
+/// What `object upload --skip-existing` compares before skipping an upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipExisting {
+    Size,
+    /// Size, then the ETag against the file's MD5
+    SizeAndEtag,
+}
+
+impl SkipExisting {
+    fn describe(self) -> &'static str {
+        match self {
+            SkipExisting::Size => "size",
+            SkipExisting::SizeAndEtag => "size and ETag",
+        }
+    }
+}
+
+/// Whether `key` already holds the contents of the file at `path`, as far as
+/// `check` can tell. A missing object (404) means no; any other failure to
+/// head it is an error rather than a reason to upload blindly. Multipart
+/// ETags aren't an MD5 of the content, so they never match.
+pub async fn remote_is_unchanged(client: &Client, bucket_name: &str, key: &str, path: &Path, check: SkipExisting) -> Result<bool> {
+    let local_size = std::fs::metadata(path)
+        .map_err(|e| anyhow::anyhow!("Cannot read '{}': {}", path.display(), e))?
+        .len();
+
+    let head = match client.head_object().bucket(bucket_name).key(key).send().await {
+        Ok(head) => head,
+        // HEAD responses have no body, so go by the status code
+        Err(err) => return match err.raw_response().map(|r| r.status().as_u16()) {
+            Some(404) => Ok(false),
+            Some(403) => Err(access_denied_error(format!("Access denied to '{}' in bucket '{}'", key, bucket_name))),
+            _ => Err(anyhow::Error::from(err).context(format!("Failed to check '{}' before uploading", key))),
+        },
+    };
+
+    if head.content_length() != Some(local_size as i64) {
+        return Ok(false);
+    }
+    if check == SkipExisting::Size {
+        return Ok(true);
+    }
+
+    let etag = head.e_tag().unwrap_or_default().trim_matches('"');
+    let mut file = tokio::fs::File::open(path).await?;
+    let md5 = aws_smithy_types::base64::decode(compute_content_md5(&mut file).await?)?;
+    let md5_hex = md5.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    Ok(etag.eq_ignore_ascii_case(&md5_hex))
+}
+
+/// Upload everything read from `reader` (e.g. stdin). The data is spooled to
+/// a temporary file first, since S3 needs the length before the body is sent;
+/// large input is then uploaded in parts. The content type defaults to
+/// `application/octet-stream`.
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_from_reader<R: tokio::io::AsyncRead + Unpin>(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    reader: &mut R,
+    checksum_algorithm: Option<aws_sdk_s3::types::ChecksumAlgorithm>,
+    encryption: Option<aws_sdk_s3::types::ServerSideEncryption>,
+    kms_key_id: Option<&str>,
+    acl: Option<aws_sdk_s3::types::ObjectCannedAcl>,
+    grants: &Grants,
+    tagging: Option<&str>,
+    headers: &ObjectHeaders,
+    content_md5: bool,
+) -> Result<()> {
+    // The length is unknown until the input ends, so show a spinner
+    let spinner = item_bar("Reading", key, None, None)?;
+    let spool = tempfile::NamedTempFile::new()?;
+    let mut file = tokio::fs::File::create(spool.path()).await?;
+    tokio::io::copy(reader, &mut file).await?;
+    file.flush().await?;
+    spinner.finish_and_clear();
+
+    let len = std::fs::metadata(spool.path())?.len();
+    let headers = ObjectHeaders {
+        content_type: Some(headers.content_type.clone().unwrap_or_else(|| "application/octet-stream".to_string())),
+        ..headers.clone()
+    };
+    let pb = item_bar("Uploading", key, Some(len), None)?;
+    if len >= MULTIPART_THRESHOLD {
+        if content_md5 {
+            note("--content-md5 is ignored for multipart uploads; each part would need its own digest.");
+        }
+        // The spool file is gone after this run, so there is nothing to resume
+        upload_multipart(
+            client,
+            bucket_name,
+            spool.path(),
+            key,
+            checksum_algorithm,
+            encryption,
+            kms_key_id,
+            acl,
+            grants,
+            tagging,
+            &headers,
+            part_size_for(len),
+            None,
+            Some(&pb),
+        ).await?;
+    } else {
+        put_file(client, bucket_name, spool.path(), key, checksum_algorithm, encryption, kms_key_id, acl, grants, tagging, &headers, content_md5, Some(&pb)).await?;
+    }
+
+    pb.finish_with_message("Upload complete");
+    success(format!("Object '{}' uploaded to '{}'.", key, bucket_name));
+
+    Ok(())
+}
+
+/// Request body that advances a progress bar by the size of each chunk as
+/// it is sent.
+struct ProgressBody {
+    inner: SdkBody,
+    progress: ProgressBar,
+}
+
+impl http_body::Body for ProgressBody {
+    type Data = Bytes;
+    type Error = aws_smithy_types::body::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body::Frame<Bytes>, Self::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll
+            && let Some(data) = frame.data_ref()
+        {
+            self.progress.inc(data.len() as u64);
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    // The SDK takes Content-Length from the size hint
+    fn size_hint(&self) -> http_body::SizeHint {
+        http_body::Body::size_hint(&self.inner)
+    }
+}
+
+/// Wrap `stream` so `progress` tracks the bytes actually sent. A retry
+/// rebuilds the body and starts the bar over.
+pub fn track_progress(stream: ByteStream, progress: ProgressBar) -> ByteStream {
+    stream.map(move |body| {
+        progress.set_position(0);
+        SdkBody::from_body_1_x(ProgressBody { inner: body, progress: progress.clone() })
+    })
+}
+
+/// Upload the directory `dir` as one tar (or tar.gz) object, named after the
+/// directory unless `key` is given. As with stdin, the archive is spooled to
+/// a temporary file first, since its length is unknown until it is written;
+/// a large archive is then uploaded in parts.
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_archive(
+    client: &Client,
+    bucket_name: &str,
+    dir: &Path,
+    key: Option<String>,
+    format: ArchiveFormat,
+    checksum_algorithm: Option<aws_sdk_s3::types::ChecksumAlgorithm>,
+    encryption: Option<aws_sdk_s3::types::ServerSideEncryption>,
+    kms_key_id: Option<&str>,
+    acl: Option<aws_sdk_s3::types::ObjectCannedAcl>,
+    grants: &Grants,
+    tagging: Option<&str>,
+    headers: &ObjectHeaders,
+    dry_run: bool,
+) -> Result<()> {
+    if !dir.is_dir() {
+        return Err(usage_error(format!("--archive needs a directory, and '{}' is not one", dir.display())));
+    }
+    let entries = archive_entries(dir)?;
+    let count = entries.len();
+    let object_key = match key {
+        Some(key) => key,
+        None => format!("{}.{}", archive_root(dir)?, format.extension()),
+    };
+
+    if dry_run {
+        let operation = format!(
+            "PutObject ({} as {}; CreateMultipartUpload if the archive is {} or more)",
+            pluralize(count, "file"), format.extension(), format_size(MULTIPART_THRESHOLD as i64)
+        );
+        print_dry_run(&operation, bucket_name, Some(&object_key));
+        return Ok(());
+    }
+
+    let spinner = item_bar("Archiving", &object_key, None, None)?;
+    let spool = tempfile::NamedTempFile::new()?;
+    let file = tokio::fs::File::create(spool.path()).await?;
+    write_archive(&entries, format, file)
+        .await
+        .map_err(|e| e.context(format!("Could not archive '{}'", dir.display())))?;
+    spinner.finish_and_clear();
+
+    let len = std::fs::metadata(spool.path())?.len();
+    let headers = ObjectHeaders {
+        content_type: Some(headers.content_type.clone().unwrap_or_else(|| format.content_type().to_string())),
+        ..headers.clone()
+    };
+    let pb = item_bar("Uploading", &object_key, Some(len), None)?;
+    if len >= MULTIPART_THRESHOLD {
+        // The spool file is gone after this run, so there is nothing to resume
+        upload_multipart(
+            client,
+            bucket_name,
+            spool.path(),
+            &object_key,
+            checksum_algorithm,
+            encryption,
+            kms_key_id,
+            acl,
+            grants,
+            tagging,
+            &headers,
+            part_size_for(len),
+            None,
+            Some(&pb),
+        ).await?;
+    } else {
+        put_file(client, bucket_name, spool.path(), &object_key, checksum_algorithm, encryption, kms_key_id, acl, grants, tagging, &headers, false, Some(&pb)).await?;
+    }
+
+    pb.finish_with_message("Upload complete");
+    success(format!(
+        "Archived {} to '{}' in '{}' ({}).",
+        pluralize(count, "file"), object_key, bucket_name, format_size(len as i64)
+    ));
+    Ok(())
+}
+
+/// Base64 of the MD5 digest of everything `reader` yields, the format of the
+/// `Content-MD5` header.
+pub async fn compute_content_md5<R: tokio::io::AsyncRead + Unpin>(reader: &mut R) -> Result<String> {
+    use md5::Digest;
+    use tokio::io::AsyncReadExt;
+
+    let mut hasher = md5::Md5::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(aws_smithy_types::base64::encode(hasher.finalize()))
+}
+
+/// Upload a single file, streaming it from disk rather than buffering it.
+#[allow(clippy::too_many_arguments)]
+async fn put_file(
+    client: &Client,
+    bucket_name: &str,
+    path: &Path,
+    key: &str,
+    checksum_algorithm: Option<aws_sdk_s3::types::ChecksumAlgorithm>,
+    encryption: Option<aws_sdk_s3::types::ServerSideEncryption>,
+    kms_key_id: Option<&str>,
+    acl: Option<aws_sdk_s3::types::ObjectCannedAcl>,
+    grants: &Grants,
+    tagging: Option<&str>,
+    headers: &ObjectHeaders,
+    content_md5: bool,
+    progress: Option<&ProgressBar>,
+) -> Result<()> {
+    // Hashed before the upload starts, reading the file a chunk at a time
+    let content_md5 = if content_md5 {
+        Some(compute_content_md5(&mut tokio::fs::File::open(path).await?).await?)
+    } else {
+        None
+    };
+
+    let mut body = ByteStream::from_path(path).await?;
+    if let Some(progress) = progress {
+        body = track_progress(body, progress.clone());
+    }
+    let content_type = match &headers.content_type {
+        Some(content_type) => content_type.clone(),
+        None => mime_guess::from_path(path).first_or_octet_stream().to_string(),
+    };
+
+    client.put_object()
+        .bucket(bucket_name)
+        .key(key)
+        .body(body)
+        .content_type(content_type)
+        .set_cache_control(headers.cache_control.clone())
+        .set_content_disposition(headers.content_disposition.clone())
+        .set_content_encoding(headers.content_encoding.clone())
+        .set_expires(headers.expires)
+        .set_content_md5(content_md5)
+        .set_checksum_algorithm(checksum_algorithm)
+        .set_server_side_encryption(encryption)
+        .set_ssekms_key_id(kms_key_id.map(|k| k.to_string()))
+        .set_acl(acl)
+        .set_grant_read(grants.read.clone())
+        .set_grant_read_acp(grants.read_acp.clone())
+        .set_grant_write_acp(grants.write_acp.clone())
+        .set_grant_full_control(grants.full_control.clone())
+        .set_tagging(tagging.map(str::to_string))
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+/// Files at least this big are uploaded in parts, which can be resumed.
+pub const MULTIPART_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// S3 allows at most this many parts in one upload.
+const MAX_PARTS: u64 = 10_000;
+
+/// Part size for a file of `len` bytes: 8 MiB, or more if that would take
+/// over 10,000 parts.
+pub fn part_size_for(len: u64) -> u64 {
+    (8 * 1024 * 1024).max(len.div_ceil(MAX_PARTS))
+}
+
+/// A part S3 already has from an interrupted multipart upload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UploadedPart {
+    pub part_number: i32,
+    pub e_tag: String,
+    /// The part's checksum, when the upload uses `--checksum`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+/// Progress of a multipart upload, saved once it is created and after every
+/// part, so that running the same upload again skips the parts S3 already has.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultipartState {
+    pub bucket: String,
+    pub key: String,
+    pub upload_id: String,
+    /// The canonical path of the file being uploaded
+    #[serde(default)]
+    pub local_path: PathBuf,
+    /// The file's modification time, in nanoseconds since the Unix epoch
+    #[serde(default)]
+    pub local_modified: Option<u64>,
+    pub file_size: u64,
+    pub part_size: u64,
+    pub parts: Vec<UploadedPart>,
+}
+
+impl MultipartState {
+    /// Read a state file; a missing or unreadable one means nothing to resume.
+    pub fn load(path: &Path) -> Option<MultipartState> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Whether this is an upload of the same, unchanged file, split the same way.
+    fn matches(&self, other: &MultipartState) -> bool {
+        self.bucket == other.bucket
+            && self.key == other.key
+            && self.local_path == other.local_path
+            && self.local_modified == other.local_modified
+            && self.file_size == other.file_size
+            && self.part_size == other.part_size
+    }
+}
+
+/// A file's modification time in nanoseconds since the Unix epoch, as kept
+/// in [`MultipartState`].
+pub fn modified_nanos(metadata: &std::fs::Metadata) -> Option<u64> {
+    let since_epoch = metadata.modified().ok()?.duration_since(std::time::UNIX_EPOCH).ok()?;
+    u64::try_from(since_epoch.as_nanos()).ok()
+}
+
+/// Where resumable upload state is kept: `$XDG_CACHE_HOME/s3sh/uploads`, or
+/// `~/.cache/s3sh/uploads`.
+pub fn upload_state_dir() -> Option<PathBuf> {
+    let cache = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+    Some(cache.join("s3sh").join("uploads"))
+}
+
+/// The state file for an upload to `bucket_name`/`key`, named by a digest of both.
+pub fn upload_state_path(dir: &Path, bucket_name: &str, key: &str) -> PathBuf {
+    use md5::Digest;
+
+    let digest = md5::Md5::digest(format!("{}/{}", bucket_name, key).as_bytes());
+    let name: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    dir.join(format!("{}.json", name))
+}
+
+/// The checksum S3 computed for a part, for the upload's checksum algorithm.
+fn part_checksum(
+    output: &aws_sdk_s3::operation::upload_part::UploadPartOutput,
+    algorithm: Option<&aws_sdk_s3::types::ChecksumAlgorithm>,
+) -> Option<String> {
+    use aws_sdk_s3::types::ChecksumAlgorithm;
+
+    let checksum = match algorithm? {
+        ChecksumAlgorithm::Crc32 => output.checksum_crc32(),
+        ChecksumAlgorithm::Crc32C => output.checksum_crc32_c(),
+        ChecksumAlgorithm::Sha1 => output.checksum_sha1(),
+        ChecksumAlgorithm::Sha256 => output.checksum_sha256(),
+        _ => None,
+    };
+    checksum.map(|c| c.to_string())
+}
+
+fn completed_part(part: &UploadedPart, algorithm: Option<&aws_sdk_s3::types::ChecksumAlgorithm>) -> aws_sdk_s3::types::CompletedPart {
+    use aws_sdk_s3::types::ChecksumAlgorithm;
+
+    let builder = aws_sdk_s3::types::CompletedPart::builder()
+        .part_number(part.part_number)
+        .e_tag(&part.e_tag);
+    let checksum = part.checksum.clone();
+    match algorithm {
+        Some(ChecksumAlgorithm::Crc32) => builder.set_checksum_crc32(checksum),
+        Some(ChecksumAlgorithm::Crc32C) => builder.set_checksum_crc32_c(checksum),
+        Some(ChecksumAlgorithm::Sha1) => builder.set_checksum_sha1(checksum),
+        Some(ChecksumAlgorithm::Sha256) => builder.set_checksum_sha256(checksum),
+        _ => builder,
+    }.build()
+}
+
+/// Upload a file in `part_size` parts. With a `state_dir`, progress is saved
+/// once the upload is created and after each part: if the upload is
+/// interrupted, the same call on the unchanged file resumes it and only sends
+/// the missing parts. Without one, a failed upload is aborted.
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_multipart(
+    client: &Client,
+    bucket_name: &str,
+    path: &Path,
+    key: &str,
+    checksum_algorithm: Option<aws_sdk_s3::types::ChecksumAlgorithm>,
+    encryption: Option<aws_sdk_s3::types::ServerSideEncryption>,
+    kms_key_id: Option<&str>,
+    acl: Option<aws_sdk_s3::types::ObjectCannedAcl>,
+    grants: &Grants,
+    tagging: Option<&str>,
+    headers: &ObjectHeaders,
+    part_size: u64,
+    state_dir: Option<&Path>,
+    progress: Option<&ProgressBar>,
+) -> Result<()> {
+    let metadata = tokio::fs::metadata(path).await?;
+    let file_size = metadata.len();
+    let part_count = file_size.div_ceil(part_size).max(1);
+    let state_path = state_dir.map(|dir| upload_state_path(dir, bucket_name, key));
+
+    let fresh = MultipartState {
+        bucket: bucket_name.to_string(),
+        key: key.to_string(),
+        upload_id: String::new(),
+        local_path: tokio::fs::canonicalize(path).await?,
+        local_modified: modified_nanos(&metadata),
+        file_size,
+        part_size,
+        parts: Vec::new(),
+    };
+    let resumed = state_path.as_deref()
+        .and_then(MultipartState::load)
+        .filter(|state| state.matches(&fresh));
+    let mut state = match resumed {
+        Some(state) => {
+            note(format!("Resuming upload of '{}': {} of {} parts already uploaded.", key, state.parts.len(), part_count));
+            state
+        }
+        None => {
+            let content_type = match &headers.content_type {
+                Some(content_type) => content_type.clone(),
+                None => mime_guess::from_path(path).first_or_octet_stream().to_string(),
+            };
+            let created = client.create_multipart_upload()
+                .bucket(bucket_name)
+                .key(key)
+                .content_type(content_type)
+                .set_cache_control(headers.cache_control.clone())
+                .set_content_disposition(headers.content_disposition.clone())
+                .set_content_encoding(headers.content_encoding.clone())
+                .set_expires(headers.expires)
+                .set_checksum_algorithm(checksum_algorithm.clone())
+                .set_server_side_encryption(encryption)
+                .set_ssekms_key_id(kms_key_id.map(|k| k.to_string()))
+                .set_acl(acl)
+                .set_grant_read(grants.read.clone())
+                .set_grant_read_acp(grants.read_acp.clone())
+                .set_grant_write_acp(grants.write_acp.clone())
+                .set_grant_full_control(grants.full_control.clone())
+                .set_tagging(tagging.map(str::to_string))
+                .send()
+                .await?;
+            let upload_id = created.upload_id()
+                .ok_or_else(|| anyhow::anyhow!("S3 did not return an upload id for '{}'", key))?;
+            let state = MultipartState { upload_id: upload_id.to_string(), ..fresh };
+            // Saved before any part, so a failure on the first one can still be resumed
+            if let Some(state_path) = &state_path {
+                state.save(state_path)?;
+            }
+            state
+        }
+    };
+
+    let part_len = |number: u64| part_size.min(file_size - (number - 1) * part_size);
+    if let Some(progress) = progress {
+        progress.set_position(state.parts.iter().map(|p| part_len(p.part_number as u64)).sum());
+    }
+
+    for number in 1..=part_count {
+        let part_number = number as i32;
+        if state.parts.iter().any(|p| p.part_number == part_number) {
+            continue;
+        }
+
+        let body = ByteStream::read_from()
+            .path(path)
+            .offset((number - 1) * part_size)
+            .length(Length::Exact(part_len(number)))
+            .build()
+            .await?;
+        let uploaded = client.upload_part()
+            .bucket(bucket_name)
+            .key(key)
+            .upload_id(&state.upload_id)
+            .part_number(part_number)
+            .body(body)
+            .set_checksum_algorithm(checksum_algorithm.clone())
+            .send()
+            .await;
+        let output = match uploaded {
+            Ok(output) => output,
+            Err(err) => return Err(multipart_failed(client, &state, state_path.as_deref(), part_count, err.into()).await),
+        };
+
+        state.parts.push(UploadedPart {
+            part_number,
+            e_tag: output.e_tag().unwrap_or_default().to_string(),
+            checksum: part_checksum(&output, checksum_algorithm.as_ref()),
+        });
+        if let Some(state_path) = &state_path {
+            state.save(state_path)?;
+        }
+        if let Some(progress) = progress {
+            progress.inc(part_len(number));
+        }
+    }
+
+    state.parts.sort_by_key(|p| p.part_number);
+    let parts = state.parts.iter()
+        .map(|p| completed_part(p, checksum_algorithm.as_ref()))
+        .collect();
+    let completed = client.complete_multipart_upload()
+        .bucket(bucket_name)
+        .key(key)
+        .upload_id(&state.upload_id)
+        .multipart_upload(aws_sdk_s3::types::CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+        .send()
+        .await;
+    if let Err(err) = completed {
+        return Err(multipart_failed(client, &state, state_path.as_deref(), part_count, err.into()).await);
+    }
+
+    if let Some(state_path) = &state_path {
+        let _ = std::fs::remove_file(state_path);
+    }
+    Ok(())
+}
+
+/// Explain a failed multipart upload. A resumable upload is left in place for
+/// the next attempt, unless S3 no longer has it; otherwise it is aborted so
+/// the uploaded parts are not billed.
+async fn multipart_failed(
+    client: &Client,
+    state: &MultipartState,
+    state_path: Option<&Path>,
+    part_count: u64,
+    err: anyhow::Error,
+) -> anyhow::Error {
+    let gone = err.chain().any(|e| {
+        e.is::<aws_sdk_s3::types::error::NoSuchUpload>()
+            || e.downcast_ref::<aws_sdk_s3::error::ErrorMetadata>().and_then(|meta| meta.code()) == Some("NoSuchUpload")
+    });
+
+    match state_path {
+        Some(_) if !gone => err.context(format!(
+            "Upload of '{}' stopped after {} of {} parts; run the same command again to resume",
+            state.key,
+            state.parts.len(),
+            part_count
+        )),
+        Some(state_path) => {
+            let _ = std::fs::remove_file(state_path);
+            err.context(format!("The saved upload of '{}' no longer exists in S3; run the command again to start over", state.key))
+        }
+        None => {
+            let _ = client.abort_multipart_upload()
+                .bucket(&state.bucket)
+                .key(&state.key)
+                .upload_id(&state.upload_id)
+                .send()
+                .await;
+            err.context(format!("Upload of '{}' failed and was aborted", state.key))
+        }
+    }
+}
+
+/// Every file under `dir` paired with its object key: the path relative to
+/// `dir` with `/` separators, after `prefix`. Sorted by key.
+pub fn collect_sync_files(dir: &Path, prefix: Option<&str>, filter: &PathFilter) -> Result<Vec<(PathBuf, String)>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+
+            let relative = path.strip_prefix(dir)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if filter.is_included(&relative) {
+                files.push((path, format!("{}{}", prefix.unwrap_or(""), relative)));
+            }
+        }
+    }
+
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+/// Remote keys under `prefix` that pass `filter` (relative to the prefix)
+/// but are not among the `local` keys: what `sync --delete` removes.
+pub fn stale_keys(remote: Vec<String>, local: &[String], prefix: &str, filter: &PathFilter) -> Vec<String> {
+    let local = local.iter().map(String::as_str).collect::<HashSet<_>>();
+    remote.into_iter()
+        .filter(|key| !local.contains(key.as_str()) && filter.is_included(&key[prefix.len()..]))
+        .collect()
+}
+
+/// Upload every file under `dir` to `bucket_name`, up to `concurrency` at a
+/// time. A failed file does not stop the others; failures are listed at the end.
+/// With `delete`, remote objects under `prefix` with no local file are then
+/// removed too, unless an upload failed.
+#[allow(clippy::too_many_arguments)]
+pub async fn sync_directory(
+    client: &Client,
+    dir: &str,
+    bucket_name: &str,
+    prefix: Option<&str>,
+    concurrency: usize,
+    filter: &PathFilter,
+    delete: bool,
+    mfa: Option<&str>,
+    assume_yes: bool,
+    output: OutputFormat,
+    dry_run: bool,
+) -> Result<()> {
+    let files = collect_sync_files(Path::new(dir), prefix, filter)?;
+    let prefix = prefix.unwrap_or("");
+
+    if dry_run {
+        for (_, key) in &files {
+            print_dry_run("PutObject", bucket_name, Some(key));
+        }
+        if delete {
+            let local = files.iter().map(|(_, key)| key.clone()).collect::<Vec<_>>();
+            let remote = list_keys_under(client, bucket_name, prefix).await?;
+            for key in stale_keys(remote, &local, prefix, filter) {
+                print_dry_run("DeleteObject", bucket_name, Some(&key));
+            }
+        }
+        return Ok(());
+    }
+
+    if files.is_empty() && !delete && output != OutputFormat::Json {
+        note(format!("No files found in '{}'.", dir));
+        return Ok(());
+    }
+
+    let progress = BulkProgress::new(files.len(), "files", "Uploading", output)?;
+    let total = files.len();
+    let local = files.iter().map(|(_, key)| key.clone()).collect::<Vec<_>>();
+
+    let failures: Vec<(String, String)> = stream::iter(files)
+        .map(|(path, key)| {
+            let progress = &progress;
+            async move {
+                let len = std::fs::metadata(&path).ok().map(|m| m.len());
+                let result = match progress.start_item(&key, len) {
+                    Ok(pb) => {
+                        let result = put_file(client, bucket_name, &path, &key, None, None, None, None, &Grants::default(), None, &ObjectHeaders::default(), false, Some(&pb)).await;
+                        progress.finish_item(&pb);
+                        result
+                    }
+                    Err(err) => Err(err),
+                };
+                result.err().map(|e| (key, e.to_string()))
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .filter_map(|failure| async move { failure })
+        .collect()
+        .await;
+
+    progress.finish();
+
+    let uploaded = BulkSummary::new("sync", total, failures);
+    let synced = format!("Synced {} files to '{}'.", total, bucket_name);
+    if !delete {
+        return uploaded.report(output, "files", "upload", synced);
+    }
+    if !uploaded.failed.is_empty() {
+        if output != OutputFormat::Json {
+            note("Not deleting remote objects because some uploads failed.");
+        }
+        return uploaded.report(output, "files", "upload", synced);
+    }
+
+    let remote = list_keys_under(client, bucket_name, prefix).await?;
+    let stale = stale_keys(remote, &local, prefix, filter);
+    let target = format!("delete {} objects under '{}' from '{}' that are not in '{}'", stale.len(), prefix, bucket_name, dir);
+    let stale = if stale.is_empty() || confirm(&target, assume_yes)? {
+        stale
+    } else {
+        note("Kept remote objects that are not in the local directory.");
+        Vec::new()
+    };
+    let (failures, batch_error) = delete_keys(client, bucket_name, &stale, concurrency, mfa, output).await?;
+    let deleted = BulkSummary::new("delete", stale.len(), failures);
+
+    match output {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "upload": uploaded, "delete": deleted }))?);
+            if !deleted.failed.is_empty() {
+                let err = anyhow::anyhow!("{} of {} objects failed to delete", deleted.failed.len(), deleted.total);
+                return with_batch_error(Err(err), batch_error);
+            }
+            Ok(())
+        }
+        OutputFormat::Table | OutputFormat::Csv => {
+            uploaded.report(output, "files", "upload", synced)?;
+            let done = format!("Deleted {} remote objects not in '{}'.", deleted.succeeded, dir);
+            with_batch_error(deleted.report(output, "objects", "delete", done), batch_error)
+        }
+    }
+}
+
+pub async fn delete_object(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    version_id: Option<&str>,
+    mfa: Option<&str>,
+    assume_yes: bool,
+    dry_run: bool,
+) -> Result<()> {
+    if dry_run {
+        match version_id {
+            Some(v) => print_dry_run(&format!("DeleteObject (version '{}')", v), bucket_name, Some(key)),
+            None => print_dry_run("DeleteObject", bucket_name, Some(key)),
+        }
+        return Ok(());
+    }
+
+    let target = match version_id {
+        Some(v) => format!("delete version '{}' of object '{}' from '{}'", v, key, bucket_name),
+        None => format!("delete object '{}' from '{}'", key, bucket_name),
+    };
+    if !confirm(&target, assume_yes)? {
+        note("Aborted.");
+        return Ok(());
+    }
+
+    let resp = client.delete_object()
+        .bucket(bucket_name)
+        .key(key)
+        .set_version_id(version_id.map(|v| v.to_string()))
+        .set_mfa(mfa.map(|m| m.to_string()))
+        .send()
+        .await?;
+
+    match version_id {
+        Some(v) => success(format!("Version '{}' of object '{}' deleted from '{}'.", v, key, bucket_name)),
+        None if resp.delete_marker() == Some(true) => success(format!(
+            "Delete marker '{}' created for object '{}' in '{}'.",
+            resp.version_id().unwrap_or("<unknown>"),
+            key,
+            bucket_name
+        )),
+        None => success(format!("Object '{}' deleted from '{}'.", key, bucket_name)),
+    }
+    Ok(())
+}
+
+/// Delete every object under `prefix` whose key (relative to the prefix)
+/// passes `filter`, in batches of up to 1000 keys with up to `concurrency`
+/// batches in flight. Keys that fail are listed at the end.
+#[allow(clippy::too_many_arguments)]
+pub async fn delete_prefix(
+    client: &Client,
+    bucket_name: &str,
+    prefix: &str,
+    filter: &PathFilter,
+    concurrency: usize,
+    mfa: Option<&str>,
+    assume_yes: bool,
+    output: OutputFormat,
+    dry_run: bool,
+) -> Result<()> {
+    if dry_run {
+        print_dry_run(&format!("ListObjectsV2 (prefix '{}')", prefix), bucket_name, None);
+        print_dry_run("DeleteObjects (batches of up to 1000 matching keys)", bucket_name, None);
+        return Ok(());
+    }
+
+    let keys = list_keys_under(client, bucket_name, prefix).await?
+        .into_iter()
+        .filter(|key| filter.is_included(&key[prefix.len()..]))
+        .collect::<Vec<_>>();
+
+    if keys.is_empty() && output != OutputFormat::Json {
+        note(format!("No matching objects under '{}'.", prefix));
+        return Ok(());
+    }
+
+    let target = format!("delete {} objects under '{}' from '{}'", keys.len(), prefix, bucket_name);
+    if !keys.is_empty() && !confirm(&target, assume_yes)? {
+        note("Aborted.");
+        return Ok(());
+    }
+
+    let (failures, batch_error) = delete_keys(client, bucket_name, &keys, concurrency, mfa, output).await?;
+    let summary = BulkSummary::new("delete", keys.len(), failures);
+    let done = format!("Deleted {} objects under '{}' from '{}'.", summary.succeeded, prefix, bucket_name);
+    with_batch_error(summary.report(output, "objects", "delete", done), batch_error)
+}
+
+/// Keys that failed to delete with their errors, and the error of a batch
+/// that failed as a whole.
+type DeleteFailures = (Vec<(String, String)>, Option<anyhow::Error>);
+
+/// Delete `keys` in batches of up to 1000, up to `concurrency` batches in
+/// flight, returning the keys that failed with their errors. When a whole
+/// batch fails, its error is returned too, so the final error keeps its kind
+/// (e.g. access denied) for the exit code.
+async fn delete_keys(
+    client: &Client,
+    bucket_name: &str,
+    keys: &[String],
+    concurrency: usize,
+    mfa: Option<&str>,
+    output: OutputFormat,
+) -> Result<DeleteFailures> {
+    let batches = keys.chunks(1000)
+        .map(|chunk| {
+            chunk.iter()
+                .map(|key| aws_sdk_s3::types::ObjectIdentifier::builder().key(key).build())
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Batches have no bar of their own; the overall bar counts keys
+    let progress = BulkProgress::new(keys.len(), "objects", "Deleting", output)?;
+    let results: Vec<DeleteFailures> = stream::iter(batches)
+        .map(|batch| {
+            let progress = &progress;
+            async move {
+                let keys = batch.iter().map(|o| o.key().to_string()).collect::<Vec<_>>();
+                let count = keys.len() as u64;
+                let result = match crate::buckets::delete_batch_report(client, bucket_name, batch, mfa).await {
+                    Ok((_, errors)) => (errors, None),
+                    // The whole request failed, so none of its keys were deleted
+                    Err(err) => (keys.into_iter().map(|key| (key, err.to_string())).collect(), Some(err)),
+                };
+                progress.inc(count);
+                result
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+
+    progress.finish();
+    let mut failures = Vec::new();
+    let mut batch_error = None;
+    for (errors, err) in results {
+        failures.extend(errors);
+        batch_error = batch_error.or(err);
+    }
+    Ok((failures, batch_error))
+}
+
+/// A bulk delete's result, failing with `batch_error` underneath so that
+/// [`crate::errors::classify_error`] still sees why a batch failed.
+fn with_batch_error(result: Result<()>, batch_error: Option<anyhow::Error>) -> Result<()> {
+    match (result, batch_error) {
+        (Err(err), Some(cause)) => Err(cause.context(err.to_string())),
+        (result, _) => result,
+    }
+}
+
+/// An inclusive byte range of an object, as given to `--range START-END`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    /// The `Range` header value, e.g. `bytes=0-99`.
+    pub fn header(&self) -> String {
+        format!("bytes={}-{}", self.start, self.end)
+    }
+}
+
+/// Parse `START-END` (both inclusive byte offsets, START <= END).
+pub fn parse_byte_range(s: &str) -> Result<ByteRange> {
+    let invalid = || usage_error(format!("Invalid range '{}'. Use START-END in bytes, e.g. 0-1023", s));
+    let (start, end) = s.trim().split_once('-').ok_or_else(invalid)?;
+    let start = start.trim().parse::<u64>().map_err(|_| invalid())?;
+    let end = end.trim().parse::<u64>().map_err(|_| invalid())?;
+    if start > end {
+        return Err(usage_error(format!("Invalid range '{}': start must not be after end", s)));
+    }
+    Ok(ByteRange { start, end })
+}
+
+/// `GetObject`, for just `range` of the object when given. A range that
+/// starts past the end of the object gets a clearer error than S3's.
+async fn get_object_range(client: &Client, bucket_name: &str, key: &str, range: Option<ByteRange>) -> Result<GetObjectOutput> {
+    client.get_object()
+        .bucket(bucket_name)
+        .key(key)
+        .set_range(range.map(|r| r.header()))
+        .send()
+        .await
+        .map_err(|err| match (range, err.raw_response().map(|r| r.status().as_u16())) {
+            (Some(range), Some(416)) => usage_error(format!(
+                "Range {}-{} is not satisfiable: '{}' is smaller than that", range.start, range.end, key
+            )),
+            _ => err.into(),
+        })
+}
+
+/// Objects are only split for `--parallel` into ranges of at least this size;
+/// anything smaller is downloaded in one stream.
+pub const MIN_PARALLEL_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Split `size` bytes into at most `parts` contiguous ranges of at least
+/// `min_part_size` bytes each (the last may be shorter).
+pub fn split_ranges(size: u64, parts: usize, min_part_size: u64) -> Vec<ByteRange> {
+    if size == 0 {
+        return Vec::new();
+    }
+    let parts = (parts.max(1) as u64).min(size.div_ceil(min_part_size.max(1)));
+    let part_size = size.div_ceil(parts);
+    (0..size)
+        .step_by(part_size as usize)
+        .map(|start| ByteRange { start, end: (start + part_size).min(size) - 1 })
+        .collect()
+}
+
+/// The first byte of a `Content-Range` such as `bytes 100-199/1000`.
+fn content_range_start(content_range: &str) -> Option<u64> {
+    content_range.strip_prefix("bytes ")?.split('-').next()?.parse().ok()
+}
+
+/// Download one object to `dest`: a file path, or a directory to put it in.
+/// Without `dest` the object lands in the current directory under its file
+/// name. With `range` only those bytes are written; with `parallel` over 1
+/// the object is fetched as that many ranges at once.
+pub async fn download_object(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    dest: Option<&str>,
+    range: Option<ByteRange>,
+    parallel: usize,
+    dry_run: bool,
+) -> Result<()> {
+    let file_name = key.rsplit('/').next().filter(|name| !name.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Cannot download '{}': key does not name a file", key))?;
+    let path = match dest.map(Path::new) {
+        Some(dest) if dest.is_dir() => dest.join(file_name),
+        Some(dest) => dest.to_path_buf(),
+        None => PathBuf::from(file_name),
+    };
+
+    if dry_run {
+        let operation = match range {
+            Some(range) => format!("GetObject (range {}, to '{}')", range.header(), path.display()),
+            None if parallel > 1 => {
+                print_dry_run("HeadObject", bucket_name, Some(key));
+                format!("GetObject (up to {} ranges at once, to '{}')", parallel, path.display())
+            }
+            None => format!("GetObject (to '{}')", path.display()),
+        };
+        print_dry_run(&operation, bucket_name, Some(key));
+        return Ok(());
+    }
+
+    if range.is_none() && parallel > 1 {
+        download_parallel(client, bucket_name, key, &path, parallel, MIN_PARALLEL_PART_SIZE).await?;
+        success(format!("Object '{}' downloaded to '{}'.", key, path.display()));
+        return Ok(());
+    }
+
+    let content_range = save_object(client, bucket_name, key, &path, range).await?;
+    if let Some(content_range) = content_range {
+        note(format!("Received {}", content_range));
+    }
+    success(format!("Object '{}' downloaded to '{}'.", key, path.display()));
+    Ok(())
+}
+
+/// Where a key under `prefix` lands below `dir`, or `None` for keys that
+/// cannot be mirrored: folder placeholders ending in `/` and keys that would
+/// escape `dir` (`..`).
+pub fn download_path(dir: &Path, prefix: &str, key: &str) -> Option<PathBuf> {
+    let relative = key.strip_prefix(prefix)?.trim_start_matches('/');
+    if relative.is_empty() || key.ends_with('/') {
+        return None;
+    }
+
+    let mut path = dir.to_path_buf();
+    for part in relative.split('/') {
+        match part {
+            "" | "." => continue,
+            ".." => return None,
+            part => path.push(part),
+        }
+    }
+    Some(path)
+}
+
+/// Download every object under `prefix` into `dir`, mirroring the key layout
+/// and creating directories as needed, up to `concurrency` at a time. A failed
+/// object does not stop the others; failures are listed at the end.
+#[allow(clippy::too_many_arguments)]
+pub async fn download_prefix(
+    client: &Client,
+    bucket_name: &str,
+    prefix: &str,
+    dir: &str,
+    concurrency: usize,
+    filter: &PathFilter,
+    output: OutputFormat,
+    dry_run: bool,
+) -> Result<()> {
+    if dry_run {
+        print_dry_run(&format!("ListObjectsV2 (prefix '{}')", prefix), bucket_name, None);
+        print_dry_run(&format!("GetObject (each matching key, to '{}')", dir), bucket_name, None);
+        return Ok(());
+    }
+
+    let downloads = list_keys_under(client, bucket_name, prefix).await?
+        .into_iter()
+        .filter(|key| filter.is_included(&key[prefix.len()..]))
+        .filter_map(|key| download_path(Path::new(dir), prefix, &key).map(|path| (key, path)))
+        .collect::<Vec<_>>();
+
+    if downloads.is_empty() && output != OutputFormat::Json {
+        note(format!("No matching objects under '{}'.", prefix));
+        return Ok(());
+    }
+
+    let progress = BulkProgress::new(downloads.len(), "files", "Downloading", output)?;
+    let total = downloads.len();
+
+    let failures: Vec<(String, String)> = stream::iter(downloads)
+        .map(|(key, path)| {
+            let progress = &progress;
+            async move {
+                let result = match progress.start_item(&key, None) {
+                    Ok(pb) => {
+                        let result = save_object(client, bucket_name, &key, &path, None).await.map(|_| ());
+                        progress.finish_item(&pb);
+                        result
+                    }
+                    Err(err) => Err(err),
+                };
+                result.err().map(|e| (key, e.to_string()))
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .filter_map(|failure| async move { failure })
+        .collect()
+        .await;
+
+    progress.finish();
+
+    BulkSummary::new("download", total, failures)
+        .report(output, "objects", "download", format!("Downloaded {} objects to '{}'.", total, dir))
+}
+
+/// Download an object as up to `parts` byte ranges fetched at once, each
+/// written at its offset in a file preallocated to the object's size. Objects
+/// too small to give two ranges of `min_part_size` are streamed in one piece.
+/// Every range is pinned to the ETag the HEAD returned, so an object
+/// overwritten mid-download fails instead of mixing two versions.
+pub async fn download_parallel(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    path: &Path,
+    parts: usize,
+    min_part_size: u64,
+) -> Result<()> {
+    let head = client.head_object()
+        .bucket(bucket_name)
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| anyhow::Error::from(e).context(format!("Failed to get the size of '{}'", key)))?;
+    let size = head.content_length().unwrap_or(0).max(0) as u64;
+    let e_tag = head.e_tag();
+
+    let ranges = split_ranges(size, parts, min_part_size);
+    if ranges.len() <= 1 {
+        save_object(client, bucket_name, key, path, None).await?;
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let file = tokio::fs::File::create(path).await?;
+    file.set_len(size).await?;
+    drop(file);
+
+    let pb = item_bar("Downloading", key, Some(size), None)?;
+    let concurrency = ranges.len();
+    let results: Vec<Result<()>> = stream::iter(ranges)
+        .map(|range| {
+            let pb = &pb;
+            async move {
+                let resp = client.get_object()
+                    .bucket(bucket_name)
+                    .key(key)
+                    .range(range.header())
+                    .set_if_match(e_tag.map(str::to_string))
+                    .send()
+                    .await
+                    .map_err(|err| match err.raw_response().map(|r| r.status().as_u16()) {
+                        Some(412) => anyhow::anyhow!("'{}' was overwritten while it was being downloaded; run the download again", key),
+                        _ => err.into(),
+                    })?;
+                // Write where the bytes actually belong, per the response
+                let start = resp.content_range()
+                    .and_then(content_range_start)
+                    .ok_or_else(|| anyhow::anyhow!("S3 returned no Content-Range for {} of '{}'", range.header(), key))?;
+
+                let mut file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+                file.seek(std::io::SeekFrom::Start(start)).await?;
+                let mut body = resp.body;
+                while let Some(chunk) = body.try_next().await? {
+                    file.write_all(&chunk).await?;
+                    pb.inc(chunk.len() as u64);
+                }
+                file.flush().await?;
+                Ok(())
+            }
+        })
+        .buffer_unordered(concurrency)
+        .collect()
+        .await;
+
+    pb.finish_and_clear();
+    if let Some(err) = results.into_iter().find_map(|r| r.err()) {
+        let _ = tokio::fs::remove_file(path).await;
+        return Err(err.context(format!("Parallel download of '{}' failed", key)));
+    }
+    Ok(())
+}
+
+/// Write an object (or `range` of it) to `path`, returning the response's
+/// `Content-Range` for a ranged read.
+async fn save_object(client: &Client, bucket_name: &str, key: &str, path: &Path, range: Option<ByteRange>) -> Result<Option<String>> {
+    let resp = get_object_range(client, bucket_name, key, range).await?;
+    let content_range = resp.content_range().map(|r| r.to_string());
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut body = resp.body;
+    while let Some(chunk) = body.try_next().await? {
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+
+    Ok(content_range)
+}
+
+/// Every key under `prefix`, following continuation tokens.
+async fn list_keys_under(client: &Client, bucket_name: &str, prefix: &str) -> Result<Vec<String>> {
+    let mut keys = Vec::new();
+    let mut continuation_token = None;
+
+    loop {
+        let resp = client.list_objects_v2()
+            .bucket(bucket_name)
+            .prefix(prefix)
+            .set_continuation_token(continuation_token)
+            .send()
+            .await?;
+
+        keys.extend(resp.contents().iter().filter_map(|o| o.key()).map(|k| k.to_string()));
+
+        match resp.next_continuation_token() {
+            Some(token) if resp.is_truncated() == Some(true) => continuation_token = Some(token.to_string()),
+            _ => break,
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Percent-encode everything but unreserved characters and `keep`.
+fn percent_encode(value: &str, keep: &[u8]) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ if keep.contains(&byte) => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// The `x-amz-copy-source` value for an object: `bucket/key` with the key
+/// percent-encoded, keeping `/` separators.
+pub fn copy_source(bucket_name: &str, key: &str) -> String {
+    format!("{}/{}", bucket_name, percent_encode(key, b"/"))
+}
+
+/// S3's limits on object tags.
+const MAX_OBJECT_TAGS: usize = 10;
+const MAX_TAG_KEY_LEN: usize = 128;
+const MAX_TAG_VALUE_LEN: usize = 256;
+
+/// The `x-amz-tagging` value for `--tags`: `k1=v1&k2=v2` with keys and
+/// values percent-encoded. Checks S3's limits first; `None` for no tags.
+pub fn encode_tagging(tags: &[(String, String)]) -> Result<Option<String>> {
+    if tags.is_empty() {
+        return Ok(None);
+    }
+    if tags.len() > MAX_OBJECT_TAGS {
+        return Err(usage_error(format!("An object can have at most {} tags, not {}", MAX_OBJECT_TAGS, tags.len())));
+    }
+    for (i, (key, value)) in tags.iter().enumerate() {
+        if key.is_empty() {
+            return Err(usage_error("Tag keys cannot be empty"));
+        }
+        if key.chars().count() > MAX_TAG_KEY_LEN {
+            return Err(usage_error(format!("Tag key '{}' is longer than {} characters", key, MAX_TAG_KEY_LEN)));
+        }
+        if value.chars().count() > MAX_TAG_VALUE_LEN {
+            return Err(usage_error(format!("The value of tag '{}' is longer than {} characters", key, MAX_TAG_VALUE_LEN)));
+        }
+        if tags[..i].iter().any(|(earlier, _)| earlier == key) {
+            return Err(usage_error(format!("Tag '{}' is given more than once", key)));
+        }
+    }
+
+    let pairs: Vec<String> = tags.iter()
+        .map(|(key, value)| format!("{}={}", percent_encode(key, b""), percent_encode(value, b"")))
+        .collect();
+    Ok(Some(pairs.join("&")))
+}
+
+pub async fn get_object_acl(client: &Client, bucket_name: &str, key: &str) -> Result<()> {
+    let resp = client.get_object_acl()
+        .bucket(bucket_name)
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to get ACL for '{}': {}", key, e))?;
+
+    print!("{}", crate::buckets::format_acl(resp.owner(), resp.grants()));
+    Ok(())
+}
+
+/// Replace an object's ACL with a canned ACL or with explicit grants
+/// (exactly one of the two).
+pub async fn put_object_acl(client: &Client, bucket_name: &str, key: &str, canned: Option<&str>, grants: &Grants, dry_run: bool) -> Result<()> {
+    let acl = canned.map(parse_object_canned_acl).transpose()?;
+    grants.check_object()?;
+    if acl.is_none() == grants.is_empty() {
+        return Err(usage_error("Give either a canned ACL or --grant-* options"));
+    }
+
+    if dry_run {
+        match &acl {
+            Some(acl) => print_dry_run(&format!("PutObjectAcl (ACL '{}')", acl.as_str()), bucket_name, Some(key)),
+            None => print_dry_run("PutObjectAcl (the given grants)", bucket_name, Some(key)),
+        }
+        return Ok(());
+    }
+
+    client.put_object_acl()
+        .bucket(bucket_name)
+        .key(key)
+        .set_acl(acl)
+        .set_grant_read(grants.read.clone())
+        .set_grant_read_acp(grants.read_acp.clone())
+        .set_grant_write_acp(grants.write_acp.clone())
+        .set_grant_full_control(grants.full_control.clone())
+        .send()
+        .await?;
+
+    match canned {
+        Some(canned) => success(format!("Object '{}' ACL set to: {}", key, canned.cyan())),
+        None => success(format!("Object '{}' ACL replaced with the given grants.", key)),
+    }
+    Ok(())
+}
+
+/// Whether `key` exists, via `HeadObject`. Only a 404 counts as absent; any
+/// other failure (403, network) is returned as an error.
+pub async fn object_exists(client: &Client, bucket_name: &str, key: &str) -> Result<bool> {
+    match client.head_object().bucket(bucket_name).key(key).send().await {
+        Ok(_) => Ok(true),
+        // HEAD responses have no body, so go by the status code
+        Err(err) => match err.raw_response().map(|r| r.status().as_u16()) {
+            Some(404) => Ok(false),
+            Some(403) => Err(access_denied_error(format!("Access denied to '{}' in bucket '{}'", key, bucket_name))),
+            _ => Err(err.into()),
+        },
+    }
+}
+
+/// Copy an object, optionally changing its storage class. With the REPLACE
+/// directive the copy gets `metadata` instead of the source's user metadata.
+#[allow(clippy::too_many_arguments)]
+pub async fn copy_object(
+    client: &Client,
+    src_bucket: &str,
+    src_key: &str,
+    dst_bucket: &str,
+    dst_key: &str,
+    storage_class: Option<&str>,
+    metadata_directive: &str,
+    metadata: Vec<(String, String)>,
+    dry_run: bool,
+) -> Result<()> {
+    let storage_class = storage_class.map(parse_storage_class).transpose()?;
+    let directive = parse_metadata_directive(metadata_directive)?;
+    if !metadata.is_empty() && directive != aws_sdk_s3::types::MetadataDirective::Replace {
+        return Err(usage_error("--metadata can only be used with --metadata-directive REPLACE"));
+    }
+
+    if dry_run {
+        print_dry_run(&format!("CopyObject (to '{}/{}')", dst_bucket, dst_key), src_bucket, Some(src_key));
+        return Ok(());
+    }
+
+    let mut request = client.copy_object()
+        .copy_source(copy_source(src_bucket, src_key))
+        .bucket(dst_bucket)
+        .key(dst_key)
+        .metadata_directive(directive)
+        .set_storage_class(storage_class);
+    for (name, value) in metadata {
+        request = request.metadata(name, value);
+    }
+
+    request.send()
+        .await
+        .map_err(|e| anyhow::Error::from(e).context(format!("Failed to copy '{}/{}'", src_bucket, src_key)))?;
+
+    success(format!("Copied '{}/{}' to '{}/{}'.", src_bucket, src_key, dst_bucket, dst_key));
+    Ok(())
+}
+
+/// Objects bigger than this can't be copied with a single `CopyObject`.
+pub const MAX_COPY_OBJECT_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// Part size for multipart copies of objects over `MAX_COPY_OBJECT_SIZE`.
+const COPY_PART_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Copy every object under `src_prefix` into `dst_bucket`, replacing
+/// `src_prefix` with `dst_prefix` in each key, up to `concurrency` at a time.
+/// Objects over 5 GiB are copied in parts. A failed object does not stop the
+/// others; failures are listed at the end. The source is listed through
+/// `src_client` and the copies are sent through `client`, each set up for
+/// its bucket's region.
+#[allow(clippy::too_many_arguments)]
+pub async fn copy_prefix(
+    client: &Client,
+    src_client: &Client,
+    src_bucket: &str,
+    src_prefix: &str,
+    dst_bucket: &str,
+    dst_prefix: &str,
+    storage_class: Option<&str>,
+    concurrency: usize,
+    filter: &PathFilter,
+    output: OutputFormat,
+    dry_run: bool,
+) -> Result<()> {
+    let storage_class = storage_class.map(parse_storage_class).transpose()?;
+
+    if dry_run {
+        print_dry_run(&format!("ListObjectsV2 (prefix '{}')", src_prefix), src_bucket, None);
+        print_dry_run(&format!("CopyObject (each matching key from '{}/{}', to prefix '{}')", src_bucket, src_prefix, dst_prefix), dst_bucket, None);
+        return Ok(());
+    }
+
+    let (objects, _) = fetch_objects(src_client, src_bucket, Some(src_prefix), None, None).await?;
+    let copies = objects.iter()
+        .filter_map(|o| Some((o.key()?.to_string(), o.size().unwrap_or(0).max(0) as u64)))
+        .filter(|(key, _)| filter.is_included(&key[src_prefix.len()..]))
+        .map(|(key, size)| {
+            let dst_key = format!("{}{}", dst_prefix, &key[src_prefix.len()..]);
+            (key, dst_key, size)
+        })
+        .collect::<Vec<_>>();
+
+    if copies.is_empty() && output != OutputFormat::Json {
+        note(format!("No matching objects under '{}'.", src_prefix));
+        return Ok(());
+    }
+
+    let progress = BulkProgress::new(copies.len(), "objects", "Copying", output)?;
+    let total = copies.len();
+
+    let failures: Vec<(String, String)> = stream::iter(copies)
+        .map(|(key, dst_key, size)| {
+            let progress = &progress;
+            let storage_class = storage_class.clone();
+            async move {
+                let result = match progress.start_item(&key, None) {
+                    Ok(pb) => {
+                        let result = if size > MAX_COPY_OBJECT_SIZE {
+                            multipart_copy(client, src_client, src_bucket, &key, dst_bucket, &dst_key, size, storage_class).await
+                        } else {
+                            client.copy_object()
+                                .copy_source(copy_source(src_bucket, &key))
+                                .bucket(dst_bucket)
+                                .key(&dst_key)
+                                .set_storage_class(storage_class)
+                                .send()
+                                .await
+                                .map(|_| ())
+                                .map_err(anyhow::Error::from)
+                        };
+                        progress.finish_item(&pb);
+                        result
+                    }
+                    Err(err) => Err(err),
+                };
+                result.err().map(|e| (key, e.to_string()))
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .filter_map(|failure| async move { failure })
+        .collect()
+        .await;
+
+    progress.finish();
+
+    BulkSummary::new("copy", total, failures)
+        .report(output, "objects", "copy", format!("Copied {} to '{}/{}'.", pluralize(total, "object"), dst_bucket, dst_prefix))
+}
+
+/// Copy an object too big for `CopyObject` with `UploadPartCopy`, carrying
+/// over its content headers and user metadata. The upload is aborted if any
+/// part fails. The source is read with `src_client`; the upload goes
+/// through `client`.
+#[allow(clippy::too_many_arguments)]
+async fn multipart_copy(
+    client: &Client,
+    src_client: &Client,
+    src_bucket: &str,
+    src_key: &str,
+    dst_bucket: &str,
+    dst_key: &str,
+    size: u64,
+    storage_class: Option<aws_sdk_s3::types::StorageClass>,
+) -> Result<()> {
+    // Unlike CopyObject, a multipart upload starts with no metadata
+    let head = src_client.head_object()
+        .bucket(src_bucket)
+        .key(src_key)
+        .send()
+        .await?;
+
+    let upload = client.create_multipart_upload()
+        .bucket(dst_bucket)
+        .key(dst_key)
+        .set_content_type(head.content_type().map(|v| v.to_string()))
+        .set_cache_control(head.cache_control().map(|v| v.to_string()))
+        .set_content_disposition(head.content_disposition().map(|v| v.to_string()))
+        .set_content_encoding(head.content_encoding().map(|v| v.to_string()))
+        .set_content_language(head.content_language().map(|v| v.to_string()))
+        .set_metadata(head.metadata().cloned())
+        .set_storage_class(storage_class.or_else(|| head.storage_class().cloned()))
+        .send()
+        .await?;
+    let upload_id = upload.upload_id()
+        .ok_or_else(|| anyhow::anyhow!("S3 returned no upload ID for '{}'", dst_key))?
+        .to_string();
+
+    let result: Result<()> = async {
+        let mut parts = Vec::new();
+        let mut start = 0;
+        while start < size {
+            let end = (start + COPY_PART_SIZE).min(size) - 1;
+            let part_number = parts.len() as i32 + 1;
+            let resp = client.upload_part_copy()
+                .copy_source(copy_source(src_bucket, src_key))
+                .copy_source_range(format!("bytes={}-{}", start, end))
+                .bucket(dst_bucket)
+                .key(dst_key)
+                .upload_id(&upload_id)
+                .part_number(part_number)
+                .send()
+                .await?;
+            parts.push(
+                aws_sdk_s3::types::CompletedPart::builder()
+                    .part_number(part_number)
+                    .set_e_tag(resp.copy_part_result().and_then(|r| r.e_tag()).map(|v| v.to_string()))
+                    .build(),
+            );
+            start = end + 1;
+        }
+
+        client.complete_multipart_upload()
+            .bucket(dst_bucket)
+            .key(dst_key)
+            .upload_id(&upload_id)
+            .multipart_upload(aws_sdk_s3::types::CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+            .send()
+            .await?;
+        Ok(())
+    }.await;
+
+    if let Err(err) = result {
+        let _ = client.abort_multipart_upload()
+            .bucket(dst_bucket)
+            .key(dst_key)
+            .upload_id(&upload_id)
+            .send()
+            .await;
+        return Err(err.context(format!("Multipart copy of '{}' failed and was aborted", src_key)));
+    }
+    Ok(())
+}
+
+/// Move (or rename) an object by copying it and then deleting the source.
+/// The source is only deleted once the copy has succeeded. `CopyObject` goes
+/// to `dst_bucket` through `client` and the `DeleteObject` to `src_bucket`
+/// through `src_client`, which differ when the buckets are in different regions.
+pub async fn move_object(
+    client: &Client,
+    src_client: &Client,
+    src_bucket: &str,
+    src_key: &str,
+    dst_bucket: &str,
+    dst_key: &str,
+    dry_run: bool,
+) -> Result<()> {
+    if src_bucket == dst_bucket && src_key == dst_key {
+        return Err(anyhow::anyhow!("Source and destination are the same object"));
+    }
+
+    if dry_run {
+        print_dry_run(&format!("CopyObject (to '{}/{}')", dst_bucket, dst_key), src_bucket, Some(src_key));
+        print_dry_run("DeleteObject", src_bucket, Some(src_key));
+        return Ok(());
+    }
+
+    // COPY keeps the content type and user metadata of the source
+    client.copy_object()
+        .copy_source(copy_source(src_bucket, src_key))
+        .bucket(dst_bucket)
+        .key(dst_key)
+        .metadata_directive(aws_sdk_s3::types::MetadataDirective::Copy)
+        .send()
+        .await
+        .map_err(|e| anyhow::Error::from(e).context(format!("Failed to copy '{}/{}'", src_bucket, src_key)))?;
+
+    src_client.delete_object()
+        .bucket(src_bucket)
+        .key(src_key)
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!(
+            "Copied '{}/{}' to '{}/{}', but the source could not be deleted and still exists: {}",
+            src_bucket, src_key, dst_bucket, dst_key, e
+        ))?;
+
+    success(format!("Moved '{}/{}' to '{}/{}'.", src_bucket, src_key, dst_bucket, dst_key));
+    Ok(())
+}
+
+/// Refuse a prefix rename whose prefixes overlap: if either contains the
+/// other, renamed keys would land back under the old prefix (or be renamed
+/// onto keys still waiting to be moved).
+pub fn check_rename_prefixes(old_prefix: &str, new_prefix: &str) -> Result<()> {
+    if old_prefix.starts_with(new_prefix) || new_prefix.starts_with(old_prefix) {
+        return Err(usage_error(format!(
+            "Prefixes '{}' and '{}' overlap; rename to a prefix outside the old one (or go through a temporary prefix)",
+            old_prefix, new_prefix
+        )));
+    }
+    Ok(())
+}
+
+/// Each key's new name: `old_prefix` swapped for `new_prefix`. Keys outside
+/// `old_prefix` are left out.
+pub fn rename_plan(keys: &[String], old_prefix: &str, new_prefix: &str) -> Vec<(String, String)> {
+    keys.iter()
+        .filter_map(|key| {
+            let rest = key.strip_prefix(old_prefix)?;
+            Some((key.clone(), format!("{}{}", new_prefix, rest)))
+        })
+        .collect()
+}
+
+/// Move every object under `old_prefix` to the same key under `new_prefix`
+/// within the bucket, up to `concurrency` at a time. Each object is copied
+/// (in parts when over 5 GiB) and its source deleted only once the copy has
+/// succeeded. A failed object does not stop the others; failures are listed
+/// at the end. `--dry-run` lists the keys and prints the mapping.
+pub async fn rename_prefix(
+    client: &Client,
+    bucket_name: &str,
+    old_prefix: &str,
+    new_prefix: &str,
+    concurrency: usize,
+    output: OutputFormat,
+    dry_run: bool,
+) -> Result<()> {
+    check_rename_prefixes(old_prefix, new_prefix)?;
+
+    let (objects, _) = fetch_objects(client, bucket_name, Some(old_prefix), None, None).await?;
+    let sizes = objects.iter()
+        .filter_map(|o| Some((o.key()?.to_string(), o.size().unwrap_or(0).max(0) as u64)))
+        .collect::<BTreeMap<_, _>>();
+    let keys = sizes.keys().cloned().collect::<Vec<_>>();
+    let plan = rename_plan(&keys, old_prefix, new_prefix);
+
+    if plan.is_empty() && output != OutputFormat::Json {
+        note(format!("No objects under '{}'.", old_prefix));
+        return Ok(());
+    }
+
+    if dry_run {
+        for (key, new_key) in &plan {
+            print_dry_run(&format!("CopyObject (to '{}') and DeleteObject", new_key), bucket_name, Some(key));
+        }
+        return Ok(());
+    }
+
+    let progress = BulkProgress::new(plan.len(), "objects", "Renaming", output)?;
+    let total = plan.len();
+
+    let failures: Vec<(String, String)> = stream::iter(plan)
+        .map(|(key, new_key)| {
+            let progress = &progress;
+            let size = sizes[&key];
+            async move {
+                let result = match progress.start_item(&key, None) {
+                    Ok(pb) => {
+                        let result = rename_one(client, bucket_name, &key, &new_key, size).await;
+                        progress.finish_item(&pb);
+                        result
+                    }
+                    Err(err) => Err(err),
+                };
+                result.err().map(|e| (key, e.to_string()))
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .filter_map(|failure| async move { failure })
+        .collect()
+        .await;
+
+    progress.finish();
+
+    let summary = BulkSummary::new("rename", total, failures);
+    let done = format!("Renamed {} from '{}' to '{}'.", pluralize(summary.succeeded, "object"), old_prefix, new_prefix);
+    summary.report(output, "objects", "rename", done)
+}
+
+/// Copy one object to its new key, then delete the old one.
+async fn rename_one(client: &Client, bucket_name: &str, key: &str, new_key: &str, size: u64) -> Result<()> {
+    if size > MAX_COPY_OBJECT_SIZE {
+        multipart_copy(client, client, bucket_name, key, bucket_name, new_key, size, None).await?;
+    } else {
+        client.copy_object()
+            .copy_source(copy_source(bucket_name, key))
+            .bucket(bucket_name)
+            .key(new_key)
+            .metadata_directive(aws_sdk_s3::types::MetadataDirective::Copy)
+            .send()
+            .await?;
+    }
+
+    client.delete_object()
+        .bucket(bucket_name)
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("copied to '{}', but the source could not be deleted and still exists: {}", new_key, e))?;
+    Ok(())
+}
+
+/// Change an object's content type and/or user metadata without re-uploading
+/// it: S3 only rewrites metadata through a copy onto itself with the REPLACE
+/// directive. Everything REPLACE would reset (the other content headers,
+/// storage class, encryption and ACL) is read first and carried over; user
+/// metadata is kept unless `metadata` is given.
+pub async fn set_object_metadata(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    headers: &ObjectHeaders,
+    metadata: Vec<(String, String)>,
+    dry_run: bool,
+) -> Result<()> {
+    if dry_run {
+        print_dry_run("HeadObject", bucket_name, Some(key));
+        print_dry_run("CopyObject (onto itself, metadata directive REPLACE)", bucket_name, Some(key));
+        return Ok(());
+    }
+
+    let head = client.head_object()
+        .bucket(bucket_name)
+        .key(key)
+        .send()
+        .await?;
+    let acl = client.get_object_acl()
+        .bucket(bucket_name)
+        .key(key)
+        .send()
+        .await?;
+
+    // An unparseable Expires header is dropped rather than copied verbatim
+    let expires = head.expires_string()
+        .and_then(|v| DateTime::from_str(v, DateTimeFormat::HttpDate).ok());
+    let metadata = if metadata.is_empty() {
+        head.metadata().cloned().unwrap_or_default().into_iter().collect()
+    } else {
+        metadata
+    };
+
+    let mut request = client.copy_object()
+        .copy_source(copy_source(bucket_name, key))
+        .bucket(bucket_name)
+        .key(key)
+        .metadata_directive(aws_sdk_s3::types::MetadataDirective::Replace)
+        .set_content_type(headers.content_type.as_deref().or(head.content_type()).map(|v| v.to_string()))
+        .set_cache_control(headers.cache_control.as_deref().or(head.cache_control()).map(|v| v.to_string()))
+        .set_content_disposition(headers.content_disposition.as_deref().or(head.content_disposition()).map(|v| v.to_string()))
+        .set_content_encoding(headers.content_encoding.as_deref().or(head.content_encoding()).map(|v| v.to_string()))
+        .set_expires(headers.expires.or(expires))
+        .set_content_language(head.content_language().map(|v| v.to_string()))
+        .set_storage_class(head.storage_class().cloned())
+        .set_server_side_encryption(head.server_side_encryption().cloned())
+        .set_ssekms_key_id(head.ssekms_key_id().map(|v| v.to_string()));
+    for (name, value) in metadata {
+        request = request.metadata(name, value);
+    }
+
+    request.send()
+        .await
+        .map_err(|e| anyhow::Error::from(e).context(format!("Failed to rewrite metadata of '{}'", key)))?;
+
+    // The copy gets a private ACL; put the original grants back
+    let policy = aws_sdk_s3::types::AccessControlPolicy::builder()
+        .set_owner(acl.owner().cloned())
+        .set_grants(Some(acl.grants().to_vec()))
+        .build();
+    let restored = client.put_object_acl()
+        .bucket(bucket_name)
+        .key(key)
+        .access_control_policy(policy)
+        .send()
+        .await;
+    if let Err(err) = restored {
+        // Buckets with ACLs disabled (BucketOwnerEnforced) have nothing to restore
+        if err.code() != Some("AccessControlListNotSupported") {
+            return Err(anyhow::Error::from(err).context(format!("Metadata of '{}' updated, but its ACL could not be restored", key)));
+        }
+    }
+
+    success(format!("Metadata of '{}' updated.", key));
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct VersionInfo {
     key: String,
-    size: i64,
+    version_id: String,
+    latest: bool,
+    kind: String,
+    size: String,
     last_modified: String,
 }
 
-pub async fn list_objects(client: &Client, bucket_name: &str) -> Result<()> {
-    let resp = client.list_objects_v2().bucket(bucket_name).send().await?;
-    
-    let mut object_infos = Vec::new();
+pub async fn list_object_versions(client: &Client, bucket_name: &str, prefix: Option<&str>) -> Result<()> {
+    let mut version_infos = Vec::new();
+    let mut key_marker: Option<String> = None;
+    let mut version_id_marker: Option<String> = None;
 
-    for object in resp.contents() {
-        let key = object.key().unwrap_or("<unknown>").to_string();
-        let size = object.size().unwrap_or(0);
-        let last_modified = object.last_modified()
-            .map(|d| d.to_string())
-            .unwrap_or_else(|| "Unknown".to_string());
+    loop {
+        let resp = client.list_object_versions()
+            .bucket(bucket_name)
+            .set_prefix(prefix.map(|p| p.to_string()))
+            .set_key_marker(key_marker.take())
+            .set_version_id_marker(version_id_marker.take())
+            .send()
+            .await?;
+
+        for v in resp.versions() {
+            version_infos.push(VersionInfo {
+                key: v.key().unwrap_or("<unknown>").to_string(),
+                version_id: v.version_id().unwrap_or("null").to_string(),
+                latest: v.is_latest().unwrap_or(false),
+                kind: "Version".to_string(),
+                size: v.size().unwrap_or(0).to_string(),
+                last_modified: v.last_modified()
+                    .map(|d| d.to_string())
+                    .unwrap_or_else(|| "Unknown".to_string()),
+            });
+        }
 
-        object_infos.push(ObjectInfo { key, size, last_modified });
+        for m in resp.delete_markers() {
+            version_infos.push(VersionInfo {
+                key: m.key().unwrap_or("<unknown>").to_string(),
+                version_id: m.version_id().unwrap_or("null").to_string(),
+                latest: m.is_latest().unwrap_or(false),
+                kind: "Delete Marker".to_string(),
+                size: "-".to_string(),
+                last_modified: m.last_modified()
+                    .map(|d| d.to_string())
+                    .unwrap_or_else(|| "Unknown".to_string()),
+            });
+        }
+
+        if !resp.is_truncated().unwrap_or(false) {
+            break;
+        }
+        key_marker = resp.next_key_marker().map(|s| s.to_string());
+        version_id_marker = resp.next_version_id_marker().map(|s| s.to_string());
     }
 
-    if object_infos.is_empty() {
-        println!("{}", "No objects found.".yellow());
+    if version_infos.is_empty() {
+        note("No object versions found.");
     } else {
-        let table = Table::new(object_infos).to_string();
+        // Versions and delete markers come back in separate lists; show them together per key
+        version_infos.sort_by(|a, b| a.key.cmp(&b.key).then(b.last_modified.cmp(&a.last_modified)));
+        let table = Table::new(version_infos).to_string();
         println!("{}", table);
     }
 
     Ok(())
 }
 
-pub async fn upload_object(client: &Client, bucket_name: &str, file_path: &str, key: Option<String>) -> Result<()> {
-    let path = Path::new(file_path);
-    let file_name = path.file_name().ok_or_else(|| anyhow::anyhow!("Invalid file path"))?.to_str().unwrap();
-    let object_key = key.unwrap_or_else(|| file_name.to_string());
+pub fn parse_tier(tier: &str) -> Result<aws_sdk_s3::types::Tier> {
+    match tier.to_ascii_lowercase().as_str() {
+        "standard" => Ok(aws_sdk_s3::types::Tier::Standard),
+        "bulk" => Ok(aws_sdk_s3::types::Tier::Bulk),
+        "expedited" => Ok(aws_sdk_s3::types::Tier::Expedited),
+        _ => Err(usage_error(format!("Invalid restore tier: {}. Use 'Standard', 'Bulk' or 'Expedited'", tier))),
+    }
+}
+
+fn restore_request(days: i32, tier: &aws_sdk_s3::types::Tier) -> Result<aws_sdk_s3::types::RestoreRequest> {
+    Ok(aws_sdk_s3::types::RestoreRequest::builder()
+        .days(days)
+        .glacier_job_parameters(
+            aws_sdk_s3::types::GlacierJobParameters::builder()
+                .tier(tier.clone())
+                .build()?
+        )
+        .build())
+}
 
-    let body = ByteStream::from_path(path).await?;
-    let content_type = mime_guess::from_path(path).first_or_octet_stream();
+/// Send a restore request for `key`. Returns false if one is already in
+/// progress.
+async fn request_restore(client: &Client, bucket_name: &str, key: &str, request: &aws_sdk_s3::types::RestoreRequest) -> Result<bool> {
+    let resp = crate::client::retry_transient("RestoreObject", || {
+        client.restore_object()
+            .bucket(bucket_name)
+            .key(key)
+            .restore_request(request.clone())
+            .send()
+    }).await;
 
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(ProgressStyle::default_spinner()
-        .template("{spinner:.green} Uploading {msg}...")?
-        .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏"));
-    pb.set_message(object_key.clone());
-    pb.enable_steady_tick(std::time::Duration::from_millis(100));
+    match resp {
+        Ok(_) => Ok(true),
+        Err(err) if err.code() == Some("RestoreAlreadyInProgress") => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
 
-    client.put_object()
+pub async fn restore_object(client: &Client, bucket_name: &str, key: &str, days: i32, tier: &str, dry_run: bool) -> Result<()> {
+    let tier = parse_tier(tier)?;
+    let request = restore_request(days, &tier)?;
+
+    if dry_run {
+        print_dry_run(&format!("RestoreObject ({} tier, {} days)", tier.as_str(), days), bucket_name, Some(key));
+        return Ok(());
+    }
+
+    if !request_restore(client, bucket_name, key, &request).await? {
+        note(format!("A restore is already in progress for '{}'. Check its status with 'object attributes'.", key));
+        return Ok(());
+    }
+
+    success(format!("Restore request initiated for '{}' ({} tier, {} days).", key, tier.as_str(), days));
+    Ok(())
+}
+
+/// Storage classes `object restore --recursive` restores by default: the
+/// ones whose objects must be restored before they can be read.
+pub const ARCHIVED_STORAGE_CLASSES: [&str; 2] = ["GLACIER", "DEEP_ARCHIVE"];
+
+/// Objects under `prefix` to restore: those in one of `storage_classes`,
+/// split into keys to request and keys already being restored.
+pub fn restore_candidates(objects: &[aws_sdk_s3::types::Object], storage_classes: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut restore = Vec::new();
+    let mut in_progress = Vec::new();
+    for object in objects {
+        let class = object.storage_class().map_or("STANDARD", |c| c.as_str());
+        if !storage_classes.iter().any(|s| s.eq_ignore_ascii_case(class)) {
+            continue;
+        }
+        let Some(key) = object.key() else { continue };
+        if object.restore_status().and_then(|s| s.is_restore_in_progress()) == Some(true) {
+            in_progress.push(key.to_string());
+        } else {
+            restore.push(key.to_string());
+        }
+    }
+    (restore, in_progress)
+}
+
+/// Request a restore of every archived object under `prefix`, up to
+/// `concurrency` at a time. Objects in other storage classes are left
+/// alone, and objects already being restored are skipped.
+#[allow(clippy::too_many_arguments)]
+pub async fn restore_prefix(
+    client: &Client,
+    bucket_name: &str,
+    prefix: &str,
+    days: i32,
+    tier: &str,
+    storage_classes: &[String],
+    concurrency: usize,
+    output: OutputFormat,
+    dry_run: bool,
+) -> Result<()> {
+    let tier = parse_tier(tier)?;
+    let request = restore_request(days, &tier)?;
+    for class in storage_classes {
+        parse_storage_class(class)?;
+    }
+
+    if dry_run {
+        print_dry_run(&format!("ListObjectsV2 (prefix '{}')", prefix), bucket_name, None);
+        print_dry_run(&format!("RestoreObject (each {} key)", storage_classes.join(" or ")), bucket_name, None);
+        return Ok(());
+    }
+
+    // Ask for each object's restore status so in-progress ones can be skipped
+    let mut objects = Vec::new();
+    let mut continuation_token = None;
+    loop {
+        let resp = client.list_objects_v2()
+            .bucket(bucket_name)
+            .prefix(prefix)
+            .optional_object_attributes(aws_sdk_s3::types::OptionalObjectAttributes::RestoreStatus)
+            .set_continuation_token(continuation_token)
+            .send()
+            .await?;
+
+        objects.extend(resp.contents().iter().cloned());
+
+        match resp.next_continuation_token() {
+            Some(token) if resp.is_truncated() == Some(true) => continuation_token = Some(token.to_string()),
+            _ => break,
+        }
+    }
+
+    let (keys, mut skipped) = restore_candidates(&objects, storage_classes);
+    if keys.is_empty() && skipped.is_empty() && output != OutputFormat::Json {
+        note(format!("No {} objects under '{}'.", storage_classes.join(" or "), prefix));
+        return Ok(());
+    }
+
+    let progress = BulkProgress::new(keys.len(), "objects", "Restoring", output)?;
+    let results: Vec<(String, Result<bool>)> = stream::iter(keys)
+        .map(|key| {
+            let (progress, request) = (&progress, &request);
+            async move {
+                let result = request_restore(client, bucket_name, &key, request).await;
+                progress.inc(1);
+                (key, result)
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+    progress.finish();
+
+    let mut failures = Vec::new();
+    let mut initiated = 0;
+    for (key, result) in results {
+        match result {
+            Ok(true) => initiated += 1,
+            // Started since the listing
+            Ok(false) => skipped.push(key),
+            Err(err) => failures.push((key, err.to_string())),
+        }
+    }
+    skipped.sort();
+
+    let summary = BulkSummary::new("restore", initiated + failures.len(), failures);
+    match output {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "restore": summary, "skipped": skipped }))?);
+            if !summary.failed.is_empty() {
+                return Err(anyhow::anyhow!("{} of {} objects failed to restore", summary.failed.len(), summary.total));
+            }
+            Ok(())
+        }
+        OutputFormat::Table | OutputFormat::Csv => {
+            if !skipped.is_empty() {
+                note(format!("Skipped {}: a restore is already in progress.", pluralize(skipped.len(), "object")));
+            }
+            let done = format!(
+                "Initiated {} under '{}' ({} tier, {} days).",
+                pluralize(initiated, "restore"), prefix, tier.as_str(), days
+            );
+            summary.report(output, "objects", "restore", done)
+        }
+    }
+}
+
+/// Show an object's attributes. Uses `GetObjectAttributes` by default, which
+/// reports multipart parts; `head` falls back to a lighter `HeadObject` that
+/// also shows content type, restore and Object Lock status. `all` prints
+/// every `HeadObject` response header instead, as JSON with `output`.
+pub async fn get_object_attributes(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    head: bool,
+    all: bool,
+    output: OutputFormat,
+) -> Result<()> {
+    if all {
+        let headers = object_headers(client, bucket_name, key).await?;
+        match output {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&headers)?),
+            OutputFormat::Table | OutputFormat::Csv => print!("{}", format_object_headers(key, &headers)),
+        }
+        return Ok(());
+    }
+
+    if head {
+        let resp = client.head_object()
+            .bucket(bucket_name)
+            .key(key)
+            .checksum_mode(aws_sdk_s3::types::ChecksumMode::Enabled)
+            .send()
+            .await?;
+
+        print!("{}", format_object_attributes(key, &resp));
+        return Ok(());
+    }
+
+    let resp = client.get_object_attributes()
         .bucket(bucket_name)
-        .key(&object_key)
-        .body(body)
-        .content_type(content_type.to_string())
+        .key(key)
+        .object_attributes(aws_sdk_s3::types::ObjectAttributes::Etag)
+        .object_attributes(aws_sdk_s3::types::ObjectAttributes::Checksum)
+        .object_attributes(aws_sdk_s3::types::ObjectAttributes::ObjectParts)
+        .object_attributes(aws_sdk_s3::types::ObjectAttributes::StorageClass)
+        .object_attributes(aws_sdk_s3::types::ObjectAttributes::ObjectSize)
         .send()
         .await?;
 
-    pb.finish_with_message("Upload complete");
-    println!("{} Object '{}' uploaded to '{}'.", "✔".green(), object_key, bucket_name);
+    print!("{}", format_get_object_attributes(key, &resp));
 
     Ok(())
 }
 
-pub async fn delete_object(client: &Client, bucket_name: &str, key: &str) -> Result<()> {
-    client.delete_object()
+/// Records the raw response headers of the operation it is attached to.
+#[derive(Debug, Clone, Default)]
+struct CaptureHeaders(Arc<Mutex<BTreeMap<String, String>>>);
+
+impl Intercept for CaptureHeaders {
+    fn name(&self) -> &'static str {
+        "CaptureHeaders"
+    }
+
+    fn read_before_deserialization(
+        &self,
+        context: &BeforeDeserializationInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let mut headers = self.0.lock().unwrap();
+        headers.clear();
+        for (name, value) in context.response().headers() {
+            // Repeated headers are joined, as HTTP allows
+            headers.entry(name.to_string())
+                .and_modify(|existing| {
+                    existing.push_str(", ");
+                    existing.push_str(value);
+                })
+                .or_insert_with(|| value.to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Every header of the `HeadObject` response for `key`, by lowercase name:
+/// user metadata (`x-amz-meta-*`), content headers, version id, encryption
+/// and so on, exactly as S3 sent them.
+pub async fn object_headers(client: &Client, bucket_name: &str, key: &str) -> Result<BTreeMap<String, String>> {
+    let capture = CaptureHeaders::default();
+    client.head_object()
         .bucket(bucket_name)
         .key(key)
+        .checksum_mode(aws_sdk_s3::types::ChecksumMode::Enabled)
+        .customize()
+        .interceptor(capture.clone())
         .send()
         .await?;
 
-    println!("{} Object '{}' deleted from '{}'.", "✔".green(), key, bucket_name);
+    let headers = capture.0.lock().unwrap().clone();
+    Ok(headers)
+}
+
+/// Render raw response headers as `name: value` lines, sorted by name.
+pub fn format_object_headers(key: &str, headers: &BTreeMap<String, String>) -> String {
+    let mut out = format!("Object: {}\n", key.bold());
+    for (name, value) in headers {
+        out.push_str(&format!("{}: {}\n", name, value));
+    }
+    out
+}
+
+/// Render the attributes of a `get_object_attributes` response, one per line.
+pub fn format_get_object_attributes(key: &str, attrs: &GetObjectAttributesOutput) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("Object: {}\n", key.bold()));
+    out.push_str(&format!("Size: {} bytes\n", attrs.object_size().unwrap_or(0)));
+    out.push_str(&format!("ETag: {}\n", attrs.e_tag().unwrap_or("unknown")));
+    out.push_str(&format!("Last Modified: {}\n", attrs.last_modified().map(|d| d.to_string()).unwrap_or_else(|| "Unknown".to_string())));
+    out.push_str(&format!("Storage Class: {}\n", attrs.storage_class().map(|c| c.as_str()).unwrap_or("STANDARD")));
+    // Only multipart uploads report parts
+    if let Some(parts) = attrs.object_parts().and_then(|p| p.total_parts_count()) {
+        out.push_str(&format!("Parts: {}\n", parts));
+    }
+    if let Some(checksum) = attrs.checksum() {
+        push_checksums(&mut out, [
+            ("CRC32", checksum.checksum_crc32()),
+            ("CRC32C", checksum.checksum_crc32_c()),
+            ("SHA1", checksum.checksum_sha1()),
+            ("SHA256", checksum.checksum_sha256()),
+        ]);
+    }
+    out
+}
+
+/// Base64 of the `algorithm` checksum of everything `reader` yields, the
+/// format S3 reports checksums in.
+pub async fn compute_checksum<R: tokio::io::AsyncRead + Unpin>(
+    reader: &mut R,
+    algorithm: &aws_sdk_s3::types::ChecksumAlgorithm,
+) -> Result<String> {
+    use aws_sdk_s3::types::ChecksumAlgorithm;
+    use tokio::io::AsyncReadExt;
+
+    let mut checksum = match algorithm {
+        ChecksumAlgorithm::Crc32 => aws_smithy_checksums::ChecksumAlgorithm::Crc32,
+        ChecksumAlgorithm::Crc32C => aws_smithy_checksums::ChecksumAlgorithm::Crc32c,
+        ChecksumAlgorithm::Sha1 => aws_smithy_checksums::ChecksumAlgorithm::Sha1,
+        ChecksumAlgorithm::Sha256 => aws_smithy_checksums::ChecksumAlgorithm::Sha256,
+        other => return Err(usage_error(format!("Unsupported checksum algorithm: {}", other.as_str()))),
+    }.into_impl();
+
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        checksum.update(&buf[..n]);
+    }
+    Ok(aws_smithy_types::base64::encode(checksum.finalize()))
+}
+
+/// Decode a checksum given as hex or as base64 (as S3 shows it), checking
+/// it has the length of an `algorithm` digest.
+pub fn decode_checksum(value: &str, algorithm: &aws_sdk_s3::types::ChecksumAlgorithm) -> Result<Vec<u8>> {
+    use aws_sdk_s3::types::ChecksumAlgorithm;
+
+    let value = value.trim();
+    let bytes = if value.len().is_multiple_of(2) && value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        (0..value.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&value[i..i + 2], 16))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        aws_smithy_types::base64::decode(value)
+            .map_err(|_| usage_error(format!("Invalid checksum '{}'. Give it as hex or base64", value)))?
+    };
+
+    let expected_len = match algorithm {
+        ChecksumAlgorithm::Sha1 => 20,
+        ChecksumAlgorithm::Sha256 => 32,
+        _ => 4,
+    };
+    if bytes.len() != expected_len {
+        return Err(usage_error(format!(
+            "Invalid checksum '{}': a {} checksum is {} bytes, not {}",
+            value, algorithm.as_str(), expected_len, bytes.len()
+        )));
+    }
+    Ok(bytes)
+}
+
+/// The full-object checksum S3 stored for `algorithm`, if any. Checksums of
+/// multipart uploads that combine the parts' checksums (`...-3`) don't count.
+fn stored_checksum<'a>(attrs: &'a GetObjectAttributesOutput, algorithm: &aws_sdk_s3::types::ChecksumAlgorithm) -> Option<&'a str> {
+    use aws_sdk_s3::types::ChecksumAlgorithm;
+
+    let checksum = attrs.checksum()?;
+    let value = match algorithm {
+        ChecksumAlgorithm::Crc32 => checksum.checksum_crc32(),
+        ChecksumAlgorithm::Crc32C => checksum.checksum_crc32_c(),
+        ChecksumAlgorithm::Sha1 => checksum.checksum_sha1(),
+        ChecksumAlgorithm::Sha256 => checksum.checksum_sha256(),
+        _ => None,
+    }?;
+    (!value.contains('-')).then_some(value)
+}
+
+/// Check an object against an `expected` checksum (hex or base64), using
+/// the checksum S3 stored for it, or by downloading the object and computing
+/// it when none is stored or `download` is set. A mismatch is an error.
+pub async fn verify_object(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    expected: &str,
+    algorithm: &str,
+    download: bool,
+) -> Result<()> {
+    let algorithm = parse_checksum_algorithm(algorithm)?;
+    let expected_bytes = decode_checksum(expected, &algorithm)?;
+    let name = algorithm.as_str();
+
+    let stored = if download {
+        None
+    } else {
+        let attrs = client.get_object_attributes()
+            .bucket(bucket_name)
+            .key(key)
+            .object_attributes(aws_sdk_s3::types::ObjectAttributes::Checksum)
+            .send()
+            .await?;
+        let stored = stored_checksum(&attrs, &algorithm).map(str::to_string);
+        if stored.is_none() {
+            note(format!("No {} checksum is stored for '{}'; downloading it to compute one.", name, key));
+        }
+        stored
+    };
+
+    let (actual, source) = match stored {
+        Some(stored) => (stored, "stored checksum"),
+        None => {
+            let resp = get_object_range(client, bucket_name, key, None).await?;
+            let mut body = resp.body.into_async_read();
+            (compute_checksum(&mut body, &algorithm).await?, "computed from the object")
+        }
+    };
+
+    if aws_smithy_types::base64::decode(&actual).ok().as_deref() == Some(expected_bytes.as_slice()) {
+        success(format!("OK: '{}' matches the expected {} checksum ({}).", key, name, source));
+        return Ok(());
+    }
+
+    let expected_b64 = aws_smithy_types::base64::encode(&expected_bytes);
+    Err(anyhow::anyhow!(
+        "{} checksum mismatch for '{}': expected {}, got {} ({})",
+        name, key, expected_b64, actual, source
+    ))
+}
+
+/// Render the attributes of a `head_object` response, one per line.
+pub fn format_object_attributes(key: &str, head: &HeadObjectOutput) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("Object: {}\n", key.bold()));
+    out.push_str(&format!("Size: {} bytes\n", head.content_length().unwrap_or(0)));
+    out.push_str(&format!("Content Type: {}\n", head.content_type().unwrap_or("unknown")));
+    out.push_str(&format!("Last Modified: {}\n", head.last_modified().map(|d| d.to_string()).unwrap_or_else(|| "Unknown".to_string())));
+    // S3 omits the storage class header for STANDARD objects
+    out.push_str(&format!("Storage Class: {}\n", head.storage_class().map(|c| c.as_str()).unwrap_or("STANDARD")));
+    if let Some(restore) = head.restore() {
+        out.push_str(&format!("Restore: {}\n", describe_restore_status(restore)));
+    }
+    if let Some(mode) = head.object_lock_mode() {
+        let until = head.object_lock_retain_until_date()
+            .map(|d| d.to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+        out.push_str(&format!("Retention: {} until {}\n", mode.as_str(), until));
+    }
+    if let Some(hold) = head.object_lock_legal_hold_status() {
+        out.push_str(&format!("Legal Hold: {}\n", hold.as_str()));
+    }
+    push_checksums(&mut out, [
+        ("CRC32", head.checksum_crc32()),
+        ("CRC32C", head.checksum_crc32_c()),
+        ("SHA1", head.checksum_sha1()),
+        ("SHA256", head.checksum_sha256()),
+    ]);
+    out
+}
+
+fn push_checksums(out: &mut String, checksums: [(&str, Option<&str>); 4]) {
+    for (name, value) in checksums {
+        if let Some(value) = value {
+            out.push_str(&format!("Checksum ({}): {}\n", name, value));
+        }
+    }
+}
+
+/// Poll `HeadObject` until the restore of `key` has finished, giving up after `timeout`.
+pub async fn wait_for_restore(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    poll_interval: std::time::Duration,
+    timeout: std::time::Duration,
+) -> Result<()> {
+    let pb = ProgressBar::with_draw_target(None, progress_target());
+    pb.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.green} Waiting for {msg} to be restored... [{elapsed_precise}]")?
+            .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏")
+    );
+    pb.set_message(key.to_string());
+    pb.enable_steady_tick(std::time::Duration::from_millis(100));
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let head = client.head_object()
+            .bucket(bucket_name)
+            .key(key)
+            .send()
+            .await;
+        let head = match head {
+            Ok(head) => head,
+            Err(err) => {
+                pb.finish_and_clear();
+                return Err(err.into());
+            }
+        };
+
+        match head.restore().map(|h| restore_header_field(h, "ongoing-request")) {
+            Some(Some("false")) => {
+                pb.finish_and_clear();
+                success(format!("'{}' is restored and ready to download.", key));
+                return Ok(());
+            }
+            Some(_) => {}
+            None => {
+                pb.finish_and_clear();
+                return Err(anyhow::anyhow!("No restore has been requested for '{}'", key));
+            }
+        }
+
+        let now = tokio::time::Instant::now();
+        if now >= deadline {
+            pb.finish_and_clear();
+            return Err(anyhow::anyhow!(
+                "Timed out after {}s waiting for '{}' to be restored; the restore is still in progress",
+                timeout.as_secs(),
+                key
+            ));
+        }
+        tokio::time::sleep(poll_interval.min(deadline - now)).await;
+    }
+}
+
+/// Describe an `x-amz-restore` header value, e.g.
+/// `ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT"`.
+pub fn describe_restore_status(header: &str) -> String {
+    let ongoing = restore_header_field(header, "ongoing-request");
+    let expiry = restore_header_field(header, "expiry-date");
+
+    match (ongoing, expiry) {
+        (Some("true"), _) => "In progress".yellow().to_string(),
+        (_, Some(date)) => format!("{} (restored copy expires {})", "Completed".green(), date),
+        (Some("false"), None) => "Completed".green().to_string(),
+        _ => header.to_string(),
+    }
+}
+
+fn restore_header_field<'a>(header: &'a str, name: &str) -> Option<&'a str> {
+    let start = header.find(&format!("{}=\"", name))? + name.len() + 2;
+    let len = header[start..].find('"')?;
+    Some(&header[start..start + len])
+}
+
+pub fn parse_retention_mode(mode: &str) -> Result<aws_sdk_s3::types::ObjectLockRetentionMode> {
+    match mode.to_ascii_uppercase().as_str() {
+        "GOVERNANCE" => Ok(aws_sdk_s3::types::ObjectLockRetentionMode::Governance),
+        "COMPLIANCE" => Ok(aws_sdk_s3::types::ObjectLockRetentionMode::Compliance),
+        _ => Err(usage_error(format!("Invalid retention mode: {}. Use 'GOVERNANCE' or 'COMPLIANCE'", mode))),
+    }
+}
+
+pub fn parse_legal_hold(state: &str) -> Result<aws_sdk_s3::types::ObjectLockLegalHoldStatus> {
+    match state.to_ascii_lowercase().as_str() {
+        "on" => Ok(aws_sdk_s3::types::ObjectLockLegalHoldStatus::On),
+        "off" => Ok(aws_sdk_s3::types::ObjectLockLegalHoldStatus::Off),
+        _ => Err(usage_error(format!("Invalid legal hold state: {}. Use 'on' or 'off'", state))),
+    }
+}
+
+/// Replace the raw error S3 returns when Object Lock is not enabled on the bucket.
+fn object_lock_error<E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static>(err: E, bucket_name: &str) -> anyhow::Error {
+    let lock_missing = err.code() == Some("ObjectLockConfigurationNotFoundError")
+        || (err.code() == Some("InvalidRequest") && err.message().is_some_and(|m| m.contains("Object Lock")));
+    if lock_missing {
+        anyhow::anyhow!("Object Lock is not enabled on bucket '{}'. It can only be enabled when the bucket is created", bucket_name)
+    } else {
+        err.into()
+    }
+}
+
+pub async fn set_object_retention(client: &Client, bucket_name: &str, key: &str, mode: &str, retain_until: &str, dry_run: bool) -> Result<()> {
+    let mode = parse_retention_mode(mode)?;
+    let until = DateTime::from_str(retain_until, DateTimeFormat::DateTimeWithOffset)
+        .map_err(|e| usage_error(format!("Invalid retain-until date '{}' (expected RFC3339, e.g. 2030-01-01T00:00:00Z): {}", retain_until, e)))?;
+
+    if dry_run {
+        print_dry_run("PutObjectRetention", bucket_name, Some(key));
+        return Ok(());
+    }
+
+    client.put_object_retention()
+        .bucket(bucket_name)
+        .key(key)
+        .retention(
+            aws_sdk_s3::types::ObjectLockRetention::builder()
+                .mode(mode.clone())
+                .retain_until_date(until)
+                .build()
+        )
+        .send()
+        .await
+        .map_err(|e| object_lock_error(e, bucket_name))?;
+
+    success(format!("Retention for '{}' set to {} until {}.", key, mode.as_str().cyan(), until));
     Ok(())
 }
 
-pub async fn restore_object(client: &Client, bucket_name: &str, key: &str) -> Result<()> {
-     client.restore_object()
+pub async fn set_object_legal_hold(client: &Client, bucket_name: &str, key: &str, state: &str, dry_run: bool) -> Result<()> {
+    let status = parse_legal_hold(state)?;
+
+    if dry_run {
+        print_dry_run("PutObjectLegalHold", bucket_name, Some(key));
+        return Ok(());
+    }
+
+    client.put_object_legal_hold()
         .bucket(bucket_name)
         .key(key)
-        .restore_request(
-            aws_sdk_s3::types::RestoreRequest::builder()
-                .days(1)
-                .glacier_job_parameters(
-                    aws_sdk_s3::types::GlacierJobParameters::builder()
-                        .tier(aws_sdk_s3::types::Tier::Standard)
-                        .build()?
-                )
+        .legal_hold(
+            aws_sdk_s3::types::ObjectLockLegalHold::builder()
+                .status(status.clone())
                 .build()
         )
         .send()
-        .await?;
+        .await
+        .map_err(|e| object_lock_error(e, bucket_name))?;
+
+    success(format!("Legal hold for '{}' set to {}.", key, status.as_str().cyan()));
+    Ok(())
+}
+
+/// Print an object's contents to stdout. Nothing else is written to stdout so
+/// the output can be piped.
+pub async fn cat_object(client: &Client, bucket_name: &str, key: &str, range: Option<ByteRange>) -> Result<()> {
+    let mut stdout = tokio::io::stdout();
+    stream_object(client, bucket_name, key, range, &mut stdout).await
+}
+
+/// Stream an object's body (or just `range` of it) into `writer` chunk by
+/// chunk. The received `Content-Range` goes to stderr, keeping `writer` clean.
+pub async fn stream_object<W: AsyncWrite + Unpin>(client: &Client, bucket_name: &str, key: &str, range: Option<ByteRange>, writer: &mut W) -> Result<()> {
+    let resp = get_object_range(client, bucket_name, key, range).await?;
+
+    if let Some(content_range) = resp.content_range()
+        && !is_quiet()
+    {
+        eprintln!("{}", format!("Received {}", content_range).yellow());
+    }
+
+    if let Some(content_type) = resp.content_type()
+        && is_binary_content_type(content_type)
+    {
+        eprintln!("{} '{}' looks like binary content ({}).", "⚠".yellow(), key, content_type);
+    }
+
+    let mut body = resp.body;
+    while let Some(chunk) = body.try_next().await? {
+        writer.write_all(&chunk).await?;
+    }
+    writer.flush().await?;
 
-    println!("{} Restore request initiated for '{}'.", "✔".green(), key);
     Ok(())
 }
 
-pub async fn get_object_attributes(client: &Client, bucket_name: &str, key: &str) -> Result<()> {
-    let resp = client.head_object()
+/// Build the S3 Select input serialization for `--input-format` (csv or
+/// json) and `--compression` (none or gzip). CSV files are read with their
+/// first line as column names; JSON is read as one object per line.
+pub fn select_input_serialization(format: &str, compression: &str) -> Result<aws_sdk_s3::types::InputSerialization> {
+    let compression_type = match compression.to_ascii_lowercase().as_str() {
+        "none" => aws_sdk_s3::types::CompressionType::None,
+        "gzip" => aws_sdk_s3::types::CompressionType::Gzip,
+        _ => return Err(usage_error(format!("Invalid compression: {}. Use 'none' or 'gzip'", compression))),
+    };
+
+    let builder = aws_sdk_s3::types::InputSerialization::builder().compression_type(compression_type);
+    let builder = match format.to_ascii_lowercase().as_str() {
+        "csv" => builder.csv(
+            aws_sdk_s3::types::CsvInput::builder()
+                .file_header_info(aws_sdk_s3::types::FileHeaderInfo::Use)
+                .build(),
+        ),
+        "json" => builder.json(
+            aws_sdk_s3::types::JsonInput::builder()
+                .r#type(aws_sdk_s3::types::JsonType::Lines)
+                .build(),
+        ),
+        _ => return Err(usage_error(format!("Invalid input format: {}. Use 'csv' or 'json'", format))),
+    };
+    Ok(builder.build())
+}
+
+/// Build the S3 Select output serialization for `--output-format` (csv or
+/// json), one record per line.
+pub fn select_output_serialization(format: &str) -> Result<aws_sdk_s3::types::OutputSerialization> {
+    let builder = aws_sdk_s3::types::OutputSerialization::builder();
+    let builder = match format.to_ascii_lowercase().as_str() {
+        "csv" => builder.csv(aws_sdk_s3::types::CsvOutput::builder().record_delimiter("\n").build()),
+        "json" => builder.json(aws_sdk_s3::types::JsonOutput::builder().record_delimiter("\n").build()),
+        _ => return Err(usage_error(format!("Invalid output format: {}. Use 'csv' or 'json'", format))),
+    };
+    Ok(builder.build())
+}
+
+/// Run an S3 Select SQL query against an object and print the matching
+/// records to stdout.
+pub async fn select_object(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    query: &str,
+    input: aws_sdk_s3::types::InputSerialization,
+    output: aws_sdk_s3::types::OutputSerialization,
+) -> Result<()> {
+    let mut stdout = tokio::io::stdout();
+    stream_select(client, bucket_name, key, query, input, output, &mut stdout).await
+}
+
+/// Run an S3 Select query and write each `Records` event into `writer` as
+/// it arrives. Fails if the stream stops before S3 sends its `End` event,
+/// since the output would then be incomplete.
+pub async fn stream_select<W: AsyncWrite + Unpin>(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    query: &str,
+    input: aws_sdk_s3::types::InputSerialization,
+    output: aws_sdk_s3::types::OutputSerialization,
+    writer: &mut W,
+) -> Result<()> {
+    use aws_sdk_s3::types::SelectObjectContentEventStream;
+
+    let mut resp = client.select_object_content()
         .bucket(bucket_name)
         .key(key)
+        .expression(query)
+        .expression_type(aws_sdk_s3::types::ExpressionType::Sql)
+        .input_serialization(input)
+        .output_serialization(output)
         .send()
         .await?;
 
-    println!("Object: {}", key.bold());
-    println!("Size: {} bytes", resp.content_length().unwrap_or(0));
-    println!("Content Type: {}", resp.content_type().unwrap_or("unknown"));
-    println!("Last Modified: {}", resp.last_modified().map(|d| d.to_string()).unwrap_or_else(|| "Unknown".to_string()));
-    
+    let mut complete = false;
+    while let Some(event) = resp.payload.recv().await? {
+        match event {
+            SelectObjectContentEventStream::Records(records) => {
+                if let Some(payload) = records.payload() {
+                    writer.write_all(payload.as_ref()).await?;
+                }
+            }
+            SelectObjectContentEventStream::Stats(stats) => {
+                if let Some(details) = stats.details() {
+                    tracing::debug!(
+                        scanned = details.bytes_scanned(),
+                        processed = details.bytes_processed(),
+                        returned = details.bytes_returned(),
+                        "S3 Select finished"
+                    );
+                }
+            }
+            SelectObjectContentEventStream::End(_) => complete = true,
+            _ => {}
+        }
+    }
+    writer.flush().await?;
+
+    if !complete {
+        return Err(anyhow::anyhow!("S3 Select response for '{}' ended early; the output is incomplete", key));
+    }
     Ok(())
 }
+
+fn is_binary_content_type(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    if essence.starts_with("text/") {
+        return false;
+    }
+    !matches!(
+        essence.as_str(),
+        "application/json" | "application/xml" | "application/javascript" | "application/x-yaml" | "application/yaml" | "application/x-sh" | "image/svg+xml" | ""
+    ) && !essence.ends_with("+json") && !essence.ends_with("+xml")
+}
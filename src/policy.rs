@@ -0,0 +1,235 @@
+use anyhow::Result;
+use crate::ui::{success, OutputFormat};
+use colored::*;
+use serde_json::Value;
+
+/// Policy language versions IAM accepts.
+const POLICY_VERSIONS: [&str; 2] = ["2012-10-17", "2008-10-17"];
+
+/// Top-level keys a policy document may have.
+const POLICY_KEYS: [&str; 3] = ["Version", "Id", "Statement"];
+
+/// Keys a statement may have.
+const STATEMENT_KEYS: [&str; 9] = [
+    "Sid", "Effect", "Principal", "NotPrincipal", "Action", "NotAction", "Resource", "NotResource", "Condition",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// S3 would reject the policy
+    Error,
+    /// The policy is accepted but probably not what was meant
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PolicyIssue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl PolicyIssue {
+    fn error(message: impl Into<String>) -> PolicyIssue {
+        PolicyIssue { severity: Severity::Error, message: message.into() }
+    }
+
+    fn warning(message: impl Into<String>) -> PolicyIssue {
+        PolicyIssue { severity: Severity::Warning, message: message.into() }
+    }
+}
+
+/// Check a bucket policy document locally: the JSON parses, it has a
+/// `Version` and `Statement`, and every statement has an `Effect`,
+/// `Principal`, `Action` and `Resource`. Statements that open the bucket to
+/// everyone are warnings.
+pub fn lint_policy(document: &str) -> Vec<PolicyIssue> {
+    let policy = match serde_json::from_str::<Value>(document) {
+        Ok(Value::Object(policy)) => policy,
+        Ok(_) => return vec![PolicyIssue::error("The policy must be a JSON object")],
+        Err(e) => return vec![PolicyIssue::error(format!("Invalid JSON: {}", e))],
+    };
+
+    let mut issues = Vec::new();
+    for key in policy.keys().filter(|k| !POLICY_KEYS.contains(&k.as_str())) {
+        issues.push(PolicyIssue::warning(format!("Unknown top-level field '{}'", key)));
+    }
+
+    match policy.get("Version") {
+        None => issues.push(PolicyIssue::error("Missing required field 'Version' (use \"2012-10-17\")")),
+        Some(Value::String(version)) if version == "2012-10-17" => {}
+        Some(Value::String(version)) if POLICY_VERSIONS.contains(&version.as_str()) => {
+            issues.push(PolicyIssue::warning(format!("Version {} does not support policy variables; use 2012-10-17", version)));
+        }
+        Some(version) => issues.push(PolicyIssue::error(format!("Invalid Version {} (use \"2012-10-17\")", version))),
+    }
+
+    let statements = match policy.get("Statement") {
+        None => {
+            issues.push(PolicyIssue::error("Missing required field 'Statement'"));
+            return issues;
+        }
+        Some(Value::Array(statements)) => statements.iter().collect(),
+        Some(statement @ Value::Object(_)) => vec![statement],
+        Some(_) => {
+            issues.push(PolicyIssue::error("'Statement' must be an object or a list of objects"));
+            return issues;
+        }
+    };
+    if statements.is_empty() {
+        issues.push(PolicyIssue::error("'Statement' has no statements"));
+    }
+
+    for (index, statement) in statements.into_iter().enumerate() {
+        lint_statement(index, statement, &mut issues);
+    }
+    issues
+}
+
+fn lint_statement(index: usize, statement: &Value, issues: &mut Vec<PolicyIssue>) {
+    let Value::Object(statement) = statement else {
+        issues.push(PolicyIssue::error(format!("Statement {} must be an object", index)));
+        return;
+    };
+    let name = match statement.get("Sid").and_then(Value::as_str) {
+        Some(sid) => format!("Statement {} ({})", index, sid),
+        None => format!("Statement {}", index),
+    };
+    let mut error = |message: String| issues.push(PolicyIssue::error(format!("{}: {}", name, message)));
+
+    for key in statement.keys().filter(|k| !STATEMENT_KEYS.contains(&k.as_str())) {
+        error(format!("Unknown field '{}'", key));
+    }
+
+    let effect = statement.get("Effect").and_then(Value::as_str);
+    match statement.get("Effect") {
+        None => error("Missing required field 'Effect'".to_string()),
+        Some(Value::String(e)) if e == "Allow" || e == "Deny" => {}
+        Some(e) => error(format!("Effect must be \"Allow\" or \"Deny\", not {}", e)),
+    }
+
+    let principal = either(statement, "Principal", "NotPrincipal", &mut error);
+    let actions = either(statement, "Action", "NotAction", &mut error)
+        .map(|(field, value)| (field, strings(field, value, &mut error)));
+    let resources = either(statement, "Resource", "NotResource", &mut error)
+        .map(|(field, value)| (field, strings(field, value, &mut error)));
+
+    if let Some((_, actions)) = &actions {
+        for action in actions.iter().filter(|a| *a != "*" && !a.to_ascii_lowercase().starts_with("s3:")) {
+            error(format!("'{}' is not an S3 action (expected e.g. s3:GetObject)", action));
+        }
+    }
+    if let Some((_, resources)) = &resources {
+        for resource in resources.iter().filter(|r| *r != "*" && !(r.starts_with("arn:") && r.contains(":s3:::"))) {
+            error(format!("'{}' is not an S3 resource ARN (expected e.g. arn:aws:s3:::bucket/*)", resource));
+        }
+    }
+
+    // Only grants to everyone are worth a warning
+    let public = matches!(principal, Some(("Principal", value)) if is_everyone(value));
+    if effect != Some("Allow") || !public || statement.contains_key("Condition") {
+        return;
+    }
+    let Some((field, actions)) = &actions else {
+        return;
+    };
+    if *field == "NotAction" {
+        issues.push(PolicyIssue::warning(format!("{}: allows everyone every S3 action not listed in NotAction", name)));
+    } else if actions.iter().any(|a| a == "*" || a.eq_ignore_ascii_case("s3:*")) {
+        issues.push(PolicyIssue::warning(format!("{}: allows everyone every S3 action; anyone can read, overwrite and delete", name)));
+    } else {
+        let writes: Vec<&str> = actions.iter()
+            .map(String::as_str)
+            .filter(|a| ["s3:put", "s3:delete"].iter().any(|p| a.to_ascii_lowercase().starts_with(p)))
+            .collect();
+        if writes.is_empty() {
+            issues.push(PolicyIssue::warning(format!("{}: makes the bucket public to everyone, with no Condition", name)));
+        } else {
+            issues.push(PolicyIssue::warning(format!("{}: lets everyone {}", name, writes.join(", "))));
+        }
+    }
+}
+
+/// The one of `field` and `not_field` a statement sets; it must set exactly one.
+fn either<'a>(
+    statement: &'a serde_json::Map<String, Value>,
+    field: &'static str,
+    not_field: &'static str,
+    error: &mut impl FnMut(String),
+) -> Option<(&'static str, &'a Value)> {
+    match (statement.get(field), statement.get(not_field)) {
+        (Some(value), None) => Some((field, value)),
+        (None, Some(value)) => Some((not_field, value)),
+        (Some(_), Some(_)) => {
+            error(format!("Use either '{}' or '{}', not both", field, not_field));
+            None
+        }
+        (None, None) => {
+            error(format!("Missing required field '{}'", field));
+            None
+        }
+    }
+}
+
+/// A field that is a string or a non-empty list of strings.
+fn strings(field: &str, value: &Value, error: &mut impl FnMut(String)) -> Vec<String> {
+    let values: Option<Vec<String>> = match value {
+        Value::String(s) => Some(vec![s.clone()]),
+        Value::Array(items) if !items.is_empty() => items.iter().map(|i| i.as_str().map(str::to_string)).collect(),
+        _ => None,
+    };
+    values.unwrap_or_else(|| {
+        error(format!("'{}' must be a string or a list of strings", field));
+        Vec::new()
+    })
+}
+
+/// `"*"` or `{"AWS": "*"}` (alone or in a list).
+fn is_everyone(principal: &Value) -> bool {
+    match principal {
+        Value::String(s) => s == "*",
+        Value::Object(map) => match map.get("AWS") {
+            Some(Value::String(s)) => s == "*",
+            Some(Value::Array(items)) => items.iter().any(|i| i.as_str() == Some("*")),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Lint the policy in `file_path` without calling AWS. Fails when it has
+/// errors; warnings are only printed.
+pub fn validate_policy_file(file_path: &str, output: OutputFormat) -> Result<()> {
+    let document = std::fs::read_to_string(file_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read policy file '{}': {}", file_path, e))?;
+    let issues = lint_policy(&document);
+    let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+    let warnings = issues.len() - errors;
+
+    if output == OutputFormat::Json {
+        println!("{}", serde_json::json!({ "file": file_path, "valid": errors == 0, "issues": issues }));
+    } else {
+        for issue in &issues {
+            match issue.severity {
+                Severity::Error => eprintln!("{} {}", "ERROR:".red().bold(), issue.message.red()),
+                Severity::Warning => eprintln!("{} {}", "WARNING:".yellow().bold(), issue.message.yellow()),
+            }
+        }
+    }
+
+    if errors > 0 {
+        return Err(anyhow::anyhow!(
+            "Policy '{}' is invalid: {}, {}",
+            file_path,
+            crate::ui::pluralize(errors, "error"),
+            crate::ui::pluralize(warnings, "warning")
+        ));
+    }
+    if output != OutputFormat::Json {
+        match warnings {
+            0 => success(format!("Policy '{}' is valid.", file_path)),
+            n => success(format!("Policy '{}' is valid, with {}.", file_path, crate::ui::pluralize(n, "warning"))),
+        }
+    }
+    Ok(())
+}
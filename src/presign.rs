@@ -0,0 +1,119 @@
+use anyhow::Result;
+use aws_config::SdkConfig;
+use aws_sdk_s3::config::{Credentials, ProvideCredentials};
+use aws_sdk_s3::primitives::{DateTime, DateTimeFormat};
+use crate::errors::usage_error;
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+/// What a presigned POST form lets the browser upload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostPolicy {
+    pub bucket: String,
+    /// Uploaded keys must start with this; the form's `key` field is
+    /// `<prefix>${filename}`, which S3 fills in with the uploaded file name.
+    pub key_prefix: String,
+    pub expires_in: Duration,
+    /// Allowed upload size in bytes, inclusive
+    pub content_length_range: Option<(u64, u64)>,
+    /// Required Content-Type; one ending in `/` (e.g. `image/`) is a prefix
+    pub content_type: Option<String>,
+}
+
+/// Parse `--content-length-range MIN,MAX` (bytes).
+pub fn parse_length_range(value: &str) -> Result<(u64, u64)> {
+    let invalid = || usage_error(format!("Invalid content length range '{}'. Use MIN,MAX in bytes, e.g. 0,10485760", value));
+    let (min, max) = value.split_once(',').ok_or_else(invalid)?;
+    let min: u64 = min.trim().parse().map_err(|_| invalid())?;
+    let max: u64 = max.trim().parse().map_err(|_| invalid())?;
+    if min > max {
+        return Err(invalid());
+    }
+    Ok((min, max))
+}
+
+/// The URL the form posts to: the bucket's virtual-hosted endpoint, or
+/// `<endpoint>/<bucket>` for a custom endpoint. Bucket names with dots use
+/// path style, since they don't match the wildcard certificate.
+pub fn post_url(bucket: &str, region: &str, endpoint_url: Option<&str>) -> String {
+    match endpoint_url {
+        Some(endpoint) => format!("{}/{}", endpoint.trim_end_matches('/'), bucket),
+        None if bucket.contains('.') => format!("https://s3.{}.amazonaws.com/{}", region, bucket),
+        None => format!("https://{}.s3.{}.amazonaws.com/", bucket, region),
+    }
+}
+
+/// Build and sign (SigV4) the POST policy, returning the form fields to send
+/// along with the file.
+pub fn presign_post_fields(
+    policy: &PostPolicy,
+    credentials: &Credentials,
+    region: &str,
+    now: SystemTime,
+) -> Result<BTreeMap<String, String>> {
+    // Whole seconds, so the timestamps have no fractional part
+    let now_secs = now.duration_since(SystemTime::UNIX_EPOCH)?.as_secs();
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(now_secs);
+    let timestamp = DateTime::from(now).fmt(DateTimeFormat::DateTime)?;
+    let amz_date = timestamp.replace(['-', ':'], "");
+    let expiration = DateTime::from(now + policy.expires_in).fmt(DateTimeFormat::DateTime)?;
+    let credential = format!("{}/{}/{}/s3/aws4_request", credentials.access_key_id(), &amz_date[..8], region);
+
+    let mut fields = BTreeMap::new();
+    fields.insert("key".to_string(), format!("{}${{filename}}", policy.key_prefix));
+    fields.insert("x-amz-algorithm".to_string(), "AWS4-HMAC-SHA256".to_string());
+    fields.insert("x-amz-credential".to_string(), credential);
+    fields.insert("x-amz-date".to_string(), amz_date);
+    if let Some(token) = credentials.session_token() {
+        fields.insert("x-amz-security-token".to_string(), token.to_string());
+    }
+
+    let mut conditions = vec![
+        serde_json::json!({ "bucket": policy.bucket }),
+        serde_json::json!(["starts-with", "$key", policy.key_prefix]),
+    ];
+    for (name, value) in &fields {
+        if name != "key" {
+            conditions.push(serde_json::json!({ name: value }));
+        }
+    }
+    if let Some((min, max)) = policy.content_length_range {
+        conditions.push(serde_json::json!(["content-length-range", min, max]));
+    }
+    if let Some(content_type) = &policy.content_type {
+        if content_type.ends_with('/') {
+            conditions.push(serde_json::json!(["starts-with", "$Content-Type", content_type]));
+        } else {
+            conditions.push(serde_json::json!({ "Content-Type": content_type }));
+            fields.insert("Content-Type".to_string(), content_type.clone());
+        }
+    }
+
+    let document = serde_json::json!({ "expiration": expiration, "conditions": conditions });
+    let encoded = aws_smithy_types::base64::encode(document.to_string());
+    let signing_key = aws_sigv4::sign::v4::generate_signing_key(credentials.secret_access_key(), now, region, "s3");
+    let signature = aws_sigv4::sign::v4::calculate_signature(signing_key, encoded.as_bytes());
+
+    fields.insert("policy".to_string(), encoded);
+    fields.insert("x-amz-signature".to_string(), signature);
+    Ok(fields)
+}
+
+/// Print the URL and signed form fields for a browser upload as JSON.
+/// Nothing is sent to S3.
+pub async fn presign_post(config: &SdkConfig, region: &str, policy: &PostPolicy) -> Result<()> {
+    let provider = config.credentials_provider()
+        .ok_or_else(|| anyhow::anyhow!("No credentials are configured to sign the form with"))?;
+    let credentials = provider.provide_credentials().await?;
+    if credentials.expiry().is_some_and(|expiry| expiry < SystemTime::now() + policy.expires_in) {
+        crate::ui::note("The credentials expire before the form does; uploads will fail once they do.");
+    }
+
+    let fields = presign_post_fields(policy, &credentials, region, SystemTime::now())?;
+    let form = serde_json::json!({
+        "url": post_url(&policy.bucket, region, config.endpoint_url()),
+        "fields": fields,
+    });
+    println!("{}", serde_json::to_string_pretty(&form)?);
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use anyhow::Result;
+use colored::*;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::Serialize;
+use crate::ui::{progress_target, success, OutputFormat};
+
+/// Progress shared by the bulk commands (sync and recursive copy, delete and
+/// download): an overall bar counting finished items, plus a transient bar
+/// for each item in flight. Nothing is drawn under `--quiet` or `--output json`.
+pub struct BulkProgress {
+    multi: MultiProgress,
+    overall: ProgressBar,
+    verb: &'static str,
+}
+
+impl BulkProgress {
+    /// `unit` names the items in the overall bar (e.g. "files"); `verb`
+    /// labels each item's bar (e.g. "Uploading").
+    pub fn new(total: usize, unit: &str, verb: &'static str, output: OutputFormat) -> Result<BulkProgress> {
+        let target = match output {
+            OutputFormat::Json => ProgressDrawTarget::hidden(),
+            OutputFormat::Table | OutputFormat::Csv => progress_target(),
+        };
+        BulkProgress::with_draw_target(total, unit, verb, target)
+    }
+
+    pub fn with_draw_target(total: usize, unit: &str, verb: &'static str, target: ProgressDrawTarget) -> Result<BulkProgress> {
+        let multi = MultiProgress::with_draw_target(target);
+        let overall = multi.add(ProgressBar::new(total as u64));
+        overall.set_style(ProgressStyle::default_bar().template(&format!("{{bar:40.green}} {{pos}}/{{len}} {}", unit))?);
+        Ok(BulkProgress { multi, overall, verb })
+    }
+
+    /// Add the bar for one item, shown while it is in flight.
+    pub fn start_item(&self, name: &str, len: Option<u64>) -> Result<ProgressBar> {
+        item_bar(self.verb, name, len, Some(&self.multi))
+    }
+
+    /// Remove an item's bar and count the item as done.
+    pub fn finish_item(&self, bar: &ProgressBar) {
+        bar.finish_and_clear();
+        self.overall.inc(1);
+    }
+
+    /// Count `count` items as done without a bar of their own (e.g. a batch).
+    pub fn inc(&self, count: u64) {
+        self.overall.inc(count);
+    }
+
+    pub fn completed(&self) -> u64 {
+        self.overall.position()
+    }
+
+    pub fn finish(&self) {
+        self.overall.finish();
+    }
+}
+
+/// The bar for one transfer: bytes against `len` when it is known, a spinner
+/// otherwise. Attached to `multi` within a bulk command, drawn on its own
+/// (unless quiet) for a single transfer.
+pub fn item_bar(verb: &str, name: &str, len: Option<u64>, multi: Option<&MultiProgress>) -> Result<ProgressBar> {
+    let pb = match multi {
+        Some(multi) => multi.add(ProgressBar::new(len.unwrap_or(0))),
+        None => ProgressBar::with_draw_target(len, progress_target()),
+    };
+    match len {
+        Some(len) => {
+            pb.set_length(len);
+            pb.set_style(ProgressStyle::default_bar()
+                .template(&format!("{} {{msg}} {{bar:40.green}} {{bytes}}/{{total_bytes}} ({{bytes_per_sec}})", verb))?);
+        }
+        None => {
+            pb.set_style(ProgressStyle::default_spinner()
+                .template(&format!("{{spinner:.green}} {} {{msg}}...", verb))?
+                .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏"));
+            pb.enable_steady_tick(std::time::Duration::from_millis(100));
+        }
+    }
+    pb.set_message(name.to_string());
+    Ok(pb)
+}
+
+/// An item a bulk command could not process, and why.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BulkFailure {
+    pub item: String,
+    pub error: String,
+}
+
+/// Outcome of a bulk command; the final object printed with `--output json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BulkSummary {
+    pub operation: String,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: Vec<BulkFailure>,
+}
+
+impl BulkSummary {
+    /// A summary of `total` items, with `failures` as (item, error) pairs.
+    pub fn new(operation: &str, total: usize, failures: Vec<(String, String)>) -> BulkSummary {
+        BulkSummary {
+            operation: operation.to_string(),
+            total,
+            succeeded: total.saturating_sub(failures.len()),
+            failed: failures.into_iter().map(|(item, error)| BulkFailure { item, error }).collect(),
+        }
+    }
+
+    /// Print the summary: the JSON object, or each failure followed by
+    /// `done` when there were none. Fails with e.g. "2 of 5 files failed to
+    /// upload" if anything failed, so the exit code reflects it.
+    pub fn report(&self, output: OutputFormat, unit: &str, action: &str, done: impl std::fmt::Display) -> Result<()> {
+        match output {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(self)?),
+            OutputFormat::Table | OutputFormat::Csv => {
+                for failure in &self.failed {
+                    println!("{} {}: {}", "✘".red(), failure.item, failure.error);
+                }
+                if self.failed.is_empty() {
+                    success(done);
+                }
+            }
+        }
+
+        if !self.failed.is_empty() {
+            return Err(anyhow::anyhow!("{} of {} {} failed to {}", self.failed.len(), self.total, unit, action));
+        }
+        Ok(())
+    }
+}
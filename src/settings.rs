@@ -0,0 +1,57 @@
+use anyhow::Result;
+use crate::errors::usage_error;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Defaults read from `s3sh.toml`. Every field is optional; command-line
+/// flags override whatever is set here.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub region: Option<String>,
+    pub profile: Option<String>,
+    pub endpoint_url: Option<String>,
+    pub force_path_style: Option<bool>,
+    pub output: Option<String>,
+    pub color: Option<bool>,
+}
+
+impl Settings {
+    /// Load the first config file found in `config_paths()`, or the built-in
+    /// defaults if there is none.
+    pub fn load() -> Result<Settings> {
+        match config_paths().into_iter().find(|p| p.is_file()) {
+            Some(path) => Settings::from_file(&path),
+            None => Ok(Settings::default()),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Settings> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read config file '{}': {}", path.display(), e))?;
+        toml::from_str(&contents)
+            .map_err(|e| usage_error(format!("Invalid config file '{}': {}", path.display(), e)))
+    }
+
+    /// Layer `overrides` (the command-line flags) on top of these settings.
+    pub fn merge(self, overrides: Settings) -> Settings {
+        Settings {
+            region: overrides.region.or(self.region),
+            profile: overrides.profile.or(self.profile),
+            endpoint_url: overrides.endpoint_url.or(self.endpoint_url),
+            force_path_style: overrides.force_path_style.or(self.force_path_style),
+            output: overrides.output.or(self.output),
+            color: overrides.color.or(self.color),
+        }
+    }
+}
+
+/// Where the config file is looked for: `./s3sh.toml`, then
+/// `~/.config/s3sh/config.toml`.
+pub fn config_paths() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from("s3sh.toml")];
+    if let Some(home) = std::env::var_os("HOME") {
+        paths.push(Path::new(&home).join(".config").join("s3sh").join("config.toml"));
+    }
+    paths
+}
@@ -1 +1,201 @@
-// Placeholder for UI helpers if needed, currently using direct println/tabled in modules
+use anyhow::Result;
+use crate::errors::usage_error;
+use colored::*;
+use indicatif::ProgressDrawTarget;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tabled::Tabled;
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Ask the user to confirm a destructive action on stdin.
+///
+/// Returns `Ok(true)` immediately when `assume_yes` is set. When stdin is not
+/// a terminal and `assume_yes` is not set, refuses instead of waiting for input.
+pub fn confirm(action: &str, assume_yes: bool) -> Result<bool> {
+    if assume_yes {
+        return Ok(true);
+    }
+
+    if !io::stdin().is_terminal() {
+        return Err(usage_error(format!(
+            "Refusing to {} without confirmation: stdin is not a terminal (use --yes to skip the prompt)",
+            action
+        )));
+    }
+
+    println!("{} About to {}.", "⚠".yellow(), action);
+    print!("Are you sure? [y/N] ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    Ok(is_affirmative(&answer))
+}
+
+/// Whether an answer to a `[y/N]` prompt means yes.
+pub fn is_affirmative(answer: &str) -> bool {
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+/// Column a listing is sorted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Size,
+    Modified,
+}
+
+pub fn parse_sort_key(key: &str) -> Result<SortKey> {
+    match key.to_ascii_lowercase().as_str() {
+        "name" => Ok(SortKey::Name),
+        "size" => Ok(SortKey::Size),
+        "modified" => Ok(SortKey::Modified),
+        _ => Err(usage_error(format!("Invalid sort key: {}. Use 'name', 'size' or 'modified'", key))),
+    }
+}
+
+/// How command results are printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    /// Listings as CSV with a header row; other commands print as for `Table`
+    Csv,
+}
+
+pub fn parse_output_format(format: &str) -> Result<OutputFormat> {
+    match format.to_ascii_lowercase().as_str() {
+        "table" => Ok(OutputFormat::Table),
+        "json" => Ok(OutputFormat::Json),
+        "csv" => Ok(OutputFormat::Csv),
+        _ => Err(usage_error(format!("Invalid output format: {}. Use 'table', 'json' or 'csv'", format))),
+    }
+}
+
+/// Rows as CSV: a header row, then one record per row, with the same
+/// columns in the same order as their table, less those named in `skip`.
+pub fn format_csv<T: Tabled>(rows: &[T], skip: &[&str]) -> Result<String> {
+    let headers = T::headers();
+    let keep: Vec<bool> = headers.iter().map(|h| !skip.contains(&h.as_ref())).collect();
+    let kept = |fields: Vec<std::borrow::Cow<'_, str>>| {
+        fields.into_iter().zip(&keep).filter(|(_, keep)| **keep).map(|(field, _)| field.into_owned()).collect::<Vec<_>>()
+    };
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(kept(headers))?;
+    for row in rows {
+        writer.write_record(kept(row.fields()))?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Format a byte count with binary units and one decimal place, e.g.
+/// `1023 B`, `1.0 KiB`, `4.5 GiB`.
+pub fn format_size(bytes: i64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// `1 object`, `42 objects`: a count with the noun pluralized by adding `s`.
+pub fn pluralize(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// Print the totals line under a listing, unless quiet.
+pub fn footer(message: impl std::fmt::Display) {
+    if !is_quiet() {
+        println!("{}", message.to_string().dimmed());
+    }
+}
+
+/// Whether output should be colored. An explicit `--color` or `--no-color`
+/// wins; otherwise `NO_COLOR` turns color off, and color is only used when
+/// stdout is a terminal.
+pub fn should_color(force_color: bool, no_color: bool, no_color_env: bool, is_terminal: bool) -> bool {
+    if force_color {
+        true
+    } else if no_color || no_color_env {
+        false
+    } else {
+        is_terminal
+    }
+}
+
+/// Apply the color choice to every `colored` string printed afterwards.
+pub fn init_color(force_color: bool, no_color: bool) {
+    // Per https://no-color.org, only a non-empty NO_COLOR disables color
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let enabled = should_color(force_color, no_color, no_color_env, io::stdout().is_terminal());
+    colored::control::set_override(enabled);
+}
+
+/// Turn `--quiet` on or off: confirmations, notes and progress bars are
+/// suppressed, leaving command output and errors.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Print a "✔" confirmation for a completed action, unless quiet.
+pub fn success(message: impl std::fmt::Display) {
+    if !is_quiet() {
+        println!("{} {}", "✔".green(), message);
+    }
+}
+
+/// Print an informational note (e.g. "No objects found."), unless quiet.
+pub fn note(message: impl std::fmt::Display) {
+    if !is_quiet() {
+        println!("{}", message.to_string().yellow());
+    }
+}
+
+/// Print a progress line for a long-running action, unless quiet.
+pub fn status(message: impl std::fmt::Display) {
+    if !is_quiet() {
+        println!("{}", message);
+    }
+}
+
+/// Where progress bars draw: stderr, or nowhere when quiet.
+pub fn progress_target() -> ProgressDrawTarget {
+    if is_quiet() {
+        ProgressDrawTarget::hidden()
+    } else {
+        ProgressDrawTarget::stderr()
+    }
+}
+
+/// Describe an API call skipped by `--dry-run`, e.g.
+/// `Would call DeleteObject on bucket 'b' (key 'k')`.
+pub fn describe_dry_run(operation: &str, bucket: &str, key: Option<&str>) -> String {
+    match key {
+        Some(key) => format!("Would call {} on bucket '{}' (key '{}')", operation, bucket, key),
+        None => format!("Would call {} on bucket '{}'", operation, bucket),
+    }
+}
+
+/// Print an API call that a mutating command would make under `--dry-run`.
+pub fn print_dry_run(operation: &str, bucket: &str, key: Option<&str>) {
+    println!("{} {}", "[dry-run]".cyan(), describe_dry_run(operation, bucket, key));
+}
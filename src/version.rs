@@ -0,0 +1,29 @@
+use serde::Serialize;
+
+/// What `s3sh version` reports, for bug reports.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VersionInfo {
+    pub s3sh: &'static str,
+    pub aws_sdk_s3: &'static str,
+    pub target: &'static str,
+    pub git_hash: &'static str,
+}
+
+/// The versions this binary was built with. The git hash is "unknown" when
+/// it was built outside a git checkout.
+pub fn version_info() -> VersionInfo {
+    VersionInfo {
+        s3sh: env!("CARGO_PKG_VERSION"),
+        aws_sdk_s3: aws_sdk_s3::meta::PKG_VERSION,
+        target: env!("S3SH_TARGET"),
+        git_hash: env!("S3SH_GIT_HASH"),
+    }
+}
+
+/// The human-readable version report, one `name: value` line each.
+pub fn format_version(info: &VersionInfo) -> String {
+    format!(
+        "s3sh {} ({})\naws-sdk-s3: {}\ntarget: {}\n",
+        info.s3sh, info.git_hash, info.aws_sdk_s3, info.target
+    )
+}
@@ -0,0 +1,94 @@
+use s3sh::archive::{archive_entries, parse_archive_format, write_archive, ArchiveEntry, ArchiveFormat};
+use futures::StreamExt;
+use tokio::io::AsyncReadExt;
+
+fn archive_fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("site");
+    std::fs::create_dir_all(root.join("css")).unwrap();
+    std::fs::write(root.join("index.html"), "<h1>hello</h1>").unwrap();
+    std::fs::write(root.join("css/site.css"), "body {}").unwrap();
+    std::fs::write(root.join("empty.txt"), "").unwrap();
+    dir
+}
+
+async fn archive_bytes(entries: &[ArchiveEntry], format: ArchiveFormat) -> Vec<u8> {
+    write_archive(entries, format, Vec::new()).await.unwrap()
+}
+
+/// The name and contents of every file in a tar stream.
+async fn read_tar(tar: Vec<u8>) -> Vec<(String, Vec<u8>)> {
+    let mut archive = tokio_tar::Archive::new(std::io::Cursor::new(tar));
+    let mut entries = archive.entries().unwrap();
+    let mut files = Vec::new();
+    while let Some(entry) = entries.next().await {
+        let mut entry = entry.unwrap();
+        let name = entry.path().unwrap().to_string_lossy().into_owned();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).await.unwrap();
+        files.push((name, data));
+    }
+    files
+}
+
+async fn gunzip(gz: &[u8]) -> Vec<u8> {
+    let mut tar = Vec::new();
+    async_compression::tokio::bufread::GzipDecoder::new(gz).read_to_end(&mut tar).await.unwrap();
+    tar
+}
+
+#[test]
+fn test_parse_archive_format() {
+    assert_eq!(parse_archive_format("tar").unwrap(), ArchiveFormat::Tar);
+    assert_eq!(parse_archive_format("tar.gz").unwrap(), ArchiveFormat::TarGz);
+    assert_eq!(parse_archive_format("tgz").unwrap(), ArchiveFormat::TarGz);
+    assert!(parse_archive_format("zip").is_err());
+}
+
+#[test]
+fn test_archive_entries_are_named_under_the_directory() {
+    let dir = archive_fixture();
+    let names = archive_entries(&dir.path().join("site")).unwrap()
+        .into_iter()
+        .map(|e| e.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["site/css/site.css", "site/empty.txt", "site/index.html"]);
+}
+
+#[tokio::test]
+async fn test_write_archive_round_trips() {
+    let dir = archive_fixture();
+    let entries = archive_entries(&dir.path().join("site")).unwrap();
+    let expected = vec![
+        ("site/css/site.css".to_string(), b"body {}".to_vec()),
+        ("site/empty.txt".to_string(), Vec::new()),
+        ("site/index.html".to_string(), b"<h1>hello</h1>".to_vec()),
+    ];
+
+    let tar = archive_bytes(&entries, ArchiveFormat::Tar).await;
+    assert_eq!(tar.len() % 512, 0);
+    assert_eq!(read_tar(tar.clone()).await, expected);
+
+    let gz = archive_bytes(&entries, ArchiveFormat::TarGz).await;
+    assert_eq!(&gz[..3], &[0x1f, 0x8b, 8]);
+    assert_eq!(gunzip(&gz).await, tar);
+}
+
+#[tokio::test]
+async fn test_write_archive_compresses_and_keeps_long_names() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("big");
+    let nested = root.join("d".repeat(120));
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(nested.join("f".repeat(90)), vec![7u8; 200_000]).unwrap();
+
+    let entries = archive_entries(&root).unwrap();
+    let tar = archive_bytes(&entries, ArchiveFormat::Tar).await;
+    let gz = archive_bytes(&entries, ArchiveFormat::TarGz).await;
+    assert!(gz.len() < tar.len() / 10, "{} of {} bytes", gz.len(), tar.len());
+
+    let files = read_tar(gunzip(&gz).await).await;
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].0, format!("big/{}/{}", "d".repeat(120), "f".repeat(90)));
+    assert_eq!(files[0].1.len(), 200_000);
+}
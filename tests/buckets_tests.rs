@@ -1,8 +1,16 @@
-use s3sh::buckets::{create_bucket, validate_bucket_name};
+use s3sh::buckets::{create_bucket, validate_bucket_name, sort_buckets, BucketInfo, format_versioning_status, bucket_exists,
+    format_bucket_website, format_bucket_notifications,
+    format_bucket_replication, format_bucket_encryption, format_tags, format_acl, parse_bucket_canned_acl, format_inventory_configurations, format_bucket_logging, format_accelerate_status, parse_payer, parse_object_ownership, is_public_canned_acl, NameFilter,
+    suggest_bucket_name, suggest_missing_bucket, parse_bucket_encryption, summarize_usage, bucket_usage, Usage, NO_SUB_PREFIX};
+use s3sh::grants::Grants;
+use s3sh::ui::{OutputFormat, SortKey};
+use aws_sdk_s3::primitives::DateTime;
 use aws_sdk_s3::Client;
 use aws_sdk_s3::config::{Region, BehaviorVersion};
 use aws_smithy_runtime::client::http::test_util::StaticReplayClient;
 use aws_smithy_types::body::SdkBody;
+use std::io::Write;
+use tempfile::NamedTempFile;
 
 #[test]
 fn test_validate_bucket_name() {
@@ -40,10 +48,65 @@ async fn test_create_bucket_simple() {
     
     let client = Client::from_conf(config);
 
-    let result = create_bucket(&client, "my-test-bucket", "us-east-1", None, None, None, vec![]).await;
+    let result = create_bucket(&client, "my-test-bucket", "us-east-1", None, None, None, None, vec![], false).await;
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_create_bucket_with_canned_acl() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/acl-bucket")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/private-bucket")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let acl = parse_bucket_canned_acl("public-read").unwrap();
+    assert!(create_bucket(&client, "acl-bucket", "us-east-1", Some(acl), None, None, None, vec![], false).await.is_ok());
+    let acl = parse_bucket_canned_acl("private").unwrap();
+    assert!(create_bucket(&client, "private-bucket", "us-east-1", Some(acl), None, None, None, vec![], false).await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests[0].headers().get("x-amz-acl"), Some("public-read"));
+    // Public ACLs need ACLs turned back on for the new bucket
+    assert_eq!(requests[0].headers().get("x-amz-object-ownership"), Some("BucketOwnerPreferred"));
+    assert_eq!(requests[1].headers().get("x-amz-acl"), Some("private"));
+    assert_eq!(requests[1].headers().get("x-amz-object-ownership"), None);
+}
+
+#[test]
+fn test_is_public_canned_acl() {
+    assert!(is_public_canned_acl(&parse_bucket_canned_acl("public-read").unwrap()));
+    assert!(is_public_canned_acl(&parse_bucket_canned_acl("authenticated-read").unwrap()));
+    assert!(!is_public_canned_acl(&parse_bucket_canned_acl("private").unwrap()));
+}
+
 #[tokio::test]
 async fn test_create_bucket_with_config() {
     let http_client = StaticReplayClient::new(vec![
@@ -98,10 +161,12 @@ async fn test_create_bucket_with_config() {
         &client, 
         "config-bucket", 
         "us-east-1", 
+        None,
         Some(true), 
         Some(true), 
         None, 
-        vec![]
+        vec![],
+        false
     ).await;
     
     assert!(result.is_ok());
@@ -152,15 +217,15 @@ async fn test_put_lifecycle_rule() {
 
     let transitions_json = r#"[{"days": 30, "storage_class": "STANDARD_IA"}]"#;
 
-    let result = s3sh::buckets::put_lifecycle_rule(
-        &client, 
-        "lifecycle-bucket", 
-        "rule-1", 
-        "logs/", 
-        transitions_json, 
-        Some(365), 
-        true
-    ).await;
+    let rule = s3sh::buckets::LifecycleRuleSpec {
+        id: "rule-1".to_string(),
+        prefix: "logs/".to_string(),
+        transitions: transitions_json.to_string(),
+        expiration_days: Some(365),
+        enabled: true,
+        ..Default::default()
+    };
+    let result = s3sh::buckets::put_lifecycle_rule(&client, "lifecycle-bucket", &rule, false).await;
     
     assert!(result.is_ok());
 }
@@ -179,20 +244,82 @@ async fn test_put_lifecycle_rule_invalid_json() {
 
     let transitions_json = r#"invalid-json"#;
 
-    let result = s3sh::buckets::put_lifecycle_rule(
-        &client, 
-        "lifecycle-bucket", 
-        "rule-1", 
-        "logs/", 
-        transitions_json, 
-        Some(365), 
-        true
-    ).await;
+    let rule = s3sh::buckets::LifecycleRuleSpec {
+        id: "rule-1".to_string(),
+        prefix: "logs/".to_string(),
+        transitions: transitions_json.to_string(),
+        expiration_days: Some(365),
+        enabled: true,
+        ..Default::default()
+    };
+    let result = s3sh::buckets::put_lifecycle_rule(&client, "lifecycle-bucket", &rule, false).await;
     
     assert!(result.is_err());
     assert!(result.unwrap_err().to_string().contains("Invalid transitions JSON"));
 }
 
+#[test]
+fn test_lifecycle_rule_with_tag_and_size_filter() {
+    let rule = s3sh::buckets::LifecycleRuleSpec {
+        id: "expire-big-tmp".to_string(),
+        filter: Some(r#"{"prefix": "tmp/", "tags": {"env": "dev"}, "object_size_greater_than": 1048576}"#.to_string()),
+        transitions: "[]".to_string(),
+        expiration_days: Some(7),
+        noncurrent_expiration_days: Some(30),
+        abort_incomplete_upload_days: Some(2),
+        enabled: true,
+        ..Default::default()
+    };
+    let built = rule.build().unwrap();
+
+    // Several criteria are combined with And
+    let filter = built.filter().unwrap();
+    assert!(filter.prefix().is_none());
+    let and = filter.and().unwrap();
+    assert_eq!(and.prefix(), Some("tmp/"));
+    assert_eq!(and.tags().len(), 1);
+    assert_eq!(and.tags()[0].key(), "env");
+    assert_eq!(and.tags()[0].value(), "dev");
+    assert_eq!(and.object_size_greater_than(), Some(1048576));
+    assert_eq!(and.object_size_less_than(), None);
+    assert_eq!(s3sh::buckets::describe_lifecycle_filter(Some(filter)), "prefix=tmp/, env=dev, size>1048576");
+
+    assert_eq!(built.expiration().and_then(|e| e.days()), Some(7));
+    assert_eq!(built.noncurrent_version_expiration().and_then(|e| e.noncurrent_days()), Some(30));
+    assert_eq!(built.abort_incomplete_multipart_upload().and_then(|a| a.days_after_initiation()), Some(2));
+}
+
+#[test]
+fn test_parse_lifecycle_filter() {
+    use s3sh::buckets::parse_lifecycle_filter;
+
+    // A single criterion stands alone
+    let filter = parse_lifecycle_filter(r#"{"object_size_less_than": 4096}"#).unwrap();
+    assert!(filter.and().is_none());
+    assert_eq!(filter.object_size_less_than(), Some(4096));
+
+    let filter = parse_lifecycle_filter(r#"{"tags": {"archive": "yes"}}"#).unwrap();
+    assert_eq!(filter.tag().map(|t| t.key()), Some("archive"));
+
+    // No criteria match every object
+    assert_eq!(parse_lifecycle_filter("{}").unwrap().prefix(), Some(""));
+
+    assert!(parse_lifecycle_filter(r#"{"object_size_greater_than": 10, "object_size_less_than": 5}"#).is_err());
+    assert!(parse_lifecycle_filter(r#"{"prefx": "logs/"}"#).is_err());
+    assert!(parse_lifecycle_filter("not json").is_err());
+}
+
+#[test]
+fn test_lifecycle_rule_needs_an_action() {
+    let rule = s3sh::buckets::LifecycleRuleSpec {
+        id: "empty".to_string(),
+        transitions: "[]".to_string(),
+        enabled: true,
+        ..Default::default()
+    };
+    assert!(rule.build().is_err());
+}
+
 #[tokio::test]
 async fn test_get_bucket_config_empty_region() {
     let http_client = StaticReplayClient::new(vec![
@@ -268,7 +395,7 @@ async fn test_get_bucket_config_empty_region() {
     
     let client = Client::from_conf(config);
 
-    let result = s3sh::buckets::get_bucket_config(&client, "test-bucket").await;
+    let result = s3sh::buckets::get_bucket_config(&client, "test-bucket", OutputFormat::Table).await;
     assert!(result.is_ok());
 }
 
@@ -296,7 +423,7 @@ async fn test_delete_bucket() {
     
     let client = Client::from_conf(config);
 
-    let result = s3sh::buckets::delete_bucket(&client, "test-bucket").await;
+    let result = s3sh::buckets::delete_bucket(&client, "test-bucket", true, false).await;
     assert!(result.is_ok());
 }
 
@@ -371,6 +498,2377 @@ async fn test_empty_bucket() {
     
     let client = Client::from_conf(config);
 
-    let result = s3sh::buckets::empty_bucket(&client, "test-bucket").await;
+    let result = s3sh::buckets::empty_bucket(&client, "test-bucket", true, None, true, false).await;
+    assert!(result.is_ok());
+}
+
+const TWO_LIFECYCLE_RULES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+    <LifecycleConfiguration>
+        <Rule>
+            <ID>rule-1</ID>
+            <Filter><Prefix>logs/</Prefix></Filter>
+            <Status>Enabled</Status>
+            <Transition>
+                <Days>30</Days>
+                <StorageClass>STANDARD_IA</StorageClass>
+            </Transition>
+            <Expiration><Days>365</Days></Expiration>
+        </Rule>
+        <Rule>
+            <ID>rule-2</ID>
+            <Filter><Prefix>tmp/</Prefix></Filter>
+            <Status>Disabled</Status>
+            <Expiration><Days>7</Days></Expiration>
+        </Rule>
+    </LifecycleConfiguration>"#;
+
+#[tokio::test]
+async fn test_list_lifecycle_rules() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/lifecycle-bucket?lifecycle")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(TWO_LIFECYCLE_RULES))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = s3sh::buckets::list_lifecycle_rules(&client, "lifecycle-bucket").await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_delete_lifecycle_rule() {
+    let http_client = StaticReplayClient::new(vec![
+        // Get current lifecycle config with two rules
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/lifecycle-bucket?lifecycle")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(TWO_LIFECYCLE_RULES))
+                .unwrap(),
+        ),
+        // Put back the remaining rule
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/lifecycle-bucket?lifecycle")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = s3sh::buckets::delete_lifecycle_rule(&client, "lifecycle-bucket", "rule-1", false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    let body = std::str::from_utf8(requests[1].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<ID>rule-2</ID>"));
+    assert!(!body.contains("<ID>rule-1</ID>"));
+}
+
+#[tokio::test]
+async fn test_delete_lifecycle_rule_no_configuration() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/lifecycle-bucket?lifecycle")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(404)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>NoSuchLifecycleConfiguration</Code>
+                        <Message>The lifecycle configuration does not exist</Message>
+                    </Error>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = s3sh::buckets::delete_lifecycle_rule(&client, "lifecycle-bucket", "rule-1", false).await;
+    assert!(result.is_ok());
+}
+
+const POLICY_JSON: &str = r#"{"Version":"2012-10-17","Statement":[{"Sid":"PublicRead","Effect":"Allow","Principal":"*","Action":"s3:GetObject","Resource":"arn:aws:s3:::policy-bucket/*"}]}"#;
+
+#[tokio::test]
+async fn test_get_bucket_policy() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/policy-bucket?policy")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(POLICY_JSON))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = s3sh::buckets::get_bucket_policy(&client, "policy-bucket").await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_get_bucket_policy_not_set() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/policy-bucket?policy")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(404)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>NoSuchBucketPolicy</Code>
+                        <Message>The bucket policy does not exist</Message>
+                    </Error>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = s3sh::buckets::get_bucket_policy(&client, "policy-bucket").await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_put_bucket_policy_from_file() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/policy-bucket?policy")
+                .body(SdkBody::from(POLICY_JSON))
+                .unwrap(),
+            http::Response::builder()
+                .status(204)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let mut policy_file = NamedTempFile::new().unwrap();
+    write!(policy_file, "{}", POLICY_JSON).unwrap();
+    let path = policy_file.path().to_str().unwrap();
+
+    let result = s3sh::buckets::put_bucket_policy(&client, "policy-bucket", path, false).await;
     assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].body().bytes().unwrap(), POLICY_JSON.as_bytes());
+}
+
+#[tokio::test]
+async fn test_put_bucket_policy_invalid_json() {
+    let http_client = StaticReplayClient::new(vec![]); // No requests expected
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let mut policy_file = NamedTempFile::new().unwrap();
+    write!(policy_file, "{{ not json").unwrap();
+    let path = policy_file.path().to_str().unwrap();
+
+    let result = s3sh::buckets::put_bucket_policy(&client, "policy-bucket", path, false).await;
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("Invalid policy JSON"));
+}
+
+#[tokio::test]
+async fn test_get_bucket_cors() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/site-bucket?cors")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <CORSConfiguration>
+                        <CORSRule>
+                            <AllowedOrigin>https://example.com</AllowedOrigin>
+                            <AllowedMethod>GET</AllowedMethod>
+                            <AllowedMethod>HEAD</AllowedMethod>
+                            <AllowedHeader>*</AllowedHeader>
+                            <MaxAgeSeconds>3000</MaxAgeSeconds>
+                        </CORSRule>
+                    </CORSConfiguration>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = s3sh::buckets::get_bucket_cors(&client, "site-bucket").await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_put_bucket_cors_from_file() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/site-bucket?cors")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let mut cors_file = NamedTempFile::new().unwrap();
+    write!(cors_file, r#"[{{"allowed_origins": ["https://example.com"], "allowed_methods": ["GET", "PUT"], "max_age_seconds": 600}}]"#).unwrap();
+    let path = cors_file.path().to_str().unwrap();
+
+    let result = s3sh::buckets::put_bucket_cors(&client, "site-bucket", path, false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    let body = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<AllowedOrigin>https://example.com</AllowedOrigin>"));
+    assert!(body.contains("<AllowedMethod>PUT</AllowedMethod>"));
+    assert!(body.contains("<MaxAgeSeconds>600</MaxAgeSeconds>"));
+}
+
+fn get_event(uri: &str, status: u16, body: &'static str) -> aws_smithy_runtime::client::http::test_util::ReplayEvent {
+    aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("GET")
+            .uri(uri)
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(status)
+            .body(SdkBody::from(body))
+            .unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_get_bucket_config_full() {
+    let http_client = StaticReplayClient::new(vec![
+        get_event("https://s3.us-east-1.amazonaws.com/full-bucket?location", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <LocationConstraint>eu-west-1</LocationConstraint>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/full-bucket?publicAccessBlock", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <PublicAccessBlockConfiguration>
+                <BlockPublicAcls>true</BlockPublicAcls>
+                <IgnorePublicAcls>true</IgnorePublicAcls>
+                <BlockPublicPolicy>true</BlockPublicPolicy>
+                <RestrictPublicBuckets>true</RestrictPublicBuckets>
+            </PublicAccessBlockConfiguration>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/full-bucket?encryption", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <ServerSideEncryptionConfiguration>
+                <Rule>
+                    <ApplyServerSideEncryptionByDefault>
+                        <SSEAlgorithm>AES256</SSEAlgorithm>
+                    </ApplyServerSideEncryptionByDefault>
+                </Rule>
+            </ServerSideEncryptionConfiguration>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/full-bucket?versioning", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <VersioningConfiguration><Status>Enabled</Status></VersioningConfiguration>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/full-bucket?tagging", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Tagging>
+                <TagSet>
+                    <Tag><Key>Env</Key><Value>Dev</Value></Tag>
+                </TagSet>
+            </Tagging>"#),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = s3sh::buckets::get_bucket_config(&client, "full-bucket", OutputFormat::Table).await;
+    assert!(result.is_ok());
+    assert_eq!(http_client.actual_requests().count(), 5);
+}
+
+#[tokio::test]
+async fn test_get_bucket_config_not_configured() {
+    let http_client = StaticReplayClient::new(vec![
+        get_event("https://s3.us-east-1.amazonaws.com/bare-bucket?location", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <LocationConstraint></LocationConstraint>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/bare-bucket?publicAccessBlock", 404, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Error><Code>NoSuchPublicAccessBlockConfiguration</Code><Message>not found</Message></Error>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/bare-bucket?encryption", 404, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Error><Code>ServerSideEncryptionConfigurationNotFoundError</Code><Message>not found</Message></Error>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/bare-bucket?versioning", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <VersioningConfiguration/>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/bare-bucket?tagging", 404, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Error><Code>NoSuchTagSet</Code><Message>The TagSet does not exist</Message></Error>"#),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = s3sh::buckets::get_bucket_config(&client, "bare-bucket", OutputFormat::Table).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_export_bucket_config_json() {
+    let http_client = StaticReplayClient::new(vec![
+        get_event("https://s3.us-east-1.amazonaws.com/full-bucket?location", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <LocationConstraint>eu-west-1</LocationConstraint>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/full-bucket?publicAccessBlock", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <PublicAccessBlockConfiguration>
+                <BlockPublicAcls>true</BlockPublicAcls>
+                <IgnorePublicAcls>true</IgnorePublicAcls>
+                <BlockPublicPolicy>false</BlockPublicPolicy>
+                <RestrictPublicBuckets>true</RestrictPublicBuckets>
+            </PublicAccessBlockConfiguration>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/full-bucket?encryption", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <ServerSideEncryptionConfiguration>
+                <Rule>
+                    <ApplyServerSideEncryptionByDefault>
+                        <SSEAlgorithm>aws:kms</SSEAlgorithm>
+                        <KMSMasterKeyID>alias/data</KMSMasterKeyID>
+                    </ApplyServerSideEncryptionByDefault>
+                    <BucketKeyEnabled>true</BucketKeyEnabled>
+                </Rule>
+            </ServerSideEncryptionConfiguration>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/full-bucket?versioning", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <VersioningConfiguration><Status>Enabled</Status></VersioningConfiguration>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/full-bucket?tagging", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Tagging>
+                <TagSet>
+                    <Tag><Key>Env</Key><Value>Dev</Value></Tag>
+                </TagSet>
+            </Tagging>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/full-bucket?lifecycle", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <LifecycleConfiguration>
+                <Rule>
+                    <ID>archive-logs</ID>
+                    <Filter>
+                        <And>
+                            <Prefix>logs/</Prefix>
+                            <Tag><Key>tier</Key><Value>cold</Value></Tag>
+                        </And>
+                    </Filter>
+                    <Status>Enabled</Status>
+                    <Transition><Days>30</Days><StorageClass>GLACIER</StorageClass></Transition>
+                    <Expiration><Days>365</Days></Expiration>
+                </Rule>
+            </LifecycleConfiguration>"#),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+    let client = Client::from_conf(config);
+
+    let export = s3sh::buckets::export_bucket_config(&client, "full-bucket").await.unwrap();
+    assert_eq!(http_client.actual_requests().count(), 6);
+
+    let json = serde_json::to_value(&export).unwrap();
+    let keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+    for key in ["bucket", "region", "public_access_block", "encryption", "versioning", "tags", "lifecycle"] {
+        assert!(keys.contains(&key), "missing {} in {:?}", key, keys);
+    }
+    assert_eq!(json["region"], "eu-west-1");
+    assert_eq!(json["public_access_block"]["block_public_policy"], false);
+    assert_eq!(json["encryption"]["kms_key_id"], "alias/data");
+    assert_eq!(json["encryption"]["bucket_key"], true);
+    assert_eq!(json["versioning"]["status"], "Enabled");
+    assert_eq!(json["tags"]["Env"], "Dev");
+
+    let rule = &json["lifecycle"][0];
+    assert_eq!(rule["id"], "archive-logs");
+    assert_eq!(rule["filter"]["prefix"], "logs/");
+    assert_eq!(rule["filter"]["tags"]["tier"], "cold");
+    assert_eq!(rule["transitions"][0]["storage_class"], "GLACIER");
+    assert_eq!(rule["expiration_days"], 365);
+
+    // The exported document reads back into the same shape
+    let parsed: s3sh::buckets::BucketConfigExport = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, export);
+}
+
+#[tokio::test]
+async fn test_export_bucket_config_not_configured() {
+    let http_client = StaticReplayClient::new(vec![
+        get_event("https://s3.us-east-1.amazonaws.com/bare-bucket?location", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <LocationConstraint></LocationConstraint>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/bare-bucket?publicAccessBlock", 404, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Error><Code>NoSuchPublicAccessBlockConfiguration</Code><Message>not found</Message></Error>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/bare-bucket?encryption", 404, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Error><Code>ServerSideEncryptionConfigurationNotFoundError</Code><Message>not found</Message></Error>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/bare-bucket?versioning", 200, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <VersioningConfiguration/>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/bare-bucket?tagging", 404, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Error><Code>NoSuchTagSet</Code><Message>The TagSet does not exist</Message></Error>"#),
+        get_event("https://s3.us-east-1.amazonaws.com/bare-bucket?lifecycle", 404, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Error><Code>NoSuchLifecycleConfiguration</Code><Message>not found</Message></Error>"#),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    let client = Client::from_conf(config);
+
+    let export = s3sh::buckets::export_bucket_config(&client, "bare-bucket").await.unwrap();
+    assert_eq!(export.region, "us-east-1");
+    let json = serde_json::to_value(&export).unwrap();
+    assert!(json["public_access_block"].is_null());
+    assert!(json["encryption"].is_null());
+    assert!(json["versioning"].is_null());
+    assert_eq!(json["tags"], serde_json::json!({}));
+    assert_eq!(json["lifecycle"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_empty_bucket_current_objects() {
+    let http_client = StaticReplayClient::new(vec![
+        // 1. ListObjectsV2
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?list-type=2")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Name>test-bucket</Name>
+                        <IsTruncated>false</IsTruncated>
+                        <Contents><Key>a.txt</Key><Size>1</Size></Contents>
+                        <Contents><Key>b.txt</Key><Size>2</Size></Contents>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        ),
+        // 2. DeleteObjects
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?delete")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <DeleteResult>
+                        <Deleted><Key>a.txt</Key></Deleted>
+                        <Deleted><Key>b.txt</Key></Deleted>
+                    </DeleteResult>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = s3sh::buckets::empty_bucket(&client, "test-bucket", false, None, true, false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    let body = std::str::from_utf8(requests[1].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<Key>a.txt</Key>"));
+    assert!(body.contains("<Key>b.txt</Key>"));
+    assert!(!body.contains("<VersionId>"));
+}
+
+#[tokio::test]
+async fn test_empty_bucket_reports_failed_deletes() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?list-type=2")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Contents><Key>locked.txt</Key></Contents>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?delete")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <DeleteResult>
+                        <Error>
+                            <Key>locked.txt</Key>
+                            <Code>AccessDenied</Code>
+                            <Message>Access Denied</Message>
+                        </Error>
+                    </DeleteResult>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = s3sh::buckets::empty_bucket(&client, "test-bucket", false, None, true, false).await;
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("locked.txt"));
+}
+
+#[tokio::test]
+async fn test_dry_run_sends_no_requests() {
+    let http_client = StaticReplayClient::new(vec![]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let transitions_json = r#"[{"days": 30, "storage_class": "STANDARD_IA"}]"#;
+
+    assert!(create_bucket(&client, "dry-bucket", "us-west-2", None, Some(false), Some(true), None, vec![], true).await.is_ok());
+    assert!(s3sh::buckets::update_bucket(&client, "dry-bucket", None, Some(false), None, None, vec![], None, true).await.is_ok());
+    let rule = s3sh::buckets::LifecycleRuleSpec {
+        id: "rule-1".to_string(),
+        prefix: "logs/".to_string(),
+        transitions: transitions_json.to_string(),
+        enabled: true,
+        ..Default::default()
+    };
+    assert!(s3sh::buckets::put_lifecycle_rule(&client, "dry-bucket", &rule, true).await.is_ok());
+    assert!(s3sh::buckets::delete_lifecycle_rule(&client, "dry-bucket", "rule-1", true).await.is_ok());
+    // Dry-run must not prompt, so assume_yes is false here
+    assert!(s3sh::buckets::empty_bucket(&client, "dry-bucket", true, None, false, true).await.is_ok());
+    assert!(s3sh::buckets::delete_bucket(&client, "dry-bucket", false, true).await.is_ok());
+    let mut policy_file = NamedTempFile::new().unwrap();
+    write!(policy_file, "{}", POLICY_JSON).unwrap();
+    assert!(s3sh::buckets::put_bucket_policy(&client, "dry-bucket", policy_file.path().to_str().unwrap(), true).await.is_ok());
+    assert!(s3sh::buckets::delete_bucket_policy(&client, "dry-bucket", true).await.is_ok());
+    let mut cors_file = NamedTempFile::new().unwrap();
+    write!(cors_file, r#"[{{"allowed_origins": ["https://example.com"], "allowed_methods": ["GET"]}}]"#).unwrap();
+    assert!(s3sh::buckets::put_bucket_cors(&client, "dry-bucket", cors_file.path().to_str().unwrap(), true).await.is_ok());
+    assert!(s3sh::buckets::delete_bucket_cors(&client, "dry-bucket", true).await.is_ok());
+    assert!(s3sh::buckets::delete_bucket_analytics(&client, "dry-bucket", "analytics-1", true).await.is_ok());
+    assert!(s3sh::buckets::delete_bucket_metrics(&client, "dry-bucket", "metrics-1", true).await.is_ok());
+    assert!(s3sh::buckets::delete_bucket_inventory(&client, "dry-bucket", "inventory-1", true).await.is_ok());
+    assert!(s3sh::buckets::delete_bucket_replication(&client, "dry-bucket", true).await.is_ok());
+    assert!(s3sh::buckets::delete_bucket_encryption(&client, "dry-bucket", true).await.is_ok());
+    assert!(s3sh::buckets::delete_bucket_tags(&client, "dry-bucket", true).await.is_ok());
+    assert!(s3sh::buckets::put_bucket_website(&client, "dry-bucket", "index.html", Some("error.html"), true).await.is_ok());
+    assert!(s3sh::buckets::delete_bucket_website(&client, "dry-bucket", true).await.is_ok());
+    assert!(s3sh::buckets::put_bucket_acl(&client, "dry-bucket", Some("public-read"), &Grants::default(), true).await.is_ok());
+    assert!(s3sh::buckets::put_bucket_ownership_controls(&client, "dry-bucket", "BucketOwnerEnforced", true).await.is_ok());
+    assert!(s3sh::buckets::put_bucket_logging(&client, "dry-bucket", Some("log-bucket"), "logs/", true).await.is_ok());
+    assert!(s3sh::buckets::put_bucket_logging(&client, "dry-bucket", None, "", true).await.is_ok());
+    assert!(s3sh::buckets::put_bucket_accelerate(&client, "dry-bucket", true, true).await.is_ok());
+    assert!(s3sh::buckets::put_bucket_request_payment(&client, "dry-bucket", "Requester", true).await.is_ok());
+
+    assert_eq!(http_client.actual_requests().count(), 0);
+}
+
+#[tokio::test]
+async fn test_dry_run_still_validates_input() {
+    let http_client = StaticReplayClient::new(vec![]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(create_bucket(&client, "Bad_Bucket", "us-east-1", None, None, None, None, vec![], true).await.is_err());
+    let rule = s3sh::buckets::LifecycleRuleSpec {
+        id: "rule-1".to_string(),
+        transitions: "invalid-json".to_string(),
+        enabled: true,
+        ..Default::default()
+    };
+    assert!(s3sh::buckets::put_lifecycle_rule(&client, "dry-bucket", &rule, true).await.is_err());
+    // Neither a canned ACL nor grants
+    assert!(s3sh::buckets::put_bucket_acl(&client, "dry-bucket", None, &Grants::default(), true).await.is_err());
+    assert!(s3sh::buckets::put_bucket_acl(&client, "dry-bucket", Some("everyone"), &Grants::default(), true).await.is_err());
+    assert_eq!(http_client.actual_requests().count(), 0);
+}
+
+#[test]
+fn test_sort_buckets() {
+    let bucket = |name: &str, secs: i64| BucketInfo {
+        name: name.to_string(),
+        creation_date: DateTime::from_secs(secs).to_string(),
+        created: Some(DateTime::from_secs(secs)),
+    };
+    let mut infos = vec![bucket("logs", 1_650_000_000), bucket("assets", 1_700_000_000), bucket("backups", 1_600_000_000)];
+
+    sort_buckets(&mut infos, SortKey::Name, false).unwrap();
+    assert_eq!(infos.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), ["assets", "backups", "logs"]);
+
+    sort_buckets(&mut infos, SortKey::Modified, false).unwrap();
+    assert_eq!(infos.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), ["backups", "logs", "assets"]);
+
+    sort_buckets(&mut infos, SortKey::Modified, true).unwrap();
+    assert_eq!(infos.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), ["assets", "logs", "backups"]);
+
+    assert!(sort_buckets(&mut infos, SortKey::Size, false).is_err());
+}
+
+#[test]
+fn test_bucket_csv_has_table_columns() {
+    let infos = vec![BucketInfo {
+        name: "assets".to_string(),
+        creation_date: DateTime::from_secs(1_700_000_000).to_string(),
+        created: Some(DateTime::from_secs(1_700_000_000)),
+    }];
+
+    let csv = s3sh::ui::format_csv(&infos, &[]).unwrap();
+    assert_eq!(csv, "name,creation_date\nassets,2023-11-14T22:13:20Z\n");
+    assert_eq!(s3sh::ui::format_csv::<BucketInfo>(&[], &[]).unwrap(), "name,creation_date\n");
+}
+
+async fn versioning_output(body: &'static str) -> String {
+    let event = || aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("GET")
+            .uri("https://s3.us-east-1.amazonaws.com/test-bucket?versioning")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(body))
+            .unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![event(), event()]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_versioning_status(&client, "test-bucket").await.is_ok());
+
+    let resp = client.get_bucket_versioning()
+        .bucket("test-bucket")
+        .send()
+        .await
+        .unwrap();
+
+    format_versioning_status("test-bucket", &resp)
+}
+
+#[tokio::test]
+async fn test_versioning_status_enabled() {
+    let output = versioning_output(r#"<?xml version="1.0" encoding="UTF-8"?>
+        <VersioningConfiguration>
+            <Status>Enabled</Status>
+            <MfaDelete>Disabled</MfaDelete>
+        </VersioningConfiguration>"#).await;
+    assert!(output.contains("Versioning: Enabled"));
+    assert!(output.contains("MFA Delete: Disabled"));
+}
+
+#[tokio::test]
+async fn test_versioning_status_suspended() {
+    let output = versioning_output(r#"<?xml version="1.0" encoding="UTF-8"?>
+        <VersioningConfiguration>
+            <Status>Suspended</Status>
+        </VersioningConfiguration>"#).await;
+    assert!(output.contains("Versioning: Suspended"));
+    assert!(output.contains("MFA Delete: Not set"));
+}
+
+#[tokio::test]
+async fn test_versioning_status_never_configured() {
+    let output = versioning_output(r#"<?xml version="1.0" encoding="UTF-8"?>
+        <VersioningConfiguration/>"#).await;
+    assert!(output.contains("Versioning: Not set"));
+    assert!(output.contains("MFA Delete: Not set"));
+}
+
+fn head_bucket_client(status: u16) -> Client {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("HEAD")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(status)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    Client::from_conf(config)
+}
+
+#[tokio::test]
+async fn test_bucket_exists() {
+    assert!(bucket_exists(&head_bucket_client(200), "test-bucket").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_bucket_exists_not_found() {
+    assert!(!bucket_exists(&head_bucket_client(404), "test-bucket").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_bucket_exists_forbidden() {
+    let err = bucket_exists(&head_bucket_client(403), "test-bucket").await.unwrap_err();
+    assert!(err.to_string().contains("Access denied"));
+}
+
+#[tokio::test]
+async fn test_create_bucket_already_owned() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://my-test-bucket.s3.eu-west-1.amazonaws.com/")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(409)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>BucketAlreadyOwnedByYou</Code>
+                        <Message>Your previous request to create the named bucket succeeded and you already own it.</Message>
+                    </Error>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("eu-west-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let err = create_bucket(&client, "my-test-bucket", "eu-west-1", None, None, None, None, vec![], false).await.unwrap_err();
+    assert_eq!(err.to_string(), "Bucket 'my-test-bucket' already exists and is owned by you");
+}
+
+#[tokio::test]
+async fn test_create_bucket_already_exists_checks_access() {
+    use aws_smithy_runtime::client::http::test_util::ReplayEvent;
+
+    let create_then_head = |head_status: u16| {
+        let http_client = StaticReplayClient::new(vec![
+            ReplayEvent::new(
+                http::Request::builder()
+                    .method("PUT")
+                    .uri("https://my-test-bucket.s3.us-east-1.amazonaws.com/")
+                    .body(SdkBody::empty())
+                    .unwrap(),
+                http::Response::builder()
+                    .status(409)
+                    .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                        <Error>
+                            <Code>BucketAlreadyExists</Code>
+                            <Message>The requested bucket name is not available.</Message>
+                        </Error>"#))
+                    .unwrap(),
+            ),
+            ReplayEvent::new(
+                http::Request::builder()
+                    .method("HEAD")
+                    .uri("https://my-test-bucket.s3.us-east-1.amazonaws.com/")
+                    .body(SdkBody::empty())
+                    .unwrap(),
+                http::Response::builder().status(head_status).body(SdkBody::empty()).unwrap(),
+            ),
+        ]);
+        let config = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new("us-east-1"))
+            .http_client(http_client.clone())
+            .build();
+        (Client::from_conf(config), http_client)
+    };
+
+    // A bucket we can reach is ours
+    let (client, http_client) = create_then_head(200);
+    let err = create_bucket(&client, "my-test-bucket", "us-east-1", None, None, None, None, vec![], false).await.unwrap_err();
+    assert_eq!(err.to_string(), "Bucket 'my-test-bucket' already exists and is owned by you");
+    assert_eq!(http_client.actual_requests().nth(1).unwrap().method(), "HEAD");
+
+    let (client, _) = create_then_head(403);
+    let err = create_bucket(&client, "my-test-bucket", "us-east-1", None, None, None, None, vec![], false).await.unwrap_err();
+    assert_eq!(err.to_string(), "Bucket name 'my-test-bucket' is already taken by another account");
+}
+
+#[tokio::test]
+async fn test_put_bucket_website() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/site-bucket?website")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = s3sh::buckets::put_bucket_website(&client, "site-bucket", "index.html", Some("404.html"), false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    let body = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<Suffix>index.html</Suffix>"));
+    assert!(body.contains("<Key>404.html</Key>"));
+}
+
+#[tokio::test]
+async fn test_get_bucket_website() {
+    let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <WebsiteConfiguration>
+            <IndexDocument><Suffix>index.html</Suffix></IndexDocument>
+            <ErrorDocument><Key>error.html</Key></ErrorDocument>
+            <RoutingRules>
+                <RoutingRule>
+                    <Condition><KeyPrefixEquals>docs/</KeyPrefixEquals></Condition>
+                    <Redirect><ReplaceKeyPrefixWith>documents/</ReplaceKeyPrefixWith></Redirect>
+                </RoutingRule>
+            </RoutingRules>
+        </WebsiteConfiguration>"#;
+    let event = || aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("GET")
+            .uri("https://s3.us-east-1.amazonaws.com/site-bucket?website")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(body))
+            .unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![event(), event()]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_bucket_website(&client, "site-bucket").await.is_ok());
+
+    let website = client.get_bucket_website().bucket("site-bucket").send().await.unwrap();
+    let output = format_bucket_website(&website);
+    assert!(output.contains("Index Document: index.html"));
+    assert!(output.contains("Error Document: error.html"));
+    assert!(output.contains("Redirect Rule: prefix 'docs/' -> prefix 'documents/'"));
+}
+
+#[tokio::test]
+async fn test_get_bucket_website_not_configured() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/site-bucket?website")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(404)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>NoSuchWebsiteConfiguration</Code>
+                        <Message>The specified bucket does not have a website configuration</Message>
+                    </Error>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_bucket_website(&client, "site-bucket").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_get_bucket_notifications() {
+    let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <NotificationConfiguration>
+            <QueueConfiguration>
+                <Id>uploads</Id>
+                <Queue>arn:aws:sqs:us-east-1:123456789012:uploads</Queue>
+                <Event>s3:ObjectCreated:Put</Event>
+                <Event>s3:ObjectRemoved:*</Event>
+                <Filter>
+                    <S3Key>
+                        <FilterRule><Name>prefix</Name><Value>images/</Value></FilterRule>
+                    </S3Key>
+                </Filter>
+            </QueueConfiguration>
+        </NotificationConfiguration>"#;
+    let event = || aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("GET")
+            .uri("https://s3.us-east-1.amazonaws.com/events-bucket?notification")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(body))
+            .unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![event(), event()]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_bucket_notifications(&client, "events-bucket").await.is_ok());
+
+    let notifications = client.get_bucket_notification_configuration().bucket("events-bucket").send().await.unwrap();
+    let output = format_bucket_notifications(&notifications);
+    assert!(output.contains("Queue"));
+    assert!(output.contains("arn:aws:sqs:us-east-1:123456789012:uploads"));
+    assert!(output.contains("s3:ObjectCreated:Put, s3:ObjectRemoved:*"));
+    assert!(output.contains("prefix=images/"));
+}
+
+#[tokio::test]
+async fn test_get_bucket_notifications_empty() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/events-bucket?notification")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <NotificationConfiguration/>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let notifications = client.get_bucket_notification_configuration().bucket("events-bucket").send().await.unwrap();
+    assert!(format_bucket_notifications(&notifications).contains("No notifications configured."));
+}
+
+#[tokio::test]
+async fn test_get_bucket_replication() {
+    let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <ReplicationConfiguration>
+            <Role>arn:aws:iam::123456789012:role/replication</Role>
+            <Rule>
+                <ID>to-dr</ID>
+                <Priority>1</Priority>
+                <Status>Enabled</Status>
+                <Filter><Prefix></Prefix></Filter>
+                <DeleteMarkerReplication><Status>Disabled</Status></DeleteMarkerReplication>
+                <Destination>
+                    <Bucket>arn:aws:s3:::dr-bucket</Bucket>
+                    <StorageClass>STANDARD_IA</StorageClass>
+                </Destination>
+            </Rule>
+            <Rule>
+                <ID>logs</ID>
+                <Priority>2</Priority>
+                <Status>Disabled</Status>
+                <Filter><Prefix>logs/</Prefix></Filter>
+                <DeleteMarkerReplication><Status>Disabled</Status></DeleteMarkerReplication>
+                <Destination>
+                    <Bucket>arn:aws:s3:::log-archive</Bucket>
+                </Destination>
+            </Rule>
+        </ReplicationConfiguration>"#;
+    let event = || aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("GET")
+            .uri("https://s3.us-east-1.amazonaws.com/source-bucket?replication")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(body))
+            .unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![event(), event()]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_bucket_replication(&client, "source-bucket").await.is_ok());
+
+    let resp = client.get_bucket_replication().bucket("source-bucket").send().await.unwrap();
+    let output = format_bucket_replication(resp.replication_configuration().unwrap());
+    assert!(output.contains("Role: arn:aws:iam::123456789012:role/replication"));
+    assert!(output.contains("to-dr"));
+    assert!(output.contains("arn:aws:s3:::dr-bucket (STANDARD_IA)"));
+    assert!(output.contains("logs"));
+    assert!(output.contains("Disabled"));
+    assert!(output.contains("arn:aws:s3:::log-archive"));
+}
+
+#[tokio::test]
+async fn test_get_bucket_replication_not_configured() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/source-bucket?replication")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(404)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>ReplicationConfigurationNotFoundError</Code>
+                        <Message>The replication configuration was not found</Message>
+                    </Error>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_bucket_replication(&client, "source-bucket").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_delete_bucket_replication() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("DELETE")
+                .uri("https://s3.us-east-1.amazonaws.com/source-bucket?replication")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(204)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::delete_bucket_replication(&client, "source-bucket", false).await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method(), "DELETE");
+    assert!(requests[0].uri().contains("replication"));
+}
+
+#[tokio::test]
+async fn test_get_bucket_encryption() {
+    let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <ServerSideEncryptionConfiguration>
+            <Rule>
+                <ApplyServerSideEncryptionByDefault>
+                    <SSEAlgorithm>AES256</SSEAlgorithm>
+                </ApplyServerSideEncryptionByDefault>
+                <BucketKeyEnabled>false</BucketKeyEnabled>
+            </Rule>
+        </ServerSideEncryptionConfiguration>"#;
+    let event = || aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("GET")
+            .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?encryption")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(body))
+            .unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![event(), event()]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_bucket_encryption(&client, "test-bucket").await.is_ok());
+
+    let resp = client.get_bucket_encryption().bucket("test-bucket").send().await.unwrap();
+    let output = format_bucket_encryption(resp.server_side_encryption_configuration().unwrap());
+    assert!(output.contains("AES256"));
+    assert!(output.contains("disabled"));
+    assert!(!output.contains("aws:kms"));
+}
+
+#[tokio::test]
+async fn test_get_bucket_encryption_not_configured() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?encryption")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(404)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>ServerSideEncryptionConfigurationNotFoundError</Code>
+                        <Message>The server side encryption configuration was not found</Message>
+                    </Error>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_bucket_encryption(&client, "test-bucket").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_delete_bucket_encryption() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("DELETE")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?encryption")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(204)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::delete_bucket_encryption(&client, "test-bucket", false).await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method(), "DELETE");
+    assert!(requests[0].uri().contains("encryption"));
+}
+
+#[tokio::test]
+async fn test_get_bucket_tags() {
+    let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <Tagging>
+            <TagSet>
+                <Tag><Key>Project</Key><Value>s3sh</Value></Tag>
+                <Tag><Key>Env</Key><Value>Dev</Value></Tag>
+            </TagSet>
+        </Tagging>"#;
+    let event = || aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("GET")
+            .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?tagging")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(body))
+            .unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![event(), event()]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_bucket_tags(&client, "test-bucket").await.is_ok());
+
+    let resp = client.get_bucket_tagging().bucket("test-bucket").send().await.unwrap();
+    let output = format_tags(resp.tag_set());
+    assert!(output.contains("Project"));
+    assert!(output.contains("s3sh"));
+    // Sorted by key
+    assert!(output.find("Env").unwrap() < output.find("Project").unwrap());
+}
+
+#[tokio::test]
+async fn test_get_bucket_tags_not_set() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?tagging")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(404)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>NoSuchTagSet</Code>
+                        <Message>The TagSet does not exist</Message>
+                    </Error>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_bucket_tags(&client, "test-bucket").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_delete_bucket_tags() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("DELETE")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?tagging")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(204)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::delete_bucket_tags(&client, "test-bucket", false).await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method(), "DELETE");
+    assert!(requests[0].uri().contains("tagging"));
+}
+
+#[tokio::test]
+async fn test_update_bucket_enables_mfa_delete() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/secure-bucket?versioning")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mfa = "arn:aws:iam::123456789012:mfa/root 123456";
+    let result = s3sh::buckets::update_bucket(&client, "secure-bucket", None, Some(true), Some(true), None, vec![], Some(mfa), false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests[0].headers().get("x-amz-mfa"), Some(mfa));
+    let body = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<Status>Enabled</Status>"));
+    assert!(body.contains("<MfaDelete>Enabled</MfaDelete>"));
+}
+
+#[tokio::test]
+async fn test_update_bucket_mfa_delete_requires_mfa() {
+    let http_client = StaticReplayClient::new(vec![]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = s3sh::buckets::update_bucket(&client, "secure-bucket", None, Some(true), Some(true), None, vec![], None, false).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_update_bucket_sets_kms_key() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/secure-bucket?encryption")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let key = "arn:aws:kms:us-east-1:123456789012:key/1234abcd-12ab-34cd-56ef-1234567890ab";
+    let encryption = parse_bucket_encryption("aws:kms", Some(key), true).unwrap();
+    let result = s3sh::buckets::update_bucket(&client, "secure-bucket", None, None, None, Some(encryption), vec![], None, false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    let body = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<SSEAlgorithm>aws:kms</SSEAlgorithm>"));
+    assert!(body.contains(&format!("<KMSMasterKeyID>{}</KMSMasterKeyID>", key)));
+    assert!(body.contains("<BucketKeyEnabled>true</BucketKeyEnabled>"));
+}
+
+#[test]
+fn test_parse_bucket_encryption() {
+    let aes = parse_bucket_encryption("AES256", None, false).unwrap();
+    assert_eq!(aes.algorithm, aws_sdk_s3::types::ServerSideEncryption::Aes256);
+    assert!(aes.kms_key_id.is_none());
+
+    let kms = parse_bucket_encryption("aws:kms", Some("alias/my-key"), false).unwrap();
+    assert_eq!(kms.kms_key_id.as_deref(), Some("alias/my-key"));
+
+    assert!(parse_bucket_encryption("AES256", Some("alias/my-key"), false).is_err());
+    assert!(parse_bucket_encryption("aes", None, false).is_err());
+}
+
+#[test]
+fn test_parse_bucket_canned_acl() {
+    assert_eq!(parse_bucket_canned_acl("public-read").unwrap(), aws_sdk_s3::types::BucketCannedAcl::PublicRead);
+    assert!(parse_bucket_canned_acl("authenticated-read").is_ok());
+    assert!(parse_bucket_canned_acl("bucket-owner-full-control").is_err());
+    assert!(parse_bucket_canned_acl("public").is_err());
+}
+
+#[tokio::test]
+async fn test_get_bucket_acl() {
+    let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <AccessControlPolicy xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+            <Owner><ID>owner-id</ID><DisplayName>alice</DisplayName></Owner>
+            <AccessControlList>
+                <Grant>
+                    <Grantee xsi:type="CanonicalUser"><ID>owner-id</ID><DisplayName>alice</DisplayName></Grantee>
+                    <Permission>FULL_CONTROL</Permission>
+                </Grant>
+                <Grant>
+                    <Grantee xsi:type="Group"><URI>http://acs.amazonaws.com/groups/global/AllUsers</URI></Grantee>
+                    <Permission>READ</Permission>
+                </Grant>
+            </AccessControlList>
+        </AccessControlPolicy>"#;
+    let event = || aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("GET")
+            .uri("https://s3.us-east-1.amazonaws.com/acl-bucket?acl")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(body))
+            .unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![event(), event()]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_bucket_acl(&client, "acl-bucket").await.is_ok());
+
+    let acl = client.get_bucket_acl().bucket("acl-bucket").send().await.unwrap();
+    let output = format_acl(acl.owner(), acl.grants());
+    assert!(output.contains("Owner: alice"));
+    assert!(output.contains("FULL_CONTROL"));
+    assert!(output.contains("http://acs.amazonaws.com/groups/global/AllUsers"));
+    assert!(output.contains("Group"));
+    assert!(output.contains("READ"));
+}
+
+#[tokio::test]
+async fn test_put_bucket_acl_canned() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/acl-bucket?acl")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::put_bucket_acl(&client, "acl-bucket", Some("private"), &Grants::default(), false).await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].headers().get("x-amz-acl"), Some("private"));
+}
+
+#[tokio::test]
+async fn test_list_bucket_inventory() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?inventory")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListInventoryConfigurationsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                        <InventoryConfiguration>
+                            <Id>daily-report</Id>
+                            <IsEnabled>true</IsEnabled>
+                            <Destination>
+                                <S3BucketDestination>
+                                    <Bucket>arn:aws:s3:::inventory-bucket</Bucket>
+                                    <Format>CSV</Format>
+                                    <Prefix>reports</Prefix>
+                                </S3BucketDestination>
+                            </Destination>
+                            <Schedule><Frequency>Daily</Frequency></Schedule>
+                            <IncludedObjectVersions>Current</IncludedObjectVersions>
+                        </InventoryConfiguration>
+                        <IsTruncated>false</IsTruncated>
+                    </ListInventoryConfigurationsResult>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::list_bucket_inventory(&client, "test-bucket").await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].uri().contains("inventory"));
+}
+
+#[tokio::test]
+async fn test_list_bucket_inventory_empty() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?inventory")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListInventoryConfigurationsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                        <IsTruncated>false</IsTruncated>
+                    </ListInventoryConfigurationsResult>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::list_bucket_inventory(&client, "test-bucket").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_list_bucket_analytics() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?analytics")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketAnalyticsConfigurationResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                        <AnalyticsConfiguration>
+                            <Id>logs-analysis</Id>
+                            <Filter>
+                                <And>
+                                    <Prefix>logs/</Prefix>
+                                    <Tag><Key>env</Key><Value>prod</Value></Tag>
+                                </And>
+                            </Filter>
+                            <StorageClassAnalysis>
+                                <DataExport>
+                                    <OutputSchemaVersion>V_1</OutputSchemaVersion>
+                                    <Destination>
+                                        <S3BucketDestination>
+                                            <Format>CSV</Format>
+                                            <Bucket>arn:aws:s3:::analytics-bucket</Bucket>
+                                            <Prefix>exports</Prefix>
+                                        </S3BucketDestination>
+                                    </Destination>
+                                </DataExport>
+                            </StorageClassAnalysis>
+                        </AnalyticsConfiguration>
+                        <AnalyticsConfiguration>
+                            <Id>everything</Id>
+                            <StorageClassAnalysis/>
+                        </AnalyticsConfiguration>
+                        <IsTruncated>false</IsTruncated>
+                    </ListBucketAnalyticsConfigurationResult>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?analytics")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketAnalyticsConfigurationResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                        <IsTruncated>false</IsTruncated>
+                    </ListBucketAnalyticsConfigurationResult>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let resp = client.list_bucket_analytics_configurations().bucket("test-bucket").send().await.unwrap();
+    let table = s3sh::buckets::format_analytics_configurations(resp.analytics_configuration_list());
+    assert!(table.contains("logs-analysis"));
+    assert!(table.contains("prefix=logs/, env=prod"));
+    assert!(table.contains("analytics-bucket/exports (CSV)"));
+    assert!(table.contains("everything"));
+    assert!(table.contains("(whole bucket)"));
+
+    // No configurations is a note, not an error
+    assert!(s3sh::buckets::list_bucket_analytics(&client, "test-bucket").await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].uri().contains("analytics"));
+}
+
+#[tokio::test]
+async fn test_delete_bucket_metrics() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("DELETE")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?metrics&id=EntireBucket")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(204)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::delete_bucket_metrics(&client, "test-bucket", "EntireBucket", false).await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method(), "DELETE");
+    assert!(requests[0].uri().contains("metrics"));
+    assert!(requests[0].uri().contains("id=EntireBucket"));
+}
+
+#[test]
+fn test_format_metrics_configurations() {
+    use aws_sdk_s3::types::{MetricsConfiguration, MetricsFilter};
+
+    let configurations = vec![
+        MetricsConfiguration::builder().id("EntireBucket").build().unwrap(),
+        MetricsConfiguration::builder()
+            .id("images")
+            .filter(MetricsFilter::Prefix("images/".to_string()))
+            .build()
+            .unwrap(),
+        MetricsConfiguration::builder()
+            .id("via-access-point")
+            .filter(MetricsFilter::AccessPointArn("arn:aws:s3:us-east-1:123456789012:accesspoint/ap".to_string()))
+            .build()
+            .unwrap(),
+    ];
+
+    let table = s3sh::buckets::format_metrics_configurations(&configurations);
+    assert!(table.contains("EntireBucket"));
+    assert!(table.contains("(whole bucket)"));
+    assert!(table.contains("prefix=images/"));
+    assert!(table.contains("access-point=arn:aws:s3:us-east-1:123456789012:accesspoint/ap"));
+}
+
+#[test]
+fn test_format_inventory_configurations() {
+    use aws_sdk_s3::types::{
+        InventoryConfiguration, InventoryDestination, InventoryFormat, InventoryFrequency,
+        InventoryIncludedObjectVersions, InventoryS3BucketDestination, InventorySchedule,
+    };
+
+    let configuration = InventoryConfiguration::builder()
+        .id("weekly-parquet")
+        .is_enabled(false)
+        .destination(
+            InventoryDestination::builder()
+                .s3_bucket_destination(
+                    InventoryS3BucketDestination::builder()
+                        .bucket("arn:aws:s3:::inventory-bucket")
+                        .format(InventoryFormat::Parquet)
+                        .build()
+                        .unwrap(),
+                )
+                .build(),
+        )
+        .schedule(InventorySchedule::builder().frequency(InventoryFrequency::Weekly).build().unwrap())
+        .included_object_versions(InventoryIncludedObjectVersions::All)
+        .build()
+        .unwrap();
+
+    let rendered = format_inventory_configurations(&[configuration]);
+    assert!(rendered.contains("weekly-parquet"));
+    assert!(rendered.contains("inventory-bucket"));
+    assert!(!rendered.contains("arn:aws:s3:::"));
+    assert!(rendered.contains("Parquet"));
+    assert!(rendered.contains("Weekly"));
+    assert!(rendered.contains("All"));
+}
+
+#[tokio::test]
+async fn test_delete_bucket_inventory() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("DELETE")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?inventory&id=daily-report")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(204)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::delete_bucket_inventory(&client, "test-bucket", "daily-report", false).await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method(), "DELETE");
+    assert!(requests[0].uri().contains("inventory"));
+    assert!(requests[0].uri().contains("id=daily-report"));
+}
+
+#[tokio::test]
+async fn test_enable_bucket_logging_and_read_back() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?logging")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?logging")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <BucketLoggingStatus xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                        <LoggingEnabled>
+                            <TargetBucket>log-bucket</TargetBucket>
+                            <TargetPrefix>logs/</TargetPrefix>
+                        </LoggingEnabled>
+                    </BucketLoggingStatus>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::put_bucket_logging(&client, "test-bucket", Some("log-bucket"), "logs/", false).await.is_ok());
+
+    let resp = client.get_bucket_logging().bucket("test-bucket").send().await.unwrap();
+    let rendered = format_bucket_logging(&resp);
+    assert!(rendered.contains("enabled"));
+    assert!(rendered.contains("Target Bucket: log-bucket"));
+    assert!(rendered.contains("Target Prefix: logs/"));
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].method(), "PUT");
+    let body = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<TargetBucket>log-bucket</TargetBucket>"));
+    assert!(body.contains("<TargetPrefix>logs/</TargetPrefix>"));
+}
+
+#[tokio::test]
+async fn test_disable_bucket_logging_sends_empty_status() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?logging")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::put_bucket_logging(&client, "test-bucket", None, "", false).await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    let body = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("BucketLoggingStatus"));
+    assert!(!body.contains("LoggingEnabled"));
+}
+
+#[test]
+fn test_format_bucket_logging_disabled() {
+    let output = aws_sdk_s3::operation::get_bucket_logging::GetBucketLoggingOutput::builder().build();
+    assert!(format_bucket_logging(&output).contains("disabled"));
+}
+
+#[tokio::test]
+async fn test_get_bucket_accelerate() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?accelerate")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <AccelerateConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                        <Status>Enabled</Status>
+                    </AccelerateConfiguration>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_bucket_accelerate(&client, "test-bucket").await.is_ok());
+    http_client.relaxed_requests_match();
+}
+
+#[test]
+fn test_format_accelerate_status() {
+    use aws_sdk_s3::types::BucketAccelerateStatus;
+
+    let enabled = format_accelerate_status("test-bucket", Some(&BucketAccelerateStatus::Enabled));
+    assert!(enabled.contains("Enabled"));
+    assert!(enabled.contains("test-bucket.s3-accelerate.amazonaws.com"));
+
+    let suspended = format_accelerate_status("test-bucket", Some(&BucketAccelerateStatus::Suspended));
+    assert!(suspended.contains("Suspended"));
+    assert!(!suspended.contains("s3-accelerate"));
+
+    assert!(format_accelerate_status("test-bucket", None).contains("NotSet"));
+}
+
+#[tokio::test]
+async fn test_put_bucket_accelerate() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?accelerate")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::put_bucket_accelerate(&client, "test-bucket", false, false).await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method(), "PUT");
+    let body = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<Status>Suspended</Status>"));
+}
+
+#[test]
+fn test_parse_payer() {
+    assert_eq!(parse_payer("Requester").unwrap(), aws_sdk_s3::types::Payer::Requester);
+    assert_eq!(parse_payer("bucketowner").unwrap(), aws_sdk_s3::types::Payer::BucketOwner);
+    assert!(parse_payer("someone").is_err());
+}
+
+#[tokio::test]
+async fn test_request_payment_set_and_get() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?requestPayment")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?requestPayment")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <RequestPaymentConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                        <Payer>Requester</Payer>
+                    </RequestPaymentConfiguration>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::put_bucket_request_payment(&client, "test-bucket", "requester", false).await.is_ok());
+    assert!(s3sh::buckets::get_bucket_request_payment(&client, "test-bucket").await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    let body = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<Payer>Requester</Payer>"));
+    assert!(requests[1].uri().contains("requestPayment"));
+}
+
+#[test]
+fn test_suggest_bucket_name() {
+    let candidates = ["my-bucket", "my-logs", "production-assets", "staging-assets"];
+
+    assert_eq!(suggest_bucket_name("my-bukcet", &candidates), Some("my-bucket"));
+    assert_eq!(suggest_bucket_name("mybucket", &candidates), Some("my-bucket"));
+    assert_eq!(suggest_bucket_name("prodution-asets", &candidates), Some("production-assets"));
+    // Nothing close enough, and an exact match is not a typo
+    assert_eq!(suggest_bucket_name("analytics", &candidates), None);
+    assert_eq!(suggest_bucket_name("my-bucket", &["my-bucket"]), None);
+    assert_eq!(suggest_bucket_name("my-bucket", &[]), None);
+}
+
+#[tokio::test]
+async fn test_suggest_missing_bucket() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bukcet.s3.us-east-1.amazonaws.com/?list-type=2")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(404)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>NoSuchBucket</Code>
+                        <Message>The specified bucket does not exist</Message>
+                    </Error>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://s3.us-east-1.amazonaws.com/?x-id=ListBuckets")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<ListAllMyBucketsResult>
+                    <Buckets>
+                        <Bucket><Name>test-bucket</Name></Bucket>
+                        <Bucket><Name>other</Name></Bucket>
+                    </Buckets>
+                </ListAllMyBucketsResult>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let err = s3sh::objects::fetch_objects(&client, "test-bukcet", None, None, None).await.unwrap_err();
+    let err = suggest_missing_bucket(&client, "test-bukcet", err).await;
+    assert!(err.to_string().contains("did you mean 'test-bucket'?"), "{}", err);
+    assert_eq!(s3sh::errors::classify_error(&err), s3sh::errors::FailureKind::NotFound);
+}
+
+fn usage_page(body: &'static str) -> aws_smithy_runtime::client::http::test_util::ReplayEvent {
+    aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .uri("https://data-bucket.s3.us-east-1.amazonaws.com/?list-type=2&prefix=data%2F")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(body))
+            .unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_bucket_usage_over_pages() {
+    let pages = || vec![
+        usage_page(r#"<?xml version="1.0" encoding="UTF-8"?>
+            <ListBucketResult>
+                <Name>data-bucket</Name>
+                <Prefix>data/</Prefix>
+                <IsTruncated>true</IsTruncated>
+                <NextContinuationToken>page-2</NextContinuationToken>
+                <Contents><Key>data/2024/a.csv</Key><Size>1000</Size></Contents>
+                <Contents><Key>data/2024/b.csv</Key><Size>2000</Size></Contents>
+                <Contents><Key>data/readme.txt</Key><Size>24</Size></Contents>
+            </ListBucketResult>"#),
+        usage_page(r#"<?xml version="1.0" encoding="UTF-8"?>
+            <ListBucketResult>
+                <Name>data-bucket</Name>
+                <Prefix>data/</Prefix>
+                <IsTruncated>false</IsTruncated>
+                <Contents><Key>data/2025/c.csv</Key><Size>4096</Size></Contents>
+            </ListBucketResult>"#),
+    ];
+
+    let http_client = StaticReplayClient::new(pages().into_iter().chain(pages()).collect());
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(bucket_usage(&client, "data-bucket", Some("data/"), true, false, OutputFormat::Table).await.is_ok());
+
+    let (objects, _) = s3sh::objects::fetch_objects(&client, "data-bucket", Some("data/"), None, None).await.unwrap();
+    let (total, groups) = summarize_usage(&objects, Some("data/"), true);
+    assert_eq!(total, Usage { count: 4, bytes: 7120 });
+    assert_eq!(groups, vec![
+        (NO_SUB_PREFIX.to_string(), Usage { count: 1, bytes: 24 }),
+        ("data/2024/".to_string(), Usage { count: 2, bytes: 3000 }),
+        ("data/2025/".to_string(), Usage { count: 1, bytes: 4096 }),
+    ]);
+
+    // The second page was requested with the continuation token
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 4);
+    assert!(requests[1].uri().contains("continuation-token=page-2"));
+
+    let (_, groups) = summarize_usage(&objects, Some("data/"), false);
+    assert!(groups.is_empty());
+}
+
+#[test]
+fn test_parse_object_ownership() {
+    assert_eq!(parse_object_ownership("BucketOwnerEnforced").unwrap(), aws_sdk_s3::types::ObjectOwnership::BucketOwnerEnforced);
+    assert_eq!(parse_object_ownership("bucketownerpreferred").unwrap(), aws_sdk_s3::types::ObjectOwnership::BucketOwnerPreferred);
+    assert_eq!(parse_object_ownership("ObjectWriter").unwrap(), aws_sdk_s3::types::ObjectOwnership::ObjectWriter);
+    assert!(parse_object_ownership("Owner").is_err());
+}
+
+#[tokio::test]
+async fn test_ownership_controls_set_and_get() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?ownershipControls")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?ownershipControls")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <OwnershipControls xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                        <Rule><ObjectOwnership>BucketOwnerEnforced</ObjectOwnership></Rule>
+                    </OwnershipControls>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::put_bucket_ownership_controls(&client, "test-bucket", "BucketOwnerEnforced", false).await.is_ok());
+    assert!(s3sh::buckets::get_bucket_ownership_controls(&client, "test-bucket").await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].method(), "PUT");
+    let body = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<ObjectOwnership>BucketOwnerEnforced</ObjectOwnership>"), "{}", body);
+    assert!(requests[1].uri().contains("ownershipControls"));
+}
+
+#[tokio::test]
+async fn test_ownership_controls_get_not_configured() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?ownershipControls")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(404)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>OwnershipControlsNotFoundError</Code>
+                        <Message>The bucket ownership controls were not found</Message>
+                    </Error>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(s3sh::buckets::get_bucket_ownership_controls(&client, "test-bucket").await.is_ok());
+    assert!(s3sh::buckets::put_bucket_ownership_controls(&client, "test-bucket", "nobody", false).await.is_err());
+    assert_eq!(http_client.actual_requests().count(), 1);
+}
+
+const BUCKET_NAMES: [&str; 5] = ["prod-logs", "prod-assets", "staging-logs", "dev-scratch", "Prod-Archive"];
+
+fn filtered(filter: &NameFilter) -> Vec<&'static str> {
+    BUCKET_NAMES.iter().copied().filter(|name| filter.matches(name)).collect()
+}
+
+#[test]
+fn test_name_filter_substring() {
+    assert_eq!(filtered(&NameFilter::new("logs", false).unwrap()), vec!["prod-logs", "staging-logs"]);
+    // Case-insensitive, and regex characters are taken literally
+    assert_eq!(filtered(&NameFilter::new("PROD", false).unwrap()), vec!["prod-logs", "prod-assets", "Prod-Archive"]);
+    assert!(filtered(&NameFilter::new("prod.*", false).unwrap()).is_empty());
+}
+
+#[test]
+fn test_name_filter_regex() {
+    assert_eq!(filtered(&NameFilter::new("^prod-", true).unwrap()), vec!["prod-logs", "prod-assets"]);
+    assert_eq!(filtered(&NameFilter::new("-(logs|scratch)$", true).unwrap()), vec!["prod-logs", "staging-logs", "dev-scratch"]);
+
+    let err = NameFilter::new("prod-(", true).unwrap_err();
+    assert!(err.to_string().contains("Invalid --filter regex"), "{}", err);
+    assert_eq!(s3sh::errors::classify_error(&err), s3sh::errors::FailureKind::Usage);
 }
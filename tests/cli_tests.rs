@@ -0,0 +1,130 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::process::Command;
+
+/// Answer every request with an empty 204, as S3 does for a successful
+/// DeleteObject, and return the endpoint URL.
+fn serve_no_content() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                }
+            }
+            let mut body = vec![0; content_length];
+            let _ = reader.read_exact(&mut body);
+
+            let _ = stream.write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        }
+    });
+
+    endpoint
+}
+
+/// Run s3sh against `endpoint` with static credentials and no user config.
+fn run_against(endpoint: &str, args: &[&str]) -> std::process::Output {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("s3sh.toml"),
+        format!("endpoint_url = \"{}\"\nforce_path_style = true\nregion = \"us-east-1\"\n", endpoint),
+    ).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_s3sh"))
+        .args(args)
+        .current_dir(dir.path())
+        .env("HOME", dir.path())
+        .env("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE")
+        .env("AWS_SECRET_ACCESS_KEY", "secret")
+        .env("AWS_EC2_METADATA_DISABLED", "true")
+        .env_remove("AWS_PROFILE")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_bash_completions() {
+    let output = Command::new(env!("CARGO_BIN_EXE_s3sh"))
+        .args(["completions", "bash"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let script = String::from_utf8(output.stdout).unwrap();
+    assert!(script.contains("bucket"));
+    assert!(script.contains("object"));
+    assert!(script.contains("complete -F _s3sh"));
+}
+
+#[test]
+fn test_version_command_needs_no_credentials() {
+    let output = Command::new(env!("CARGO_BIN_EXE_s3sh"))
+        .arg("version")
+        .env("AWS_EC2_METADATA_DISABLED", "true")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains(env!("CARGO_PKG_VERSION")));
+    assert!(text.contains("aws-sdk-s3: "));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_s3sh"))
+        .arg("--version")
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), format!("s3sh {}", env!("CARGO_PKG_VERSION")));
+}
+
+#[test]
+fn test_completions_hidden_from_help() {
+    let output = Command::new(env!("CARGO_BIN_EXE_s3sh"))
+        .arg("--help")
+        .output()
+        .unwrap();
+
+    let help = String::from_utf8(output.stdout).unwrap();
+    assert!(help.contains("bucket"));
+    assert!(!help.contains("completions"));
+}
+
+#[test]
+fn test_delete_prints_confirmation() {
+    let endpoint = serve_no_content();
+
+    let output = run_against(&endpoint, &["object", "delete", "test-bucket", "old.txt", "--yes"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8(output.stdout).unwrap().contains("deleted"));
+}
+
+#[test]
+fn test_quiet_delete_prints_nothing() {
+    let endpoint = serve_no_content();
+
+    let output = run_against(&endpoint, &["--quiet", "object", "delete", "test-bucket", "old.txt", "--yes"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.stdout.is_empty(), "{}", String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn test_interactive_requires_terminal() {
+    let endpoint = serve_no_content();
+
+    let output = run_against(&endpoint, &["interactive"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("needs a terminal"));
+}
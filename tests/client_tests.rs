@@ -0,0 +1,587 @@
+use s3sh::client::{
+    assume_role_provider, default_session_name, explain_error, validate_region, validate_mfa, create_client,
+    retry_config, timeout_config, client_for_bucket, resolve_bucket_region, ping, with_requester_pays,
+    resolve_path_style, s3_client, s3_endpoint_url, with_page_size, static_credentials, load_config,
+};
+use aws_sdk_s3::Client;
+use aws_sdk_s3::config::{Region, BehaviorVersion, Credentials};
+use aws_smithy_runtime::client::http::test_util::StaticReplayClient;
+use aws_smithy_types::body::SdkBody;
+
+#[test]
+fn test_validate_mfa() {
+    assert!(validate_mfa("arn:aws:iam::123456789012:mfa/root 123456").is_ok());
+    assert!(validate_mfa("GAHT12345678 000111").is_ok());
+
+    assert!(validate_mfa("arn:aws:iam::123456789012:mfa/root").is_err());
+    assert!(validate_mfa("serial 12345").is_err());
+    assert!(validate_mfa("serial abcdef").is_err());
+    assert!(validate_mfa("serial  123456").is_err());
+}
+
+#[test]
+fn test_validate_region() {
+    assert!(validate_region("us-east-1").is_ok());
+    assert!(validate_region("eu-central-2").is_ok());
+    assert!(validate_region("us-gov-west-1").is_ok());
+    // S3-compatible services name their regions freely
+    assert!(validate_region("auto").is_ok());
+    assert!(validate_region("minio").is_ok());
+    assert!(validate_region("garage").is_ok());
+
+    assert!(validate_region("US-EAST-1").is_err());
+    assert!(validate_region("us east 1").is_err());
+    assert!(validate_region("").is_err());
+    assert!(validate_region("-us-east-1").is_err());
+    assert!(validate_region("us-east-").is_err());
+}
+
+#[tokio::test]
+async fn test_auto_region_with_custom_endpoint() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Cloudflare R2 and others expect the region 'auto'
+    validate_region("auto").unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let request = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![0; 4096];
+        let n = socket.read(&mut request).await.unwrap();
+        let body = "<ListAllMyBucketsResult><Buckets><Bucket><Name>r2-bucket</Name></Bucket></Buckets></ListAllMyBucketsResult>";
+        let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+        socket.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&request[..n]).into_owned()
+    });
+
+    let config = aws_config::SdkConfig::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("auto"))
+        .credentials_provider(aws_sdk_s3::config::SharedCredentialsProvider::new(Credentials::new("AKID", "secret", None, None, "test")))
+        .endpoint_url(endpoint)
+        .build();
+
+    let client = s3_client(&config, None, false);
+    let resp = client.list_buckets().send().await.unwrap();
+    assert_eq!(resp.buckets()[0].name(), Some("r2-bucket"));
+    // Signed for the 'auto' region
+    assert!(request.await.unwrap().contains("/auto/s3/aws4_request"));
+}
+
+#[tokio::test]
+async fn test_access_denied_has_friendly_message() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(403)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>AccessDenied</Code>
+                        <Message>Access Denied</Message>
+                    </Error>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let err = s3sh::buckets::list_buckets(&client, s3sh::ui::SortKey::Name, false, None, s3sh::ui::OutputFormat::Table).await.unwrap_err();
+
+    let message = explain_error(err, None).to_string();
+    assert!(message.contains("AccessDenied"));
+    assert!(message.contains("aws configure"));
+}
+
+#[test]
+fn test_other_errors_unchanged() {
+    let err = anyhow::anyhow!("Bucket name must be between 3 and 63 characters");
+
+    let message = explain_error(err, Some("dev")).to_string();
+    assert_eq!(message, "Bucket name must be between 3 and 63 characters");
+}
+
+#[tokio::test]
+async fn test_assume_role_provider_builds() {
+    let base_config = aws_config::defaults(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .credentials_provider(Credentials::new("AKIDEXAMPLE", "secret", None, None, "test"))
+        .load()
+        .await;
+
+    // Building the provider must not call STS; credentials are fetched lazily
+    let _provider = assume_role_provider(
+        &base_config,
+        "arn:aws:iam::123456789012:role/cross-account".to_string(),
+        Some("custom-session".to_string()),
+    ).await;
+
+    let _default_session = assume_role_provider(
+        &base_config,
+        "arn:aws:iam::123456789012:role/cross-account".to_string(),
+        None,
+    ).await;
+}
+
+#[test]
+fn test_default_session_name() {
+    let name = default_session_name();
+    assert!(name.starts_with("s3sh-"));
+    assert!(name["s3sh-".len()..].parse::<u64>().is_ok());
+}
+
+#[test]
+fn test_retry_and_timeout_defaults_untouched() {
+    assert!(retry_config(None).is_none());
+    assert!(timeout_config(None).is_none());
+
+    assert_eq!(retry_config(Some(5)).unwrap().max_attempts(), 6);
+    assert_eq!(
+        timeout_config(Some(30)).unwrap().operation_timeout(),
+        Some(std::time::Duration::from_secs(30))
+    );
+}
+
+#[tokio::test]
+async fn test_create_client_with_retry_settings() {
+    let client = create_client(Some("eu-west-1".to_string()), None, None, None, Some(0), Some(10), None, None, false, None).await;
+
+    assert_eq!(client.config().retry_config().unwrap().max_attempts(), 1);
+    assert_eq!(
+        client.config().timeout_config().unwrap().operation_timeout(),
+        Some(std::time::Duration::from_secs(10))
+    );
+}
+
+#[tokio::test]
+async fn test_client_for_bucket_uses_bucket_region() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://eu-bucket.s3.us-east-1.amazonaws.com/?location")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <LocationConstraint xmlns="http://s3.amazonaws.com/doc/2006-03-01/">eu-west-1</LocationConstraint>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://eu-bucket.s3.eu-west-1.amazonaws.com/?list-type=2")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Name>eu-bucket</Name>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .credentials_provider(Credentials::new("AKIDEXAMPLE", "secret", None, None, "test"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let regional = client_for_bucket(&client, "eu-bucket").await;
+    assert_eq!(regional.config().region().unwrap().as_ref(), "eu-west-1");
+
+    // The region is cached, so resolving again sends no request
+    assert_eq!(resolve_bucket_region(&client, "eu-bucket").await.unwrap(), "eu-west-1");
+
+    s3sh::objects::list_objects(&regional, "eu-bucket", None, None, None, Default::default(), false, false, false, None, s3sh::ui::SortKey::Name, false, s3sh::ui::OutputFormat::Table).await.unwrap();
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].uri().contains("s3.eu-west-1.amazonaws.com"));
+}
+
+#[tokio::test]
+async fn test_client_for_bucket_falls_back_on_lookup_failure() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://hidden-bucket.s3.us-east-1.amazonaws.com/?location")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(403)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>AccessDenied</Code>
+                        <Message>Access Denied</Message>
+                    </Error>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .credentials_provider(Credentials::new("AKIDEXAMPLE", "secret", None, None, "test"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let fallback = client_for_bucket(&client, "hidden-bucket").await;
+    assert_eq!(fallback.config().region().unwrap().as_ref(), "us-east-1");
+}
+
+#[tokio::test]
+async fn test_ping_success() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListAllMyBucketsResult><Buckets></Buckets></ListAllMyBucketsResult>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .credentials_provider(Credentials::new("AKIDEXAMPLE", "secret", None, None, "test"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(ping(&client, None).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_ping_missing_bucket() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("HEAD")
+                .uri("https://missing-bucket.s3.us-east-1.amazonaws.com/")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(404)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .credentials_provider(Credentials::new("AKIDEXAMPLE", "secret", None, None, "test"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let message = ping(&client, Some("missing-bucket")).await.unwrap_err().to_string();
+    assert!(message.contains("missing-bucket"));
+    assert!(message.contains("does not exist (404)"));
+}
+
+#[tokio::test]
+async fn test_requester_pays_header_on_get_object() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://s3.us-east-1.amazonaws.com/paid-bucket/data.csv?x-id=GetObject")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from("a,b\n"))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = with_requester_pays(&Client::from_conf(config));
+
+    let mut out = Vec::new();
+    s3sh::objects::stream_object(&client, "paid-bucket", "data.csv", None, &mut out).await.unwrap();
+    assert_eq!(out, b"a,b\n");
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests[0].headers().get("x-amz-request-payer"), Some("requester"));
+}
+
+fn list_page(keys: &[&str], next_token: Option<&str>) -> aws_smithy_runtime::client::http::test_util::ReplayEvent {
+    let contents = keys.iter()
+        .map(|key| format!("<Contents><Key>{}</Key><Size>1</Size></Contents>", key))
+        .collect::<String>();
+    let next = next_token
+        .map(|token| format!("<IsTruncated>true</IsTruncated><NextContinuationToken>{}</NextContinuationToken>", token))
+        .unwrap_or_else(|| "<IsTruncated>false</IsTruncated>".to_string());
+    aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(format!("<ListBucketResult><Name>test-bucket</Name>{}{}</ListBucketResult>", next, contents)))
+            .unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_page_size_sets_max_keys_on_listings() {
+    let http_client = StaticReplayClient::new(vec![
+        list_page(&["a", "b"], Some("t1")),
+        list_page(&["c"], None),
+        list_page(&["a"], Some("t1")),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = with_page_size(&Client::from_conf(config), 2);
+
+    // Every page is still fetched
+    let (objects, truncated) = s3sh::objects::fetch_objects(&client, "test-bucket", None, None, None).await.unwrap();
+    assert_eq!(objects.len(), 3);
+    assert!(!truncated);
+
+    // A smaller --limit still asks for just what it needs
+    let (objects, _) = s3sh::objects::fetch_objects(&client, "test-bucket", None, None, Some(1)).await.unwrap();
+    assert_eq!(objects.len(), 1);
+
+    let max_keys = http_client.actual_requests()
+        .map(|r| {
+            let uri = r.uri().parse::<http::Uri>().unwrap();
+            let query = uri.query().unwrap_or("").to_string();
+            assert_eq!(query.matches("max-keys=").count(), 1, "{}", query);
+            query.split('&').find_map(|p| p.strip_prefix("max-keys=").map(str::to_string)).unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(max_keys, ["2", "2", "1"]);
+}
+
+#[test]
+fn test_resolve_path_style() {
+    assert!(resolve_path_style(Some("http://localhost:9000"), None));
+    assert!(resolve_path_style(Some("http://127.0.0.1:9000/"), None));
+    assert!(resolve_path_style(Some("http://[::1]:9000"), None));
+    assert!(resolve_path_style(Some("https://minio.example.com"), None));
+    // An amazonaws.com lookalike is not AWS
+    assert!(resolve_path_style(Some("https://s3.notamazonaws.com"), None));
+
+    assert!(!resolve_path_style(None, None));
+    assert!(!resolve_path_style(Some("https://s3.eu-west-1.amazonaws.com"), None));
+    assert!(!resolve_path_style(Some("https://s3.cn-north-1.amazonaws.com.cn"), None));
+    assert!(!resolve_path_style(Some("https://bucket.vpce-123.s3.us-east-1.vpce.amazonaws.com:443"), None));
+
+    // An explicit choice always wins
+    assert!(!resolve_path_style(Some("http://localhost:9000"), Some(false)));
+    assert!(resolve_path_style(Some("https://s3.eu-west-1.amazonaws.com"), Some(true)));
+}
+
+async fn list_uri(endpoint_url: &str, force_path_style: Option<bool>) -> String {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder().uri(endpoint_url).body(SdkBody::empty()).unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from("<ListBucketResult><Name>test-bucket</Name></ListBucketResult>"))
+                .unwrap(),
+        ),
+    ]);
+    let config = aws_config::SdkConfig::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .endpoint_url(endpoint_url)
+        .http_client(http_client.clone())
+        .build();
+
+    let client = s3_client(&config, force_path_style, false);
+    client.list_objects_v2().bucket("test-bucket").send().await.unwrap();
+
+    http_client.actual_requests().next().unwrap().uri().to_string()
+}
+
+#[tokio::test]
+async fn test_s3_client_path_style_for_custom_endpoint() {
+    let uri = list_uri("http://localhost:9000", None).await;
+    assert!(uri.starts_with("http://localhost:9000/test-bucket/?"), "{}", uri);
+    assert!(list_uri("http://localhost:9000", Some(false)).await.starts_with("http://test-bucket.localhost:9000/"));
+    assert!(list_uri("https://s3.us-east-1.amazonaws.com", None).await.starts_with("https://test-bucket.s3.us-east-1.amazonaws.com/"));
+}
+
+#[tokio::test]
+async fn test_s3_endpoint_from_services_section() {
+    use aws_runtime::env_config::file::{EnvConfigFileKind, EnvConfigFiles};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config");
+    std::fs::write(
+        &path,
+        "[profile test]\nservices = local\n\n[services local]\ns3 =\n  endpoint_url = http://localhost:9000\n",
+    ).unwrap();
+    let loader = || {
+        aws_config::defaults(BehaviorVersion::latest())
+            .profile_files(EnvConfigFiles::builder().with_file(EnvConfigFileKind::Config, &path).build())
+            .profile_name("test")
+            .region(Region::new("us-east-1"))
+            .credentials_provider(Credentials::new("AKID", "secret", None, None, "test"))
+    };
+
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder().uri("http://localhost:9000/test-bucket/?list-type=2").body(SdkBody::empty()).unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from("<ListBucketResult><Name>test-bucket</Name></ListBucketResult>"))
+                .unwrap(),
+        ),
+    ]);
+    let config = loader().http_client(http_client.clone()).load().await;
+    assert_eq!(s3_endpoint_url(&config).as_deref(), Some("http://localhost:9000"));
+
+    // The profile's endpoint also turns on path-style addressing
+    let client = s3_client(&config, None, false);
+    client.list_objects_v2().bucket("test-bucket").send().await.unwrap();
+    let uri = http_client.actual_requests().next().unwrap().uri().to_string();
+    assert!(uri.starts_with("http://localhost:9000/test-bucket/?"), "{}", uri);
+
+    // An explicit endpoint still wins
+    let config = loader().endpoint_url("http://override:9001").load().await;
+    assert_eq!(s3_endpoint_url(&config).as_deref(), Some("http://override:9001"));
+}
+
+#[tokio::test]
+async fn test_create_client_with_no_verify_ssl() {
+    let client = create_client(
+        Some("us-east-1".to_string()), None, None, None, None, None,
+        Some("https://localhost:9443".to_string()), None, true, None,
+    ).await;
+
+    assert!(client.config().http_client().is_some());
+}
+
+#[tokio::test]
+async fn test_insecure_client_sends_requests() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // A bare HTTP server standing in for a local S3-compatible endpoint
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![0; 4096];
+        let _ = socket.read(&mut request).await.unwrap();
+        let body = "<ListAllMyBucketsResult><Buckets><Bucket><Name>local-bucket</Name></Bucket></Buckets></ListAllMyBucketsResult>";
+        let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+        socket.write_all(response.as_bytes()).await.unwrap();
+    });
+
+    let config = aws_config::SdkConfig::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .credentials_provider(aws_sdk_s3::config::SharedCredentialsProvider::new(Credentials::new("AKID", "secret", None, None, "test")))
+        .endpoint_url(endpoint)
+        .build();
+
+    let client = s3_client(&config, None, true);
+    let resp = client.list_buckets().send().await.unwrap();
+    assert_eq!(resp.buckets()[0].name(), Some("local-bucket"));
+}
+
+#[tokio::test]
+async fn test_insecure_client_applies_read_timeout() {
+    use aws_config::timeout::TimeoutConfig;
+    use std::time::{Duration, Instant};
+
+    // Accepts the connection but never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (_socket, _) = listener.accept().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(30)).await;
+    });
+
+    let config = aws_config::SdkConfig::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .credentials_provider(aws_sdk_s3::config::SharedCredentialsProvider::new(Credentials::new("AKID", "secret", None, None, "test")))
+        .endpoint_url(endpoint)
+        .retry_config(aws_config::retry::RetryConfig::disabled())
+        .timeout_config(TimeoutConfig::builder().read_timeout(Duration::from_millis(200)).build())
+        .build();
+
+    let client = s3_client(&config, None, true);
+    let started = Instant::now();
+    let err = client.list_buckets().send().await.unwrap_err();
+    assert!(matches!(&err, aws_sdk_s3::error::SdkError::DispatchFailure(f) if f.is_timeout()), "{:?}", err);
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn test_static_credentials_pairing() {
+    assert!(static_credentials(None, None, None).unwrap().is_none());
+
+    let credentials = static_credentials(Some("AKID".to_string()), Some("secret".to_string()), Some("token".to_string()))
+        .unwrap()
+        .unwrap();
+    assert_eq!(credentials.access_key_id(), "AKID");
+    assert_eq!(credentials.session_token(), Some("token"));
+
+    assert!(static_credentials(Some("AKID".to_string()), None, None).is_err());
+    assert!(static_credentials(None, Some("secret".to_string()), None).is_err());
+    assert!(static_credentials(None, None, Some("token".to_string())).is_err());
+}
+
+#[tokio::test]
+async fn test_create_client_with_static_credentials() {
+    use aws_sdk_s3::config::ProvideCredentials;
+
+    let credentials = Credentials::new("AKIDSTATIC", "secret", None, None, "test");
+    let client = create_client(
+        Some("eu-west-1".to_string()), Some("does-not-exist".to_string()), None, None, None, None,
+        None, None, false, Some(credentials.clone()),
+    ).await;
+    assert_eq!(client.config().region().map(|r| r.as_ref()), Some("eu-west-1"));
+
+    // The static keys win over the (missing) profile
+    let config = load_config(
+        Some("eu-west-1".to_string()), Some("does-not-exist".to_string()), None, None, None, None, None, Some(credentials),
+    ).await;
+    let provided = config.credentials_provider().unwrap().provide_credentials().await.unwrap();
+    assert_eq!(provided.access_key_id(), "AKIDSTATIC");
+}
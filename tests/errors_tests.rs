@@ -0,0 +1,118 @@
+use s3sh::errors::{classify_error, classify_error_code, not_found_error, usage_error, FailureKind};
+use aws_sdk_s3::Client;
+use aws_sdk_s3::config::{Region, BehaviorVersion};
+use aws_smithy_runtime::client::http::test_util::{ReplayEvent, StaticReplayClient};
+use aws_smithy_types::body::SdkBody;
+
+fn client_returning(status: u16, body: &'static str) -> Client {
+    let http_client = StaticReplayClient::new(vec![
+        ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(status)
+                .body(SdkBody::from(body))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    Client::from_conf(config)
+}
+
+#[test]
+fn test_exit_codes() {
+    assert_eq!(FailureKind::Generic.exit_code(), 1);
+    assert_eq!(FailureKind::Usage.exit_code(), 2);
+    assert_eq!(FailureKind::AccessDenied.exit_code(), 3);
+    assert_eq!(FailureKind::NotFound.exit_code(), 4);
+}
+
+#[test]
+fn test_classify_error_code() {
+    assert_eq!(classify_error_code("AccessDenied"), FailureKind::AccessDenied);
+    assert_eq!(classify_error_code("ExpiredToken"), FailureKind::AccessDenied);
+    assert_eq!(classify_error_code("NoSuchLifecycleConfiguration"), FailureKind::NotFound);
+    assert_eq!(classify_error_code("ReplicationConfigurationNotFoundError"), FailureKind::NotFound);
+    assert_eq!(classify_error_code("InvalidBucketName"), FailureKind::Usage);
+    assert_eq!(classify_error_code("SlowDown"), FailureKind::Generic);
+}
+
+#[tokio::test]
+async fn test_classify_no_such_key() {
+    let client = client_returning(404, r#"<?xml version="1.0" encoding="UTF-8"?>
+        <Error>
+            <Code>NoSuchKey</Code>
+            <Message>The specified key does not exist.</Message>
+        </Error>"#);
+
+    let err: anyhow::Error = client.get_object().bucket("test-bucket").key("missing.txt").send().await.unwrap_err().into();
+    assert_eq!(classify_error(&err), FailureKind::NotFound);
+}
+
+#[tokio::test]
+async fn test_classify_head_not_found() {
+    let client = client_returning(404, "");
+
+    let err: anyhow::Error = client.head_object().bucket("test-bucket").key("missing.txt").send().await.unwrap_err().into();
+    assert_eq!(classify_error(&err), FailureKind::NotFound);
+}
+
+#[tokio::test]
+async fn test_classify_access_denied() {
+    let client = client_returning(403, r#"<?xml version="1.0" encoding="UTF-8"?>
+        <Error>
+            <Code>AccessDenied</Code>
+            <Message>Access Denied</Message>
+        </Error>"#);
+
+    let err = s3sh::buckets::list_buckets(&client, s3sh::ui::SortKey::Name, false, None, s3sh::ui::OutputFormat::Table).await.unwrap_err();
+    // The credentials hint added on top must not hide the code
+    let err = s3sh::client::explain_error(err, None);
+    assert_eq!(classify_error(&err), FailureKind::AccessDenied);
+}
+
+#[tokio::test]
+async fn test_classify_unmodeled_not_found() {
+    let client = client_returning(404, r#"<?xml version="1.0" encoding="UTF-8"?>
+        <Error>
+            <Code>NoSuchLifecycleConfiguration</Code>
+            <Message>The lifecycle configuration does not exist</Message>
+        </Error>"#);
+
+    let err: anyhow::Error = client.get_bucket_lifecycle_configuration().bucket("test-bucket").send().await.unwrap_err().into();
+    assert_eq!(classify_error(&err), FailureKind::NotFound);
+}
+
+#[tokio::test]
+async fn test_classify_server_error_is_generic() {
+    let client = client_returning(400, r#"<?xml version="1.0" encoding="UTF-8"?>
+        <Error>
+            <Code>BucketNotEmpty</Code>
+            <Message>The bucket you tried to delete is not empty</Message>
+        </Error>"#);
+
+    let err: anyhow::Error = client.delete_bucket().bucket("test-bucket").send().await.unwrap_err().into();
+    assert_eq!(classify_error(&err), FailureKind::Generic);
+}
+
+#[test]
+fn test_classify_usage_errors() {
+    assert_eq!(classify_error(&usage_error("bad flag")), FailureKind::Usage);
+    assert_eq!(classify_error(&s3sh::client::validate_region("US-EAST-1").unwrap_err()), FailureKind::Usage);
+    assert_eq!(classify_error(&anyhow::anyhow!("something else")), FailureKind::Generic);
+}
+
+#[test]
+fn test_classify_not_found_error() {
+    let err = not_found_error("Object 'a.txt' not found in bucket 'b'");
+    assert_eq!(classify_error(&err), FailureKind::NotFound);
+    assert_eq!(classify_error(&err).exit_code(), 4);
+}
@@ -0,0 +1,54 @@
+use s3sh::filter::{FilterRule, PathFilter};
+
+fn include(pattern: &str) -> FilterRule {
+    FilterRule::Include(pattern.to_string())
+}
+
+fn exclude(pattern: &str) -> FilterRule {
+    FilterRule::Exclude(pattern.to_string())
+}
+
+#[test]
+fn test_no_rules_includes_everything() {
+    let filter = PathFilter::default();
+    assert!(filter.is_included("a.txt"));
+    assert!(filter.is_included("deep/nested/b.log"));
+}
+
+#[test]
+fn test_exclude_matches_whole_relative_path() {
+    let filter = PathFilter::new(&[exclude("*.log")]).unwrap();
+    assert!(!filter.is_included("app.log"));
+    // `*` also matches `/`
+    assert!(!filter.is_included("logs/2024/app.log"));
+    assert!(filter.is_included("app.txt"));
+
+    let filter = PathFilter::new(&[exclude("tmp/*")]).unwrap();
+    assert!(!filter.is_included("tmp/a.txt"));
+    assert!(filter.is_included("src/tmp.txt"));
+}
+
+#[test]
+fn test_exclude_all_then_include() {
+    let filter = PathFilter::new(&[exclude("*"), include("*.txt")]).unwrap();
+    assert!(filter.is_included("notes.txt"));
+    assert!(filter.is_included("docs/readme.txt"));
+    assert!(!filter.is_included("image.png"));
+}
+
+#[test]
+fn test_later_rule_wins() {
+    // Include first, then exclude everything: the exclude wins
+    let filter = PathFilter::new(&[include("*.txt"), exclude("*")]).unwrap();
+    assert!(!filter.is_included("notes.txt"));
+
+    let filter = PathFilter::new(&[exclude("*"), include("*.txt"), exclude("drafts/*")]).unwrap();
+    assert!(filter.is_included("notes.txt"));
+    assert!(!filter.is_included("drafts/notes.txt"));
+    assert!(!filter.is_included("image.png"));
+}
+
+#[test]
+fn test_invalid_pattern() {
+    assert!(PathFilter::new(&[include("a[")]).is_err());
+}
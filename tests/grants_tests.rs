@@ -0,0 +1,41 @@
+use s3sh::grants::{parse_grantees, Grants};
+
+fn grantees(values: &[&str]) -> anyhow::Result<Option<String>> {
+    parse_grantees(&values.iter().map(|v| v.to_string()).collect::<Vec<_>>())
+}
+
+#[test]
+fn test_parse_grantees_quotes_values() {
+    assert_eq!(grantees(&["id=abc123"]).unwrap().as_deref(), Some("id=\"abc123\""));
+    assert_eq!(
+        grantees(&["uri=http://acs.amazonaws.com/groups/global/AllUsers"]).unwrap().as_deref(),
+        Some("uri=\"http://acs.amazonaws.com/groups/global/AllUsers\"")
+    );
+    // Already-quoted values are not quoted twice
+    assert_eq!(grantees(&["id=\"abc123\""]).unwrap().as_deref(), Some("id=\"abc123\""));
+}
+
+#[test]
+fn test_parse_grantees_joins_several() {
+    let header = grantees(&["id=abc, emailaddress=ops@example.com", "id=def"]).unwrap();
+    assert_eq!(header.as_deref(), Some("id=\"abc\", emailAddress=\"ops@example.com\", id=\"def\""));
+    assert_eq!(grantees(&[]).unwrap(), None);
+}
+
+#[test]
+fn test_parse_grantees_rejects_bad_format() {
+    assert!(grantees(&["abc123"]).is_err());
+    assert!(grantees(&["user=abc123"]).is_err());
+    assert!(grantees(&["id="]).is_err());
+    assert!(grantees(&["uri=AllUsers"]).is_err());
+}
+
+#[test]
+fn test_grants_check_object() {
+    assert!(Grants::default().is_empty());
+    assert!(Grants::default().check_object().is_ok());
+
+    let grants = Grants { write: Some("id=\"abc\"".to_string()), ..Grants::default() };
+    assert!(!grants.is_empty());
+    assert!(grants.check_object().is_err());
+}
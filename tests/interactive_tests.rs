@@ -0,0 +1,33 @@
+use s3sh::interactive::{object_menu_items, render_menu, OBJECT_ACTIONS};
+use aws_sdk_s3::types::Object;
+
+fn items(n: usize) -> Vec<String> {
+    (1..=n).map(|i| format!("item-{}", i)).collect()
+}
+
+#[test]
+fn test_object_menu_items() {
+    colored::control::set_override(false);
+    let objects = vec![
+        Object::builder().key("index.html").size(512).build(),
+        Object::builder().key("logs/app.log").size(3 * 1024 * 1024).build(),
+    ];
+    assert_eq!(object_menu_items(&objects), ["index.html (512 B)", "logs/app.log (3.0 MiB)"]);
+    assert_eq!(OBJECT_ACTIONS, ["Download", "Delete", "Back"]);
+}
+
+#[test]
+fn test_render_menu_marks_cursor() {
+    colored::control::set_override(false);
+    assert_eq!(render_menu(&items(3), 1, 10), ["  item-1", "> item-2", "  item-3"]);
+}
+
+#[test]
+fn test_render_menu_pages() {
+    colored::control::set_override(false);
+    let lines = render_menu(&items(12), 6, 5);
+    assert_eq!(lines, ["  item-6", "> item-7", "  item-8", "  item-9", "  item-10", "  (6-10 of 12)"]);
+
+    let last = render_menu(&items(12), 11, 5);
+    assert_eq!(last, ["  item-11", "> item-12", "  (11-12 of 12)"]);
+}
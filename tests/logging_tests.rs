@@ -0,0 +1,102 @@
+use s3sh::logging::{format_timings, init_logging, log_filter, with_request_logging, with_timing, CallTiming, CallTimings};
+use aws_sdk_s3::Client;
+use aws_sdk_s3::config::{Region, BehaviorVersion};
+use aws_smithy_runtime::client::http::test_util::StaticReplayClient;
+use aws_smithy_types::body::SdkBody;
+use std::time::Duration;
+
+#[test]
+fn test_log_filter_levels() {
+    assert_eq!(log_filter(0), None);
+    assert!(log_filter(1).unwrap().contains("s3sh=info"));
+    assert!(log_filter(2).unwrap().contains("aws_smithy_runtime=debug"));
+    assert_eq!(log_filter(3), log_filter(7));
+}
+
+#[tokio::test]
+async fn test_init_logging_and_request_logger() {
+    // Quiet mode installs nothing, so a later init still succeeds
+    init_logging(0).unwrap();
+    init_logging(1).unwrap();
+
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListAllMyBucketsResult><Buckets></Buckets></ListAllMyBucketsResult>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = with_request_logging(&Client::from_conf(config));
+    assert!(client.list_buckets().send().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_timing_records_each_call() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListAllMyBucketsResult><Buckets></Buckets></ListAllMyBucketsResult>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("HEAD")
+                .uri("https://missing-bucket.s3.us-east-1.amazonaws.com/")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(404)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let timings = CallTimings::default();
+    let client = with_timing(&Client::from_conf(config), &timings);
+    assert!(client.list_buckets().send().await.is_ok());
+    // Failed calls are timed too
+    assert!(client.head_bucket().bucket("missing-bucket").send().await.is_err());
+
+    let calls = timings.calls();
+    let operations = calls.iter().map(|c| c.operation.as_str()).collect::<Vec<_>>();
+    assert_eq!(operations, ["ListBuckets", "HeadBucket"]);
+    assert!(calls.iter().all(|c| c.duration > Duration::ZERO));
+}
+
+#[test]
+fn test_format_timings() {
+    colored::control::set_override(false);
+    let calls = [
+        CallTiming { operation: "ListObjectsV2".to_string(), duration: Duration::from_micros(12_345) },
+        CallTiming { operation: "GetObject".to_string(), duration: Duration::from_millis(3) },
+    ];
+    let out = format_timings(&calls, Duration::from_millis(20));
+    assert_eq!(out, "      12.3 ms  ListObjectsV2\n       3.0 ms  GetObject\nTotal: 2 S3 calls in 20.0 ms\n");
+}
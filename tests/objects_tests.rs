@@ -1,4 +1,17 @@
-use s3sh::objects::{list_objects, upload_object, delete_object};
+use s3sh::objects::{
+    list_objects, upload_object, delete_object, stream_object, download_object, download_parallel, split_ranges, parse_byte_range, ByteRange, restore_object, restore_prefix, restore_candidates, parse_tier,
+    format_object_attributes, describe_restore_status, list_object_versions,
+    set_object_retention, set_object_legal_hold, parse_retention_mode, parse_legal_hold,
+    parse_checksum_algorithm, get_object_attributes, format_get_object_attributes,
+    sort_objects, fetch_objects, fetch_objects_within, ObjectInfo, move_object, rename_prefix, rename_plan, check_rename_prefixes, set_object_metadata, copy_source, copy_object, copy_prefix, parse_storage_class, parse_object_canned_acl, get_object_acl, track_progress, upload_from_reader, object_exists, delete_prefix, download_prefix, download_path, sync_directory, collect_sync_files, stale_keys, compute_checksum, decode_checksum, encode_tagging, verify_object, upload_archive, parse_sse, stream_select, select_input_serialization, select_output_serialization, object_infos, format_object_table, format_object_csv, fetch_object_metadata, sniff_content_type, guess_content_type, object_headers, format_object_headers,
+    ObjectHeaders, parse_expires, parse_time_bound, ModifiedWindow, put_object_acl, summarize_objects, wait_for_restore, compute_content_md5,
+    upload_multipart, SkipExisting, upload_state_path, part_size_for, MULTIPART_THRESHOLD, MultipartState, modified_nanos, UploadedPart, KeyTree,
+};
+use s3sh::ui::{OutputFormat, SortKey};
+use s3sh::filter::{FilterRule, PathFilter};
+use s3sh::grants::{parse_grantees, Grants};
+use s3sh::archive::{archive_entries, write_archive, ArchiveFormat};
+use aws_sdk_s3::primitives::DateTime;
 use aws_sdk_s3::Client;
 use aws_sdk_s3::config::{Region, BehaviorVersion};
 use aws_smithy_runtime::client::http::test_util::StaticReplayClient;
@@ -38,10 +51,210 @@ async fn test_list_objects() {
     
     let client = Client::from_conf(config);
 
-    let result = list_objects(&client, "test-bucket").await;
+    let result = list_objects(&client, "test-bucket", None, None, None, ModifiedWindow::default(), false, false, false, None, SortKey::Name, false, OutputFormat::Table).await;
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_key_tree_groups_keys_by_segment() {
+    let tree = KeyTree::from_keys(vec![
+        ("README.md", 10),
+        ("logs/", 0),
+        ("logs/2024/app.log", 200),
+        ("logs/2024/db.log", 300),
+        ("logs/2025/app.log", 400),
+        ("photos/cat.jpg", 5000),
+    ]);
+
+    assert_eq!(tree.children.keys().collect::<Vec<_>>(), vec!["README.md", "logs", "photos"]);
+    assert_eq!(tree.children["README.md"].size, Some(10));
+    assert!(tree.children["README.md"].children.is_empty());
+
+    let logs = &tree.children["logs"];
+    assert_eq!(logs.size, None);
+    assert_eq!(logs.children.keys().collect::<Vec<_>>(), vec!["2024", "2025"]);
+    let year = &logs.children["2024"];
+    assert_eq!(year.children.keys().collect::<Vec<_>>(), vec!["app.log", "db.log"]);
+    assert_eq!(year.children["db.log"].size, Some(300));
+    assert_eq!(tree.children["photos"].children["cat.jpg"].size, Some(5000));
+
+    colored::control::set_override(false);
+    let rendered = tree.render(true);
+    assert_eq!(rendered, "README.md (10)\nlogs/\n  2024/\n    app.log (200)\n    db.log (300)\n  2025/\n    app.log (400)\nphotos/\n  cat.jpg (5000)\n");
+}
+
+#[tokio::test]
+async fn test_object_table_wide_columns() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Name>test-bucket</Name>
+                        <Contents>
+                            <Key>archive.tar</Key>
+                            <Size>2048</Size>
+                            <LastModified>2023-01-01T00:00:00.000Z</LastModified>
+                            <ETag>&quot;9b2cf535f27731c974343645a3985328&quot;</ETag>
+                            <StorageClass>GLACIER</StorageClass>
+                        </Contents>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let (objects, _) = fetch_objects(&client, "test-bucket", None, None, None).await.unwrap();
+    let infos = object_infos(&objects, false);
+    assert_eq!(infos[0].e_tag, "9b2cf535f27731c974343645a3985328");
+    assert_eq!(infos[0].storage_class, "GLACIER");
+
+    let wide = format_object_table(&infos, true, false);
+    assert!(wide.contains("etag"));
+    assert!(wide.contains("9b2cf535f27731c974343645a3985328"));
+    assert!(wide.contains("GLACIER"));
+
+    let compact = format_object_table(&infos, false, false);
+    assert!(compact.contains("archive.tar"));
+    assert!(!compact.contains("9b2cf535f27731c974343645a3985328"));
+    assert!(!compact.contains("GLACIER"));
+}
+
+#[tokio::test]
+async fn test_fetch_object_metadata_fills_table_columns() {
+    use aws_smithy_runtime::client::http::test_util::ReplayEvent;
+
+    let head = |key: &str, response: http::Response<SdkBody>| ReplayEvent::new(
+        http::Request::builder()
+            .method("HEAD")
+            .uri(format!("https://test-bucket.s3.us-east-1.amazonaws.com/{}", key))
+            .body(SdkBody::empty())
+            .unwrap(),
+        response,
+    );
+    let http_client = StaticReplayClient::new(vec![
+        ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Name>test-bucket</Name>
+                        <Contents><Key>a.json</Key><Size>10</Size></Contents>
+                        <Contents><Key>b.png</Key><Size>20</Size></Contents>
+                        <Contents><Key>gone.txt</Key><Size>30</Size></Contents>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        ),
+        head("a.json", http::Response::builder()
+            .status(200)
+            .header("Content-Type", "application/json")
+            .header("x-amz-meta-owner", "data-team")
+            .header("x-amz-meta-source", "etl")
+            .body(SdkBody::empty())
+            .unwrap()),
+        head("b.png", http::Response::builder()
+            .status(200)
+            .header("Content-Type", "image/png")
+            .body(SdkBody::empty())
+            .unwrap()),
+        head("gone.txt", http::Response::builder()
+            .status(404)
+            .body(SdkBody::empty())
+            .unwrap()),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let (objects, _) = fetch_objects(&client, "test-bucket", None, None, None).await.unwrap();
+    let mut infos = object_infos(&objects, false);
+    // One at a time, so the replayed responses line up with the keys
+    fetch_object_metadata(&client, "test-bucket", &mut infos, 1).await;
+    http_client.assert_requests_match(&[]);
+
+    assert_eq!(infos[0].content_type.as_deref(), Some("application/json"));
+    let metadata = infos[0].metadata.as_ref().unwrap();
+    assert_eq!(metadata["owner"], "data-team");
+    assert_eq!(metadata["source"], "etl");
+    assert_eq!(infos[1].content_type.as_deref(), Some("image/png"));
+    assert!(infos[1].metadata.as_ref().unwrap().is_empty());
+    // A failed head leaves the fields blank
+    assert_eq!(infos[2].content_type, None);
+    assert_eq!(infos[2].metadata, None);
+
+    let table = format_object_table(&infos, false, true);
+    assert!(table.contains("content_type"));
+    assert!(table.contains("application/json"));
+    assert!(table.contains("owner=data-team, source=etl"));
+
+    let plain = format_object_table(&infos, false, false);
+    assert!(!plain.contains("content_type"));
+    assert!(!plain.contains("data-team"));
+}
+
+#[test]
+fn test_object_csv_round_trips() {
+    let object = |key: &str, size: i64, class: &str| aws_sdk_s3::types::Object::builder()
+        .key(key)
+        .size(size)
+        .last_modified(DateTime::from_secs(1_700_000_000))
+        .e_tag("\"abc\"")
+        .storage_class(aws_sdk_s3::types::ObjectStorageClass::from(class))
+        .build();
+    let objects = vec![
+        object("reports/q1, q2.csv", 2048, "STANDARD"),
+        object("say \"hi\".txt", 5, "GLACIER"),
+    ];
+    let mut infos = object_infos(&objects, true);
+    infos[0].content_type = Some("text/csv".to_string());
+    infos[0].metadata = Some([("owner".to_string(), "a, b".to_string())].into_iter().collect());
+
+    let records = |csv: &str| csv::Reader::from_reader(csv.as_bytes())
+        .records()
+        .map(|r| r.unwrap().iter().map(str::to_string).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let headers = |csv: &str| csv::Reader::from_reader(csv.as_bytes()).headers().unwrap().iter().map(str::to_string).collect::<Vec<_>>();
+
+    let compact = format_object_csv(&infos, false, false).unwrap();
+    assert_eq!(headers(&compact), ["key", "size", "last_modified"]);
+    assert_eq!(records(&compact), vec![
+        vec!["reports/q1, q2.csv", "2048", "2023-11-14T22:13:20Z"],
+        vec!["say \"hi\".txt", "5", "2023-11-14T22:13:20Z"],
+    ]);
+    assert!(compact.contains("\"reports/q1, q2.csv\""));
+    assert!(compact.contains("\"say \"\"hi\"\".txt\""));
+
+    let full = format_object_csv(&infos, true, true).unwrap();
+    assert_eq!(headers(&full), ["key", "size", "last_modified", "etag", "storage_class", "content_type", "metadata"]);
+    assert_eq!(records(&full), vec![
+        vec!["reports/q1, q2.csv", "2048", "2023-11-14T22:13:20Z", "abc", "STANDARD", "text/csv", "owner=a, b"],
+        vec!["say \"hi\".txt", "5", "2023-11-14T22:13:20Z", "abc", "GLACIER", "", ""],
+    ]);
+}
+
 #[tokio::test]
 async fn test_upload_object() {
     let http_client = StaticReplayClient::new(vec![
@@ -71,10 +284,116 @@ async fn test_upload_object() {
     write!(temp_file, "hello world").unwrap();
     let path = temp_file.path().to_str().unwrap();
 
-    let result = upload_object(&client, "test-bucket", path, Some("test-file.txt".to_string())).await;
+    let result = upload_object(&client, "test-bucket", path, Some("test-file.txt".to_string()), None, None, None, None, &Grants::default(), &[], &ObjectHeaders::default(), false, true, None, None, false).await;
     assert!(result.is_ok());
 }
 
+fn head_then_put(head: http::Response<SdkBody>) -> StaticReplayClient {
+    use aws_smithy_runtime::client::http::test_util::ReplayEvent;
+
+    StaticReplayClient::new(vec![
+        ReplayEvent::new(
+            http::Request::builder()
+                .method("HEAD")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/test-file.txt")
+                .body(SdkBody::empty())
+                .unwrap(),
+            head,
+        ),
+        ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/test-file.txt?x-id=PutObject")
+                .body(SdkBody::from("hello world"))
+                .unwrap(),
+            http::Response::builder().status(200).body(SdkBody::empty()).unwrap(),
+        ),
+    ])
+}
+
+async fn upload_skipping_existing(http_client: &StaticReplayClient, check: SkipExisting) -> anyhow::Result<()> {
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+    let client = Client::from_conf(config);
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    write!(temp_file, "hello world").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+
+    upload_object(&client, "test-bucket", path, Some("test-file.txt".to_string()), None, None, None, None, &Grants::default(), &[], &ObjectHeaders::default(), false, true, Some(check), None, false).await
+}
+
+#[tokio::test]
+async fn test_upload_skips_object_with_same_size_and_etag() {
+    // MD5 of "hello world"
+    let http_client = head_then_put(http::Response::builder()
+        .status(200)
+        .header("Content-Length", "11")
+        .header("ETag", "\"5eb63bbbe01eeed093cb22bb8f5acdc3\"")
+        .body(SdkBody::empty())
+        .unwrap());
+
+    upload_skipping_existing(&http_client, SkipExisting::SizeAndEtag).await.unwrap();
+
+    // Only the HEAD; nothing is uploaded
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method(), "HEAD");
+}
+
+#[tokio::test]
+async fn test_upload_proceeds_when_size_or_etag_changed() {
+    let changed_size = head_then_put(http::Response::builder()
+        .status(200)
+        .header("Content-Length", "5")
+        .body(SdkBody::empty())
+        .unwrap());
+    upload_skipping_existing(&changed_size, SkipExisting::Size).await.unwrap();
+    let requests = changed_size.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].method(), "PUT");
+
+    // Same size but different content
+    let changed_etag = head_then_put(http::Response::builder()
+        .status(200)
+        .header("Content-Length", "11")
+        .header("ETag", "\"00000000000000000000000000000000\"")
+        .body(SdkBody::empty())
+        .unwrap());
+    upload_skipping_existing(&changed_etag, SkipExisting::SizeAndEtag).await.unwrap();
+    assert_eq!(changed_etag.actual_requests().count(), 2);
+
+    // Not there yet
+    let missing = head_then_put(http::Response::builder().status(404).body(SdkBody::empty()).unwrap());
+    upload_skipping_existing(&missing, SkipExisting::Size).await.unwrap();
+    assert_eq!(missing.actual_requests().count(), 2);
+}
+
+#[tokio::test]
+async fn test_upload_skip_check_fails_on_other_errors() {
+    let http_client = head_then_put(http::Response::builder().status(500).body(SdkBody::empty()).unwrap());
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .retry_config(aws_sdk_s3::config::retry::RetryConfig::disabled())
+        .build();
+    let client = Client::from_conf(config);
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    write!(temp_file, "hello world").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+
+    let result = upload_object(&client, "test-bucket", path, Some("test-file.txt".to_string()), None, None, None, None, &Grants::default(), &[], &ObjectHeaders::default(), false, true, Some(SkipExisting::Size), None, false).await;
+    assert!(result.is_err());
+    // A failed check doesn't upload
+    assert_eq!(http_client.actual_requests().count(), 1);
+}
+
 #[tokio::test]
 async fn test_delete_object() {
     let http_client = StaticReplayClient::new(vec![
@@ -99,6 +418,3490 @@ async fn test_delete_object() {
     
     let client = Client::from_conf(config);
 
-    let result = delete_object(&client, "test-bucket", "file-to-delete.txt").await;
+    let result = delete_object(&client, "test-bucket", "file-to-delete.txt", None, None, true, false).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_stream_object() {
+    let body = "line one\nline two\n";
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/notes.txt")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .header("Content-Type", "text/plain")
+                .body(SdkBody::from(body))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let mut output: Vec<u8> = Vec::new();
+    let result = stream_object(&client, "test-bucket", "notes.txt", None, &mut output).await;
     assert!(result.is_ok());
+    assert_eq!(output, body.as_bytes());
+}
+
+fn ranged_get_client(body: &'static str, content_range: &str) -> StaticReplayClient {
+    StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/big.log?x-id=GetObject")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(206)
+                .header("Content-Range", content_range)
+                .header("Content-Length", body.len().to_string())
+                .body(SdkBody::from(body))
+                .unwrap(),
+        )
+    ])
+}
+
+#[tokio::test]
+async fn test_download_object_range() {
+    let http_client = ranged_get_client("56789", "bytes 5-9/26");
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("part.log");
+    let range = ByteRange { start: 5, end: 9 };
+    download_object(&client, "test-bucket", "big.log", Some(dest.to_str().unwrap()), Some(range), 1, false).await.unwrap();
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].headers().get("range"), Some("bytes=5-9"));
+    // Only the ranged bytes are written
+    assert_eq!(std::fs::read(&dest).unwrap(), b"56789");
+}
+
+#[tokio::test]
+async fn test_stream_object_range() {
+    let http_client = ranged_get_client("0123", "bytes 0-3/26");
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mut output: Vec<u8> = Vec::new();
+    stream_object(&client, "test-bucket", "big.log", Some(ByteRange { start: 0, end: 3 }), &mut output).await.unwrap();
+    assert_eq!(output, b"0123");
+    assert_eq!(http_client.actual_requests().next().unwrap().headers().get("range"), Some("bytes=0-3"));
+}
+
+#[tokio::test]
+async fn test_download_parallel_assembles_ranges() {
+    use aws_smithy_runtime::client::http::test_util::ReplayEvent;
+
+    const CONTENT: &str = "abcdefghijklmnopqrstuvwxyz";
+    let ranged_get = |start: usize, end: usize| ReplayEvent::new(
+        http::Request::builder()
+            .method("GET")
+            .uri("https://test-bucket.s3.us-east-1.amazonaws.com/big.bin?x-id=GetObject")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(206)
+            .header("Content-Range", format!("bytes {}-{}/{}", start, end, CONTENT.len()))
+            .header("Content-Length", (end - start + 1).to_string())
+            .body(SdkBody::from(&CONTENT[start..=end]))
+            .unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![
+        ReplayEvent::new(
+            http::Request::builder()
+                .method("HEAD")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/big.bin")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .header("Content-Length", CONTENT.len().to_string())
+                .header("ETag", "\"v1\"")
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+        ranged_get(0, 8),
+        ranged_get(9, 17),
+        ranged_get(18, 25),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("big.bin");
+    download_parallel(&client, "test-bucket", "big.bin", &dest, 3, 9).await.unwrap();
+
+    // Each range is written at the offset S3 reports, whatever order they finish in
+    assert_eq!(std::fs::read_to_string(&dest).unwrap(), CONTENT);
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 4);
+    assert_eq!(requests[0].method(), "HEAD");
+    let mut ranges = requests[1..].iter()
+        .map(|r| r.headers().get("range").unwrap().to_string())
+        .collect::<Vec<_>>();
+    ranges.sort();
+    assert_eq!(ranges, vec!["bytes=0-8", "bytes=18-25", "bytes=9-17"]);
+    // Each range is pinned to the version the HEAD saw
+    assert!(requests[1..].iter().all(|r| r.headers().get("if-match") == Some("\"v1\"")));
+}
+
+#[tokio::test]
+async fn test_download_parallel_fails_if_overwritten() {
+    use aws_smithy_runtime::client::http::test_util::ReplayEvent;
+
+    let get = |status: u16, body: &'static str| ReplayEvent::new(
+        http::Request::builder()
+            .method("GET")
+            .uri("https://test-bucket.s3.us-east-1.amazonaws.com/big.bin?x-id=GetObject")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(status)
+            .header("Content-Range", "bytes 0-8/18")
+            .body(SdkBody::from(body))
+            .unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![
+        ReplayEvent::new(
+            http::Request::builder()
+                .method("HEAD")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/big.bin")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .header("Content-Length", "18")
+                .header("ETag", "\"v1\"")
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+        get(206, "abcdefghi"),
+        get(412, r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Error><Code>PreconditionFailed</Code><Message>At least one of the pre-conditions you specified did not hold</Message></Error>"#),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("big.bin");
+    let err = download_parallel(&client, "test-bucket", "big.bin", &dest, 2, 9).await.unwrap_err();
+    assert!(format!("{:#}", err).contains("was overwritten while it was being downloaded"), "{:#}", err);
+    // The partly written file is removed
+    assert!(!dest.exists());
+}
+
+#[tokio::test]
+async fn test_download_parallel_streams_small_objects() {
+    use aws_smithy_runtime::client::http::test_util::ReplayEvent;
+
+    let http_client = StaticReplayClient::new(vec![
+        ReplayEvent::new(
+            http::Request::builder()
+                .method("HEAD")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/small.txt")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .header("Content-Length", "5")
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+        ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/small.txt?x-id=GetObject")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from("hello"))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("small.txt");
+    download_parallel(&client, "test-bucket", "small.txt", &dest, 4, 1024).await.unwrap();
+
+    assert_eq!(std::fs::read_to_string(&dest).unwrap(), "hello");
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].headers().get("range"), None);
+}
+
+#[test]
+fn test_split_ranges() {
+    let r = |start, end| ByteRange { start, end };
+    assert_eq!(split_ranges(100, 4, 10), vec![r(0, 24), r(25, 49), r(50, 74), r(75, 99)]);
+    // Uneven sizes leave a shorter last range
+    assert_eq!(split_ranges(10, 3, 1), vec![r(0, 3), r(4, 7), r(8, 9)]);
+    // Never smaller than the minimum part size
+    assert_eq!(split_ranges(100, 8, 40), vec![r(0, 33), r(34, 67), r(68, 99)]);
+    assert_eq!(split_ranges(100, 8, 1000), vec![r(0, 99)]);
+    assert!(split_ranges(0, 4, 10).is_empty());
+}
+
+#[test]
+fn test_parse_byte_range() {
+    assert_eq!(parse_byte_range("0-1023").unwrap(), ByteRange { start: 0, end: 1023 });
+    assert_eq!(parse_byte_range(" 5 - 5 ").unwrap(), ByteRange { start: 5, end: 5 });
+    assert_eq!(parse_byte_range("100-199").unwrap().header(), "bytes=100-199");
+    assert!(parse_byte_range("10-5").is_err());
+    assert!(parse_byte_range("1024").is_err());
+    assert!(parse_byte_range("-100").is_err());
+    assert!(parse_byte_range("a-b").is_err());
+}
+
+/// Encode one S3 Select event-stream message, as S3 sends it.
+fn select_event(event_type: &'static str, payload: &'static [u8]) -> Vec<u8> {
+    use aws_smithy_types::event_stream::{Header, HeaderValue, Message};
+
+    let message = Message::new(payload)
+        .add_header(Header::new(":message-type", HeaderValue::String("event".into())))
+        .add_header(Header::new(":event-type", HeaderValue::String(event_type.into())))
+        .add_header(Header::new(":content-type", HeaderValue::String("application/octet-stream".into())));
+    let mut buffer = Vec::new();
+    aws_smithy_eventstream::frame::write_message_to(&message, &mut buffer).unwrap();
+    buffer
+}
+
+fn select_client(events: Vec<Vec<u8>>) -> (Client, StaticReplayClient) {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/people.csv?select&select-type=2")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(events.concat()))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    (Client::from_conf(config), http_client)
+}
+
+#[tokio::test]
+async fn test_stream_select() {
+    let (client, http_client) = select_client(vec![
+        select_event("Records", b"Jane,Chicago\n"),
+        select_event("Records", b"Ravi,Paris\n"),
+        select_event("End", b""),
+    ]);
+
+    let input = select_input_serialization("csv", "gzip").unwrap();
+    let output = select_output_serialization("csv").unwrap();
+    let query = "SELECT s.name, s.city FROM s3object s";
+    let mut out: Vec<u8> = Vec::new();
+    let result = stream_select(&client, "test-bucket", "people.csv", query, input, output, &mut out).await;
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!(out, b"Jane,Chicago\nRavi,Paris\n");
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests[0].method(), "POST");
+    assert!(requests[0].uri().contains("select"));
+    let body = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<Expression>SELECT s.name, s.city FROM s3object s</Expression>"));
+    assert!(body.contains("<ExpressionType>SQL</ExpressionType>"));
+    assert!(body.contains("<CompressionType>GZIP</CompressionType>"));
+    assert!(body.contains("<FileHeaderInfo>USE</FileHeaderInfo>"));
+}
+
+#[tokio::test]
+async fn test_stream_select_without_end_is_incomplete() {
+    let (client, _) = select_client(vec![select_event("Records", b"Jane,Chicago\n")]);
+
+    let input = select_input_serialization("csv", "none").unwrap();
+    let output = select_output_serialization("csv").unwrap();
+    let mut out: Vec<u8> = Vec::new();
+    let err = stream_select(&client, "test-bucket", "people.csv", "SELECT * FROM s3object", input, output, &mut out).await.unwrap_err();
+    assert!(err.to_string().contains("incomplete"));
+}
+
+#[test]
+fn test_select_serialization() {
+    let input = select_input_serialization("json", "none").unwrap();
+    assert_eq!(input.compression_type(), Some(&aws_sdk_s3::types::CompressionType::None));
+    assert_eq!(input.json().and_then(|j| j.r#type()), Some(&aws_sdk_s3::types::JsonType::Lines));
+    assert!(input.csv().is_none());
+
+    let output = select_output_serialization("JSON").unwrap();
+    assert_eq!(output.json().and_then(|j| j.record_delimiter()), Some("\n"));
+
+    assert!(select_input_serialization("parquet", "none").is_err());
+    assert!(select_input_serialization("csv", "zip").is_err());
+    assert!(select_output_serialization("xml").is_err());
+}
+
+#[tokio::test]
+async fn test_restore_object_with_tier_and_days() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/archive.zip?restore")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(202)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = restore_object(&client, "test-bucket", "archive.zip", 7, "expedited", false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    let body = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<Days>7</Days>"));
+    assert!(body.contains("<Tier>Expedited</Tier>"));
+}
+
+#[tokio::test]
+async fn test_restore_prefix_restores_each_archived_key() {
+    let restore = |key: &str| aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("POST")
+            .uri(format!("https://s3.us-east-1.amazonaws.com/test-bucket/{}?restore", key))
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(202)
+            .body(SdkBody::empty())
+            .unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?list-type=2&prefix=logs%2F")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Name>test-bucket</Name>
+                        <Prefix>logs/</Prefix>
+                        <IsTruncated>false</IsTruncated>
+                        <Contents><Key>logs/2023.tar</Key><Size>10</Size><StorageClass>GLACIER</StorageClass></Contents>
+                        <Contents><Key>logs/2022.tar</Key><Size>10</Size><StorageClass>DEEP_ARCHIVE</StorageClass></Contents>
+                        <Contents><Key>logs/current.log</Key><Size>10</Size><StorageClass>STANDARD</StorageClass></Contents>
+                        <Contents>
+                            <Key>logs/2021.tar</Key><Size>10</Size><StorageClass>GLACIER</StorageClass>
+                            <RestoreStatus><IsRestoreInProgress>true</IsRestoreInProgress></RestoreStatus>
+                        </Contents>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        ),
+        restore("logs/2023.tar"),
+        restore("logs/2022.tar"),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let classes = vec!["GLACIER".to_string(), "DEEP_ARCHIVE".to_string()];
+    let result = restore_prefix(&client, "test-bucket", "logs/", 3, "bulk", &classes, 1, OutputFormat::Json, false).await;
+    assert!(result.is_ok(), "{:?}", result);
+
+    // The standard object and the one already restoring get no request
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].headers().get("x-amz-optional-object-attributes"), Some("RestoreStatus"));
+    let mut restored: Vec<&str> = requests[1..].iter().map(|r| r.uri().rsplit('/').next().unwrap()).collect();
+    restored.sort();
+    assert_eq!(restored, vec!["2022.tar?restore", "2023.tar?restore"]);
+    for request in &requests[1..] {
+        let body = std::str::from_utf8(request.body().bytes().unwrap()).unwrap();
+        assert!(body.contains("<Days>3</Days>"));
+        assert!(body.contains("<Tier>Bulk</Tier>"));
+    }
+}
+
+#[test]
+fn test_restore_candidates_filter_by_storage_class() {
+    let object = |key: &str, class: &str| aws_sdk_s3::types::Object::builder()
+        .key(key)
+        .storage_class(aws_sdk_s3::types::ObjectStorageClass::from(class))
+        .build();
+    let objects = vec![object("a", "GLACIER"), object("b", "DEEP_ARCHIVE"), object("c", "STANDARD_IA")];
+
+    let (restore, in_progress) = restore_candidates(&objects, &["deep_archive".to_string()]);
+    assert_eq!(restore, vec!["b"]);
+    assert!(in_progress.is_empty());
+    let (restore, _) = restore_candidates(&objects, &["GLACIER".to_string(), "DEEP_ARCHIVE".to_string()]);
+    assert_eq!(restore, vec!["a", "b"]);
+}
+
+#[tokio::test]
+async fn test_restore_object_already_in_progress() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/archive.zip?restore")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(409)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>RestoreAlreadyInProgress</Code>
+                        <Message>Object restore is already in progress</Message>
+                    </Error>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = restore_object(&client, "test-bucket", "archive.zip", 1, "Standard", false).await;
+    assert!(result.is_ok());
+}
+
+fn head_with_restore(restore: &str) -> aws_smithy_runtime::client::http::test_util::ReplayEvent {
+    aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("HEAD")
+            .uri("https://s3.us-east-1.amazonaws.com/test-bucket/archive.zip")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .header("x-amz-restore", restore)
+            .header("x-amz-storage-class", "GLACIER")
+            .body(SdkBody::empty())
+            .unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_wait_for_restore_until_complete() {
+    let http_client = StaticReplayClient::new(vec![
+        head_with_restore(r#"ongoing-request="true""#),
+        head_with_restore(r#"ongoing-request="true""#),
+        head_with_restore(r#"ongoing-request="false", expiry-date="Fri, 21 Dec 2029 00:00:00 GMT""#),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = wait_for_restore(
+        &client,
+        "test-bucket",
+        "archive.zip",
+        std::time::Duration::from_millis(10),
+        std::time::Duration::from_secs(60),
+    ).await;
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!(http_client.actual_requests().count(), 3);
+}
+
+#[tokio::test]
+async fn test_wait_for_restore_times_out() {
+    let http_client = StaticReplayClient::new(vec![
+        head_with_restore(r#"ongoing-request="true""#),
+        head_with_restore(r#"ongoing-request="true""#),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let err = wait_for_restore(
+        &client,
+        "test-bucket",
+        "archive.zip",
+        std::time::Duration::from_secs(60),
+        std::time::Duration::from_millis(10),
+    ).await.unwrap_err();
+    assert!(err.to_string().contains("Timed out"), "{}", err);
+    // One check at the start and a final one at the deadline
+    assert_eq!(http_client.actual_requests().count(), 2);
+}
+
+#[test]
+fn test_parse_tier() {
+    assert!(parse_tier("Standard").is_ok());
+    assert!(parse_tier("bulk").is_ok());
+    assert!(parse_tier("EXPEDITED").is_ok());
+    assert!(parse_tier("fast").is_err());
+}
+
+#[tokio::test]
+async fn test_object_attributes_restore_in_progress() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("HEAD")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/archive.zip")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .header("Content-Length", "2048")
+                .header("Content-Type", "application/zip")
+                .header("x-amz-storage-class", "GLACIER")
+                .header("x-amz-restore", r#"ongoing-request="true""#)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let head = client.head_object()
+        .bucket("test-bucket")
+        .key("archive.zip")
+        .send()
+        .await
+        .unwrap();
+
+    let output = format_object_attributes("archive.zip", &head);
+    assert!(output.contains("Storage Class: GLACIER"));
+    assert!(output.contains("In progress"));
+}
+
+#[test]
+fn test_describe_restore_status_completed() {
+    let status = describe_restore_status(r#"ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT""#);
+    assert!(status.contains("Completed"));
+    assert!(status.contains("Fri, 21 Dec 2012 00:00:00 GMT"));
+}
+
+#[tokio::test]
+async fn test_list_object_versions() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?versions&prefix=docs%2F")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListVersionsResult>
+                        <Name>test-bucket</Name>
+                        <Prefix>docs/</Prefix>
+                        <IsTruncated>false</IsTruncated>
+                        <Version>
+                            <Key>docs/readme.txt</Key>
+                            <VersionId>v2</VersionId>
+                            <IsLatest>false</IsLatest>
+                            <Size>120</Size>
+                            <LastModified>2023-01-02T00:00:00.000Z</LastModified>
+                        </Version>
+                        <Version>
+                            <Key>docs/readme.txt</Key>
+                            <VersionId>v1</VersionId>
+                            <IsLatest>false</IsLatest>
+                            <Size>100</Size>
+                            <LastModified>2023-01-01T00:00:00.000Z</LastModified>
+                        </Version>
+                        <DeleteMarker>
+                            <Key>docs/readme.txt</Key>
+                            <VersionId>v3</VersionId>
+                            <IsLatest>true</IsLatest>
+                            <LastModified>2023-01-03T00:00:00.000Z</LastModified>
+                        </DeleteMarker>
+                    </ListVersionsResult>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = list_object_versions(&client, "test-bucket", Some("docs/")).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_delete_object_version() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("DELETE")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/docs/readme.txt?versionId=v1")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(204)
+                .header("x-amz-version-id", "v1")
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+    
+    let client = Client::from_conf(config);
+
+    let result = delete_object(&client, "test-bucket", "docs/readme.txt", Some("v1"), None, true, false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].uri().contains("versionId=v1"));
+}
+
+#[tokio::test]
+async fn test_delete_object_version_with_mfa() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("DELETE")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/docs/readme.txt?versionId=v1")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(204)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mfa = "arn:aws:iam::123456789012:mfa/root 123456";
+    let result = delete_object(&client, "test-bucket", "docs/readme.txt", Some("v1"), Some(mfa), true, false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests[0].headers().get("x-amz-mfa"), Some(mfa));
+}
+
+#[test]
+fn test_parse_retention_mode() {
+    assert!(parse_retention_mode("governance").is_ok());
+    assert!(parse_retention_mode("COMPLIANCE").is_ok());
+    assert!(parse_retention_mode("forever").is_err());
+    assert!(parse_legal_hold("ON").is_ok());
+    assert!(parse_legal_hold("maybe").is_err());
+}
+
+#[tokio::test]
+async fn test_set_object_retention() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/file1.txt?retention")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = set_object_retention(&client, "test-bucket", "file1.txt", "GOVERNANCE", "2030-01-01T00:00:00Z", false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    let body = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<Mode>GOVERNANCE</Mode>"));
+    assert!(body.contains("<RetainUntilDate>2030-01-01T00:00:00Z</RetainUntilDate>"));
+}
+
+#[tokio::test]
+async fn test_set_object_retention_rejects_bad_date() {
+    let http_client = StaticReplayClient::new(vec![]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let err = set_object_retention(&client, "test-bucket", "file1.txt", "GOVERNANCE", "next year", false).await.unwrap_err();
+    assert_eq!(s3sh::errors::classify_error(&err), s3sh::errors::FailureKind::Usage);
+    assert_eq!(http_client.actual_requests().count(), 0);
+}
+
+#[tokio::test]
+async fn test_toggle_object_legal_hold() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/file1.txt?legal-hold")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/file1.txt?legal-hold")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(set_object_legal_hold(&client, "test-bucket", "file1.txt", "on", false).await.is_ok());
+    assert!(set_object_legal_hold(&client, "test-bucket", "file1.txt", "off", false).await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    let on = std::str::from_utf8(requests[0].body().bytes().unwrap()).unwrap();
+    let off = std::str::from_utf8(requests[1].body().bytes().unwrap()).unwrap();
+    assert!(on.contains("<Status>ON</Status>"));
+    assert!(off.contains("<Status>OFF</Status>"));
+}
+
+#[tokio::test]
+async fn test_legal_hold_without_object_lock() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/file1.txt?legal-hold")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(400)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>InvalidRequest</Code>
+                        <Message>Bucket is missing Object Lock Configuration</Message>
+                    </Error>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let err = set_object_legal_hold(&client, "test-bucket", "file1.txt", "on", false).await.unwrap_err();
+    assert!(err.to_string().contains("Object Lock is not enabled"));
+}
+
+#[tokio::test]
+async fn test_object_dry_run_sends_no_requests() {
+    let http_client = StaticReplayClient::new(vec![]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(file, "dry run").unwrap();
+    let path = file.path().to_str().unwrap();
+
+    assert!(upload_object(&client, "test-bucket", path, Some("dry.txt".to_string()), None, None, None, None, &Grants::default(), &[], &ObjectHeaders::default(), false, true, None, None, true).await.is_ok());
+    // Dry-run must not prompt, so assume_yes is false here
+    assert!(delete_object(&client, "test-bucket", "dry.txt", None, None, false, true).await.is_ok());
+    assert!(delete_object(&client, "test-bucket", "dry.txt", Some("v1"), None, false, true).await.is_ok());
+
+    assert!(set_object_retention(&client, "test-bucket", "dry.txt", "COMPLIANCE", "2030-01-01T00:00:00Z", true).await.is_ok());
+    assert!(set_object_legal_hold(&client, "test-bucket", "dry.txt", "on", true).await.is_ok());
+    assert!(put_object_acl(&client, "test-bucket", "dry.txt", Some("public-read"), &Grants::default(), true).await.is_ok());
+    assert!(restore_object(&client, "test-bucket", "archive.zip", 7, "Standard", true).await.is_ok());
+
+    assert_eq!(http_client.actual_requests().count(), 0);
+}
+
+#[test]
+fn test_parse_checksum_algorithm() {
+    assert!(parse_checksum_algorithm("crc32").is_ok());
+    assert!(parse_checksum_algorithm("CRC32C").is_ok());
+    assert!(parse_checksum_algorithm("sha1").is_ok());
+    assert!(parse_checksum_algorithm("sha256").is_ok());
+    assert!(parse_checksum_algorithm("md5").is_err());
+}
+
+#[tokio::test]
+async fn test_upload_object_with_checksum() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/test-file.txt")
+                .body(SdkBody::from("hello world"))
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    write!(temp_file, "hello world").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+
+    let result = upload_object(&client, "test-bucket", path, Some("test-file.txt".to_string()), Some("sha256"), None, None, None, &Grants::default(), &[], &ObjectHeaders::default(), false, true, None, None, false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].headers().get("x-amz-sdk-checksum-algorithm"), Some("SHA256"));
+}
+
+#[tokio::test]
+async fn test_object_attributes_shows_checksum() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("HEAD")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/file1.txt")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .header("Content-Length", "11")
+                .header("x-amz-checksum-sha256", "uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=")
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let head = client.head_object()
+        .bucket("test-bucket")
+        .key("file1.txt")
+        .send()
+        .await
+        .unwrap();
+
+    let output = format_object_attributes("file1.txt", &head);
+    assert!(output.contains("Checksum (SHA256): uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek="));
+}
+
+#[tokio::test]
+async fn test_compute_checksum() {
+    let cases = [
+        ("crc32", "DUoRhQ=="),
+        ("crc32c", "yZRlqg=="),
+        ("sha1", "Kq5sNclPz7QV2+lfQIuc6R7oRu0="),
+        ("sha256", "uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek="),
+    ];
+    for (algorithm, expected) in cases {
+        let algorithm = parse_checksum_algorithm(algorithm).unwrap();
+        let checksum = compute_checksum(&mut &b"hello world"[..], &algorithm).await.unwrap();
+        assert_eq!(checksum, expected, "{}", algorithm.as_str());
+    }
+}
+
+#[test]
+fn test_decode_checksum_hex_or_base64() {
+    let sha1 = parse_checksum_algorithm("sha1").unwrap();
+    let from_hex = decode_checksum("2aae6c35c94fcfb415dbe95f408b9ce91ee846ed", &sha1).unwrap();
+    let from_base64 = decode_checksum("Kq5sNclPz7QV2+lfQIuc6R7oRu0=", &sha1).unwrap();
+    assert_eq!(from_hex, from_base64);
+
+    // A CRC32 checksum is not a SHA-1
+    assert!(decode_checksum("DUoRhQ==", &sha1).is_err());
+    assert!(decode_checksum("not a checksum!", &sha1).is_err());
+}
+
+#[tokio::test]
+async fn test_verify_object_against_stored_checksum() {
+    let attributes = || aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("GET")
+            .uri("https://test-bucket.s3.us-east-1.amazonaws.com/hello.txt?attributes")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                <GetObjectAttributesResponse>
+                    <Checksum>
+                        <ChecksumSHA256>uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=</ChecksumSHA256>
+                    </Checksum>
+                </GetObjectAttributesResponse>"#))
+            .unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![attributes(), attributes()]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let expected = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+    assert!(verify_object(&client, "test-bucket", "hello.txt", expected, "sha256", false).await.is_ok());
+
+    let wrong = "0000000000000000000000000000000000000000000000000000000000000000";
+    let err = verify_object(&client, "test-bucket", "hello.txt", wrong, "sha256", false).await.unwrap_err();
+    assert!(err.to_string().contains("mismatch"), "{}", err);
+    assert!(err.to_string().contains("uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek="), "{}", err);
+
+    // Only GetObjectAttributes was needed; nothing was downloaded
+    assert_eq!(http_client.actual_requests().count(), 2);
+}
+
+#[tokio::test]
+async fn test_verify_object_downloads_without_stored_checksum() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/hello.txt?attributes")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from("<GetObjectAttributesResponse></GetObjectAttributesResponse>"))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/hello.txt?x-id=GetObject")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from("hello world"))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(verify_object(&client, "test-bucket", "hello.txt", "DUoRhQ==", "crc32", false).await.is_ok());
+    assert_eq!(http_client.actual_requests().count(), 2);
+}
+
+#[tokio::test]
+async fn test_get_object_attributes_multipart() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/big.bin?attributes")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .header("Last-Modified", "Sun, 01 Jan 2023 00:00:00 GMT")
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <GetObjectAttributesResponse>
+                        <ETag>3858f62230ac3c915f300c664312c11f-3</ETag>
+                        <Checksum>
+                            <ChecksumCRC32>AAAAAA==</ChecksumCRC32>
+                        </Checksum>
+                        <ObjectParts>
+                            <PartsCount>3</PartsCount>
+                            <IsTruncated>false</IsTruncated>
+                        </ObjectParts>
+                        <StorageClass>STANDARD_IA</StorageClass>
+                        <ObjectSize>26214400</ObjectSize>
+                    </GetObjectAttributesResponse>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/big.bin?attributes")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <GetObjectAttributesResponse>
+                        <ObjectSize>26214400</ObjectSize>
+                    </GetObjectAttributesResponse>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let attrs = client.get_object_attributes()
+        .bucket("test-bucket")
+        .key("big.bin")
+        .object_attributes(aws_sdk_s3::types::ObjectAttributes::ObjectParts)
+        .send()
+        .await
+        .unwrap();
+
+    let output = format_get_object_attributes("big.bin", &attrs);
+    assert!(output.contains("Size: 26214400 bytes"));
+    assert!(output.contains("Parts: 3"));
+    assert!(output.contains("Storage Class: STANDARD_IA"));
+    assert!(output.contains("Checksum (CRC32): AAAAAA=="));
+
+    let result = get_object_attributes(&client, "test-bucket", "big.bin", false, false, OutputFormat::Table).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    let wanted = requests[1].headers().get_all("x-amz-object-attributes").collect::<Vec<_>>().join(",");
+    assert!(wanted.contains("ObjectParts"));
+    assert!(wanted.contains("ObjectSize"));
+}
+
+#[tokio::test]
+async fn test_object_headers_include_metadata_and_sse() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("HEAD")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/report.csv")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .header("Content-Length", "1024")
+                .header("Content-Type", "text/csv")
+                .header("Cache-Control", "max-age=3600")
+                .header("Content-Encoding", "gzip")
+                .header("ETag", "\"abc123\"")
+                .header("x-amz-version-id", "v2")
+                .header("x-amz-server-side-encryption", "aws:kms")
+                .header("x-amz-server-side-encryption-aws-kms-key-id", "arn:aws:kms:us-east-1:123456789012:key/k1")
+                .header("x-amz-meta-owner", "data-team")
+                .header("x-amz-meta-source", "nightly-export")
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let headers = object_headers(&client, "test-bucket", "report.csv").await.unwrap();
+    assert_eq!(headers["x-amz-meta-owner"], "data-team");
+    assert_eq!(headers["x-amz-meta-source"], "nightly-export");
+    assert_eq!(headers["x-amz-server-side-encryption"], "aws:kms");
+    assert_eq!(headers["x-amz-server-side-encryption-aws-kms-key-id"], "arn:aws:kms:us-east-1:123456789012:key/k1");
+    assert_eq!(headers["x-amz-version-id"], "v2");
+    assert_eq!(headers["cache-control"], "max-age=3600");
+    assert_eq!(headers["content-encoding"], "gzip");
+    assert_eq!(headers["etag"], "\"abc123\"");
+
+    let output = format_object_headers("report.csv", &headers);
+    assert!(output.contains("x-amz-meta-owner: data-team\n"));
+    assert!(output.contains("x-amz-server-side-encryption: aws:kms\n"));
+    // Sorted by name
+    assert!(output.find("cache-control").unwrap() < output.find("x-amz-meta-owner").unwrap());
+
+    let json = serde_json::to_value(&headers).unwrap();
+    assert_eq!(json["x-amz-meta-owner"], "data-team");
+}
+
+fn object_info(key: &str, size_bytes: i64, modified_secs: i64) -> ObjectInfo {
+    ObjectInfo {
+        key: key.to_string(),
+        size: size_bytes.to_string(),
+        last_modified: DateTime::from_secs(modified_secs).to_string(),
+        e_tag: "-".to_string(),
+        storage_class: "STANDARD".to_string(),
+        content_type: None,
+        metadata: None,
+        size_bytes,
+        modified: Some(DateTime::from_secs(modified_secs)),
+    }
+}
+
+fn sorted_keys(key: SortKey, reverse: bool) -> Vec<String> {
+    // Sizes 9 and 10 would be misordered if compared as strings
+    let mut infos = vec![
+        object_info("c.txt", 9, 1_700_000_000),
+        object_info("a.txt", 10, 1_600_000_000),
+        object_info("b.txt", 2048, 1_650_000_000),
+    ];
+    sort_objects(&mut infos, key, reverse);
+    infos.into_iter().map(|i| i.key).collect()
+}
+
+#[test]
+fn test_sort_objects() {
+    assert_eq!(sorted_keys(SortKey::Name, false), ["a.txt", "b.txt", "c.txt"]);
+    assert_eq!(sorted_keys(SortKey::Size, false), ["c.txt", "a.txt", "b.txt"]);
+    assert_eq!(sorted_keys(SortKey::Modified, false), ["a.txt", "b.txt", "c.txt"]);
+    assert_eq!(sorted_keys(SortKey::Size, true), ["b.txt", "a.txt", "c.txt"]);
+    assert_eq!(sorted_keys(SortKey::Modified, true), ["c.txt", "b.txt", "a.txt"]);
+}
+
+#[test]
+fn test_summarize_objects() {
+    let infos = vec![
+        object_info("a.txt", 1024, 1_600_000_000),
+        object_info("b.txt", 512, 1_650_000_000),
+        object_info("c.bin", 3 * 1024 * 1024 * 1024, 1_700_000_000),
+    ];
+    assert_eq!(summarize_objects(&infos, false), "3 objects, 3.0 GiB");
+    assert_eq!(summarize_objects(&infos, true), "3 objects, 3221227008 bytes");
+    assert_eq!(summarize_objects(&infos[..1], false), "1 object, 1.0 KiB");
+}
+
+fn sync_fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("css")).unwrap();
+    for name in ["index.html", "about.html", "logo.png", "css/site.css", "css/print.css"] {
+        std::fs::write(dir.path().join(name), name).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn test_collect_sync_files() {
+    let dir = sync_fixture();
+
+    let keys = collect_sync_files(dir.path(), Some("www/"), &PathFilter::default()).unwrap()
+        .into_iter()
+        .map(|(_, key)| key)
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["www/about.html", "www/css/print.css", "www/css/site.css", "www/index.html", "www/logo.png"]);
+}
+
+#[test]
+fn test_collect_sync_files_filtered() {
+    let dir = sync_fixture();
+    let filter = PathFilter::new(&[FilterRule::Exclude("css/*".to_string())]).unwrap();
+
+    let keys = collect_sync_files(dir.path(), None, &filter).unwrap()
+        .into_iter()
+        .map(|(_, key)| key)
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["about.html", "index.html", "logo.png"]);
+}
+
+#[tokio::test]
+async fn test_sync_directory_concurrently() {
+    let dir = sync_fixture();
+
+    let events = (0..5)
+        .map(|_| aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ))
+        .collect();
+    let http_client = StaticReplayClient::new(events);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = sync_directory(&client, dir.path().to_str().unwrap(), "test-bucket", None, 4, &PathFilter::default(), false, None, false, OutputFormat::Table, false).await;
+    assert!(result.is_ok());
+
+    let mut uploaded = http_client.actual_requests()
+        .map(|r| r.uri().parse::<http::Uri>().unwrap().path().to_string())
+        .collect::<Vec<_>>();
+    uploaded.sort();
+    assert_eq!(uploaded, ["/about.html", "/css/print.css", "/css/site.css", "/index.html", "/logo.png"]);
+}
+
+#[tokio::test]
+async fn test_upload_archive_sends_one_object() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("site");
+    std::fs::create_dir_all(root.join("css")).unwrap();
+    std::fs::write(root.join("index.html"), "<h1>hello</h1>").unwrap();
+    std::fs::write(root.join("css/site.css"), "body {}").unwrap();
+
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/site.tar.gz?x-id=PutObject")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = upload_archive(
+        &client, "test-bucket", &root, None, ArchiveFormat::TarGz, None, None, None, None, &Grants::default(), None, &ObjectHeaders::default(), false,
+    ).await;
+    assert!(result.is_ok(), "{:?}", result);
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].uri().contains("/site.tar.gz"), "{}", requests[0].uri());
+    assert_eq!(requests[0].headers().get("content-type"), Some("application/gzip"));
+
+    let entries = archive_entries(&root).unwrap();
+    let archive = write_archive(&entries, ArchiveFormat::TarGz, Vec::new()).await.unwrap();
+    // The body is sent aws-chunked with a checksum trailer, so check its decoded length
+    assert!(!archive.is_empty());
+    assert_eq!(requests[0].headers().get("x-amz-decoded-content-length"), Some(archive.len().to_string().as_str()));
+}
+
+#[tokio::test]
+async fn test_sync_directory_deletes_stale_remote_keys() {
+    let dir = sync_fixture();
+
+    let mut events = ["about.html", "index.html", "logo.png"].iter()
+        .map(|name| aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri(format!("https://test-bucket.s3.us-east-1.amazonaws.com/www/{}?x-id=PutObject", name))
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ))
+        .collect::<Vec<_>>();
+    events.push(aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("GET")
+            .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2&prefix=www%2F")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                <ListBucketResult>
+                    <Name>test-bucket</Name>
+                    <Prefix>www/</Prefix>
+                    <IsTruncated>false</IsTruncated>
+                    <Contents><Key>www/about.html</Key><Size>1</Size></Contents>
+                    <Contents><Key>www/css/old.css</Key><Size>1</Size></Contents>
+                    <Contents><Key>www/index.html</Key><Size>1</Size></Contents>
+                    <Contents><Key>www/logo.png</Key><Size>1</Size></Contents>
+                    <Contents><Key>www/stale.html</Key><Size>1</Size></Contents>
+                </ListBucketResult>"#))
+            .unwrap(),
+    ));
+    events.push(aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("POST")
+            .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?delete")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                <DeleteResult><Deleted><Key>www/stale.html</Key></Deleted></DeleteResult>"#))
+            .unwrap(),
+    ));
+    let http_client = StaticReplayClient::new(events);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    // Excluded paths are neither uploaded nor deleted
+    let filter = PathFilter::new(&[FilterRule::Exclude("css/*".to_string())]).unwrap();
+    let result = sync_directory(
+        &client, dir.path().to_str().unwrap(), "test-bucket", Some("www/"), 1, &filter, true, None, true, OutputFormat::Table, false,
+    ).await;
+    assert!(result.is_ok(), "{:?}", result);
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 5);
+    let body = std::str::from_utf8(requests[4].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<Key>www/stale.html</Key>"));
+    assert!(!body.contains("old.css"));
+    assert!(!body.contains("index.html"));
+}
+
+#[test]
+fn test_stale_keys() {
+    let remote = ["logs/a.log", "logs/b.log", "logs/keep.txt"].map(String::from).to_vec();
+    let local = ["logs/a.log".to_string()];
+    let filter = PathFilter::new(&[FilterRule::Exclude("*.txt".to_string())]).unwrap();
+    assert_eq!(stale_keys(remote, &local, "logs/", &filter), ["logs/b.log"]);
+}
+
+#[test]
+fn test_parse_sse() {
+    assert!(parse_sse(None, None).unwrap().is_none());
+    assert!(parse_sse(Some("AES256"), None).is_ok());
+    assert!(parse_sse(Some("aws:kms"), Some("alias/app")).is_ok());
+    assert!(parse_sse(Some("AES256"), Some("alias/app")).is_err());
+    assert!(parse_sse(None, Some("alias/app")).is_err());
+    assert!(parse_sse(Some("kms"), None).is_err());
+}
+
+#[tokio::test]
+async fn test_upload_object_with_kms_key() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/secret.txt")
+                .body(SdkBody::from("hello world"))
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    write!(temp_file, "hello world").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+
+    let result = upload_object(
+        &client,
+        "test-bucket",
+        path,
+        Some("secret.txt".to_string()),
+        None,
+        Some("aws:kms"),
+        Some("arn:aws:kms:us-east-1:123456789012:key/abcd"),
+        None,
+        &Grants::default(),
+        &[],
+        &ObjectHeaders::default(),
+        false,
+        true,
+        None,
+        None,
+        false,
+    ).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].headers().get("x-amz-server-side-encryption"), Some("aws:kms"));
+    assert_eq!(
+        requests[0].headers().get("x-amz-server-side-encryption-aws-kms-key-id"),
+        Some("arn:aws:kms:us-east-1:123456789012:key/abcd")
+    );
+}
+
+#[test]
+fn test_copy_source_encoding() {
+    assert_eq!(copy_source("bucket", "docs/readme.txt"), "bucket/docs/readme.txt");
+    assert_eq!(copy_source("bucket", "my file+1.txt"), "bucket/my%20file%2B1.txt");
+}
+
+#[tokio::test]
+async fn test_move_object() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/new/name.txt")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <CopyObjectResult>
+                        <LastModified>2023-01-01T00:00:00.000Z</LastModified>
+                        <ETag>"abc"</ETag>
+                    </CopyObjectResult>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("DELETE")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/old/name.txt")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(204)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = move_object(&client, &client, "test-bucket", "old/name.txt", "test-bucket", "new/name.txt", false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].method(), "PUT");
+    assert_eq!(requests[0].headers().get("x-amz-copy-source"), Some("test-bucket/old/name.txt"));
+    assert_eq!(requests[0].headers().get("x-amz-metadata-directive"), Some("COPY"));
+    assert_eq!(requests[1].method(), "DELETE");
+    assert!(requests[1].uri().contains("old/name.txt"));
+}
+
+#[tokio::test]
+async fn test_move_object_keeps_source_when_copy_fails() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/other-bucket/name.txt")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(403)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>AccessDenied</Code>
+                        <Message>Access Denied</Message>
+                    </Error>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = move_object(&client, &client, "test-bucket", "name.txt", "other-bucket", "name.txt", false).await;
+    assert!(result.is_err());
+
+    // No delete is attempted
+    assert_eq!(http_client.actual_requests().count(), 1);
+}
+
+#[tokio::test]
+async fn test_move_object_across_regions_deletes_with_source_client() {
+    use aws_smithy_runtime::client::http::test_util::ReplayEvent;
+
+    let dst_http = StaticReplayClient::new(vec![
+        ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.eu-west-1.amazonaws.com/eu-bucket/name.txt")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <CopyObjectResult><ETag>"abc"</ETag></CopyObjectResult>"#))
+                .unwrap(),
+        ),
+    ]);
+    let src_http = StaticReplayClient::new(vec![
+        ReplayEvent::new(
+            http::Request::builder()
+                .method("DELETE")
+                .uri("https://s3.us-east-1.amazonaws.com/us-bucket/name.txt")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder().status(204).body(SdkBody::empty()).unwrap(),
+        ),
+    ]);
+
+    let client_in = |region: &'static str, http_client: &StaticReplayClient| Client::from_conf(
+        aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new(region))
+            .http_client(http_client.clone())
+            .build(),
+    );
+    let dst_client = client_in("eu-west-1", &dst_http);
+    let src_client = client_in("us-east-1", &src_http);
+
+    move_object(&dst_client, &src_client, "us-bucket", "name.txt", "eu-bucket", "name.txt", false).await.unwrap();
+
+    let copies: Vec<_> = dst_http.actual_requests().collect();
+    assert_eq!(copies.len(), 1);
+    assert!(copies[0].uri().starts_with("https://eu-bucket.s3.eu-west-1.amazonaws.com/"), "{}", copies[0].uri());
+    let deletes: Vec<_> = src_http.actual_requests().collect();
+    assert_eq!(deletes.len(), 1);
+    assert_eq!(deletes[0].method(), "DELETE");
+    assert!(deletes[0].uri().starts_with("https://us-bucket.s3.us-east-1.amazonaws.com/"), "{}", deletes[0].uri());
+}
+
+#[tokio::test]
+async fn test_rename_prefix_copies_then_deletes_each_key() {
+    use aws_smithy_runtime::client::http::test_util::ReplayEvent;
+
+    let copy = |key: &str| ReplayEvent::new(
+        http::Request::builder()
+            .method("PUT")
+            .uri(format!("https://test-bucket.s3.us-east-1.amazonaws.com/{}?x-id=CopyObject", key))
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(r#"<CopyObjectResult><ETag>"abc"</ETag></CopyObjectResult>"#))
+            .unwrap(),
+    );
+    let delete = |key: &str| ReplayEvent::new(
+        http::Request::builder()
+            .method("DELETE")
+            .uri(format!("https://test-bucket.s3.us-east-1.amazonaws.com/{}?x-id=DeleteObject", key))
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder().status(204).body(SdkBody::empty()).unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![
+        ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2&prefix=old%2F")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Name>test-bucket</Name>
+                        <Contents><Key>old/a.txt</Key><Size>10</Size></Contents>
+                        <Contents><Key>old/sub/b.txt</Key><Size>20</Size></Contents>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        ),
+        copy("new/a.txt"),
+        delete("old/a.txt"),
+        copy("new/sub/b.txt"),
+        delete("old/sub/b.txt"),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    // One at a time, so the replayed responses line up with the keys
+    rename_prefix(&client, "test-bucket", "old/", "new/", 1, OutputFormat::Table, false).await.unwrap();
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 5);
+    let pairs = [("old/a.txt", "new/a.txt"), ("old/sub/b.txt", "new/sub/b.txt")];
+    for (i, (old, new)) in pairs.iter().enumerate() {
+        let copy = requests[1 + 2 * i];
+        assert_eq!(copy.method(), "PUT");
+        assert!(copy.uri().contains(new));
+        assert_eq!(copy.headers().get("x-amz-copy-source"), Some(format!("test-bucket/{}", old).as_str()));
+        assert_eq!(copy.headers().get("x-amz-metadata-directive"), Some("COPY"));
+        let delete = requests[2 + 2 * i];
+        assert_eq!(delete.method(), "DELETE");
+        assert!(delete.uri().contains(old));
+    }
+}
+
+#[test]
+fn test_rename_plan_and_overlap_guard() {
+    let keys = vec!["old/a.txt".to_string(), "old/sub/b.txt".to_string()];
+    assert_eq!(rename_plan(&keys, "old/", "archive/old/"), vec![
+        ("old/a.txt".to_string(), "archive/old/a.txt".to_string()),
+        ("old/sub/b.txt".to_string(), "archive/old/sub/b.txt".to_string()),
+    ]);
+
+    assert!(check_rename_prefixes("old/", "new/").is_ok());
+    assert!(check_rename_prefixes("old/", "archive/old/").is_ok());
+    // Either prefix containing the other could move keys back under the old one
+    assert!(check_rename_prefixes("old/", "old/nested/").is_err());
+    assert!(check_rename_prefixes("old/nested/", "old/").is_err());
+    assert!(check_rename_prefixes("old/", "old/").is_err());
+    assert!(check_rename_prefixes("", "new/").is_err());
+}
+
+#[test]
+fn test_parse_storage_class() {
+    assert_eq!(parse_storage_class("STANDARD_IA").unwrap(), aws_sdk_s3::types::StorageClass::StandardIa);
+    assert_eq!(parse_storage_class("glacier").unwrap(), aws_sdk_s3::types::StorageClass::Glacier);
+    assert!(parse_storage_class("COLD").is_err());
+}
+
+#[tokio::test]
+async fn test_copy_object_with_storage_class_and_metadata() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/archive-bucket/report.csv")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <CopyObjectResult>
+                        <LastModified>2023-01-01T00:00:00.000Z</LastModified>
+                        <ETag>"abc"</ETag>
+                    </CopyObjectResult>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = copy_object(
+        &client,
+        "test-bucket",
+        "report.csv",
+        "archive-bucket",
+        "report.csv",
+        Some("GLACIER"),
+        "REPLACE",
+        vec![("owner".to_string(), "data-team".to_string())],
+        false,
+    ).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].headers().get("x-amz-copy-source"), Some("test-bucket/report.csv"));
+    assert_eq!(requests[0].headers().get("x-amz-storage-class"), Some("GLACIER"));
+    assert_eq!(requests[0].headers().get("x-amz-metadata-directive"), Some("REPLACE"));
+    assert_eq!(requests[0].headers().get("x-amz-meta-owner"), Some("data-team"));
+}
+
+#[tokio::test]
+async fn test_copy_object_metadata_needs_replace() {
+    let http_client = StaticReplayClient::new(vec![]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let err = copy_object(
+        &client,
+        "test-bucket",
+        "report.csv",
+        "archive-bucket",
+        "report.csv",
+        None,
+        "COPY",
+        vec![("owner".to_string(), "data-team".to_string())],
+        false,
+    ).await.unwrap_err();
+    assert_eq!(s3sh::errors::classify_error(&err), s3sh::errors::FailureKind::Usage);
+    assert_eq!(http_client.actual_requests().count(), 0);
+}
+
+#[tokio::test]
+async fn test_copy_object_rejects_metadata_without_replace() {
+    let http_client = StaticReplayClient::new(vec![]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = copy_object(
+        &client,
+        "test-bucket",
+        "report.csv",
+        "archive-bucket",
+        "report.csv",
+        None,
+        "COPY",
+        vec![("owner".to_string(), "data-team".to_string())],
+        false,
+    ).await;
+    assert!(result.is_err());
+    assert_eq!(http_client.actual_requests().count(), 0);
+}
+
+#[tokio::test]
+async fn test_copy_prefix_across_buckets() {
+    let list_body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Name>test-bucket</Name>
+    <Prefix>reports/</Prefix>
+    <KeyCount>3</KeyCount>
+    <IsTruncated>false</IsTruncated>
+    <Contents><Key>reports/2024/q1.csv</Key><Size>5</Size></Contents>
+    <Contents><Key>reports/summary.csv</Key><Size>5</Size></Contents>
+    <Contents><Key>reports/scratch.tmp</Key><Size>5</Size></Contents>
+</ListBucketResult>"#;
+
+    let copy = |key: &str| aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("PUT")
+            .uri(format!("https://archive-bucket.s3.eu-west-1.amazonaws.com/{}?x-id=CopyObject", key))
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from("<CopyObjectResult><ETag>\"abc\"</ETag></CopyObjectResult>"))
+            .unwrap(),
+    );
+    // The source is listed in its region, the copies go to the destination's
+    let src_http = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2&prefix=reports%2F")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder().status(200).body(SdkBody::from(list_body)).unwrap(),
+        ),
+    ]);
+    let http_client = StaticReplayClient::new(vec![
+        copy("2025/reports/2024/q1.csv"),
+        copy("2025/reports/summary.csv"),
+    ]);
+
+    let client_in = |region: &'static str, http_client: &StaticReplayClient| Client::from_conf(
+        aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new(region))
+            .http_client(http_client.clone())
+            .build(),
+    );
+    let src_client = client_in("us-east-1", &src_http);
+    let client = client_in("eu-west-1", &http_client);
+    let filter = PathFilter::new(&[FilterRule::Exclude("*.tmp".to_string())]).unwrap();
+
+    let result = copy_prefix(&client, &src_client, "test-bucket", "reports/", "archive-bucket", "2025/reports/", Some("STANDARD_IA"), 1, &filter, OutputFormat::Table, false).await;
+    assert!(result.is_ok(), "{:?}", result);
+
+    assert_eq!(src_http.actual_requests().count(), 1);
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].uri(), "https://archive-bucket.s3.eu-west-1.amazonaws.com/2025/reports/2024/q1.csv?x-id=CopyObject");
+    assert_eq!(requests[0].headers().get("x-amz-copy-source"), Some("test-bucket/reports/2024/q1.csv"));
+    assert_eq!(requests[0].headers().get("x-amz-storage-class"), Some("STANDARD_IA"));
+    assert_eq!(requests[1].uri(), "https://archive-bucket.s3.eu-west-1.amazonaws.com/2025/reports/summary.csv?x-id=CopyObject");
+    assert_eq!(requests[1].headers().get("x-amz-copy-source"), Some("test-bucket/reports/summary.csv"));
+}
+
+fn head_object_client(status: u16) -> Client {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("HEAD")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/report.csv")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(status)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    Client::from_conf(config)
+}
+
+#[tokio::test]
+async fn test_object_exists_present() {
+    let client = head_object_client(200);
+    assert!(object_exists(&client, "test-bucket", "report.csv").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_object_exists_absent() {
+    let client = head_object_client(404);
+    assert!(!object_exists(&client, "test-bucket", "report.csv").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_object_exists_access_denied_is_an_error() {
+    let client = head_object_client(403);
+    let err = object_exists(&client, "test-bucket", "report.csv").await.unwrap_err();
+    assert!(err.to_string().contains("Access denied"));
+}
+
+#[tokio::test]
+async fn test_delete_prefix_applies_filter() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?list-type=2&prefix=logs%2F")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Name>test-bucket</Name>
+                        <Prefix>logs/</Prefix>
+                        <IsTruncated>false</IsTruncated>
+                        <Contents><Key>logs/app.log</Key><Size>1</Size></Contents>
+                        <Contents><Key>logs/keep.txt</Key><Size>1</Size></Contents>
+                        <Contents><Key>logs/old/web.log</Key><Size>1</Size></Contents>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?delete")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <DeleteResult>
+                        <Deleted><Key>logs/app.log</Key></Deleted>
+                        <Deleted><Key>logs/old/web.log</Key></Deleted>
+                    </DeleteResult>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let filter = PathFilter::new(&[
+        FilterRule::Exclude("*".to_string()),
+        FilterRule::Include("*.log".to_string()),
+    ]).unwrap();
+    let result = delete_prefix(&client, "test-bucket", "logs/", &filter, 1, None, true, OutputFormat::Table, false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    let body = std::str::from_utf8(requests[1].body().bytes().unwrap()).unwrap();
+    assert!(body.contains("<Key>logs/app.log</Key>"));
+    assert!(body.contains("<Key>logs/old/web.log</Key>"));
+    assert!(!body.contains("keep.txt"));
+}
+
+#[test]
+fn test_parse_object_canned_acl() {
+    assert_eq!(parse_object_canned_acl("public-read").unwrap(), aws_sdk_s3::types::ObjectCannedAcl::PublicRead);
+    assert!(parse_object_canned_acl("bucket-owner-full-control").is_ok());
+    assert!(parse_object_canned_acl("PUBLIC-READ").is_err());
+}
+
+#[tokio::test]
+async fn test_get_object_acl() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/report.csv?acl")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <AccessControlPolicy xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+                        <Owner><ID>owner-id</ID><DisplayName>alice</DisplayName></Owner>
+                        <AccessControlList>
+                            <Grant>
+                                <Grantee xsi:type="CanonicalUser"><ID>owner-id</ID></Grantee>
+                                <Permission>FULL_CONTROL</Permission>
+                            </Grant>
+                        </AccessControlList>
+                    </AccessControlPolicy>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    assert!(get_object_acl(&client, "test-bucket", "report.csv").await.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].uri().contains("acl"));
+}
+
+#[tokio::test]
+async fn test_upload_object_with_canned_acl() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/public.txt")
+                .body(SdkBody::from("hello world"))
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    write!(temp_file, "hello world").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+
+    let result = upload_object(
+        &client,
+        "test-bucket",
+        path,
+        Some("public.txt".to_string()),
+        None,
+        None,
+        None,
+        Some("public-read"),
+        &Grants::default(),
+        &[],
+        &ObjectHeaders::default(),
+        false,
+        true,
+        None,
+        None,
+        false,
+    ).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests[0].headers().get("x-amz-acl"), Some("public-read"));
+}
+
+#[tokio::test]
+async fn test_upload_object_with_grants() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/shared.txt")
+                .body(SdkBody::from("hello world"))
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    write!(temp_file, "hello world").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+
+    let grants = Grants {
+        read: parse_grantees(&["uri=http://acs.amazonaws.com/groups/global/AllUsers".to_string()]).unwrap(),
+        full_control: parse_grantees(&["id=owner-id".to_string()]).unwrap(),
+        ..Grants::default()
+    };
+    let result = upload_object(
+        &client,
+        "test-bucket",
+        path,
+        Some("shared.txt".to_string()),
+        None,
+        None,
+        None,
+        None,
+        &grants,
+        &[],
+        &ObjectHeaders::default(),
+        false,
+        true,
+        None,
+        None,
+        false,
+    ).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests[0].headers().get("x-amz-grant-read"), Some("uri=\"http://acs.amazonaws.com/groups/global/AllUsers\""));
+    assert_eq!(requests[0].headers().get("x-amz-grant-full-control"), Some("id=\"owner-id\""));
+    assert_eq!(requests[0].headers().get("x-amz-acl"), None);
+}
+
+#[tokio::test]
+async fn test_upload_object_rejects_acl_with_grants() {
+    let http_client = StaticReplayClient::new(vec![]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let grants = Grants { read: Some("id=\"abc\"".to_string()), ..Grants::default() };
+    let err = upload_object(&client, "test-bucket", "file.txt", None, None, None, None, Some("private"), &grants, &[], &ObjectHeaders::default(), false, true, None, None, false).await.unwrap_err();
+    assert!(err.to_string().contains("--grant-*"));
+
+    let grants = Grants { write: Some("id=\"abc\"".to_string()), ..Grants::default() };
+    let err = upload_object(&client, "test-bucket", "file.txt", None, None, None, None, None, &grants, &[], &ObjectHeaders::default(), false, true, None, None, false).await.unwrap_err();
+    assert!(err.to_string().contains("only applies to buckets"));
+}
+
+#[tokio::test]
+async fn test_upload_object_with_http_headers() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/report.pdf")
+                .body(SdkBody::from("hello world"))
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    write!(temp_file, "hello world").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+
+    let headers = ObjectHeaders {
+        cache_control: Some("public, max-age=31536000".to_string()),
+        content_disposition: Some("attachment; filename=report.pdf".to_string()),
+        content_encoding: Some("gzip".to_string()),
+        expires: Some(DateTime::from_secs(1_792_567_680)),
+        ..Default::default()
+    };
+    let result = upload_object(&client, "test-bucket", path, Some("report.pdf".to_string()), None, None, None, None, &Grants::default(), &[], &headers, false, true, None, None, false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests[0].headers().get("cache-control"), Some("public, max-age=31536000"));
+    assert_eq!(requests[0].headers().get("content-disposition"), Some("attachment; filename=report.pdf"));
+    assert_eq!(requests[0].headers().get("content-encoding"), Some("gzip"));
+    assert_eq!(requests[0].headers().get("expires"), Some("Wed, 21 Oct 2026 07:28:00 GMT"));
+}
+
+#[tokio::test]
+async fn test_upload_object_with_tags() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/tagged.txt")
+                .body(SdkBody::from("hello world"))
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    write!(temp_file, "hello world").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+
+    let tags = vec![
+        ("team".to_string(), "data eng".to_string()),
+        ("cost-center".to_string(), "a&b=c/d".to_string()),
+    ];
+    let result = upload_object(&client, "test-bucket", path, Some("tagged.txt".to_string()), None, None, None, None, &Grants::default(), &tags, &ObjectHeaders::default(), false, true, None, None, false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].headers().get("x-amz-tagging"), Some("team=data%20eng&cost-center=a%26b%3Dc%2Fd"));
+}
+
+#[test]
+fn test_encode_tagging_checks_limits() {
+    let tag = |k: &str, v: &str| (k.to_string(), v.to_string());
+
+    assert_eq!(encode_tagging(&[]).unwrap(), None);
+    assert_eq!(encode_tagging(&[tag("env", "prod"), tag("owner", "")]).unwrap().as_deref(), Some("env=prod&owner="));
+    assert_eq!(encode_tagging(&[tag("näme", "ü+")]).unwrap().as_deref(), Some("n%C3%A4me=%C3%BC%2B"));
+
+    let ten: Vec<_> = (0..10).map(|i| tag(&format!("k{}", i), "v")).collect();
+    assert!(encode_tagging(&ten).is_ok());
+    let eleven: Vec<_> = (0..11).map(|i| tag(&format!("k{}", i), "v")).collect();
+    assert!(encode_tagging(&eleven).unwrap_err().to_string().contains("at most 10 tags"));
+
+    assert!(encode_tagging(&[tag(&"k".repeat(128), &"v".repeat(256))]).is_ok());
+    assert!(encode_tagging(&[tag(&"k".repeat(129), "v")]).is_err());
+    assert!(encode_tagging(&[tag("k", &"v".repeat(257))]).is_err());
+    assert!(encode_tagging(&[tag("", "v")]).is_err());
+    assert!(encode_tagging(&[tag("env", "a"), tag("env", "b")]).unwrap_err().to_string().contains("more than once"));
+}
+
+#[tokio::test]
+async fn test_upload_object_with_content_md5() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/test-file.txt")
+                .body(SdkBody::from("hello world"))
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    write!(temp_file, "hello world").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+
+    let result = upload_object(&client, "test-bucket", path, Some("test-file.txt".to_string()), None, None, None, None, &Grants::default(), &[], &ObjectHeaders::default(), true, true, None, None, false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests[0].headers().get("content-md5"), Some("XrY7u+Ae7tCTyyK7j1rNww=="));
+}
+
+#[tokio::test]
+async fn test_compute_content_md5() {
+    let mut empty: &[u8] = b"";
+    assert_eq!(compute_content_md5(&mut empty).await.unwrap(), "1B2M2Y8AsgTpgAmY7PhCfg==");
+
+    let mut hello: &[u8] = b"hello world";
+    assert_eq!(compute_content_md5(&mut hello).await.unwrap(), "XrY7u+Ae7tCTyyK7j1rNww==");
+
+    // Larger than one read buffer
+    let data = vec![b'a'; 200 * 1024];
+    let mut reader: &[u8] = &data;
+    assert_eq!(compute_content_md5(&mut reader).await.unwrap(), "h4A6wcq9Im7rnHZl7+KnWA==");
+}
+
+#[test]
+fn test_parse_expires() {
+    let now = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000);
+
+    assert_eq!(parse_expires("Wed, 21 Oct 2026 07:28:00 GMT", now).unwrap(), DateTime::from_secs(1_792_567_680));
+    assert_eq!(parse_expires("2026-10-21T07:28:00Z", now).unwrap(), DateTime::from_secs(1_792_567_680));
+    assert_eq!(parse_expires("90s", now).unwrap(), DateTime::from_secs(1_090));
+    assert_eq!(parse_expires("2h", now).unwrap(), DateTime::from_secs(8_200));
+    assert_eq!(parse_expires("7d", now).unwrap(), DateTime::from_secs(605_800));
+    assert!(parse_expires("7", now).is_err());
+    assert!(parse_expires("soon", now).is_err());
+    assert!(parse_expires("3y", now).is_err());
+}
+
+#[test]
+fn test_parse_time_bound() {
+    let now = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+
+    assert_eq!(parse_time_bound("2026-10-21T07:28:00Z", now).unwrap(), DateTime::from_secs(1_792_567_680));
+    assert_eq!(parse_time_bound("2026-10-21T09:28:00+02:00", now).unwrap(), DateTime::from_secs(1_792_567_680));
+    assert_eq!(parse_time_bound("2026-10-21", now).unwrap(), DateTime::from_secs(1_792_540_800));
+    assert_eq!(parse_time_bound("90s", now).unwrap(), DateTime::from_secs(999_910));
+    assert_eq!(parse_time_bound("7d", now).unwrap(), DateTime::from_secs(395_200));
+    assert!(parse_time_bound("7", now).is_err());
+    assert!(parse_time_bound("yesterday", now).is_err());
+}
+
+#[test]
+fn test_modified_window_filters_objects() {
+    let object = |key: &str, size: i64, modified: Option<i64>| aws_sdk_s3::types::Object::builder()
+        .key(key)
+        .size(size)
+        .set_last_modified(modified.map(DateTime::from_secs))
+        .build();
+    let objects = vec![
+        object("logs/a.log", 30, Some(100)),
+        object("logs/b.log", 10, Some(200)),
+        object("logs/c.log", 20, Some(300)),
+        object("logs/d.log", 40, Some(400)),
+        object("logs/unknown.log", 50, None),
+    ];
+    let keys = |window: ModifiedWindow, sort: SortKey| {
+        let mut infos = object_infos(&objects, true);
+        infos.retain(|info| window.contains(info.modified.as_ref()));
+        sort_objects(&mut infos, sort, false);
+        infos.into_iter().map(|info| info.key).collect::<Vec<_>>()
+    };
+
+    assert_eq!(keys(ModifiedWindow::default(), SortKey::Name).len(), 5);
+    let since = ModifiedWindow::new(Some(DateTime::from_secs(200)), None).unwrap();
+    assert_eq!(keys(since, SortKey::Name), vec!["logs/b.log", "logs/c.log", "logs/d.log"]);
+    let until = ModifiedWindow::new(None, Some(DateTime::from_secs(200))).unwrap();
+    assert_eq!(keys(until, SortKey::Name), vec!["logs/a.log", "logs/b.log"]);
+    let between = ModifiedWindow::new(Some(DateTime::from_secs(150)), Some(DateTime::from_secs(400))).unwrap();
+    assert_eq!(keys(between, SortKey::Size), vec!["logs/b.log", "logs/c.log", "logs/d.log"]);
+
+    assert!(ModifiedWindow::new(Some(DateTime::from_secs(300)), Some(DateTime::from_secs(200))).is_err());
+}
+
+#[tokio::test]
+async fn test_fetch_objects_within_limits_matching_objects() {
+    let page = |query: &str, body: &str| aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .uri(format!("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2{}", query))
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(body.to_string()))
+            .unwrap(),
+    );
+    let http_client = StaticReplayClient::new(vec![
+        page("", r#"<ListBucketResult>
+            <IsTruncated>true</IsTruncated>
+            <NextContinuationToken>page-2</NextContinuationToken>
+            <Contents><Key>old.log</Key><Size>1</Size><LastModified>2020-01-01T00:00:00.000Z</LastModified></Contents>
+        </ListBucketResult>"#),
+        page("&continuation-token=page-2", r#"<ListBucketResult>
+            <IsTruncated>false</IsTruncated>
+            <Contents><Key>new-1.log</Key><Size>2</Size><LastModified>2026-01-01T00:00:00.000Z</LastModified></Contents>
+            <Contents><Key>new-2.log</Key><Size>3</Size><LastModified>2026-02-01T00:00:00.000Z</LastModified></Contents>
+        </ListBucketResult>"#),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+    let client = Client::from_conf(config);
+
+    // The old object does not use up the limit
+    let since = ModifiedWindow::new(Some(DateTime::from_secs(1_735_689_600)), None).unwrap();
+    let (objects, truncated) = fetch_objects_within(&client, "test-bucket", None, None, Some(1), since).await.unwrap();
+    let keys: Vec<_> = objects.iter().filter_map(|o| o.key()).collect();
+    assert_eq!(keys, vec!["new-1.log"]);
+    assert!(truncated);
+
+    // Full pages are requested, since any page may hold fewer matches
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    assert!(!requests[0].uri().contains("max-keys"));
+}
+
+#[tokio::test]
+async fn test_upload_progress_reaches_file_length() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/progress.txt")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let contents = "x".repeat(64 * 1024 + 7);
+    let mut temp_file = NamedTempFile::new().unwrap();
+    write!(temp_file, "{}", contents).unwrap();
+
+    // A hidden bar still counts, without drawing to the terminal
+    let progress = indicatif::ProgressBar::hidden();
+    progress.set_length(contents.len() as u64);
+    let body = aws_sdk_s3::primitives::ByteStream::from_path(temp_file.path()).await.unwrap();
+
+    client.put_object()
+        .bucket("test-bucket")
+        .key("progress.txt")
+        .body(track_progress(body, progress.clone()))
+        .send()
+        .await
+        .unwrap();
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    // The SDK sends the file aws-chunked with a checksum trailer, sized from the body's hint
+    assert_eq!(requests[0].headers().get("x-amz-decoded-content-length"), Some(contents.len().to_string().as_str()));
+
+    // The replay client does not read the body, so send it the way a real connection would
+    aws_sdk_s3::primitives::ByteStream::new(requests[0].body().try_clone().unwrap())
+        .collect()
+        .await
+        .unwrap();
+    assert_eq!(progress.position(), contents.len() as u64);
+}
+
+#[tokio::test]
+async fn test_upload_from_reader() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/piped.bin")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mut reader: &[u8] = b"piped bytes";
+    let result = upload_from_reader(&client, "test-bucket", "piped.bin", &mut reader, None, None, None, None, &Grants::default(), None, &ObjectHeaders::default(), false).await;
+    assert!(result.is_ok());
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].headers().get("content-type"), Some("application/octet-stream"));
+    // The spooled temp file is gone by now, so check the size the SDK sent
+    assert_eq!(requests[0].headers().get("x-amz-decoded-content-length"), Some("11"));
+}
+
+#[tokio::test]
+async fn test_upload_from_reader_uses_multipart_for_large_input() {
+    let initiate = aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("POST")
+            .uri("https://s3.us-east-1.amazonaws.com/test-bucket/big.bin?uploads")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                <InitiateMultipartUploadResult>
+                    <Bucket>test-bucket</Bucket>
+                    <Key>big.bin</Key>
+                    <UploadId>upload-stdin</UploadId>
+                </InitiateMultipartUploadResult>"#))
+            .unwrap(),
+    );
+    let complete = aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("POST")
+            .uri("https://s3.us-east-1.amazonaws.com/test-bucket/big.bin")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                <CompleteMultipartUploadResult>
+                    <Bucket>test-bucket</Bucket>
+                    <Key>big.bin</Key>
+                    <ETag>"final"</ETag>
+                </CompleteMultipartUploadResult>"#))
+            .unwrap(),
+    );
+    // The threshold is 8 parts of the smallest part size
+    let parts = (1..=8).map(|n| part_response(&format!("\"etag-{}\"", n)));
+    let http_client = StaticReplayClient::new(std::iter::once(initiate).chain(parts).chain(std::iter::once(complete)).collect());
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let mut reader = tokio::io::AsyncReadExt::take(tokio::io::repeat(b'x'), MULTIPART_THRESHOLD);
+    let result = upload_from_reader(&client, "test-bucket", "big.bin", &mut reader, None, None, None, None, &Grants::default(), None, &ObjectHeaders::default(), false).await;
+    assert!(result.is_ok(), "{:?}", result);
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 10);
+    assert!(requests[0].uri().ends_with("?uploads"), "{}", requests[0].uri());
+    assert!(requests[1..9].iter().all(|r| r.uri().contains("uploadId=upload-stdin")));
+    assert_eq!(requests[0].headers().get("content-type"), Some("application/octet-stream"));
+}
+
+#[tokio::test]
+async fn test_upload_from_stdin_requires_key() {
+    let http_client = StaticReplayClient::new(vec![]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let err = upload_object(&client, "test-bucket", "-", None, None, None, None, None, &Grants::default(), &[], &ObjectHeaders::default(), false, true, None, None, false).await.unwrap_err();
+    assert!(err.to_string().contains("--key"));
+}
+
+#[test]
+fn test_download_path() {
+    let dir = std::path::Path::new("out");
+    assert_eq!(download_path(dir, "photos/", "photos/2024/a.jpg"), Some(dir.join("2024").join("a.jpg")));
+    assert_eq!(download_path(dir, "photos", "photos/a.jpg"), Some(dir.join("a.jpg")));
+    assert_eq!(download_path(dir, "photos/", "photos/2024/"), None);
+    assert_eq!(download_path(dir, "photos/", "photos/../../etc/passwd"), None);
+}
+
+#[tokio::test]
+async fn test_download_prefix_mirrors_keys() {
+    let list_body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Name>test-bucket</Name>
+    <Prefix>photos/</Prefix>
+    <KeyCount>4</KeyCount>
+    <IsTruncated>false</IsTruncated>
+    <Contents><Key>photos/2024/</Key><Size>0</Size></Contents>
+    <Contents><Key>photos/2024/b.jpg</Key><Size>5</Size></Contents>
+    <Contents><Key>photos/a.jpg</Key><Size>5</Size></Contents>
+    <Contents><Key>photos/notes.txt</Key><Size>5</Size></Contents>
+</ListBucketResult>"#;
+
+    let get = |key: &str, body: &'static str| aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .uri(format!("https://test-bucket.s3.us-east-1.amazonaws.com/{}?x-id=GetObject", key))
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(body))
+            .unwrap(),
+    );
+
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2&prefix=photos%2F")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(list_body))
+                .unwrap(),
+        ),
+        get("photos/2024/b.jpg", "bbbbb"),
+        get("photos/a.jpg", "aaaaa"),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+    let filter = PathFilter::new(&[FilterRule::Exclude("*.txt".to_string())]).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    // One at a time so the replayed bodies line up with the keys
+    let result = download_prefix(&client, "test-bucket", "photos/", dir.path().to_str().unwrap(), 1, &filter, OutputFormat::Table, false).await;
+    assert!(result.is_ok(), "{:?}", result);
+
+    http_client.relaxed_requests_match();
+    assert_eq!(std::fs::read_to_string(dir.path().join("2024").join("b.jpg")).unwrap(), "bbbbb");
+    assert_eq!(std::fs::read_to_string(dir.path().join("a.jpg")).unwrap(), "aaaaa");
+    assert!(!dir.path().join("notes.txt").exists());
+}
+
+#[tokio::test]
+async fn test_delete_prefix_batches_concurrently() {
+    let keys = (0..1001).map(|i| format!("big/{:04}.dat", i)).collect::<Vec<_>>();
+    let listing = keys.iter()
+        .map(|key| format!("<Contents><Key>{}</Key><Size>1</Size></Contents>", key))
+        .collect::<String>();
+    let deleted = keys[..1000].iter()
+        .map(|key| format!("<Deleted><Key>{}</Key></Deleted>", key))
+        .collect::<String>();
+
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?list-type=2&prefix=big%2F")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(format!(
+                    "<ListBucketResult><Name>test-bucket</Name><IsTruncated>false</IsTruncated>{}</ListBucketResult>",
+                    listing
+                )))
+                .unwrap(),
+        ),
+        // Batches run concurrently, so responses may pair with either request
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?delete")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(format!("<DeleteResult>{}</DeleteResult>", deleted)))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?delete")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(
+                    "<DeleteResult><Error><Key>big/1000.dat</Key><Code>AccessDenied</Code><Message>Access Denied</Message></Error></DeleteResult>",
+                ))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let err = delete_prefix(&client, "test-bucket", "big/", &PathFilter::default(), 4, None, true, OutputFormat::Table, false).await.unwrap_err();
+    assert!(err.to_string().contains("1 of 1001"), "{}", err);
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 3);
+
+    let mut sent = Vec::new();
+    for request in &requests[1..] {
+        let body = std::str::from_utf8(request.body().bytes().unwrap()).unwrap();
+        let batch = body.split("<Key>").skip(1)
+            .map(|part| part.split("</Key>").next().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert!(batch.len() <= 1000);
+        sent.extend(batch);
+    }
+    sent.sort();
+    assert_eq!(sent, keys);
+}
+
+#[tokio::test]
+async fn test_delete_prefix_keeps_batch_error_kind() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?list-type=2&prefix=logs%2F")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(
+                    "<ListBucketResult><Name>test-bucket</Name><IsTruncated>false</IsTruncated>\
+                     <Contents><Key>logs/a.log</Key><Size>1</Size></Contents>\
+                     <Contents><Key>logs/b.log</Key><Size>1</Size></Contents></ListBucketResult>",
+                ))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket?delete")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(403)
+                .body(SdkBody::from(
+                    "<Error><Code>AccessDenied</Code><Message>Access Denied</Message></Error>",
+                ))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client)
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let err = delete_prefix(&client, "test-bucket", "logs/", &PathFilter::default(), 1, None, true, OutputFormat::Table, false).await.unwrap_err();
+    assert_eq!(err.to_string(), "2 of 2 objects failed to delete");
+    // The whole batch was refused, so the exit code is the access-denied one
+    assert_eq!(s3sh::errors::classify_error(&err), s3sh::errors::FailureKind::AccessDenied);
+}
+
+#[tokio::test]
+async fn test_restore_object_retries_slow_down() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/archive.zip?restore")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(503)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>SlowDown</Code>
+                        <Message>Please reduce your request rate.</Message>
+                    </Error>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/archive.zip?restore")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(202)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+    ]);
+
+    // Leave the retrying to the app layer
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .retry_config(aws_sdk_s3::config::retry::RetryConfig::disabled())
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = restore_object(&client, "test-bucket", "archive.zip", 1, "Standard", false).await;
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!(http_client.actual_requests().count(), 2);
+}
+
+#[tokio::test]
+async fn test_restore_object_does_not_retry_other_errors() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/archive.zip?restore")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(403)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>InvalidObjectState</Code>
+                        <Message>Restore is not allowed for the object's current storage class</Message>
+                    </Error>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .retry_config(aws_sdk_s3::config::retry::RetryConfig::disabled())
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = restore_object(&client, "test-bucket", "archive.zip", 1, "Standard", false).await;
+    assert!(result.is_err());
+    assert_eq!(http_client.actual_requests().count(), 1);
+}
+
+#[tokio::test]
+async fn test_set_object_metadata_self_copies_with_replace() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("HEAD")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/docs/readme.md")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .header("content-type", "text/plain")
+                .header("cache-control", "max-age=60")
+                .header("x-amz-storage-class", "STANDARD_IA")
+                .header("x-amz-meta-old", "1")
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("GET")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/docs/readme.md?acl")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <AccessControlPolicy xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+                        <Owner><ID>owner-id</ID></Owner>
+                        <AccessControlList>
+                            <Grant>
+                                <Grantee xsi:type="Group"><URI>http://acs.amazonaws.com/groups/global/AllUsers</URI></Grantee>
+                                <Permission>READ</Permission>
+                            </Grant>
+                        </AccessControlList>
+                    </AccessControlPolicy>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/docs/readme.md")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <CopyObjectResult><ETag>"abc"</ETag></CopyObjectResult>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/docs/readme.md?acl")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = set_object_metadata(
+        &client,
+        "test-bucket",
+        "docs/readme.md",
+        &ObjectHeaders {
+            content_type: Some("text/markdown".to_string()),
+            content_disposition: Some("inline".to_string()),
+            ..Default::default()
+        },
+        vec![("owner".to_string(), "data-team".to_string())],
+        false,
+    ).await;
+    assert!(result.is_ok(), "{:?}", result);
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 4);
+
+    let copy = requests[2];
+    assert_eq!(copy.method(), "PUT");
+    assert_eq!(copy.headers().get("x-amz-copy-source"), Some("test-bucket/docs/readme.md"));
+    assert_eq!(copy.headers().get("x-amz-metadata-directive"), Some("REPLACE"));
+    assert_eq!(copy.headers().get("content-type"), Some("text/markdown"));
+    assert_eq!(copy.headers().get("x-amz-meta-owner"), Some("data-team"));
+    assert_eq!(copy.headers().get("x-amz-meta-old"), None);
+    assert_eq!(copy.headers().get("cache-control"), Some("max-age=60"));
+    assert_eq!(copy.headers().get("content-disposition"), Some("inline"));
+    assert_eq!(copy.headers().get("x-amz-storage-class"), Some("STANDARD_IA"));
+
+    let acl = requests[3];
+    assert!(acl.uri().contains("acl"));
+    let body = std::str::from_utf8(acl.body().bytes().unwrap()).unwrap();
+    assert!(body.contains("AllUsers"));
+}
+
+#[tokio::test]
+async fn test_fetch_objects_stops_at_limit() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2&max-keys=2&prefix=logs%2F")
+                .body(SdkBody::empty())
+                .unwrap(),
+            // Return more than asked for to check the limit is enforced locally too
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Name>test-bucket</Name>
+                        <Prefix>logs/</Prefix>
+                        <IsTruncated>true</IsTruncated>
+                        <NextContinuationToken>next</NextContinuationToken>
+                        <Contents><Key>logs/a.log</Key><Size>1</Size></Contents>
+                        <Contents><Key>logs/b.log</Key><Size>1</Size></Contents>
+                        <Contents><Key>logs/c.log</Key><Size>1</Size></Contents>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        )
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let (objects, truncated) = fetch_objects(&client, "test-bucket", Some("logs/"), None, Some(2)).await.unwrap();
+    assert_eq!(objects.iter().map(|o| o.key().unwrap()).collect::<Vec<_>>(), vec!["logs/a.log", "logs/b.log"]);
+    assert!(truncated);
+
+    http_client.relaxed_requests_match();
+}
+
+#[tokio::test]
+async fn test_fetch_objects_follows_pages_without_limit() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Name>test-bucket</Name>
+                        <IsTruncated>true</IsTruncated>
+                        <NextContinuationToken>page-2</NextContinuationToken>
+                        <Contents><Key>a.txt</Key><Size>1</Size></Contents>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2&continuation-token=page-2")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Name>test-bucket</Name>
+                        <IsTruncated>false</IsTruncated>
+                        <Contents><Key>b.txt</Key><Size>1</Size></Contents>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let (objects, truncated) = fetch_objects(&client, "test-bucket", None, None, None).await.unwrap();
+    assert_eq!(objects.len(), 2);
+    assert!(!truncated);
+
+    http_client.relaxed_requests_match();
+}
+
+#[tokio::test]
+async fn test_fetch_objects_start_after() {
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Name>test-bucket</Name>
+                        <IsTruncated>true</IsTruncated>
+                        <NextContinuationToken>page-2</NextContinuationToken>
+                        <Contents><Key>logs/c.log</Key><Size>1</Size></Contents>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .uri("https://test-bucket.s3.us-east-1.amazonaws.com/?list-type=2")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <ListBucketResult>
+                        <Name>test-bucket</Name>
+                        <IsTruncated>false</IsTruncated>
+                        <Contents><Key>logs/d.log</Key><Size>1</Size></Contents>
+                    </ListBucketResult>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let (objects, truncated) = fetch_objects(&client, "test-bucket", Some("logs/"), Some("logs/b.log"), None).await.unwrap();
+    assert_eq!(objects.iter().map(|o| o.key().unwrap()).collect::<Vec<_>>(), vec!["logs/c.log", "logs/d.log"]);
+    assert!(!truncated);
+
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    let first = requests[0].uri();
+    assert!(first.contains("start-after=logs%2Fb.log"), "{}", first);
+    assert!(first.contains("prefix=logs%2F"), "{}", first);
+    // The continuation token carries the position from the second page on
+    let second = requests[1].uri();
+    assert!(!second.contains("start-after"), "{}", second);
+    assert!(second.contains("continuation-token=page-2"), "{}", second);
+}
+
+fn multipart_fixture() -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("big.bin");
+    std::fs::write(&path, "0123456789").unwrap();
+    (dir, path)
+}
+
+/// The state an interrupted upload of `path` in 4-byte parts leaves behind.
+fn saved_state(path: &std::path::Path, parts: Vec<UploadedPart>) -> MultipartState {
+    MultipartState {
+        bucket: "test-bucket".to_string(),
+        key: "big.bin".to_string(),
+        upload_id: "upload-123".to_string(),
+        local_path: std::fs::canonicalize(path).unwrap(),
+        local_modified: modified_nanos(&std::fs::metadata(path).unwrap()),
+        file_size: 10,
+        part_size: 4,
+        parts,
+    }
+}
+
+fn part_response(etag: &str) -> aws_smithy_runtime::client::http::test_util::ReplayEvent {
+    aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+        http::Request::builder()
+            .method("PUT")
+            .uri("https://s3.us-east-1.amazonaws.com/test-bucket/big.bin")
+            .body(SdkBody::empty())
+            .unwrap(),
+        http::Response::builder()
+            .status(200)
+            .header("ETag", etag)
+            .body(SdkBody::empty())
+            .unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_upload_multipart_resumes_remaining_parts() {
+    let (dir, path) = multipart_fixture();
+    let state_dir = dir.path().join("state");
+    let state_path = upload_state_path(&state_dir, "test-bucket", "big.bin");
+    saved_state(&path, vec![UploadedPart { part_number: 1, e_tag: "\"etag-1\"".to_string(), checksum: None }])
+        .save(&state_path)
+        .unwrap();
+
+    let http_client = StaticReplayClient::new(vec![
+        part_response("\"etag-2\""),
+        part_response("\"etag-3\""),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/big.bin")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <CompleteMultipartUploadResult>
+                        <Bucket>test-bucket</Bucket>
+                        <Key>big.bin</Key>
+                        <ETag>"final-3"</ETag>
+                    </CompleteMultipartUploadResult>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = upload_multipart(
+        &client,
+        "test-bucket",
+        &path,
+        "big.bin",
+        None,
+        None,
+        None,
+        None,
+        &Grants::default(),
+        None,
+        &ObjectHeaders::default(),
+        4,
+        Some(&state_dir),
+        None,
+    ).await;
+    assert!(result.is_ok(), "{:?}", result);
+
+    // Part 1 was skipped; only parts 2 and 3 were sent
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 3);
+    assert!(requests[0].uri().contains("partNumber=2"), "{}", requests[0].uri());
+    assert!(requests[0].uri().contains("uploadId=upload-123"), "{}", requests[0].uri());
+    assert_eq!(requests[0].headers().get("x-amz-decoded-content-length"), Some("4"));
+    assert!(requests[1].uri().contains("partNumber=3"), "{}", requests[1].uri());
+    assert_eq!(requests[1].headers().get("x-amz-decoded-content-length"), Some("2"));
+
+    let complete = std::str::from_utf8(requests[2].body().bytes().unwrap()).unwrap();
+    for part in ["etag-1", "etag-2", "etag-3"] {
+        assert!(complete.contains(part), "{}", complete);
+    }
+    assert!(!state_path.exists());
+}
+
+#[tokio::test]
+async fn test_upload_multipart_saves_state_when_interrupted() {
+    let (dir, path) = multipart_fixture();
+    let state_dir = dir.path().join("state");
+
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/big.bin?uploads")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <InitiateMultipartUploadResult>
+                        <Bucket>test-bucket</Bucket>
+                        <Key>big.bin</Key>
+                        <UploadId>upload-456</UploadId>
+                    </InitiateMultipartUploadResult>"#))
+                .unwrap(),
+        ),
+        part_response("\"etag-1\""),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/big.bin")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(400)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error>
+                        <Code>RequestTimeout</Code>
+                        <Message>Your socket connection to the server was not read from or written to within the timeout period.</Message>
+                    </Error>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .retry_config(aws_sdk_s3::config::retry::RetryConfig::disabled())
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let err = upload_multipart(
+        &client,
+        "test-bucket",
+        &path,
+        "big.bin",
+        None,
+        None,
+        None,
+        None,
+        &Grants::default(),
+        None,
+        &ObjectHeaders::default(),
+        4,
+        Some(&state_dir),
+        None,
+    ).await.unwrap_err();
+    assert!(err.to_string().contains("run the same command again to resume"), "{}", err);
+
+    let state = MultipartState::load(&upload_state_path(&state_dir, "test-bucket", "big.bin")).unwrap();
+    assert_eq!(state.upload_id, "upload-456");
+    assert_eq!(state.parts, vec![UploadedPart { part_number: 1, e_tag: "\"etag-1\"".to_string(), checksum: None }]);
+    // Nothing was aborted
+    assert_eq!(http_client.actual_requests().count(), 3);
+}
+
+#[tokio::test]
+async fn test_upload_multipart_starts_over_for_a_different_file() {
+    let (dir, path) = multipart_fixture();
+    let state_dir = dir.path().join("state");
+    let state_path = upload_state_path(&state_dir, "test-bucket", "big.bin");
+
+    // An interrupted upload of another file of the same size, to the same key
+    let other = dir.path().join("other.bin");
+    std::fs::write(&other, "abcdefghij").unwrap();
+    saved_state(&other, vec![UploadedPart { part_number: 1, e_tag: "\"etag-1\"".to_string(), checksum: None }])
+        .save(&state_path)
+        .unwrap();
+
+    let http_client = StaticReplayClient::new(vec![
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/big.bin?uploads")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(200)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <InitiateMultipartUploadResult>
+                        <Bucket>test-bucket</Bucket>
+                        <Key>big.bin</Key>
+                        <UploadId>upload-789</UploadId>
+                    </InitiateMultipartUploadResult>"#))
+                .unwrap(),
+        ),
+        aws_smithy_runtime::client::http::test_util::ReplayEvent::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("https://s3.us-east-1.amazonaws.com/test-bucket/big.bin")
+                .body(SdkBody::empty())
+                .unwrap(),
+            http::Response::builder()
+                .status(400)
+                .body(SdkBody::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <Error><Code>RequestTimeout</Code><Message>timed out</Message></Error>"#))
+                .unwrap(),
+        ),
+    ]);
+
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .retry_config(aws_sdk_s3::config::retry::RetryConfig::disabled())
+        .http_client(http_client.clone())
+        .build();
+
+    let client = Client::from_conf(config);
+
+    let result = upload_multipart(
+        &client,
+        "test-bucket",
+        &path,
+        "big.bin",
+        None,
+        None,
+        None,
+        None,
+        &Grants::default(),
+        None,
+        &ObjectHeaders::default(),
+        4,
+        Some(&state_dir),
+        None,
+    ).await;
+    assert!(result.is_err());
+
+    // The other file's parts were not reused: a new upload was created, sending part 1
+    let requests = http_client.actual_requests().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].uri().contains("partNumber=1"), "{}", requests[1].uri());
+
+    // Saved as soon as it was created, so failing on part 1 still leaves it resumable
+    let state = MultipartState::load(&state_path).unwrap();
+    assert_eq!(state, MultipartState { upload_id: "upload-789".to_string(), ..saved_state(&path, Vec::new()) });
+}
+
+#[test]
+fn test_part_size_for() {
+    assert_eq!(part_size_for(100 * 1024 * 1024), 8 * 1024 * 1024);
+    // 200 GiB needs bigger parts to stay within 10,000 of them
+    let size: u64 = 200 * 1024 * 1024 * 1024;
+    assert!(size.div_ceil(part_size_for(size)) <= 10_000);
+}
+
+const PNG_MAGIC: [u8; 16] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0x0D, b'I', b'H', b'D', b'R'];
+
+#[test]
+fn test_sniff_content_type() {
+    assert_eq!(sniff_content_type(&PNG_MAGIC), Some("image/png"));
+    assert_eq!(sniff_content_type(b"%PDF-1.7\n"), Some("application/pdf"));
+    assert_eq!(sniff_content_type(b"just some text"), None);
+    assert_eq!(sniff_content_type(b""), None);
+}
+
+#[test]
+fn test_guess_content_type() {
+    let dir = tempfile::tempdir().unwrap();
+
+    // No extension: detected from the PNG magic bytes
+    let image = dir.path().join("photo");
+    std::fs::write(&image, PNG_MAGIC).unwrap();
+    assert_eq!(guess_content_type(&image), "image/png");
+
+    // The extension still wins when it is known
+    let text = dir.path().join("notes.txt");
+    std::fs::write(&text, PNG_MAGIC).unwrap();
+    assert_eq!(guess_content_type(&text), "text/plain");
+
+    let unknown = dir.path().join("data");
+    std::fs::write(&unknown, b"plain bytes").unwrap();
+    assert_eq!(guess_content_type(&unknown), "application/octet-stream");
 }
@@ -0,0 +1,123 @@
+use s3sh::policy::{lint_policy, validate_policy_file, PolicyIssue, Severity};
+use s3sh::ui::OutputFormat;
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+fn messages(issues: &[PolicyIssue], severity: Severity) -> Vec<&str> {
+    issues.iter().filter(|i| i.severity == severity).map(|i| i.message.as_str()).collect()
+}
+
+#[test]
+fn test_lint_policy_accepts_scoped_policy() {
+    let issues = lint_policy(r#"{
+        "Version": "2012-10-17",
+        "Statement": [{
+            "Sid": "AppRead",
+            "Effect": "Allow",
+            "Principal": {"AWS": "arn:aws:iam::123456789012:role/app"},
+            "Action": ["s3:GetObject", "s3:ListBucket"],
+            "Resource": ["arn:aws:s3:::my-bucket", "arn:aws:s3:::my-bucket/*"]
+        }]
+    }"#);
+    assert!(issues.is_empty(), "{:?}", issues);
+}
+
+#[test]
+fn test_lint_policy_rejects_bad_json_and_missing_top_level_fields() {
+    let issues = lint_policy("{\"Version\": \"2012-10-17\",");
+    assert_eq!(issues.len(), 1);
+    assert!(issues[0].message.starts_with("Invalid JSON"));
+
+    assert_eq!(messages(&lint_policy("[]"), Severity::Error), vec!["The policy must be a JSON object"]);
+
+    let issues = lint_policy("{}");
+    assert_eq!(messages(&issues, Severity::Error), vec![
+        "Missing required field 'Version' (use \"2012-10-17\")",
+        "Missing required field 'Statement'",
+    ]);
+
+    let issues = lint_policy(r#"{"Version": "2024-01-01", "Statement": []}"#);
+    assert_eq!(messages(&issues, Severity::Error), vec![
+        "Invalid Version \"2024-01-01\" (use \"2012-10-17\")",
+        "'Statement' has no statements",
+    ]);
+}
+
+#[test]
+fn test_lint_policy_checks_each_statement() {
+    // A single statement object is allowed in place of a list
+    let issues = lint_policy(r#"{
+        "Version": "2012-10-17",
+        "Statement": {"Sid": "Broken", "Effect": "Permit", "Principal": "*", "Actions": "s3:GetObject"}
+    }"#);
+    assert_eq!(messages(&issues, Severity::Error), vec![
+        "Statement 0 (Broken): Unknown field 'Actions'",
+        "Statement 0 (Broken): Effect must be \"Allow\" or \"Deny\", not \"Permit\"",
+        "Statement 0 (Broken): Missing required field 'Action'",
+        "Statement 0 (Broken): Missing required field 'Resource'",
+    ]);
+
+    let issues = lint_policy(r#"{
+        "Version": "2012-10-17",
+        "Statement": [
+            "s3:GetObject",
+            {
+                "Effect": "Deny",
+                "Principal": "*",
+                "Action": ["GetObject", 42],
+                "Resource": "my-bucket/*",
+                "NotResource": "arn:aws:s3:::other/*"
+            },
+            {
+                "Effect": "Deny",
+                "NotPrincipal": {"AWS": "arn:aws:iam::123456789012:root"},
+                "Action": "iam:PassRole",
+                "Resource": "arn:aws-cn:s3:::my-bucket/*"
+            }
+        ]
+    }"#);
+    assert_eq!(messages(&issues, Severity::Error), vec![
+        "Statement 0 must be an object",
+        "Statement 1: 'Action' must be a string or a list of strings",
+        "Statement 1: Use either 'Resource' or 'NotResource', not both",
+        "Statement 2: 'iam:PassRole' is not an S3 action (expected e.g. s3:GetObject)",
+    ]);
+}
+
+#[test]
+fn test_lint_policy_warns_about_public_grants() {
+    let issues = lint_policy(r#"{
+        "Version": "2008-10-17",
+        "Statement": [
+            {"Sid": "Everything", "Effect": "Allow", "Principal": "*", "Action": "s3:*", "Resource": "arn:aws:s3:::b/*"},
+            {"Sid": "Uploads", "Effect": "Allow", "Principal": {"AWS": ["*"]}, "Action": ["s3:GetObject", "s3:PutObject"], "Resource": "arn:aws:s3:::b/*"},
+            {"Sid": "Website", "Effect": "Allow", "Principal": "*", "Action": "s3:GetObject", "Resource": "arn:aws:s3:::b/*"},
+            {"Sid": "AllButDelete", "Effect": "Allow", "Principal": "*", "NotAction": "s3:DeleteObject", "Resource": "arn:aws:s3:::b/*"},
+            {"Sid": "FromVpc", "Effect": "Allow", "Principal": "*", "Action": "s3:*", "Resource": "arn:aws:s3:::b/*",
+             "Condition": {"StringEquals": {"aws:SourceVpce": "vpce-123"}}},
+            {"Sid": "DenyAll", "Effect": "Deny", "Principal": "*", "Action": "s3:*", "Resource": "arn:aws:s3:::b/*"}
+        ]
+    }"#);
+    assert!(messages(&issues, Severity::Error).is_empty());
+    assert_eq!(messages(&issues, Severity::Warning), vec![
+        "Version 2008-10-17 does not support policy variables; use 2012-10-17",
+        "Statement 0 (Everything): allows everyone every S3 action; anyone can read, overwrite and delete",
+        "Statement 1 (Uploads): lets everyone s3:PutObject",
+        "Statement 2 (Website): makes the bucket public to everyone, with no Condition",
+        "Statement 3 (AllButDelete): allows everyone every S3 action not listed in NotAction",
+    ]);
+}
+
+#[test]
+fn test_validate_policy_file_fails_only_on_errors() {
+    let mut warned = NamedTempFile::new().unwrap();
+    write!(warned, r#"{{"Version": "2012-10-17", "Statement": [{{"Effect": "Allow", "Principal": "*", "Action": "s3:*", "Resource": "*"}}]}}"#).unwrap();
+    assert!(validate_policy_file(warned.path().to_str().unwrap(), OutputFormat::Json).is_ok());
+
+    let mut broken = NamedTempFile::new().unwrap();
+    write!(broken, r#"{{"Statement": [{{"Effect": "Allow"}}]}}"#).unwrap();
+    let err = validate_policy_file(broken.path().to_str().unwrap(), OutputFormat::Table).unwrap_err();
+    assert!(err.to_string().ends_with("is invalid: 4 errors, 0 warnings"), "{}", err);
+
+    assert!(validate_policy_file("/nonexistent/policy.json", OutputFormat::Table).is_err());
+}
@@ -0,0 +1,80 @@
+use aws_sdk_s3::config::Credentials;
+use s3sh::presign::{parse_length_range, post_url, presign_post_fields, PostPolicy};
+use std::time::{Duration, SystemTime};
+
+fn policy() -> PostPolicy {
+    PostPolicy {
+        bucket: "upload-bucket".to_string(),
+        key_prefix: "uploads/".to_string(),
+        expires_in: Duration::from_secs(3600),
+        content_length_range: Some((0, 10_485_760)),
+        content_type: Some("image/".to_string()),
+    }
+}
+
+fn decode_policy(encoded: &str) -> serde_json::Value {
+    let bytes = aws_smithy_types::base64::decode(encoded).unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[test]
+fn test_presign_post_fields() {
+    let credentials = Credentials::new("AKIDEXAMPLE", "secret", None, None, "test");
+    // 2026-01-02T03:04:05Z
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_767_323_045);
+
+    let fields = presign_post_fields(&policy(), &credentials, "eu-west-1", now).unwrap();
+    assert_eq!(fields["key"], "uploads/${filename}");
+    assert_eq!(fields["x-amz-algorithm"], "AWS4-HMAC-SHA256");
+    assert_eq!(fields["x-amz-credential"], "AKIDEXAMPLE/20260102/eu-west-1/s3/aws4_request");
+    assert_eq!(fields["x-amz-date"], "20260102T030405Z");
+    assert_eq!(fields["x-amz-signature"].len(), 64);
+    assert!(fields["x-amz-signature"].chars().all(|c| c.is_ascii_hexdigit()));
+    assert!(!fields.contains_key("x-amz-security-token"));
+    // A Content-Type prefix is a condition only; the browser sends the actual type
+    assert!(!fields.contains_key("Content-Type"));
+
+    let document = decode_policy(&fields["policy"]);
+    assert_eq!(document["expiration"], "2026-01-02T04:04:05Z");
+    let conditions = document["conditions"].as_array().unwrap();
+    assert!(conditions.contains(&serde_json::json!({ "bucket": "upload-bucket" })));
+    assert!(conditions.contains(&serde_json::json!(["starts-with", "$key", "uploads/"])));
+    assert!(conditions.contains(&serde_json::json!(["content-length-range", 0, 10_485_760])));
+    assert!(conditions.contains(&serde_json::json!(["starts-with", "$Content-Type", "image/"])));
+    assert!(conditions.contains(&serde_json::json!({ "x-amz-date": "20260102T030405Z" })));
+
+    // Signing is deterministic for the same inputs
+    let again = presign_post_fields(&policy(), &credentials, "eu-west-1", now).unwrap();
+    assert_eq!(again["x-amz-signature"], fields["x-amz-signature"]);
+}
+
+#[test]
+fn test_presign_post_fields_with_session_token_and_exact_type() {
+    let credentials = Credentials::new("ASIAEXAMPLE", "secret", Some("session-token".to_string()), None, "test");
+    let policy = PostPolicy { content_type: Some("text/csv".to_string()), content_length_range: None, ..policy() };
+
+    let fields = presign_post_fields(&policy, &credentials, "us-east-1", SystemTime::now()).unwrap();
+    assert_eq!(fields["x-amz-security-token"], "session-token");
+    assert_eq!(fields["Content-Type"], "text/csv");
+
+    let conditions = decode_policy(&fields["policy"])["conditions"].as_array().unwrap().clone();
+    assert!(conditions.contains(&serde_json::json!({ "x-amz-security-token": "session-token" })));
+    assert!(conditions.contains(&serde_json::json!({ "Content-Type": "text/csv" })));
+    assert!(!conditions.iter().any(|c| c[0] == "content-length-range"));
+}
+
+#[test]
+fn test_parse_length_range() {
+    assert_eq!(parse_length_range("0,1024").unwrap(), (0, 1024));
+    assert_eq!(parse_length_range(" 10 , 20 ").unwrap(), (10, 20));
+    assert!(parse_length_range("1024").is_err());
+    assert!(parse_length_range("20,10").is_err());
+    assert!(parse_length_range("a,b").is_err());
+}
+
+#[test]
+fn test_post_url() {
+    assert_eq!(post_url("my-bucket", "eu-west-1", None), "https://my-bucket.s3.eu-west-1.amazonaws.com/");
+    assert_eq!(post_url("my.bucket", "eu-west-1", None), "https://s3.eu-west-1.amazonaws.com/my.bucket");
+    assert_eq!(post_url("my-bucket", "us-east-1", Some("http://localhost:9000/")), "http://localhost:9000/my-bucket");
+}
@@ -0,0 +1,56 @@
+use indicatif::{MultiProgress, ProgressDrawTarget};
+use s3sh::progress::{item_bar, BulkProgress, BulkSummary};
+use s3sh::ui::OutputFormat;
+
+#[tokio::test]
+async fn test_bulk_progress_counts_items() {
+    let progress = BulkProgress::with_draw_target(5, "files", "Uploading", ProgressDrawTarget::hidden()).unwrap();
+
+    let bars = futures::future::join_all((0..5).map(|i| {
+        let progress = &progress;
+        async move {
+            let pb = progress.start_item(&format!("file-{}", i), Some(10)).unwrap();
+            pb.inc(10);
+            progress.finish_item(&pb);
+            pb
+        }
+    })).await;
+    progress.finish();
+
+    assert_eq!(progress.completed(), 5);
+    assert!(bars.iter().all(|pb| pb.is_finished() && pb.position() == 10));
+}
+
+#[test]
+fn test_bulk_progress_hidden_for_json() {
+    let progress = BulkProgress::new(3, "objects", "Copying", OutputFormat::Json).unwrap();
+    progress.inc(3);
+    progress.finish();
+    assert_eq!(progress.completed(), 3);
+}
+
+#[test]
+fn test_item_bar_length() {
+    let multi = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+    assert_eq!(item_bar("Uploading", "a.txt", Some(42), Some(&multi)).unwrap().length(), Some(42));
+
+    let spinner = item_bar("Downloading", "b.txt", None, Some(&multi)).unwrap();
+    assert_eq!(spinner.message(), "b.txt");
+}
+
+#[test]
+fn test_bulk_summary_report() {
+    let summary = BulkSummary::new("sync", 3, vec![("b.txt".to_string(), "AccessDenied".to_string())]);
+    assert_eq!(summary.succeeded, 2);
+
+    let json = serde_json::to_value(&summary).unwrap();
+    assert_eq!(json["operation"], "sync");
+    assert_eq!(json["total"], 3);
+    assert_eq!(json["failed"][0]["item"], "b.txt");
+    assert_eq!(json["failed"][0]["error"], "AccessDenied");
+
+    let err = summary.report(OutputFormat::Json, "files", "upload", "done").unwrap_err();
+    assert_eq!(err.to_string(), "1 of 3 files failed to upload");
+
+    assert!(BulkSummary::new("sync", 3, vec![]).report(OutputFormat::Table, "files", "upload", "done").is_ok());
+}
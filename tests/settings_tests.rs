@@ -0,0 +1,41 @@
+use s3sh::settings::Settings;
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+#[test]
+fn test_settings_from_file_and_merge() {
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(file, r#"
+region = "eu-west-1"
+profile = "work"
+endpoint_url = "http://localhost:9000"
+force_path_style = true
+output = "json"
+color = false
+"#).unwrap();
+
+    let from_file = Settings::from_file(file.path()).unwrap();
+    assert_eq!(from_file.region.as_deref(), Some("eu-west-1"));
+    assert_eq!(from_file.force_path_style, Some(true));
+
+    // Flags override the file; anything not given on the command line falls through
+    let merged = from_file.merge(Settings {
+        region: Some("us-west-2".to_string()),
+        color: Some(true),
+        ..Settings::default()
+    });
+    assert_eq!(merged.region.as_deref(), Some("us-west-2"));
+    assert_eq!(merged.profile.as_deref(), Some("work"));
+    assert_eq!(merged.endpoint_url.as_deref(), Some("http://localhost:9000"));
+    assert_eq!(merged.output.as_deref(), Some("json"));
+    assert_eq!(merged.color, Some(true));
+}
+
+#[test]
+fn test_settings_rejects_unknown_keys() {
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(file, r#"regoin = "eu-west-1""#).unwrap();
+
+    let err = Settings::from_file(file.path()).unwrap_err();
+    assert!(err.to_string().contains("Invalid config file"));
+}
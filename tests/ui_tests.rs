@@ -0,0 +1,84 @@
+use s3sh::ui::{confirm, is_affirmative, describe_dry_run, should_color, format_size, parse_sort_key, SortKey,
+    parse_output_format, OutputFormat, pluralize};
+use colored::*;
+
+#[test]
+fn test_confirm_assume_yes_skips_prompt() {
+    assert!(confirm("delete object 'a' from 'b'", true).unwrap());
+}
+
+#[test]
+fn test_is_affirmative() {
+    assert!(is_affirmative("y\n"));
+    assert!(is_affirmative("YES"));
+    assert!(is_affirmative("  yes  "));
+
+    assert!(!is_affirmative(""));
+    assert!(!is_affirmative("\n"));
+    assert!(!is_affirmative("n"));
+    assert!(!is_affirmative("nope"));
+}
+
+#[test]
+fn test_describe_dry_run() {
+    assert_eq!(describe_dry_run("DeleteBucket", "b", None), "Would call DeleteBucket on bucket 'b'");
+    assert_eq!(
+        describe_dry_run("PutObject", "b", Some("dir/k.txt")),
+        "Would call PutObject on bucket 'b' (key 'dir/k.txt')"
+    );
+}
+
+#[test]
+fn test_should_color() {
+    // Terminal, nothing set
+    assert!(should_color(false, false, false, true));
+    // Piped output defaults to plain text
+    assert!(!should_color(false, false, false, false));
+    // --no-color and NO_COLOR turn it off
+    assert!(!should_color(false, true, false, true));
+    assert!(!should_color(false, false, true, true));
+    // --color forces it back on
+    assert!(should_color(true, false, true, false));
+}
+
+#[test]
+fn test_color_override_strips_escape_codes() {
+    colored::control::set_override(false);
+    assert_eq!("done".green().to_string(), "done");
+    assert_eq!("[dry-run]".cyan().to_string(), "[dry-run]");
+    colored::control::unset_override();
+}
+
+#[test]
+fn test_format_size() {
+    assert_eq!(format_size(0), "0 B");
+    assert_eq!(format_size(1023), "1023 B");
+    assert_eq!(format_size(1024), "1.0 KiB");
+    assert_eq!(format_size(1536), "1.5 KiB");
+    assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+    assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    assert_eq!(format_size(5_000_000_000), "4.7 GiB");
+}
+
+#[test]
+fn test_pluralize() {
+    assert_eq!(pluralize(0, "bucket"), "0 buckets");
+    assert_eq!(pluralize(1, "bucket"), "1 bucket");
+    assert_eq!(pluralize(7, "bucket"), "7 buckets");
+}
+
+#[test]
+fn test_parse_sort_key() {
+    assert_eq!(parse_sort_key("name").unwrap(), SortKey::Name);
+    assert_eq!(parse_sort_key("Size").unwrap(), SortKey::Size);
+    assert_eq!(parse_sort_key("modified").unwrap(), SortKey::Modified);
+    assert!(parse_sort_key("date").is_err());
+}
+
+#[test]
+fn test_parse_output_format() {
+    assert_eq!(parse_output_format("table").unwrap(), OutputFormat::Table);
+    assert_eq!(parse_output_format("JSON").unwrap(), OutputFormat::Json);
+    assert_eq!(parse_output_format("csv").unwrap(), OutputFormat::Csv);
+    assert!(parse_output_format("yaml").is_err());
+}
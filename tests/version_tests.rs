@@ -0,0 +1,19 @@
+use s3sh::version::{format_version, version_info};
+
+#[test]
+fn test_version_reports_crate_and_sdk_versions() {
+    let info = version_info();
+    assert_eq!(info.s3sh, env!("CARGO_PKG_VERSION"));
+    assert!(!info.aws_sdk_s3.is_empty());
+    assert!(!info.target.is_empty());
+    assert!(!info.git_hash.is_empty());
+
+    let text = format_version(&info);
+    assert!(text.starts_with(&format!("s3sh {} (", env!("CARGO_PKG_VERSION"))));
+    assert!(text.contains(&format!("aws-sdk-s3: {}\n", info.aws_sdk_s3)));
+    assert!(text.contains(&format!("target: {}\n", info.target)));
+
+    let json = serde_json::to_value(&info).unwrap();
+    assert_eq!(json["s3sh"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(json["git_hash"], info.git_hash);
+}